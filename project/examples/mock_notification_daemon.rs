@@ -0,0 +1,112 @@
+//! 模拟的 freedesktop 通知服务，用于在没有桌面环境时测试 Linux 桌面通知
+//!
+//! 在独立的会话总线中运行（不会影响真实桌面）：
+//!
+//! ```bash
+//! dbus-run-session -- sh -c '
+//!   cargo run --example mock_notification_daemon &
+//!   sleep 1
+//!   cargo run -- --check-reminders
+//! '
+//! ```
+//!
+//! 每收到一条通知就打印其内容（单元测试 linux_notifier::tests 也依据这些输出检查发送的参数）；设置环境变量 `MOCK_NOTIFY_ACTION=done`（或 `snooze`、`ack`）时，
+//! 会在回复后立即模拟用户点击该按钮并关闭通知。
+
+#[cfg(target_os = "linux")]
+fn main() -> zbus::Result<()> {
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use zbus::object_server::SignalEmitter;
+    use zbus::zvariant::OwnedValue;
+    use zbus::{blocking, interface};
+
+    struct MockNotifications {
+        next_id: AtomicU32,
+        action: Option<String>,
+    }
+
+    #[interface(name = "org.freedesktop.Notifications")]
+    impl MockNotifications {
+        #[allow(clippy::too_many_arguments)]
+        async fn notify(
+            &self,
+            app_name: String,
+            _replaces_id: u32,
+            app_icon: String,
+            summary: String,
+            body: String,
+            actions: Vec<String>,
+            hints: HashMap<String, OwnedValue>,
+            expire_timeout: i32,
+            #[zbus(signal_emitter)] emitter: SignalEmitter<'_>,
+        ) -> u32 {
+            let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+            let urgency = hints.get("urgency").and_then(|v| u8::try_from(v).ok());
+            let text = |key: &str| hints.get(key).and_then(|v| <&str>::try_from(&**v).ok().map(str::to_string));
+            println!(
+                "[{}] app={} icon={} urgency={:?} category={:?} desktop-entry={:?} timeout={} actions={:?}\n    {}\n    {}",
+                id,
+                app_name,
+                app_icon,
+                urgency,
+                text("category"),
+                text("desktop-entry"),
+                expire_timeout,
+                actions,
+                summary,
+                body
+            );
+
+            if let Some(action) = self.action.clone()
+                && actions.contains(&action)
+            {
+                let emitter = emitter.to_owned();
+                // 等方法返回后再发出信号
+                std::thread::spawn(move || {
+                    std::thread::sleep(std::time::Duration::from_millis(200));
+                    let _ = zbus::block_on(Self::action_invoked(&emitter, id, &action));
+                    let _ = zbus::block_on(Self::notification_closed(&emitter, id, 2));
+                });
+            }
+            id
+        }
+
+        fn close_notification(&self, id: u32) {
+            println!("[{}] closed", id);
+        }
+
+        fn get_capabilities(&self) -> Vec<String> {
+            vec!["actions".into(), "body".into(), "icon-static".into()]
+        }
+
+        fn get_server_information(&self) -> (String, String, String, String) {
+            ("mock".into(), "todolist".into(), "0.1".into(), "1.2".into())
+        }
+
+        #[zbus(signal)]
+        async fn action_invoked(emitter: &SignalEmitter<'_>, id: u32, action_key: &str) -> zbus::Result<()>;
+
+        #[zbus(signal)]
+        async fn notification_closed(emitter: &SignalEmitter<'_>, id: u32, reason: u32) -> zbus::Result<()>;
+    }
+
+    let daemon = MockNotifications {
+        next_id: AtomicU32::new(1),
+        action: std::env::var("MOCK_NOTIFY_ACTION").ok().filter(|a| !a.is_empty()),
+    };
+    let _connection = blocking::connection::Builder::session()?
+        .name("org.freedesktop.Notifications")?
+        .serve_at("/org/freedesktop/Notifications", daemon)?
+        .build()?;
+    println!("mock notification daemon ready");
+
+    loop {
+        std::thread::park();
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn main() {
+    eprintln!("mock_notification_daemon 仅支持 Linux");
+}
@@ -0,0 +1,53 @@
+use crate::init::database::Database;
+use crate::init::db_json::JsonConfig;
+use crate::service::archive_serv;
+use crate::service::output::OutputFormat;
+use anyhow::Result as AnyResult;
+use std::io::{self, Write};
+
+/// 读取一行输入
+fn read_input(prompt: &str) -> AnyResult<String> {
+    print!("{}", prompt);
+    io::stdout().flush()?;
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    Ok(input.trim().to_string())
+}
+
+/// 归档（交互式）
+pub fn archive_data(db: &Database, json_config: &JsonConfig) -> AnyResult<()> {
+    let config = archive_serv::get_archive_config(json_config)?;
+    archive_serv::print_archive(db, OutputFormat::Text)?;
+
+    println!("  1. 归档待办事项（已完成或已取消，连同子任务）");
+    println!("  2. 取消归档");
+    println!("  3. 归档已结束超过 {} 天的待办事项", config.after_days);
+    println!("  4. 取消");
+
+    match read_input("请输入选项 (1-4): ")?.as_str() {
+        choice @ ("1" | "2") => {
+            let input = read_input("请输入待办事项 ID: ")?;
+            let Ok(id) = input.parse::<i32>() else {
+                println!("❌ 无效的 ID: '{}'", input);
+                return Ok(());
+            };
+            let result = if choice == "1" { archive_serv::archive(db, id)? } else { archive_serv::unarchive(db, id)? };
+            match result {
+                Some(todos) => {
+                    let verb = if choice == "1" { "📦 已归档" } else { "📤 已取消归档" };
+                    println!("{}「{}」", verb, todos[0].title);
+                    if todos.len() > 1 {
+                        println!("   连同 {} 个子任务", todos.len() - 1);
+                    }
+                }
+                None => println!("❌ 未找到ID为 {} 的待办事项", id),
+            }
+        }
+        "3" => {
+            let archived = archive_serv::archive_expired(db, config.after_days, false)?;
+            println!("✅ 已归档 {} 个待办事项", archived.len());
+        }
+        _ => println!("操作已取消"),
+    }
+    Ok(())
+}
@@ -0,0 +1,1282 @@
+use crate::dao::{note_dao, todo_list_dao};
+use crate::data::backup::SnapshotKind;
+use crate::data::doctor::Repair;
+use crate::data::note::NoteForm;
+use crate::data::recurrence::RecurrenceRule;
+use crate::data::trash::TrashKind;
+use crate::data::reminder::{ReminderHistory, ReminderKind, parse_duration};
+use crate::init::db_json::JsonConfig;
+use crate::init::db_json_content::{MAX_DAYS, ReminderRule};
+use crate::data::todo_list::{TodoListForm, TodoStatus};
+use crate::init::{config_load, database, timezone};
+use crate::service::output::{self, OutputFormat};
+use crate::service::reminder_serv::ReminderService;
+use crate::data::transfer::{TransferFormat, TransferTable};
+use crate::service::transfer_serv::{self, ConflictPolicy};
+use crate::service::{analytics_serv, archive_serv, backup_serv, change_log_serv, doctor_serv, help, note_serv, query_serv, relation_serv, review_serv, time_tracking_serv, timezone_serv, todo_list_serv, trash_serv};
+use anyhow::{Result as AnyResult, bail};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// 命令执行成功
+pub const EXIT_OK: i32 = 0;
+/// 运行时错误（数据库、文件等）
+pub const EXIT_FAILURE: i32 = 1;
+/// 参数错误（未知子命令、缺少参数、格式错误）
+pub const EXIT_USAGE: i32 = 2;
+/// 指定的待办事项或笔记不存在
+pub const EXIT_NOT_FOUND: i32 = 3;
+
+/// 命令行模式下需要映射为特定退出码的错误
+#[derive(Debug)]
+pub enum CliError {
+    Usage(String),
+    NotFound(String),
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::Usage(msg) => write!(f, "{}", msg),
+            CliError::NotFound(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for CliError {}
+
+fn usage_error(msg: impl Into<String>) -> anyhow::Error {
+    CliError::Usage(msg.into()).into()
+}
+
+fn not_found_error(msg: impl Into<String>) -> anyhow::Error {
+    CliError::NotFound(msg.into()).into()
+}
+
+/// 解析后的命令行参数：位置参数 + `--key value` 形式的选项
+struct ParsedArgs {
+    positionals: Vec<String>,
+    flags: HashMap<String, String>,
+}
+
+impl ParsedArgs {
+    /// 解析参数，支持 `--key value` 与 `--key=value` 两种写法
+    /// switches 为该命令不带参数值的开关选项（如 `--cascade`），同名选项在其他命令中可以带参数值
+    fn parse(args: &[String], switches: &[&str]) -> AnyResult<Self> {
+        let mut positionals = Vec::new();
+        let mut flags = HashMap::new();
+        let mut iter = args.iter();
+
+        while let Some(arg) = iter.next() {
+            if let Some(key) = arg.strip_prefix("--") {
+                let (key, value) = match key.split_once('=') {
+                    Some((k, v)) => (k.to_string(), v.to_string()),
+                    None if switches.contains(&key) => (key.to_string(), "true".to_string()),
+                    None => {
+                        let value = iter
+                            .next()
+                            .ok_or_else(|| usage_error(format!("选项 --{} 缺少参数值", key)))?;
+                        (key.to_string(), value.clone())
+                    }
+                };
+                if flags.insert(key.clone(), value).is_some() {
+                    return Err(usage_error(format!("选项 --{} 重复出现", key)));
+                }
+            } else {
+                positionals.push(arg.clone());
+            }
+        }
+
+        Ok(Self { positionals, flags })
+    }
+
+    /// 检查是否存在不支持的选项
+    fn allow_only(&self, allowed: &[&str]) -> AnyResult<()> {
+        for key in self.flags.keys() {
+            if !allowed.contains(&key.as_str()) {
+                return Err(usage_error(format!("不支持的选项: --{}", key)));
+            }
+        }
+        Ok(())
+    }
+
+    /// 检查位置参数个数
+    fn expect_positionals(&self, count: usize, usage: &str) -> AnyResult<()> {
+        if self.positionals.len() != count {
+            return Err(usage_error(format!("参数个数错误，用法: {}", usage)));
+        }
+        Ok(())
+    }
+
+    fn flag(&self, key: &str) -> Option<String> {
+        self.flags.get(key).cloned()
+    }
+
+    /// 开关选项是否打开（`--cascade` 或 `--cascade=true`）
+    fn switch(&self, key: &str) -> bool {
+        self.flags.get(key).is_some_and(|v| v != "false")
+    }
+
+    fn required_flag(&self, key: &str) -> AnyResult<String> {
+        match self.flags.get(key) {
+            Some(value) if !value.trim().is_empty() => Ok(value.clone()),
+            _ => Err(usage_error(format!("缺少必填选项 --{}", key))),
+        }
+    }
+}
+
+/// 解析 ID 参数
+fn parse_id(value: &str, what: &str) -> AnyResult<i32> {
+    value
+        .trim()
+        .parse::<i32>()
+        .map_err(|_| usage_error(format!("无效的{} ID: '{}'", what, value)))
+}
+
+/// 解析提醒记录 ID
+fn parse_reminder_id(value: &str) -> AnyResult<i64> {
+    value
+        .trim()
+        .parse::<i64>()
+        .map_err(|_| usage_error(format!("无效的提醒 ID: '{}'", value)))
+}
+
+/// 解析时间参数
+fn parse_time(value: &str, key: &str) -> AnyResult<chrono::DateTime<Utc>> {
+    todo_list_serv::parse_datetime(value.trim()).map_err(|_| {
+        usage_error(format!(
+            "选项 --{} 的时间格式错误: '{}'（支持 YYYY-MM-DD [HH[:MM[:SS]]]，或 tomorrow 9am / +3d / 明天下午3点 等）",
+            key, value
+        ))
+    })
+}
+
+/// 解析状态参数
+fn parse_status(value: &str) -> AnyResult<TodoStatus> {
+    TodoStatus::from_string(value).ok_or_else(|| {
+        usage_error(format!(
+            "无效的状态: '{}'（可选 todo/in_progress/blocked/done/cancelled）",
+            value
+        ))
+    })
+}
+
+/// 解析优先级参数，空字符串表示清除优先级
+fn parse_priority(value: &str) -> AnyResult<Option<crate::data::todo_list::Priority>> {
+    todo_list_serv::parse_priority_input(value).map_err(usage_error)
+}
+
+fn parse_repeat(value: &str, begin_time: DateTime<Utc>) -> AnyResult<Option<RecurrenceRule>> {
+    todo_list_serv::parse_recurrence_input(value, begin_time)
+        .map_err(|e| usage_error(format!("重复规则无效: {}", e)))
+}
+
+/// 解析时长选项（如 90 / 30m / 2h / 1d12h）
+fn parse_duration_flag(value: &str, name: &str) -> AnyResult<i64> {
+    parse_duration(value).ok_or_else(|| usage_error(format!("--{} 的时长格式错误: '{}'（示例: 30m / 2h / 1d）", name, value)))
+}
+
+/// 解析父任务 ID，none 或空值表示没有父任务
+fn parse_parent(value: &str) -> AnyResult<Option<i32>> {
+    match value.trim().to_lowercase().as_str() {
+        "" | "none" | "-" => Ok(None),
+        _ => parse_id(value, "父任务").map(Some),
+    }
+}
+
+/// 解析 --format 选项，缺省为文本
+fn output_format(parsed: &ParsedArgs) -> AnyResult<OutputFormat> {
+    match parsed.flag("format") {
+        Some(value) => OutputFormat::from_string(value.trim()).ok_or_else(|| {
+            usage_error(format!("不支持的输出格式: '{}'（可选 text/json/ndjson/csv）", value))
+        }),
+        None => Ok(OutputFormat::Text),
+    }
+}
+
+/// 空字符串视为清空该字段
+fn optional_text(value: String) -> Option<String> {
+    if value.trim().is_empty() {
+        None
+    } else {
+        Some(value)
+    }
+}
+
+/// 命令行模式入口（非交互式）
+///
+/// 返回值为进程退出码：
+/// - 0: 成功
+/// - 1: 运行时错误
+/// - 2: 参数错误
+/// - 3: 目标不存在
+///
+/// 使用方式：
+/// ```bash
+/// project todo add --title "周报" --begin "2025-10-25 09:00"
+/// project todo toggle 12
+/// project note add --todo 3 --title "进展" --content "已完成初稿"
+/// project review stats
+/// ```
+pub fn run(args: &[String]) -> i32 {
+    change_log_serv::set_source("cli");
+    match dispatch(args) {
+        Ok(()) => EXIT_OK,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            match e.downcast_ref::<CliError>() {
+                Some(CliError::Usage(_)) => {
+                    eprintln!("💡 使用 'project help' 查看命令行用法");
+                    EXIT_USAGE
+                }
+                Some(CliError::NotFound(_)) => EXIT_NOT_FOUND,
+                None => EXIT_FAILURE,
+            }
+        }
+    }
+}
+
+/// 根据第一个参数分发到对应的命令组
+fn dispatch(args: &[String]) -> AnyResult<()> {
+    let (group, rest) = match args.split_first() {
+        Some((group, rest)) => (group.as_str(), rest),
+        None => return Err(usage_error("缺少子命令")),
+    };
+
+    if matches!(group, "help" | "--help" | "-h") {
+        help::print_cli_usage();
+        return Ok(());
+    }
+
+    // 时间参数的解析与显示都依赖配置的时区
+    let json_config = open_json_config()?;
+    timezone::load_timezone(&json_config);
+
+    match group {
+        "todo" => todo_command(rest),
+        "note" => note_command(rest),
+        "review" => review_command(rest),
+        "reminder" => reminder_command(rest),
+        "timezone" => timezone_command(rest, &json_config),
+        "export" => export_command(rest),
+        "import" => import_command(rest),
+        "backup" => backup_command(rest, &json_config),
+        "restore" => restore_command(rest, &json_config),
+        "trash" => trash_command(rest, &json_config),
+        "archive" => archive_command(rest, &json_config),
+        "track" => track_command(rest),
+        "doctor" | "fsck" => doctor_command(rest),
+        "undo" | "redo" => undo_command(group, rest),
+        "history" => history_command(rest),
+        _ => Err(usage_error(format!("未知子命令: '{}'", group))),
+    }
+}
+
+/// 打开数据库并初始化表结构
+fn open_database() -> AnyResult<database::Database> {
+    let db_path = config_load::get_config_value("database", Some("path"));
+    let db = database::Database::new(&db_path)?;
+    db.initialize_tables()?;
+    Ok(db)
+}
+
+/// timezone 命令：无参数时显示当前时区，否则设置时区
+fn timezone_command(args: &[String], json_config: &JsonConfig) -> AnyResult<()> {
+    let parsed = ParsedArgs::parse(args, &[])?;
+    parsed.allow_only(&[])?;
+    match parsed.positionals.as_slice() {
+        [] => timezone_serv::show_timezone(json_config),
+        [value] => timezone_serv::set_timezone(json_config, value)
+            .map_err(|e| usage_error(e.to_string())),
+        _ => Err(usage_error("用法: project timezone [<IANA 时区>|system]")),
+    }
+}
+
+/// export 命令：未指定 --output 时输出到标准输出
+fn export_command(args: &[String]) -> AnyResult<()> {
+    let parsed = ParsedArgs::parse(args, &[])?;
+    parsed.allow_only(&["format", "table", "output"])?;
+    parsed.expect_positionals(
+        0,
+        "project export [--format json|csv|md|ics|todotxt] [--table todos|notes|reminders] [--output <文件>]",
+    )?;
+
+    let output = parsed.flag("output").and_then(optional_text).map(PathBuf::from);
+    let format = transfer_format(&parsed, output.as_deref())?;
+    if !format.can_export() {
+        return Err(usage_error("Taskwarrior 格式仅支持导入"));
+    }
+    let table = transfer_table(&parsed, format)?;
+    let reminder = ReminderService::get_reminder_config(&open_json_config()?)?;
+    let db = open_database()?;
+    match output {
+        Some(path) => transfer_serv::export_to_file(&db, format, table, &reminder, &path)?,
+        None => {
+            let document = transfer_serv::export_document(&db)?;
+            transfer_serv::write_export(&document, format, table, &reminder, &mut io::stdout().lock())?;
+        }
+    }
+    Ok(())
+}
+
+/// import 命令
+fn import_command(args: &[String]) -> AnyResult<()> {
+    let parsed = ParsedArgs::parse(args, &["dry-run"])?;
+    parsed.allow_only(&["format", "table", "dry-run", "on-conflict"])?;
+    parsed.expect_positionals(
+        1,
+        "project import <文件> [--format json|csv|ics|todotxt|taskwarrior] [--table todos|notes] [--dry-run] [--on-conflict skip|duplicate]",
+    )?;
+
+    let path = PathBuf::from(&parsed.positionals[0]);
+    let format = transfer_format(&parsed, Some(&path))?;
+    if !format.can_import() {
+        return Err(usage_error("Markdown 仅支持导出"));
+    }
+    let table = transfer_table(&parsed, format)?;
+    if table == TransferTable::Reminders {
+        return Err(usage_error("提醒历史只能通过 JSON 导入"));
+    }
+    let policy = match parsed.flag("on-conflict") {
+        Some(value) => ConflictPolicy::from_string(&value)
+            .ok_or_else(|| usage_error(format!("无效的冲突处理方式: '{}'（可选 skip/duplicate）", value)))?,
+        None => ConflictPolicy::Skip,
+    };
+
+    let document = transfer_serv::read_import_file(&path, format, table)?;
+    let db = open_database()?;
+    let report = transfer_serv::import_document(&db, &document, policy, parsed.switch("dry-run"))?;
+    transfer_serv::print_report(&report);
+    Ok(())
+}
+
+/// backup 命令组
+fn backup_command(args: &[String], json_config: &JsonConfig) -> AnyResult<()> {
+    let (action, parsed) = split_action(args, "backup", &["dry-run"])?;
+    let config = backup_serv::get_backup_config(json_config)?;
+
+    match action.as_str() {
+        "create" => {
+            parsed.allow_only(&[])?;
+            parsed.expect_positionals(0, "project backup create")?;
+            let db = open_database()?;
+            let snapshot = backup_serv::create_snapshot(&db, &config, SnapshotKind::Manual)?;
+            println!("✅ 已创建快照: {}", snapshot.path.display());
+        }
+        "list" => {
+            parsed.allow_only(&["format"])?;
+            parsed.expect_positionals(0, "project backup list [--format ..]")?;
+            let format = output_format(&parsed)?;
+            let db = open_database()?;
+            backup_serv::print_snapshots(&db, &config, format)?;
+        }
+        "verify" => {
+            parsed.allow_only(&[])?;
+            if parsed.positionals.len() > 1 {
+                return Err(usage_error("用法: project backup verify [<序号|快照名>]"));
+            }
+            let db = open_database()?;
+            let snapshot = match parsed.positionals.first() {
+                Some(reference) => Some(
+                    backup_serv::find_snapshot(&db, &config, reference)?
+                        .ok_or_else(|| not_found_error(format!("快照 '{}' 不存在", reference)))?,
+                ),
+                None => None,
+            };
+            let results = backup_serv::verify(&db, &config, snapshot.as_ref())?;
+            if !backup_serv::print_verify(&results) {
+                bail!("完整性检查发现问题");
+            }
+        }
+        "prune" => {
+            parsed.allow_only(&["dry-run"])?;
+            parsed.expect_positionals(0, "project backup prune [--dry-run]")?;
+            let dry_run = parsed.switch("dry-run");
+            let db = open_database()?;
+            let removed = backup_serv::prune(&db, &config, dry_run)?;
+            let verb = if dry_run { "将删除" } else { "已删除" };
+            for snapshot in &removed {
+                println!("🗑️  {}: {}", verb, snapshot.name);
+            }
+            println!("✅ {} {} 个过期的自动快照", verb, removed.len());
+        }
+        _ => return Err(usage_error(format!("未知的 backup 动作: '{}'", action))),
+    }
+
+    Ok(())
+}
+
+/// restore 命令：无参数时列出快照，否则用指定的快照替换当前数据库
+fn restore_command(args: &[String], json_config: &JsonConfig) -> AnyResult<()> {
+    let parsed = ParsedArgs::parse(args, &[])?;
+    parsed.allow_only(&[])?;
+    let config = backup_serv::get_backup_config(json_config)?;
+    // 当前数据库即将被替换，先不执行迁移（恢复后再迁移）
+    let db_path = config_load::get_config_value("database", Some("path"));
+    let db = database::Database::new(&db_path)?;
+
+    match parsed.positionals.as_slice() {
+        [] => backup_serv::print_snapshots(&db, &config, OutputFormat::Text),
+        [reference] => {
+            let snapshot = backup_serv::find_snapshot(&db, &config, reference)?
+                .ok_or_else(|| not_found_error(format!("快照 '{}' 不存在", reference)))?;
+            let pre_restore = backup_serv::restore(&db, &config, &snapshot)?;
+            println!("✅ 已从快照 {} 恢复数据库", snapshot.name);
+            println!("💾 恢复前的数据已保存为快照: {}", pre_restore.name);
+            Ok(())
+        }
+        _ => Err(usage_error("用法: project restore [<序号|快照名>]")),
+    }
+}
+
+/// trash 命令组：查看回收站、恢复、彻底删除
+fn trash_command(args: &[String], json_config: &JsonConfig) -> AnyResult<()> {
+    const SWITCHES: &[&str] = &["note", "all", "dry-run"];
+    let config = trash_serv::get_trash_config(json_config)?;
+    // 不带动作时列出回收站
+    let (action, parsed) = match args.first() {
+        Some(first) if !first.starts_with("--") => split_action(args, "trash", SWITCHES)?,
+        _ => ("list".to_string(), ParsedArgs::parse(args, SWITCHES)?),
+    };
+    let kind = |parsed: &ParsedArgs| if parsed.switch("note") { TrashKind::Note } else { TrashKind::Todo };
+
+    match action.as_str() {
+        "list" => {
+            parsed.allow_only(&["format"])?;
+            parsed.expect_positionals(0, "project trash [list] [--format ..]")?;
+            let format = output_format(&parsed)?;
+            let db = open_database()?;
+            trash_serv::print_trash(&db, &config, format)?;
+        }
+        "restore" => {
+            parsed.allow_only(&["note"])?;
+            parsed.expect_positionals(1, "project trash restore <ID> [--note]")?;
+            let kind = kind(&parsed);
+            let id = parse_id(&parsed.positionals[0], kind.label())?;
+            let db = open_database()?;
+            let item = trash_serv::restore(&db, kind, id)?
+                .ok_or_else(|| not_found_error(format!("回收站中没有ID为 {} 的{}", id, kind.label())))?;
+            println!("♻️  已恢复{}「{}」", kind.label(), item.title);
+        }
+        "purge" => {
+            parsed.allow_only(&["note", "all", "dry-run"])?;
+            let db = open_database()?;
+            match parsed.positionals.as_slice() {
+                [id] => {
+                    if parsed.switch("all") || parsed.switch("dry-run") {
+                        return Err(usage_error("指定 ID 时不能使用 --all / --dry-run"));
+                    }
+                    let kind = kind(&parsed);
+                    let id = parse_id(id, kind.label())?;
+                    let item = trash_serv::purge(&db, kind, id)?
+                        .ok_or_else(|| not_found_error(format!("回收站中没有ID为 {} 的{}", id, kind.label())))?;
+                    println!("✅ 已彻底删除{}「{}」", kind.label(), item.title);
+                }
+                [] => {
+                    if parsed.switch("note") {
+                        return Err(usage_error("--note 只用于彻底删除指定的笔记"));
+                    }
+                    let all = parsed.switch("all");
+                    if !all && config.retention_days == 0 {
+                        println!("💡 未开启自动清理（trash.retention_days 为 0），使用 --all 清空回收站");
+                        return Ok(());
+                    }
+                    let dry_run = parsed.switch("dry-run");
+                    let removed = trash_serv::purge_expired(&db, &config, all, dry_run)?;
+                    let verb = if dry_run { "将彻底删除" } else { "已彻底删除" };
+                    for item in &removed {
+                        println!("🗑️  {}: [{} #{}] {}", verb, item.kind.label(), item.id, item.title);
+                    }
+                    println!("✅ {} {} 项", verb, removed.len());
+                }
+                _ => return Err(usage_error("用法: project trash purge [<ID> [--note] | --all] [--dry-run]")),
+            }
+        }
+        _ => return Err(usage_error(format!("未知的 trash 动作: '{}'", action))),
+    }
+
+    Ok(())
+}
+
+/// archive 命令组：查看、归档、取消归档与自动归档
+fn archive_command(args: &[String], json_config: &JsonConfig) -> AnyResult<()> {
+    // 不带动作时列出已归档的待办事项
+    let (action, parsed) = match args.first() {
+        Some(first) if !first.starts_with("--") => split_action(args, "archive", &["dry-run"])?,
+        _ => ("list".to_string(), ParsedArgs::parse(args, &["dry-run"])?),
+    };
+
+    match action.as_str() {
+        "list" => {
+            parsed.allow_only(&["format"])?;
+            parsed.expect_positionals(0, "project archive [list] [--format ..]")?;
+            let format = output_format(&parsed)?;
+            let db = open_database()?;
+            archive_serv::print_archive(&db, format)?;
+        }
+        "add" | "restore" => {
+            parsed.allow_only(&[])?;
+            parsed.expect_positionals(1, &format!("project archive {} <ID>", action))?;
+            let id = parse_id(&parsed.positionals[0], "待办事项")?;
+            let db = open_database()?;
+            let result = if action == "add" { archive_serv::archive(&db, id)? } else { archive_serv::unarchive(&db, id)? };
+            let todos = result.ok_or_else(|| not_found_error(format!("未找到ID为 {} 的待办事项", id)))?;
+            let verb = if action == "add" { "📦 已归档" } else { "📤 已取消归档" };
+            println!("{}「{}」", verb, todos[0].title);
+            if todos.len() > 1 {
+                println!("   连同 {} 个子任务", todos.len() - 1);
+            }
+        }
+        "auto" => {
+            parsed.allow_only(&["days", "dry-run"])?;
+            parsed.expect_positionals(0, "project archive auto [--days N] [--dry-run]")?;
+            let days = match parsed.flag("days") {
+                Some(value) => value
+                    .trim()
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|d| *d <= MAX_DAYS)
+                    .ok_or_else(|| usage_error(format!("无效的天数: '{}'，应在 0 到 {} 之间", value, MAX_DAYS)))?,
+                None => archive_serv::get_archive_config(json_config)?.after_days,
+            };
+            let dry_run = parsed.switch("dry-run");
+            let db = open_database()?;
+            let archived = archive_serv::archive_expired(&db, days, dry_run)?;
+            let verb = if dry_run { "将归档" } else { "已归档" };
+            for todo in &archived {
+                println!("📦 {}: [ID: {}] {}", verb, todo.id, todo.title);
+            }
+            println!("✅ {} {} 个已结束超过 {} 天的待办事项", verb, archived.len(), days);
+        }
+        _ => return Err(usage_error(format!("未知的 archive 动作: '{}'", action))),
+    }
+
+    Ok(())
+}
+
+/// track 命令组：开始、停止计时与查看计时状态
+fn track_command(args: &[String]) -> AnyResult<()> {
+    // 不带动作（或直接给出 ID）时查看计时状态
+    let (action, parsed) = match args.first() {
+        Some(first) if !first.starts_with("--") && first.parse::<i32>().is_err() => split_action(args, "track", &[])?,
+        _ => ("status".to_string(), ParsedArgs::parse(args, &[])?),
+    };
+
+    match action.as_str() {
+        "start" => {
+            parsed.allow_only(&[])?;
+            parsed.expect_positionals(1, "project track start <ID>")?;
+            let id = parse_id(&parsed.positionals[0], "待办事项")?;
+            let db = open_database()?;
+            let started = time_tracking_serv::start(&db, id)?
+                .ok_or_else(|| not_found_error(format!("未找到ID为 {} 的待办事项", id)))?;
+            time_tracking_serv::print_started(&started);
+        }
+        "stop" => {
+            parsed.allow_only(&[])?;
+            parsed.expect_positionals(0, "project track stop")?;
+            let db = open_database()?;
+            match time_tracking_serv::stop(&db)? {
+                Some((session, todo)) => time_tracking_serv::print_stopped(&session, &todo),
+                None => println!("💡 当前没有正在进行的计时"),
+            }
+        }
+        "status" => {
+            parsed.allow_only(&["format"])?;
+            if parsed.positionals.len() > 1 {
+                return Err(usage_error("参数个数错误，用法: project track status [ID] [--format ..]"));
+            }
+            let todo_id = match parsed.positionals.first() {
+                Some(value) => Some(parse_id(value, "待办事项")?),
+                None => None,
+            };
+            let format = output_format(&parsed)?;
+            let db = open_database()?;
+            let found = time_tracking_serv::print_status(&db, todo_id, format)?;
+            if let (false, Some(id)) = (found, todo_id) {
+                return Err(not_found_error(format!("未找到ID为 {} 的待办事项", id)));
+            }
+        }
+        _ => return Err(usage_error(format!("未知的 track 动作: '{}'", action))),
+    }
+
+    Ok(())
+}
+
+/// doctor 命令：检查孤立记录与时间格式，--fix 时修复；仍有问题时以错误退出
+fn doctor_command(args: &[String]) -> AnyResult<()> {
+    let parsed = ParsedArgs::parse(args, &["fix"])?;
+    parsed.allow_only(&["fix", "format"])?;
+    parsed.expect_positionals(0, "project doctor [--fix] [--format ..]")?;
+    let format = output_format(&parsed)?;
+    let db = open_database()?;
+
+    let mut problems = doctor_serv::diagnose(&db)?;
+    doctor_serv::print_problems(&problems, format)?;
+    if parsed.switch("fix") && problems.iter().any(|p| p.repair != Repair::Manual) {
+        let fixed = doctor_serv::repair(&db, &problems)?;
+        // 结构化输出时标准输出只保留问题列表
+        if format == OutputFormat::Text {
+            println!("🔧 已修复 {} 处数据", fixed);
+        }
+        problems = doctor_serv::diagnose(&db)?;
+    }
+
+    match problems.len() {
+        0 => Ok(()),
+        n if parsed.switch("fix") => bail!("仍有 {} 个问题需要手动处理", n),
+        n => bail!("发现 {} 个问题，使用 'project doctor --fix' 修复", n),
+    }
+}
+
+/// undo / redo 命令
+fn undo_command(group: &str, args: &[String]) -> AnyResult<()> {
+    let parsed = ParsedArgs::parse(args, &[])?;
+    parsed.allow_only(&[])?;
+    parsed.expect_positionals(0, &format!("project {}", group))?;
+    let db = open_database()?;
+    if group == "undo" {
+        match change_log_serv::undo(&db)? {
+            Some(change) => println!("↩️  已撤销: {}", change.action),
+            None => return Err(not_found_error("没有可以撤销的操作")),
+        }
+    } else {
+        match change_log_serv::redo(&db)? {
+            Some(change) => println!("↪️  已重做: {}", change.action),
+            None => return Err(not_found_error("没有可以重做的操作")),
+        }
+    }
+    Ok(())
+}
+
+/// history 命令：无参数时列出最近的操作，否则显示某个待办事项的修改历史
+fn history_command(args: &[String]) -> AnyResult<()> {
+    let parsed = ParsedArgs::parse(args, &[])?;
+    parsed.allow_only(&["limit", "format"])?;
+    let format = output_format(&parsed)?;
+    let db = open_database()?;
+
+    match parsed.positionals.as_slice() {
+        [] => {
+            let limit = match parsed.flag("limit") {
+                Some(value) => value
+                    .trim()
+                    .parse::<usize>()
+                    .map_err(|_| usage_error(format!("无效的数量: '{}'", value)))?,
+                None => 20,
+            };
+            match format {
+                OutputFormat::Text => change_log_serv::show_recent_changes(&db, limit)?,
+                _ => output::print_records(&change_log_serv::recent_changes(&db, limit)?, format)?,
+            }
+        }
+        [id] => {
+            if parsed.flag("limit").is_some() {
+                return Err(usage_error("--limit 只用于列出最近的操作"));
+            }
+            if format == OutputFormat::Csv {
+                return Err(usage_error("修改历史包含整行内容，不支持 CSV 输出，请使用 json 或 ndjson"));
+            }
+            let id = parse_id(id, "待办事项")?;
+            match format {
+                OutputFormat::Text => change_log_serv::show_todo_history(&db, id)?,
+                _ => output::print_records(&change_log_serv::collect_todo_history(&db, id)?, format)?,
+            }
+        }
+        _ => return Err(usage_error("用法: project history [<待办ID>] [--limit N] [--format ..]")),
+    }
+    Ok(())
+}
+
+/// 导入 / 导出格式：--format 优先，其次按文件扩展名推断，默认 JSON（Taskwarrior 的导出文件在读取时按内容识别）
+fn transfer_format(parsed: &ParsedArgs, path: Option<&Path>) -> AnyResult<TransferFormat> {
+    match parsed.flag("format") {
+        Some(value) => TransferFormat::from_string(&value)
+            .ok_or_else(|| usage_error(format!("不支持的格式: '{}'（可选 json/csv/md/ics/todotxt/taskwarrior）", value))),
+        None => Ok(path.and_then(TransferFormat::from_path).unwrap_or(TransferFormat::Json)),
+    }
+}
+
+/// CSV 针对的表，默认为待办事项；其他格式不接受 --table
+fn transfer_table(parsed: &ParsedArgs, format: TransferFormat) -> AnyResult<TransferTable> {
+    match parsed.flag("table") {
+        Some(_) if format != TransferFormat::Csv => Err(usage_error("--table 只用于 CSV 格式")),
+        Some(value) => TransferTable::from_string(&value)
+            .ok_or_else(|| usage_error(format!("未知的表: '{}'（可选 todos/notes/reminders）", value))),
+        None => Ok(TransferTable::Todos),
+    }
+}
+
+/// 打开 JSON 配置
+fn open_json_config() -> AnyResult<JsonConfig> {
+    let json_path = config_load::get_config_value("json", Some("path"));
+    JsonConfig::new(&json_path)
+}
+
+/// 拆分动作名与其余参数，switches 同 ParsedArgs::parse
+fn split_action(args: &[String], group: &str, switches: &[&str]) -> AnyResult<(String, ParsedArgs)> {
+    let (action, rest) = args
+        .split_first()
+        .ok_or_else(|| usage_error(format!("'{}' 缺少动作", group)))?;
+    Ok((action.clone(), ParsedArgs::parse(rest, switches)?))
+}
+
+/// 确认待办事项存在
+fn require_todo(db: &database::Database, id: i32) -> AnyResult<TodoListForm> {
+    todo_list_dao::get_todo_by_id(db.get_connection(), id)?
+        .ok_or_else(|| not_found_error(format!("未找到ID为 {} 的待办事项", id)))
+}
+
+/// 确认提醒记录存在
+fn require_reminder(db: &database::Database, id: i64) -> AnyResult<ReminderHistory> {
+    ReminderService::get_reminder(db, id)?
+        .ok_or_else(|| not_found_error(format!("未找到ID为 {} 的提醒记录", id)))
+}
+
+/// 确认笔记存在
+fn require_note(db: &database::Database, id: i32) -> AnyResult<NoteForm> {
+    note_dao::get_note_by_id(db.get_connection(), id)?
+        .ok_or_else(|| not_found_error(format!("未找到ID为 {} 的笔记", id)))
+}
+
+/// todo 命令组
+fn todo_command(args: &[String]) -> AnyResult<()> {
+    let (action, parsed) = split_action(args, "todo", &["cascade", "all"])?;
+
+    match action.as_str() {
+        "list" => {
+            parsed.allow_only(&["format", "all"])?;
+            parsed.expect_positionals(0, "project todo list [--all] [--format ..]")?;
+            let format = output_format(&parsed)?;
+            // 默认不显示已归档的待办事项，--all 时一并显示
+            let all = parsed.switch("all");
+            let db = open_database()?;
+            match format {
+                OutputFormat::Text => todo_list_serv::show_all_todos(&db, all)?,
+                // CSV 不支持嵌套的笔记列表，只输出待办事项本身
+                OutputFormat::Csv => {
+                    let todos: Vec<_> = todo_list_serv::list_todos_with_notes(&db, all)?
+                        .into_iter()
+                        .map(|t| t.todo)
+                        .collect();
+                    output::print_records(&todos, format)?;
+                }
+                _ => output::print_records(&todo_list_serv::list_todos_with_notes(&db, all)?, format)?,
+            }
+        }
+        "add" => {
+            parsed.allow_only(&[
+                "title", "desc", "begin", "end", "priority", "tags", "status", "repeat", "parent", "key1",
+                "key2", "key3",
+            ])?;
+            parsed.expect_positionals(0, "project todo add --title <标题> --begin <时间> [选项]")?;
+
+            let title = parsed.required_flag("title")?;
+            let begin_time = parse_time(&parsed.required_flag("begin")?, "begin")?;
+            let end_time = match parsed.flag("end") {
+                Some(value) => Some(parse_time(&value, "end")?),
+                None => None,
+            };
+
+            let status = match parsed.flag("status") {
+                Some(value) => parse_status(&value)?,
+                None => TodoStatus::Todo,
+            };
+
+            let new_todo = TodoListForm {
+                id: 0,
+                title,
+                description: parsed.flag("desc").and_then(optional_text),
+                status,
+                priority: match parsed.flag("priority") {
+                    Some(value) => parse_priority(&value)?,
+                    None => None,
+                },
+                tags: parsed.flag("tags").map(|v| todo_list_serv::parse_tags(&v)).unwrap_or_default(),
+                begin_time,
+                end_time,
+                key_message1: parsed.flag("key1").and_then(optional_text),
+                key_message2: parsed.flag("key2").and_then(optional_text),
+                key_message3: parsed.flag("key3").and_then(optional_text),
+                recurrence: match parsed.flag("repeat") {
+                    Some(value) => parse_repeat(&value, begin_time)?,
+                    None => None,
+                },
+                series_id: None,
+                occurrence: 1,
+                parent_id: match parsed.flag("parent") {
+                    Some(value) => parse_parent(&value)?,
+                    None => None,
+                },
+                archived_at: None,
+                completed_at: (status == TodoStatus::Done).then(Utc::now),
+            };
+
+            let db = open_database()?;
+            if let Some(parent_id) = new_todo.parent_id {
+                require_todo(&db, parent_id)?;
+            }
+            todo_list_serv::add_todo(&db, &new_todo)?;
+        }
+        "update" => {
+            parsed.allow_only(&[
+                "title", "desc", "begin", "end", "priority", "tags", "status", "repeat", "parent", "key1",
+                "key2", "key3", "cascade",
+            ])?;
+            parsed.expect_positionals(1, "project todo update <ID> [--title ..] [--desc ..] [...] [--cascade]")?;
+            if parsed.flags.keys().all(|k| k == "cascade") {
+                return Err(usage_error("至少需要指定一个要更新的字段"));
+            }
+
+            let id = parse_id(&parsed.positionals[0], "待办事项")?;
+            let db = open_database()?;
+            let mut todo = require_todo(&db, id)?;
+
+            if let Some(title) = parsed.flag("title") {
+                if title.trim().is_empty() {
+                    return Err(usage_error("标题不能为空"));
+                }
+                todo.title = title;
+            }
+            if let Some(desc) = parsed.flag("desc") {
+                todo.description = optional_text(desc);
+            }
+            if let Some(begin) = parsed.flag("begin") {
+                todo.begin_time = parse_time(&begin, "begin")?;
+            }
+            if let Some(end) = parsed.flag("end") {
+                todo.end_time = match optional_text(end) {
+                    Some(value) => Some(parse_time(&value, "end")?),
+                    None => None,
+                };
+            }
+            if let Some(status) = parsed.flag("status") {
+                todo.status = parse_status(&status)?;
+            }
+            if let Some(priority) = parsed.flag("priority") {
+                todo.priority = parse_priority(&priority)?;
+            }
+            if let Some(tags) = parsed.flag("tags") {
+                todo.tags = todo_list_serv::parse_tags(&tags);
+            }
+            if let Some(repeat) = parsed.flag("repeat") {
+                todo.recurrence = parse_repeat(&repeat, todo.begin_time)?;
+            }
+            if let Some(parent) = parsed.flag("parent") {
+                todo.parent_id = parse_parent(&parent)?;
+                if let Some(parent_id) = todo.parent_id {
+                    require_todo(&db, parent_id)?;
+                    relation_serv::check_parent(&db, id, parent_id)?;
+                }
+            }
+            if let Some(key1) = parsed.flag("key1") {
+                todo.key_message1 = optional_text(key1);
+            }
+            if let Some(key2) = parsed.flag("key2") {
+                todo.key_message2 = optional_text(key2);
+            }
+            if let Some(key3) = parsed.flag("key3") {
+                todo.key_message3 = optional_text(key3);
+            }
+
+            todo_list_serv::update_todo_direct(&db, &todo, parsed.switch("cascade"))?;
+        }
+        "delete" => {
+            parsed.allow_only(&[])?;
+            parsed.expect_positionals(1, "project todo delete <ID>")?;
+            let id = parse_id(&parsed.positionals[0], "待办事项")?;
+            let db = open_database()?;
+            require_todo(&db, id)?;
+            todo_list_serv::delete_todo_direct(&db, id)?;
+        }
+        "toggle" => {
+            parsed.allow_only(&["cascade"])?;
+            parsed.expect_positionals(1, "project todo toggle <ID> [--cascade]")?;
+            let id = parse_id(&parsed.positionals[0], "待办事项")?;
+            let db = open_database()?;
+            require_todo(&db, id)?;
+            todo_list_serv::toggle_completed(&db, id, parsed.switch("cascade"))?;
+        }
+        "parent" => {
+            parsed.allow_only(&[])?;
+            parsed.expect_positionals(2, "project todo parent <ID> <父任务ID|none>")?;
+            let id = parse_id(&parsed.positionals[0], "待办事项")?;
+            let parent_id = parse_parent(&parsed.positionals[1])?;
+            let db = open_database()?;
+            require_todo(&db, id)?;
+            if let Some(parent_id) = parent_id {
+                require_todo(&db, parent_id)?;
+            }
+            relation_serv::set_parent(&db, id, parent_id)?;
+        }
+        "depend" | "undepend" => {
+            parsed.allow_only(&[])?;
+            parsed.expect_positionals(2, "project todo depend|undepend <ID> <依赖的ID>")?;
+            let id = parse_id(&parsed.positionals[0], "待办事项")?;
+            let depends_on_id = parse_id(&parsed.positionals[1], "待办事项")?;
+            let db = open_database()?;
+            require_todo(&db, id)?;
+            require_todo(&db, depends_on_id)?;
+            if action == "depend" {
+                relation_serv::add_dependency(&db, id, depends_on_id)?;
+            } else {
+                relation_serv::remove_dependency(&db, id, depends_on_id)?;
+            }
+        }
+        "search" | "filter" => {
+            parsed.allow_only(&["format"])?;
+            let format = output_format(&parsed)?;
+            // 含空白的位置参数说明 shell 已去掉引号，重新包上引号作为短语
+            let query_text = parsed
+                .positionals
+                .iter()
+                .map(|p| {
+                    if p.contains(char::is_whitespace) && !p.contains('"') {
+                        format!("\"{}\"", p)
+                    } else {
+                        p.clone()
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+            let query = query_serv::parse_query(&query_text)
+                .map_err(|e| usage_error(format!("查询语法错误: {}", e)))?;
+            let db = open_database()?;
+            match format {
+                OutputFormat::Text => query_serv::show_search_results(&db, &query_text)?,
+                _ => output::print_records(&query_serv::search_todos(&db, &query)?, format)?,
+            }
+        }
+        "reindex" => {
+            parsed.allow_only(&[])?;
+            parsed.expect_positionals(0, "project todo reindex")?;
+            let db = open_database()?;
+            query_serv::rebuild_search_index(&db)?;
+        }
+        "status" => {
+            parsed.allow_only(&["cascade"])?;
+            parsed.expect_positionals(2, "project todo status <ID> <todo|in_progress|blocked|done|cancelled> [--cascade]")?;
+            let id = parse_id(&parsed.positionals[0], "待办事项")?;
+            let status = parse_status(&parsed.positionals[1])?;
+            let db = open_database()?;
+            require_todo(&db, id)?;
+            todo_list_serv::set_status(&db, id, status, parsed.switch("cascade"))?;
+        }
+        _ => return Err(usage_error(format!("未知的 todo 动作: '{}'", action))),
+    }
+
+    Ok(())
+}
+
+/// note 命令组
+fn note_command(args: &[String]) -> AnyResult<()> {
+    let (action, parsed) = split_action(args, "note", &[])?;
+
+    match action.as_str() {
+        "list" => {
+            parsed.allow_only(&["todo", "format"])?;
+            parsed.expect_positionals(0, "project note list --todo <待办ID> [--format ..]")?;
+            let todo_id = parse_id(&parsed.required_flag("todo")?, "待办事项")?;
+            let format = output_format(&parsed)?;
+            let db = open_database()?;
+            require_todo(&db, todo_id)?;
+            match format {
+                OutputFormat::Text => note_serv::show_notes_for_todo(&db, todo_id)?,
+                _ => output::print_records(&note_serv::list_notes_for_todo(&db, todo_id)?, format)?,
+            }
+        }
+        "add" => {
+            parsed.allow_only(&["todo", "title", "content", "priority", "tag"])?;
+            parsed.expect_positionals(
+                0,
+                "project note add --todo <待办ID> --title <标题> --content <内容> [--priority ..] [--tag ..]",
+            )?;
+            let todo_id = parse_id(&parsed.required_flag("todo")?, "待办事项")?;
+            let note_title = parsed.required_flag("title")?;
+            let note_content = parsed.required_flag("content")?;
+
+            let db = open_database()?;
+            require_todo(&db, todo_id)?;
+
+            let note = NoteForm {
+                id: 0,
+                todo_id,
+                note_title,
+                note_content,
+                note_time: Utc::now(),
+                noter: None,
+                note_type: None,
+                note_status: None,
+                note_tag: parsed.flag("tag").and_then(optional_text),
+                note_priority: parsed.flag("priority").and_then(optional_text),
+            };
+            note_serv::add_note(&db, &note)?;
+        }
+        "update" => {
+            parsed.allow_only(&["title", "content", "priority", "tag"])?;
+            parsed.expect_positionals(1, "project note update <笔记ID> [--title ..] [--content ..] [...]")?;
+            if parsed.flags.is_empty() {
+                return Err(usage_error("至少需要指定一个要更新的字段"));
+            }
+
+            let id = parse_id(&parsed.positionals[0], "笔记")?;
+            let db = open_database()?;
+            let mut note = require_note(&db, id)?;
+
+            if let Some(title) = parsed.flag("title") {
+                if title.trim().is_empty() {
+                    return Err(usage_error("笔记标题不能为空"));
+                }
+                note.note_title = title;
+            }
+            if let Some(content) = parsed.flag("content") {
+                note.note_content = content;
+            }
+            if let Some(priority) = parsed.flag("priority") {
+                note.note_priority = optional_text(priority);
+            }
+            if let Some(tag) = parsed.flag("tag") {
+                note.note_tag = optional_text(tag);
+            }
+
+            note.note_time = Utc::now();
+            note_serv::update_note(&db, &note)?;
+        }
+        "delete" => {
+            parsed.allow_only(&[])?;
+            parsed.expect_positionals(1, "project note delete <笔记ID>")?;
+            let id = parse_id(&parsed.positionals[0], "笔记")?;
+            let db = open_database()?;
+            require_note(&db, id)?;
+            note_serv::delete_note(&db, id)?;
+        }
+        _ => return Err(usage_error(format!("未知的 note 动作: '{}'", action))),
+    }
+
+    Ok(())
+}
+
+/// review 命令组
+fn review_command(args: &[String]) -> AnyResult<()> {
+    let (action, parsed) = split_action(args, "review", &[])?;
+    parsed.expect_positionals(0, "project review <stats|completed|pending|tags|notes|trends> [--format ..]")?;
+    let format = output_format(&parsed)?;
+
+    match action.as_str() {
+        "stats" => {
+            parsed.allow_only(&["format"])?;
+            let db = open_database()?;
+            match format {
+                OutputFormat::Text => review_serv::show_statistics(&db)?,
+                _ => output::print_records(&[review_serv::collect_statistics(&db)?], format)?,
+            }
+        }
+        "completed" => {
+            parsed.allow_only(&["format"])?;
+            let db = open_database()?;
+            match format {
+                OutputFormat::Text => review_serv::show_completed_todos(&db)?,
+                _ => output::print_records(&review_serv::list_completed_todos(&db)?, format)?,
+            }
+        }
+        "pending" => {
+            parsed.allow_only(&["format"])?;
+            let db = open_database()?;
+            match format {
+                OutputFormat::Text => review_serv::show_pending_todos(&db)?,
+                _ => output::print_records(&review_serv::list_pending_todos(&db)?, format)?,
+            }
+        }
+        "tags" => {
+            parsed.allow_only(&["format"])?;
+            let db = open_database()?;
+            match format {
+                OutputFormat::Text => review_serv::show_tags(&db)?,
+                _ => output::print_records(&review_serv::list_tags(&db)?, format)?,
+            }
+        }
+        "notes" => {
+            parsed.allow_only(&["limit", "format"])?;
+            let limit = match parsed.flag("limit") {
+                Some(value) => value
+                    .trim()
+                    .parse::<usize>()
+                    .map_err(|_| usage_error(format!("无效的数量: '{}'", value)))?,
+                None => 10,
+            };
+            let db = open_database()?;
+            match format {
+                OutputFormat::Text => review_serv::show_recent_notes(&db, limit)?,
+                _ => {
+                    let notes: Vec<_> = review_serv::collect_recent_notes(&db, limit)?
+                        .into_iter()
+                        .map(|(_, _, note)| note)
+                        .collect();
+                    output::print_records(&notes, format)?;
+                }
+            }
+        }
+        "trends" => {
+            parsed.allow_only(&["days", "by", "format"])?;
+            let days = match parsed.flag("days") {
+                Some(value) => value
+                    .trim()
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|d| (1..=analytics_serv::MAX_DAYS).contains(d))
+                    .ok_or_else(|| {
+                        usage_error(format!("无效的天数: '{}'，应在 1 到 {} 之间", value, analytics_serv::MAX_DAYS))
+                    })?,
+                None => analytics_serv::DEFAULT_DAYS,
+            };
+            let period = match parsed.flag("by") {
+                Some(value) => analytics_serv::TrendPeriod::from_string(&value)
+                    .ok_or_else(|| usage_error(format!("无效的统计粒度: '{}'，可选 day / week", value)))?,
+                None => analytics_serv::TrendPeriod::Day,
+            };
+            let db = open_database()?;
+            analytics_serv::print_trends(&db, days, period, format)?;
+        }
+        _ => return Err(usage_error(format!("未知的 review 动作: '{}'", action))),
+    }
+
+    Ok(())
+}
+
+/// reminder 命令组
+fn reminder_command(args: &[String]) -> AnyResult<()> {
+    let (action, parsed) = split_action(args, "reminder", &["all"])?;
+
+    match action.as_str() {
+        "history" => {
+            parsed.allow_only(&["format"])?;
+            parsed.expect_positionals(0, "project reminder history [--format ..]")?;
+            let format = output_format(&parsed)?;
+            let db = open_database()?;
+            match format {
+                OutputFormat::Text => ReminderService::show_history(&db)?,
+                _ => output::print_records(&ReminderService::list_history(&db)?, format)?,
+            }
+        }
+        "rules" => {
+            parsed.allow_only(&[])?;
+            parsed.expect_positionals(0, "project reminder rules")?;
+            ReminderService::show_rules(&open_json_config()?)?;
+        }
+        "rule-add" => {
+            parsed.allow_only(&["type", "offset", "repeat", "message", "id"])?;
+            parsed.expect_positionals(0, "project reminder rule-add --type <类型> [--offset 2h] [--repeat 1d] [--message ..]")?;
+            let rule_type = parsed.required_flag("type")?;
+            let kind = ReminderKind::from_string(&rule_type)
+                .ok_or_else(|| usage_error(format!("未知的规则类型: '{}'", rule_type)))?;
+            let offset = match parsed.flag("offset") {
+                Some(value) => parse_duration_flag(&value, "offset")?,
+                None => 0,
+            };
+            let repeat_seconds = match parsed.flag("repeat") {
+                Some(value) => Some(parse_duration_flag(&value, "repeat")?),
+                None => None,
+            };
+            let rule = ReminderRule {
+                id: parsed.flag("id").and_then(optional_text),
+                rule_type: kind.to_string(),
+                seconds_before: kind.is_before().then_some(offset),
+                seconds_after: (!kind.is_before() && offset > 0).then_some(offset),
+                repeat_seconds,
+                message_template: parsed
+                    .flag("message")
+                    .and_then(optional_text)
+                    .unwrap_or_else(|| format!("🔔 任务「{{title}}」(ID:{{id}}) {}", kind.label())),
+            };
+            rule.validate().map_err(|e| usage_error(format!("提醒规则无效: {}", e)))?;
+            ReminderService::add_rule(&open_json_config()?, rule)?;
+        }
+        "rule-remove" => {
+            parsed.allow_only(&[])?;
+            parsed.expect_positionals(1, "project reminder rule-remove <序号|规则标识>")?;
+            ReminderService::remove_rule(&open_json_config()?, &parsed.positionals[0])?;
+        }
+        "sinks" => {
+            parsed.allow_only(&[])?;
+            parsed.expect_positionals(0, "project reminder sinks")?;
+            ReminderService::show_sinks(&open_json_config()?)?;
+        }
+        "sink-test" => {
+            parsed.allow_only(&[])?;
+            if parsed.positionals.len() > 1 {
+                return Err(usage_error("用法: project reminder sink-test [<渠道名称>]"));
+            }
+            ReminderService::test_sinks(&open_json_config()?, parsed.positionals.first().map(String::as_str))?;
+        }
+        "recent" => {
+            parsed.allow_only(&["all", "format"])?;
+            parsed.expect_positionals(0, "project reminder recent [--all] [--format ..]")?;
+            let format = output_format(&parsed)?;
+            let db = open_database()?;
+            let include_acknowledged = parsed.switch("all");
+            match format {
+                OutputFormat::Text => ReminderService::show_recent(&db, include_acknowledged)?,
+                _ => output::print_records(&ReminderService::list_recent(&db, include_acknowledged)?, format)?,
+            }
+        }
+        "snooze" => {
+            parsed.allow_only(&["for"])?;
+            parsed.expect_positionals(1, "project reminder snooze <提醒ID> [--for 30m]")?;
+            let id = parse_reminder_id(&parsed.positionals[0])?;
+            let db = open_database()?;
+            let seconds = match parsed.flag("for") {
+                Some(value) => parse_duration_flag(&value, "for")?,
+                None => ReminderService::get_reminder_config(&open_json_config()?)?.snooze_minutes.max(1) as i64 * 60,
+            };
+            if seconds <= 0 {
+                return Err(usage_error("--for 的时长必须大于 0"));
+            }
+            require_reminder(&db, id)?;
+            ReminderService::snooze_reminder(&db, id, chrono::Duration::seconds(seconds))?;
+        }
+        "ack" => {
+            parsed.allow_only(&[])?;
+            parsed.expect_positionals(1, "project reminder ack <提醒ID|all>")?;
+            let db = open_database()?;
+            let target = parsed.positionals[0].trim();
+            if target.eq_ignore_ascii_case("all") {
+                ReminderService::acknowledge_all(&db)?;
+            } else {
+                let id = parse_reminder_id(target)?;
+                require_reminder(&db, id)?;
+                ReminderService::acknowledge_reminder(&db, id)?;
+            }
+        }
+        _ => return Err(usage_error(format!("未知的 reminder 动作: '{}'", action))),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn switches_are_per_command() {
+        // trash 中 --note 是开关，其后的参数仍是位置参数
+        let parsed = ParsedArgs::parse(&args(&["--note", "3"]), &["note"]).unwrap();
+        assert!(parsed.switch("note"));
+        assert_eq!(parsed.positionals, vec!["3"]);
+
+        // 其他命令中同名选项带参数值
+        let parsed = ParsedArgs::parse(&args(&["--note", "会议纪要", "--cascade"]), &["cascade"]).unwrap();
+        assert_eq!(parsed.flag("note").as_deref(), Some("会议纪要"));
+        assert!(parsed.switch("cascade") && parsed.positionals.is_empty());
+
+        assert!(ParsedArgs::parse(&args(&["--note"]), &[]).is_err());
+        assert!(!ParsedArgs::parse(&args(&["--cascade=false"]), &["cascade"]).unwrap().switch("cascade"));
+    }
+}
@@ -0,0 +1,96 @@
+use crate::data::backup::SnapshotKind;
+use crate::init::database::Database;
+use crate::init::db_json::JsonConfig;
+use crate::service::backup_serv;
+use crate::service::output::OutputFormat;
+use anyhow::Result as AnyResult;
+use std::io::{self, Write};
+
+/// 读取一行输入
+fn read_input(prompt: &str) -> AnyResult<String> {
+    print!("{}", prompt);
+    io::stdout().flush()?;
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    Ok(input.trim().to_string())
+}
+
+/// 数据库备份（交互式）
+pub fn backup_data(db: &Database, json_config: &JsonConfig) -> AnyResult<()> {
+    let config = backup_serv::get_backup_config(json_config)?;
+    println!("💾 数据库备份");
+    println!("{}", "=".repeat(60));
+    println!("  1. 立即备份");
+    println!("  2. 查看快照");
+    println!("  3. 完整性检查（当前数据库与全部快照）");
+    println!("  4. 清理过期的自动快照");
+    println!("  5. 取消");
+
+    match read_input("请输入选项 (1-5): ")?.as_str() {
+        "1" => {
+            let snapshot = backup_serv::create_snapshot(db, &config, SnapshotKind::Manual)?;
+            println!("✅ 已创建快照: {}", snapshot.path.display());
+        }
+        "2" => backup_serv::print_snapshots(db, &config, OutputFormat::Text)?,
+        "3" => {
+            let results = backup_serv::verify(db, &config, None)?;
+            if backup_serv::print_verify(&results) {
+                println!("✅ 全部通过完整性检查");
+            } else {
+                println!("⚠️  发现问题，可使用 'restore' 从完好的快照恢复");
+            }
+        }
+        "4" => {
+            let candidates = backup_serv::prune(db, &config, true)?;
+            if candidates.is_empty() {
+                println!("✓ 没有过期的自动快照（保留 {} 天 / {} 周）", config.keep_daily, config.keep_weekly);
+                return Ok(());
+            }
+            for snapshot in &candidates {
+                println!("  🗑️  {}", snapshot.name);
+            }
+            let confirm = read_input(&format!("确认删除以上 {} 个自动快照？(y/N): ", candidates.len()))?;
+            if !confirm.eq_ignore_ascii_case("y") {
+                println!("操作已取消");
+                return Ok(());
+            }
+            let removed = backup_serv::prune(db, &config, false)?;
+            println!("✅ 已删除 {} 个过期的自动快照", removed.len());
+        }
+        _ => println!("操作已取消"),
+    }
+    Ok(())
+}
+
+/// 从快照恢复数据库（交互式）
+pub fn restore_data(db: &Database, json_config: &JsonConfig) -> AnyResult<()> {
+    let config = backup_serv::get_backup_config(json_config)?;
+    let snapshots = backup_serv::list_snapshots(db, &config)?;
+    backup_serv::print_snapshots(db, &config, OutputFormat::Text)?;
+    if snapshots.is_empty() {
+        return Ok(());
+    }
+
+    let reference = read_input("\n请输入要恢复的快照序号或文件名（直接回车取消）: ")?;
+    if reference.is_empty() {
+        println!("操作已取消");
+        return Ok(());
+    }
+    let Some(snapshot) = backup_serv::find_snapshot(db, &config, &reference)? else {
+        println!("❌ 快照 '{}' 不存在", reference);
+        return Ok(());
+    };
+
+    println!("⚠️  当前数据库将被替换为 {} 时的数据", snapshot.name);
+    println!("   当前数据会先保存为恢复前快照，可随时恢复回来");
+    let confirm = read_input("确认恢复？(y/N): ")?;
+    if !confirm.eq_ignore_ascii_case("y") {
+        println!("操作已取消");
+        return Ok(());
+    }
+
+    let pre_restore = backup_serv::restore(db, &config, &snapshot)?;
+    println!("✅ 已从快照 {} 恢复数据库", snapshot.name);
+    println!("💾 恢复前的数据已保存为快照: {}", pre_restore.name);
+    Ok(())
+}
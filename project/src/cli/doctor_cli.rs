@@ -0,0 +1,28 @@
+use crate::data::doctor::Repair;
+use crate::init::database::Database;
+use crate::service::doctor_serv;
+use crate::service::output::OutputFormat;
+use anyhow::Result as AnyResult;
+use std::io::{self, Write};
+
+/// 数据库体检（交互式）：先列出问题，确认后再修复
+pub fn doctor_data(db: &Database) -> AnyResult<()> {
+    let problems = doctor_serv::diagnose(db)?;
+    doctor_serv::print_problems(&problems, OutputFormat::Text)?;
+    if !problems.iter().any(|p| p.repair != Repair::Manual) {
+        return Ok(());
+    }
+
+    print!("是否按上面的方式修复？(y/N): ");
+    io::stdout().flush()?;
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    if !input.trim().eq_ignore_ascii_case("y") {
+        println!("操作已取消");
+        return Ok(());
+    }
+
+    let fixed = doctor_serv::repair(db, &problems)?;
+    println!("🔧 已修复 {} 处数据", fixed);
+    Ok(())
+}
@@ -1,15 +1,23 @@
 use crate::service::help;
 use crate::service::switch;
+use crate::service::timezone_serv;
+use crate::service::change_log_serv;
 use crate::init::database;
 use crate::init::db_json;
 use crate::cli::todo_list_cli;
 use crate::cli::review_cli;
 use crate::cli::reminder_cli;
+use crate::cli::transfer_cli;
+use crate::cli::backup_cli;
+use crate::cli::trash_cli;
+use crate::cli::doctor_cli;
+use crate::cli::archive_cli;
+use crate::cli::track_cli;
 use anyhow::Result as AnyResult;
 
 /// 命令分发中心
 /// 这是所有命令的入口，负责：
-/// 1. 处理通用命令（help、switch）
+/// 1. 处理通用命令（help、switch、timezone、export、import、backup、restore、trash、archive、track、doctor、undo、redo、history）
 /// 2. 根据当前 mode 将其他命令分发到对应的模块
 pub fn distribute_command(
     command: &str,
@@ -26,6 +34,67 @@ pub fn distribute_command(
             switch::switch_mode(json_config)?;
             return Ok(());
         }
+        "timezone" => {
+            timezone_serv::change_timezone(json_config)?;
+            return Ok(());
+        }
+        "export" => {
+            transfer_cli::export_data(db, json_config)?;
+            return Ok(());
+        }
+        "import" => {
+            transfer_cli::import_data(db)?;
+            return Ok(());
+        }
+        "backup" => {
+            backup_cli::backup_data(db, json_config)?;
+            return Ok(());
+        }
+        "restore" => {
+            backup_cli::restore_data(db, json_config)?;
+            return Ok(());
+        }
+        "trash" => {
+            trash_cli::trash_data(db, json_config)?;
+            return Ok(());
+        }
+        "archive" => {
+            archive_cli::archive_data(db, json_config)?;
+            return Ok(());
+        }
+        "track" => {
+            track_cli::track_data(db)?;
+            return Ok(());
+        }
+        "doctor" | "fsck" => {
+            doctor_cli::doctor_data(db)?;
+            return Ok(());
+        }
+        "undo" => {
+            match change_log_serv::undo(db)? {
+                Some(change) => println!("↩️  已撤销: {}", change.action),
+                None => println!("💡 没有可以撤销的操作"),
+            }
+            return Ok(());
+        }
+        "redo" => {
+            match change_log_serv::redo(db)? {
+                Some(change) => println!("↪️  已重做: {}", change.action),
+                None => println!("💡 没有可以重做的操作"),
+            }
+            return Ok(());
+        }
+        "history" => {
+            change_log_serv::show_recent_changes(db, 20)?;
+            return Ok(());
+        }
+        cmd if cmd.starts_with("history ") => {
+            match cmd["history ".len()..].trim().parse::<i32>() {
+                Ok(id) => change_log_serv::show_todo_history(db, id)?,
+                Err(_) => println!("❌ 用法: history [<待办ID>]"),
+            }
+            return Ok(());
+        }
         _ => {
             // 不是通用命令，根据 mode 分发
         }
@@ -2,4 +2,10 @@ pub mod todo_list_cli;
 pub mod review_cli;
 pub mod help_distribute;
 pub mod reminder_cli;
-
+pub mod args_cli;
+pub mod transfer_cli;
+pub mod backup_cli;
+pub mod trash_cli;
+pub mod doctor_cli;
+pub mod archive_cli;
+pub mod track_cli;
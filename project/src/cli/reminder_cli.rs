@@ -2,6 +2,8 @@ use crate::init::database::Database;
 use crate::init::db_json::JsonConfig;
 use crate::init::config_reset;
 use crate::service::reminder_serv;
+use crate::data::reminder::{ReminderKind, parse_duration};
+use crate::init::db_json_content::ReminderRule;
 use anyhow::Result as AnyResult;
 use std::io::{self, Write};
 
@@ -26,6 +28,18 @@ pub fn order_check(order: &str, db: &Database, json_config: &JsonConfig) -> AnyR
         "test-reminder" => {
             test_reminder(db, json_config)?;
         }
+        "reminder-rules" => {
+            manage_reminder_rules(json_config)?;
+        }
+        cmd if let Some(args) = command_args(cmd, "reminder-recent") => {
+            reminder_serv::ReminderService::show_recent(db, args.first() == Some(&"all"))?;
+        }
+        cmd if let Some(args) = command_args(cmd, "reminder-snooze") => {
+            snooze_reminder(db, json_config, &args)?;
+        }
+        cmd if let Some(args) = command_args(cmd, "reminder-ack") => {
+            acknowledge_reminder(db, &args)?;
+        }
         _ => {
             println!("❌ 未知命令: '{}'", order);
             println!("💡 输入 'help' 查看可用命令");
@@ -35,6 +49,82 @@ pub fn order_check(order: &str, db: &Database, json_config: &JsonConfig) -> AnyR
     Ok(())
 }
 
+/// 命令名为 name 时返回其后的参数（如 "reminder-snooze 12 1h"）
+fn command_args<'a>(order: &'a str, name: &str) -> Option<Vec<&'a str>> {
+    let mut words = order.split_whitespace();
+    (words.next() == Some(name)).then(|| words.collect())
+}
+
+/// 读取一行输入
+fn read_input(prompt: &str) -> AnyResult<String> {
+    print!("{}", prompt);
+    io::stdout().flush()?;
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    Ok(input.trim().to_string())
+}
+
+/// 取得提醒 ID：命令中未给出时先列出未确认的提醒再提示输入
+fn reminder_id_arg(db: &Database, arg: Option<&str>) -> AnyResult<Option<String>> {
+    let input = match arg {
+        Some(arg) => arg.to_string(),
+        None => {
+            reminder_serv::ReminderService::show_recent(db, false)?;
+            read_input("提醒 ID: ")?
+        }
+    };
+    Ok((!input.is_empty()).then_some(input))
+}
+
+/// 稍后提醒：reminder-snooze [<ID> [时长]]，时长缺省为配置的 snooze_minutes
+fn snooze_reminder(db: &Database, json_config: &JsonConfig, args: &[&str]) -> AnyResult<()> {
+    let Some(id) = reminder_id_arg(db, args.first().copied())? else {
+        println!("操作已取消");
+        return Ok(());
+    };
+    let Ok(id) = id.parse::<i64>() else {
+        println!("⚠️  无效的提醒 ID: {}", id);
+        return Ok(());
+    };
+
+    let default_minutes = reminder_serv::ReminderService::get_reminder_config(json_config)?.snooze_minutes.max(1);
+    let duration = match args.get(1) {
+        Some(value) => value.to_string(),
+        None => read_input(&format!("稍后多久再提醒（如 10m / 2h / 1d，直接回车为 {} 分钟）: ", default_minutes))?,
+    };
+    let seconds = if duration.is_empty() {
+        default_minutes as i64 * 60
+    } else {
+        match parse_duration(&duration) {
+            Some(seconds) if seconds > 0 => seconds,
+            _ => {
+                println!("⚠️  无效的时长: {}（示例: 30m / 2h / 1d）", duration);
+                return Ok(());
+            }
+        }
+    };
+
+    reminder_serv::ReminderService::snooze_reminder(db, id, chrono::Duration::seconds(seconds))?;
+    Ok(())
+}
+
+/// 确认提醒：reminder-ack [<ID>|all]
+fn acknowledge_reminder(db: &Database, args: &[&str]) -> AnyResult<()> {
+    let Some(id) = reminder_id_arg(db, args.first().copied())? else {
+        println!("操作已取消");
+        return Ok(());
+    };
+    if id.eq_ignore_ascii_case("all") {
+        reminder_serv::ReminderService::acknowledge_all(db)?;
+        return Ok(());
+    }
+    let Ok(id) = id.parse::<i64>() else {
+        println!("⚠️  无效的提醒 ID: {}", id);
+        return Ok(());
+    };
+    reminder_serv::ReminderService::acknowledge_reminder(db, id)
+}
+
 /// 切换提醒功能开关
 fn toggle_reminder(json_config: &JsonConfig) -> AnyResult<()> {
     // 读取当前状态
@@ -97,7 +187,7 @@ fn toggle_reminder(json_config: &JsonConfig) -> AnyResult<()> {
                 15  // 默认值
             } else {
                 match interval_str.parse::<u32>() {
-                    Ok(val) if val >= 1 && val <= 1440 => val,
+                    Ok(val) if (1..=1440).contains(&val) => val,
                     _ => {
                         println!("⚠️  无效的输入，使用默认值 15 分钟");
                         15
@@ -159,7 +249,7 @@ fn toggle_reminder(json_config: &JsonConfig) -> AnyResult<()> {
                 15  // 默认值
             } else {
                 match interval_str.parse::<u32>() {
-                    Ok(val) if val >= 1 && val <= 1440 => val,
+                    Ok(val) if (1..=1440).contains(&val) => val,
                     _ => {
                         println!("⚠️  无效的输入，使用默认值 15 分钟");
                         15
@@ -245,20 +335,22 @@ fn set_notification_type(json_config: &JsonConfig) -> AnyResult<()> {
     println!("当前类型: {}", match current_type.as_str() {
         "console" => "📟 控制台通知（仅显示在终端）",
         "windows" => "🪟 Windows 通知（系统托盘弹窗）",
-        "both" => "🔔 双重通知（控制台 + Windows）",
+        "linux" => "🐧 Linux 桌面通知（D-Bus）",
+        "both" => "🔔 双重通知（控制台 + 系统通知）",
         _ => &current_type,
     });
     println!();
     println!("请选择通知类型:");
     println!("  1. 📟 控制台通知 - 仅在终端显示（适合开发/调试）");
     println!("  2. 🪟 Windows 通知 - 系统托盘弹窗（适合后台定时任务）");
-    println!("  3. 🔔 双重通知 - 同时使用两种方式");
-    println!("  4. 取消");
+    println!("  3. 🐧 Linux 桌面通知 - 通过 D-Bus 弹出，带 \"完成\" / \"稍后提醒\" 按钮");
+    println!("  4. 🔔 双重通知 - 控制台 + 当前平台的系统通知");
+    println!("  5. 取消");
     println!();
-    println!("💡 提示: 定时任务建议使用 Windows 通知，避免弹出控制台窗口");
+    println!("💡 提示: 定时任务建议使用系统通知（Windows / Linux），避免弹出控制台窗口");
     println!();
     
-    print!("请输入选项 (1-4): ");
+    print!("请输入选项 (1-5): ");
     io::stdout().flush()?;
     
     let mut input = String::new();
@@ -268,8 +360,9 @@ fn set_notification_type(json_config: &JsonConfig) -> AnyResult<()> {
     let new_type = match choice {
         "1" => "console",
         "2" => "windows",
-        "3" => "both",
-        "4" => {
+        "3" => "linux",
+        "4" => "both",
+        "5" => {
             println!("操作已取消");
             return Ok(());
         }
@@ -295,15 +388,28 @@ fn set_notification_type(json_config: &JsonConfig) -> AnyResult<()> {
     println!("✅ 通知类型已更新为: {}", match new_type {
         "console" => "📟 控制台通知",
         "windows" => "🪟 Windows 通知",
+        "linux" => "🐧 Linux 桌面通知",
         "both" => "🔔 双重通知",
         _ => new_type,
     });
+    if config_has_sinks(json_config) {
+        println!("⚠️  config.json 中已配置通知渠道 sinks，将以 sinks 为准，通知类型不生效");
+    }
     println!("💡 提示: 您可以使用 'test-reminder' 命令测试通知效果");
     println!();
     
     Ok(())
 }
 
+/// config.json 中是否配置了通知渠道 sinks
+fn config_has_sinks(json_config: &JsonConfig) -> bool {
+    json_config
+        .get_value("reminder")
+        .ok()
+        .and_then(|value| value.get("sinks").and_then(|s| s.as_array()).map(|s| !s.is_empty()))
+        .unwrap_or(false)
+}
+
 /// 显示提醒功能状态
 fn show_reminder_status(json_config: &JsonConfig) -> AnyResult<()> {
     let config = match json_config.get_value("reminder") {
@@ -320,21 +426,26 @@ fn show_reminder_status(json_config: &JsonConfig) -> AnyResult<()> {
     println!("通知类型: {}", match config.notification_type.as_str() {
         "console" => "📟 控制台通知",
         "windows" => "🪟 Windows 通知",
+        "linux" => "🐧 Linux 桌面通知",
         "both" => "🔔 双重通知",
         _ => &config.notification_type,
     });
+    if !config.sinks.is_empty() {
+        println!("通知渠道: 已配置 {} 个（取代通知类型）", config.sinks.len());
+        for sink in &config.sinks {
+            println!("  • {} - {}", sink.display_name(), sink.describe());
+        }
+    }
+    if config.action_wait_seconds > 0 {
+        println!("按钮等待: {} 秒（Linux 桌面通知）", config.action_wait_seconds);
+    } else {
+        println!("按钮等待: 不显示操作按钮");
+    }
+    println!("稍后提醒: 默认 {} 分钟", config.snooze_minutes);
     println!();
     println!("提醒规则:");
     for (i, rule) in config.rules.iter().enumerate() {
-        println!("  {}. {} - {}", 
-            i + 1, 
-            rule.rule_type,
-            if let Some(seconds) = rule.seconds_before {
-                format!("提前{}秒", seconds)
-            } else {
-                "逾期提醒".to_string()
-            }
-        );
+        println!("  {}. {} - {}", i + 1, rule.rule_type, rule.describe());
     }
     println!("{}", "=".repeat(60));
     println!();
@@ -342,6 +453,114 @@ fn show_reminder_status(json_config: &JsonConfig) -> AnyResult<()> {
     Ok(())
 }
 
+/// 管理提醒规则（查看 / 添加 / 删除）
+fn manage_reminder_rules(json_config: &JsonConfig) -> AnyResult<()> {
+    loop {
+        reminder_serv::ReminderService::show_rules(json_config)?;
+        println!("可用操作:");
+        println!("  add    - 添加规则");
+        println!("  delete - 删除规则");
+        println!("  back   - 返回");
+        print!("\n请输入操作: ");
+        io::stdout().flush()?;
+
+        let mut operation = String::new();
+        io::stdin().read_line(&mut operation)?;
+        match operation.trim() {
+            "add" => {
+                if let Some(rule) = read_reminder_rule()? {
+                    reminder_serv::ReminderService::add_rule(json_config, rule)?;
+                }
+            }
+            "delete" => {
+                print!("请输入要删除的规则序号或标识: ");
+                io::stdout().flush()?;
+                let mut selector = String::new();
+                io::stdin().read_line(&mut selector)?;
+                reminder_serv::ReminderService::remove_rule(json_config, &selector)?;
+            }
+            "back" | "" => break,
+            other => println!("❌ 未知操作: '{}'", other),
+        }
+    }
+    Ok(())
+}
+
+/// 交互式读取一条提醒规则
+fn read_reminder_rule() -> AnyResult<Option<ReminderRule>> {
+    println!("请选择规则类型:");
+    println!("  1. before_start - 开始前提醒");
+    println!("  2. before_end   - 截止前提醒");
+    println!("  3. after_start  - 开始后提醒（未完成时）");
+    println!("  4. after_end    - 超过截止时间提醒（未完成时）");
+    print!("请输入选项 (1-4): ");
+    io::stdout().flush()?;
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    let kind = match input.trim() {
+        "1" => ReminderKind::BeforeStart,
+        "2" => ReminderKind::BeforeEnd,
+        "3" => ReminderKind::AfterStart,
+        "4" => ReminderKind::AfterEnd,
+        _ => {
+            println!("⚠️  无效的选项");
+            return Ok(None);
+        }
+    };
+
+    print!("{}多久？(如 30m / 2h / 1d，after 类型可留空表示立即): ", kind.label());
+    io::stdout().flush()?;
+    let mut offset_input = String::new();
+    io::stdin().read_line(&mut offset_input)?;
+    let offset = if offset_input.trim().is_empty() && !kind.is_before() {
+        0
+    } else {
+        match parse_duration(&offset_input) {
+            Some(seconds) => seconds,
+            None => {
+                println!("⚠️  无法识别的时长: {}", offset_input.trim());
+                return Ok(None);
+            }
+        }
+    };
+
+    print!("重复间隔 [可选，如 1d 表示有效期内每天提醒]: ");
+    io::stdout().flush()?;
+    let mut repeat_input = String::new();
+    io::stdin().read_line(&mut repeat_input)?;
+    let repeat_seconds = if repeat_input.trim().is_empty() {
+        None
+    } else {
+        match parse_duration(&repeat_input) {
+            Some(seconds) => Some(seconds),
+            None => {
+                println!("⚠️  无法识别的时长: {}", repeat_input.trim());
+                return Ok(None);
+            }
+        }
+    };
+
+    print!("提醒消息 (可用 {{title}} {{id}} {{time}} {{end}} {{status}} {{priority}}): ");
+    io::stdout().flush()?;
+    let mut message = String::new();
+    io::stdin().read_line(&mut message)?;
+    let message = message.trim();
+    let message_template = if message.is_empty() {
+        format!("🔔 任务「{{title}}」(ID:{{id}}) {}", kind.label())
+    } else {
+        message.to_string()
+    };
+
+    Ok(Some(ReminderRule {
+        id: None,
+        rule_type: kind.to_string(),
+        seconds_before: kind.is_before().then_some(offset),
+        seconds_after: (!kind.is_before() && offset > 0).then_some(offset),
+        repeat_seconds,
+        message_template,
+    }))
+}
+
 /// 清理旧提醒历史
 fn cleanup_reminder_history(db: &Database) -> AnyResult<()> {
     println!("📋 清理提醒历史");
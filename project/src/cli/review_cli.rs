@@ -1,4 +1,8 @@
 use crate::service::review_serv;
+use crate::service::query_serv;
+use crate::service::analytics_serv::{self, TrendPeriod};
+use crate::service::output::OutputFormat;
+use crate::cli::todo_list_cli::{is_search_command, search_query_text};
 use crate::init::database;
 use anyhow::Result as AnyResult;
 
@@ -29,10 +33,38 @@ pub fn order_check(order: &str, db: &database::Database) -> AnyResult<()> {
         "pending" => {
             review_serv::show_pending_todos(db)?;
         }
+        "tags" => {
+            review_serv::show_tags(db)?;
+        }
         "notes" => {
             // 默认显示最近 10 条笔记
             review_serv::show_recent_notes(db, 10)?;
         }
+        cmd if cmd == "trends" || cmd.starts_with("trends ") => {
+            // trends [天数] [day|week]，顺序不限
+            let mut days = analytics_serv::DEFAULT_DAYS;
+            let mut period = TrendPeriod::Day;
+            for arg in cmd.split_whitespace().skip(1) {
+                if let Ok(value) = arg.parse::<u32>() {
+                    if !(1..=analytics_serv::MAX_DAYS).contains(&value) {
+                        println!("❌ 天数应在 1 到 {} 之间", analytics_serv::MAX_DAYS);
+                        return Ok(());
+                    }
+                    days = value;
+                } else if let Some(value) = TrendPeriod::from_string(arg) {
+                    period = value;
+                } else {
+                    println!("❌ 无法识别的参数: '{}'", arg);
+                    println!("💡 用法: trends [天数] [day|week]");
+                    return Ok(());
+                }
+            }
+            analytics_serv::print_trends(db, days, period, OutputFormat::Text)?;
+        }
+        cmd if is_search_command(cmd) => {
+            let query = search_query_text(cmd)?;
+            query_serv::show_search_results(db, &query)?;
+        }
         _ => {
             println!("❌ 未知命令: '{}'", order);
             println!("💡 输入 'help' 查看可用命令");
@@ -1,8 +1,13 @@
 use crate::service::todo_list_serv;
 use crate::service::note_serv;
+use crate::service::query_serv;
+use crate::service::relation_serv;
+use crate::dao::todo_list_dao;
 use crate::init::database;
 use anyhow::Result as AnyResult;
 use crate::data::note::NoteForm;
+use crate::data::todo_list::TodoStatus;
+use crate::data::local_time;
 use chrono::Utc;
 
 // // 命令定义结构
@@ -29,17 +34,17 @@ pub fn order_check(order: &str, db: &database::Database) -> AnyResult<()> {
     // 根据命令执行相应操作
     match order {
         "list" => {
-            todo_list_serv::show_all_todos(&db)?;
+            todo_list_serv::show_all_todos(db, false)?;
         }
         "new" => {
-            todo_list_serv::create_new_todo(&db)?;
+            todo_list_serv::create_new_todo(db)?;
         }
         "delete" => {
             println!("请输入要删除的任务ID:");
             let mut id = String::new();
             std::io::stdin().read_line(&mut id)?;
             let id = id.trim().parse::<i32>()?;
-            todo_list_serv::delete_todo(&db, id)?;
+            todo_list_serv::delete_todo(db, id)?;
         }
         "update" => {
             use crate::dao::todo_list_dao;
@@ -71,17 +76,173 @@ pub fn order_check(order: &str, db: &database::Database) -> AnyResult<()> {
                 todo.description = Some(description.to_string());
             }
 
-            todo_list_serv::update_todo(&db, &todo)?;
+            // 获取新的优先级
+            println!(
+                "请输入新的优先级 高/中/低 (当前: {}, 留空跳过, 输入 - 清除):",
+                todo.priority.map(|p| p.label()).unwrap_or("无")
+            );
+            let mut priority = String::new();
+            std::io::stdin().read_line(&mut priority)?;
+            let priority = priority.trim();
+            if priority == "-" {
+                todo.priority = None;
+            } else if !priority.is_empty() {
+                match todo_list_serv::parse_priority_input(priority) {
+                    Ok(p) => todo.priority = p,
+                    Err(msg) => println!("⚠️  {}，保持原优先级", msg),
+                }
+            }
+
+            // 获取新的标签
+            println!(
+                "请输入新的标签，逗号分隔 (当前: {}, 留空跳过, 输入 - 清除):",
+                if todo.tags.is_empty() { "无".to_string() } else { todo.tags.join(", ") }
+            );
+            let mut tags = String::new();
+            std::io::stdin().read_line(&mut tags)?;
+            let tags = tags.trim();
+            if tags == "-" {
+                todo.tags.clear();
+            } else if !tags.is_empty() {
+                todo.tags = todo_list_serv::parse_tags(tags);
+            }
+
+            // 获取新的开始时间
+            println!(
+                "请输入新的开始时间 (当前: {}, 留空跳过):",
+                local_time::format(&todo.begin_time, "%Y-%m-%d %H:%M:%S")
+            );
+            let mut begin = String::new();
+            std::io::stdin().read_line(&mut begin)?;
+            let begin = begin.trim();
+            if !begin.is_empty() {
+                match todo_list_serv::parse_datetime(begin) {
+                    Ok(dt) => todo.begin_time = dt,
+                    Err(_) => {
+                        todo_list_serv::print_time_format_help();
+                        println!("⚠️  保持原开始时间");
+                    }
+                }
+            }
+
+            // 获取新的结束时间
+            println!(
+                "请输入新的结束时间 (当前: {}, 留空跳过, 输入 - 清除):",
+                todo.end_time
+                    .map(|t| local_time::format(&t, "%Y-%m-%d %H:%M:%S"))
+                    .unwrap_or_else(|| "无".to_string())
+            );
+            let mut end = String::new();
+            std::io::stdin().read_line(&mut end)?;
+            let end = end.trim();
+            if end == "-" {
+                todo.end_time = None;
+            } else if !end.is_empty() {
+                match todo_list_serv::parse_datetime(end) {
+                    Ok(dt) => todo.end_time = Some(dt),
+                    Err(_) => {
+                        todo_list_serv::print_time_format_help();
+                        println!("⚠️  保持原结束时间");
+                    }
+                }
+            }
+
+            // 获取新的重复规则
+            println!(
+                "请输入新的重复规则 (当前: {}, 留空跳过, 输入 - 取消重复):",
+                todo.recurrence.as_ref().map(|r| r.describe()).unwrap_or_else(|| "不重复".to_string())
+            );
+            let mut repeat = String::new();
+            std::io::stdin().read_line(&mut repeat)?;
+            let repeat = repeat.trim();
+            if repeat == "-" {
+                todo.recurrence = None;
+            } else if !repeat.is_empty() {
+                match todo_list_serv::parse_recurrence_input(repeat, todo.begin_time) {
+                    Ok(rule) => todo.recurrence = rule,
+                    Err(msg) => println!("⚠️  {}，保持原重复规则", msg),
+                }
+            }
+
+            todo_list_serv::update_todo(db, &todo)?;
         }
         "toggle" => {
             println!("请输入要切换完成状态的待办事项ID:");
             let mut id = String::new();
             std::io::stdin().read_line(&mut id)?;
             let id = id.trim().parse::<i32>()?;
-            todo_list_serv::toggle_completed(&db, id)?;
+
+            // 即将标记完成且有未完成的子任务时，询问是否级联完成
+            let completing = todo_list_dao::get_todo_by_id(db.get_connection(), id)?
+                .is_some_and(|t| !t.is_done());
+            let cascade = completing && todo_list_serv::ask_cascade(db, id)?;
+            todo_list_serv::toggle_completed(db, id, cascade)?;
+        }
+        "status" => {
+            println!("请输入要修改状态的待办事项ID:");
+            let mut id = String::new();
+            std::io::stdin().read_line(&mut id)?;
+            let id = id.trim().parse::<i32>()?;
+
+            println!("请选择新的状态:");
+            for (index, status) in TodoStatus::ALL.iter().enumerate() {
+                println!("  {}. {} {} ({})", index + 1, status.icon(), status.label(), status.as_str());
+            }
+            let mut input = String::new();
+            std::io::stdin().read_line(&mut input)?;
+            let input = input.trim();
+            let status = input
+                .parse::<usize>()
+                .ok()
+                .and_then(|n| n.checked_sub(1))
+                .and_then(|i| TodoStatus::ALL.get(i).copied())
+                .or_else(|| TodoStatus::from_string(input));
+
+            match status {
+                Some(status) => {
+                    let cascade = status == TodoStatus::Done && todo_list_serv::ask_cascade(db, id)?;
+                    todo_list_serv::set_status(db, id, status, cascade)?;
+                }
+                None => println!("⚠️  无效的状态: '{}'", input),
+            }
         }
         "note" => {
-            handle_note_command(&db)?;
+            handle_note_command(db)?;
+        }
+        "parent" => {
+            println!("请输入子任务ID:");
+            let mut id = String::new();
+            std::io::stdin().read_line(&mut id)?;
+            let id = id.trim().parse::<i32>()?;
+
+            println!("请输入父任务ID (输入 - 取消父任务):");
+            let mut parent = String::new();
+            std::io::stdin().read_line(&mut parent)?;
+            let parent = parent.trim();
+            let parent_id = if parent == "-" { None } else { Some(parent.parse::<i32>()?) };
+            relation_serv::set_parent(db, id, parent_id)?;
+        }
+        "depend" => {
+            println!("请输入待办事项ID:");
+            let mut id = String::new();
+            std::io::stdin().read_line(&mut id)?;
+            let id = id.trim().parse::<i32>()?;
+
+            println!("请输入它依赖的待办事项ID (前加 - 表示移除依赖，如 -3):");
+            let mut target = String::new();
+            std::io::stdin().read_line(&mut target)?;
+            let target = target.trim();
+            match target.strip_prefix('-') {
+                Some(rest) => relation_serv::remove_dependency(db, id, rest.trim().parse::<i32>()?)?,
+                None => relation_serv::add_dependency(db, id, target.parse::<i32>()?)?,
+            }
+        }
+        "reindex" => {
+            query_serv::rebuild_search_index(db)?;
+        }
+        cmd if is_search_command(cmd) => {
+            let query = search_query_text(cmd)?;
+            query_serv::show_search_results(db, &query)?;
         }
         _ => {
             println!("❌ 未知命令: '{}'", order);
@@ -99,6 +260,25 @@ pub fn order_check(order: &str, db: &database::Database) -> AnyResult<()> {
 
 }
 
+/// 是否为查询命令（search / filter，可直接跟查询条件）
+pub fn is_search_command(order: &str) -> bool {
+    let name = order.split_whitespace().next().unwrap_or("");
+    name == "search" || name == "filter"
+}
+
+/// 取出查询命令后的条件，未提供时提示输入
+pub fn search_query_text(order: &str) -> AnyResult<String> {
+    let query = order.split_once(char::is_whitespace).map(|(_, q)| q.trim()).unwrap_or("");
+    if !query.is_empty() {
+        return Ok(query.to_string());
+    }
+
+    println!("请输入查询条件（例如: status:pending tag:work due<2026-11-01 \"关键字\"）:");
+    let mut input = String::new();
+    std::io::stdin().read_line(&mut input)?;
+    Ok(input.trim().to_string())
+}
+
 /// 处理笔记管理命令
 fn handle_note_command(db: &database::Database) -> AnyResult<()> {
     use crate::dao::todo_list_dao;
@@ -113,7 +293,7 @@ fn handle_note_command(db: &database::Database) -> AnyResult<()> {
 
     // 如果输入 list，显示所有 todo
     if input == "list" {
-        todo_list_serv::show_all_todos(db)?;
+        todo_list_serv::show_all_todos(db, false)?;
         println!("\n请输入要管理的待办事项ID:");
         let mut id_input = String::new();
         std::io::stdin().read_line(&mut id_input)?;
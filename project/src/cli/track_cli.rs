@@ -0,0 +1,62 @@
+use crate::init::database::Database;
+use crate::service::output::OutputFormat;
+use crate::service::time_tracking_serv;
+use anyhow::Result as AnyResult;
+use std::io::{self, Write};
+
+/// 读取一行输入
+fn read_input(prompt: &str) -> AnyResult<String> {
+    print!("{}", prompt);
+    io::stdout().flush()?;
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    Ok(input.trim().to_string())
+}
+
+/// 读取待办事项 ID，输入无效时返回 None
+fn read_id(prompt: &str) -> AnyResult<Option<i32>> {
+    let input = read_input(prompt)?;
+    match input.parse::<i32>() {
+        Ok(id) => Ok(Some(id)),
+        Err(_) => {
+            println!("❌ 无效的 ID: '{}'", input);
+            Ok(None)
+        }
+    }
+}
+
+/// 计时（交互式）
+pub fn track_data(db: &Database) -> AnyResult<()> {
+    time_tracking_serv::print_status(db, None, OutputFormat::Text)?;
+
+    println!("  1. 开始计时");
+    println!("  2. 停止计时");
+    println!("  3. 查看待办事项的计时记录");
+    println!("  4. 取消");
+
+    match read_input("请输入选项 (1-4): ")?.as_str() {
+        "1" => {
+            let Some(id) = read_id("请输入待办事项 ID: ")? else {
+                return Ok(());
+            };
+            match time_tracking_serv::start(db, id)? {
+                Some(started) => time_tracking_serv::print_started(&started),
+                None => println!("❌ 未找到ID为 {} 的待办事项", id),
+            }
+        }
+        "2" => match time_tracking_serv::stop(db)? {
+            Some((session, todo)) => time_tracking_serv::print_stopped(&session, &todo),
+            None => println!("💡 当前没有正在进行的计时"),
+        },
+        "3" => {
+            let Some(id) = read_id("请输入待办事项 ID: ")? else {
+                return Ok(());
+            };
+            if !time_tracking_serv::print_status(db, Some(id), OutputFormat::Text)? {
+                println!("❌ 未找到ID为 {} 的待办事项", id);
+            }
+        }
+        _ => println!("操作已取消"),
+    }
+    Ok(())
+}
@@ -0,0 +1,120 @@
+use crate::data::local_time;
+use crate::data::transfer::{TransferFormat, TransferTable};
+use crate::init::database::Database;
+use crate::init::db_json::JsonConfig;
+use crate::service::reminder_serv::ReminderService;
+use crate::service::transfer_serv::{self, ConflictPolicy};
+use anyhow::Result as AnyResult;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// 读取一行输入
+fn read_input(prompt: &str) -> AnyResult<String> {
+    print!("{}", prompt);
+    io::stdout().flush()?;
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    Ok(input.trim().to_string())
+}
+
+/// 导出数据（交互式）
+pub fn export_data(db: &Database, json_config: &JsonConfig) -> AnyResult<()> {
+    println!("📤 导出数据");
+    println!("{}", "=".repeat(60));
+    println!("  1. JSON（完整数据，可再次导入）");
+    println!("  2. CSV（单张表，便于用表格软件打开）");
+    println!("  3. Markdown 待办清单");
+    println!("  4. iCalendar（.ics，可供日历软件订阅，附带提醒）");
+    println!("  5. todo.txt");
+    println!("  6. 取消");
+    let format = match read_input("请输入选项 (1-6): ")?.as_str() {
+        "1" => TransferFormat::Json,
+        "2" => TransferFormat::Csv,
+        "3" => TransferFormat::Markdown,
+        "4" => TransferFormat::Ics,
+        "5" => TransferFormat::TodoTxt,
+        _ => {
+            println!("操作已取消");
+            return Ok(());
+        }
+    };
+
+    let mut table = TransferTable::Todos;
+    if format == TransferFormat::Csv {
+        println!("导出哪张表？ 1. 待办事项  2. 笔记  3. 提醒历史");
+        table = match read_input("请输入选项 (1-3，直接回车为待办事项): ")?.as_str() {
+            "" | "1" => TransferTable::Todos,
+            "2" => TransferTable::Notes,
+            "3" => TransferTable::Reminders,
+            other => {
+                println!("⚠️  无效的选项: {}", other);
+                return Ok(());
+            }
+        };
+    }
+
+    let default_path = format!(
+        "database/export-{}.{}",
+        local_time::now().format("%Y%m%d-%H%M%S"),
+        format.extension()
+    );
+    let path = read_input(&format!("保存到（直接回车为 {}）: ", default_path))?;
+    let path = if path.is_empty() { default_path } else { path };
+    let reminder = ReminderService::get_reminder_config(json_config)?;
+    transfer_serv::export_to_file(db, format, table, &reminder, Path::new(&path))
+}
+
+/// 导入数据（交互式）：先试运行显示报告，确认后再写入
+pub fn import_data(db: &Database) -> AnyResult<()> {
+    println!("📥 导入数据");
+    println!("{}", "=".repeat(60));
+    println!("支持本程序导出的 JSON 文件、待办事项 / 笔记的 CSV 文件、iCalendar（.ics）、todo.txt，");
+    println!("以及 Taskwarrior 的 task export 输出（.json）");
+    let path = read_input("文件路径: ")?;
+    if path.is_empty() {
+        println!("操作已取消");
+        return Ok(());
+    }
+    let path = PathBuf::from(path);
+
+    let format = match TransferFormat::from_path(&path) {
+        Some(TransferFormat::Markdown) => {
+            println!("⚠️  Markdown 仅支持导出");
+            return Ok(());
+        }
+        Some(format) => format,
+        None => TransferFormat::Json,
+    };
+    let mut table = TransferTable::Todos;
+    if format == TransferFormat::Csv {
+        table = match read_input("CSV 的内容？ 1. 待办事项  2. 笔记（直接回车为待办事项）: ")?.as_str() {
+            "" | "1" => TransferTable::Todos,
+            "2" => TransferTable::Notes,
+            other => {
+                println!("⚠️  无效的选项: {}", other);
+                return Ok(());
+            }
+        };
+    }
+
+    let document = transfer_serv::read_import_file(&path, format, table)?;
+    let report = transfer_serv::import_document(db, &document, ConflictPolicy::Skip, true)?;
+    transfer_serv::print_report(&report);
+
+    let mut policy = ConflictPolicy::Skip;
+    if !report.conflicts.is_empty() {
+        let answer = read_input("重复的记录是否仍然导入？(y/n，直接回车为跳过): ")?;
+        if answer.eq_ignore_ascii_case("y") {
+            policy = ConflictPolicy::Duplicate;
+        }
+    }
+    let confirm = read_input("确认导入？(y/n): ")?;
+    if !confirm.eq_ignore_ascii_case("y") {
+        println!("操作已取消");
+        return Ok(());
+    }
+
+    let report = transfer_serv::import_document(db, &document, policy, false)?;
+    transfer_serv::print_report(&report);
+    Ok(())
+}
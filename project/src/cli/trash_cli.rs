@@ -0,0 +1,86 @@
+use crate::data::trash::TrashKind;
+use crate::init::database::Database;
+use crate::init::db_json::JsonConfig;
+use crate::service::output::OutputFormat;
+use crate::service::trash_serv;
+use anyhow::Result as AnyResult;
+use std::io::{self, Write};
+
+/// 读取一行输入
+fn read_input(prompt: &str) -> AnyResult<String> {
+    print!("{}", prompt);
+    io::stdout().flush()?;
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    Ok(input.trim().to_string())
+}
+
+/// 读取要操作的项目 ID，输入无效时返回 None
+fn read_id(prompt: &str) -> AnyResult<Option<i32>> {
+    let input = read_input(prompt)?;
+    match input.parse::<i32>() {
+        Ok(id) => Ok(Some(id)),
+        Err(_) => {
+            println!("❌ 无效的 ID: '{}'", input);
+            Ok(None)
+        }
+    }
+}
+
+/// 回收站（交互式）
+pub fn trash_data(db: &Database, json_config: &JsonConfig) -> AnyResult<()> {
+    let config = trash_serv::get_trash_config(json_config)?;
+    trash_serv::print_trash(db, &config, OutputFormat::Text)?;
+    if trash_serv::list_trash(db, &config)?.is_empty() {
+        return Ok(());
+    }
+
+    println!("  1. 恢复待办事项");
+    println!("  2. 恢复笔记");
+    println!("  3. 彻底删除待办事项");
+    println!("  4. 彻底删除笔记");
+    println!("  5. 清空回收站");
+    println!("  6. 取消");
+
+    let (kind, purge) = match read_input("请输入选项 (1-6): ")?.as_str() {
+        "1" => (TrashKind::Todo, false),
+        "2" => (TrashKind::Note, false),
+        "3" => (TrashKind::Todo, true),
+        "4" => (TrashKind::Note, true),
+        "5" => {
+            let confirm = read_input("⚠️  回收站中的全部项目将被彻底删除，确认清空？(y/N): ")?;
+            if !confirm.eq_ignore_ascii_case("y") {
+                println!("操作已取消");
+                return Ok(());
+            }
+            let removed = trash_serv::purge_expired(db, &config, true, false)?;
+            println!("✅ 已彻底删除 {} 项", removed.len());
+            return Ok(());
+        }
+        _ => {
+            println!("操作已取消");
+            return Ok(());
+        }
+    };
+
+    let Some(id) = read_id(&format!("请输入{} ID: ", kind.label()))? else {
+        return Ok(());
+    };
+    if purge {
+        let confirm = read_input(&format!("⚠️  {} #{} 将被彻底删除，确认？(y/N): ", kind.label(), id))?;
+        if !confirm.eq_ignore_ascii_case("y") {
+            println!("操作已取消");
+            return Ok(());
+        }
+        match trash_serv::purge(db, kind, id)? {
+            Some(item) => println!("✅ 已彻底删除{}「{}」", kind.label(), item.title),
+            None => println!("❌ 回收站中没有ID为 {} 的{}", id, kind.label()),
+        }
+    } else {
+        match trash_serv::restore(db, kind, id)? {
+            Some(item) => println!("♻️  已恢复{}「{}」", kind.label(), item.title),
+            None => println!("❌ 回收站中没有ID为 {} 的{}", id, kind.label()),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,257 @@
+use anyhow::{Result as AnyResult, anyhow};
+use chrono::{DateTime, Utc};
+use rusqlite::types::Value as SqlValue;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension, Row};
+use serde_json::{Map, Value};
+
+use crate::data::change_log::{ChangeEntry, ChangeKind, ChangeOp, ChangeSet, ChangeState, TrackedTable};
+
+const SET_COLUMNS: &str = "id, action, actor, source, kind, target_id, state, created_at";
+const ENTRY_COLUMNS: &str = "id, change_set_id, table_name, operation, todo_id, before_image, after_image";
+
+fn map_set(row: &Row) -> AnyResult<ChangeSet> {
+    let kind: String = row.get("kind")?;
+    let state: String = row.get("state")?;
+    let created_at: String = row.get("created_at")?;
+    Ok(ChangeSet {
+        id: row.get("id")?,
+        action: row.get("action")?,
+        actor: row.get("actor")?,
+        source: row.get("source")?,
+        kind: ChangeKind::from_string(&kind).ok_or_else(|| anyhow!("未知的变更类型: {}", kind))?,
+        target_id: row.get("target_id")?,
+        state: ChangeState::from_string(&state).ok_or_else(|| anyhow!("未知的变更状态: {}", state))?,
+        created_at: DateTime::parse_from_rfc3339(&created_at)?.with_timezone(&Utc),
+    })
+}
+
+fn map_entry(row: &Row) -> AnyResult<ChangeEntry> {
+    let operation: String = row.get("operation")?;
+    let image = |column: &str| -> AnyResult<Option<Map<String, Value>>> {
+        let text: Option<String> = row.get(column)?;
+        Ok(text.map(|t| serde_json::from_str(&t)).transpose()?)
+    };
+    Ok(ChangeEntry {
+        id: row.get("id")?,
+        change_set_id: row.get("change_set_id")?,
+        table_name: row.get("table_name")?,
+        operation: ChangeOp::from_string(&operation).ok_or_else(|| anyhow!("未知的变更操作: {}", operation))?,
+        todo_id: row.get("todo_id")?,
+        before: image("before_image")?,
+        after: image("after_image")?,
+    })
+}
+
+fn query_sets(conn: &Connection, sql: &str, values: impl rusqlite::Params) -> AnyResult<Vec<ChangeSet>> {
+    let mut stmt = conn.prepare(sql)?;
+    let mut rows = stmt.query(values)?;
+    let mut results = Vec::new();
+    while let Some(row) = rows.next()? {
+        results.push(map_set(row)?);
+    }
+    Ok(results)
+}
+
+fn query_entries(conn: &Connection, sql: &str, values: impl rusqlite::Params) -> AnyResult<Vec<ChangeEntry>> {
+    let mut stmt = conn.prepare(sql)?;
+    let mut rows = stmt.query(values)?;
+    let mut results = Vec::new();
+    while let Some(row) = rows.next()? {
+        results.push(map_entry(row)?);
+    }
+    Ok(results)
+}
+
+// 新建变更集并设为当前变更集，之后各表的修改都记录到其中
+pub fn begin_change_set(
+    conn: &Connection,
+    action: &str,
+    actor: &str,
+    source: &str,
+    kind: ChangeKind,
+    target_id: Option<i64>,
+) -> AnyResult<i64> {
+    conn.execute(
+        "INSERT INTO change_sets (action, actor, source, kind, target_id, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![action, actor, source, kind.as_str(), target_id, Utc::now().to_rfc3339()],
+    )?;
+    let id = conn.last_insert_rowid();
+    conn.execute("UPDATE change_context SET change_set_id = ?1 WHERE id = 1", params![id])?;
+    Ok(id)
+}
+
+// 结束当前变更集；没有任何修改时删除该变更集，返回是否有修改
+// 普通修改结束后，之前撤销的修改不能再重做
+pub fn end_change_set(conn: &Connection, id: i64, kind: ChangeKind) -> AnyResult<bool> {
+    conn.execute("UPDATE change_context SET change_set_id = NULL WHERE id = 1", [])?;
+    let count: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM change_log WHERE change_set_id = ?1",
+        params![id],
+        |row| row.get(0),
+    )?;
+    if count == 0 {
+        conn.execute("DELETE FROM change_sets WHERE id = ?1", params![id])?;
+        return Ok(false);
+    }
+    if kind == ChangeKind::Edit {
+        conn.execute(
+            "UPDATE change_sets SET state = 'discarded' WHERE kind = 'edit' AND state = 'undone'",
+            [],
+        )?;
+    }
+    Ok(true)
+}
+
+// 当前正在记录的变更集
+pub fn active_change_set(conn: &Connection) -> AnyResult<Option<i64>> {
+    let id: Option<Option<i64>> = conn
+        .query_row("SELECT change_set_id FROM change_context WHERE id = 1", [], |row| row.get(0))
+        .optional()?;
+    Ok(id.flatten())
+}
+
+// 修改操作说明（导入等操作结束后才知道具体内容）
+pub fn set_action(conn: &Connection, id: i64, action: &str) -> AnyResult<usize> {
+    Ok(conn.execute("UPDATE change_sets SET action = ?1 WHERE id = ?2", params![action, id])?)
+}
+
+// 设置普通修改的状态
+pub fn set_state(conn: &Connection, id: i64, state: ChangeState) -> AnyResult<usize> {
+    Ok(conn.execute("UPDATE change_sets SET state = ?1 WHERE id = ?2", params![state.as_str(), id])?)
+}
+
+// 最近一个尚未撤销的普通修改
+pub fn latest_applied_edit(conn: &Connection) -> AnyResult<Option<ChangeSet>> {
+    let sql = format!(
+        "SELECT {} FROM change_sets WHERE kind = 'edit' AND state = 'applied' ORDER BY id DESC LIMIT 1",
+        SET_COLUMNS
+    );
+    Ok(query_sets(conn, &sql, [])?.pop())
+}
+
+// 最近一次撤销、且仍可重做的普通修改
+pub fn latest_undone_edit(conn: &Connection) -> AnyResult<Option<ChangeSet>> {
+    let sql = format!(
+        r#"SELECT {} FROM change_sets WHERE kind = 'edit' AND state = 'undone'
+           ORDER BY (SELECT MAX(u.id) FROM change_sets u WHERE u.kind = 'undo' AND u.target_id = change_sets.id) DESC
+           LIMIT 1"#,
+        SET_COLUMNS
+    );
+    Ok(query_sets(conn, &sql, [])?.pop())
+}
+
+// 根据 ID 获取变更集
+pub fn get_change_set(conn: &Connection, id: i64) -> AnyResult<Option<ChangeSet>> {
+    let sql = format!("SELECT {} FROM change_sets WHERE id = ?1", SET_COLUMNS);
+    Ok(query_sets(conn, &sql, params![id])?.pop())
+}
+
+// 最近的变更集（最新的在前）
+pub fn recent_change_sets(conn: &Connection, limit: usize) -> AnyResult<Vec<ChangeSet>> {
+    let sql = format!("SELECT {} FROM change_sets ORDER BY id DESC LIMIT ?1", SET_COLUMNS);
+    query_sets(conn, &sql, params![limit as i64])
+}
+
+// 变更集中的全部行变化（按发生顺序）
+pub fn list_entries(conn: &Connection, change_set_id: i64) -> AnyResult<Vec<ChangeEntry>> {
+    let sql = format!("SELECT {} FROM change_log WHERE change_set_id = ?1 ORDER BY id", ENTRY_COLUMNS);
+    query_entries(conn, &sql, params![change_set_id])
+}
+
+// 与某个待办事项有关的全部行变化（按发生顺序）
+pub fn list_entries_for_todo(conn: &Connection, todo_id: i32) -> AnyResult<Vec<ChangeEntry>> {
+    let sql = format!("SELECT {} FROM change_log WHERE todo_id = ?1 ORDER BY id", ENTRY_COLUMNS);
+    query_entries(conn, &sql, params![todo_id])
+}
+
+// 标签名称（标签可能已不存在）
+pub fn tag_name(conn: &Connection, tag_id: i64) -> AnyResult<Option<String>> {
+    Ok(conn
+        .query_row("SELECT name FROM tags WHERE id = ?1", params![tag_id], |row| row.get(0))
+        .optional()?)
+}
+
+// ---- 按整行内容读写（撤销 / 重做） ----
+
+// 表当前的全部列
+fn table_columns(conn: &Connection, table: &TrackedTable) -> AnyResult<Vec<String>> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table.name))?;
+    let columns = stmt.query_map([], |row| row.get::<_, String>(1))?.collect::<Result<_, _>>()?;
+    Ok(columns)
+}
+
+fn to_sql(value: &Value) -> SqlValue {
+    match value {
+        Value::Null => SqlValue::Null,
+        Value::Bool(b) => SqlValue::Integer(*b as i64),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => SqlValue::Integer(i),
+            None => SqlValue::Real(n.as_f64().unwrap_or_default()),
+        },
+        Value::String(s) => SqlValue::Text(s.clone()),
+        other => SqlValue::Text(other.to_string()),
+    }
+}
+
+// 主键条件与参数
+fn key_clause(table: &TrackedTable, image: &Map<String, Value>, first_param: usize) -> AnyResult<(String, Vec<SqlValue>)> {
+    let mut conditions = Vec::new();
+    let mut values = Vec::new();
+    for (index, column) in table.key.iter().enumerate() {
+        let value = image.get(*column).ok_or_else(|| anyhow!("{} 的记录缺少主键列 {}", table.name, column))?;
+        conditions.push(format!("\"{}\" = ?{}", column, first_param + index));
+        values.push(to_sql(value));
+    }
+    Ok((conditions.join(" AND "), values))
+}
+
+// 读取一行的当前内容（按 image 中的主键定位），不存在时返回 None
+pub fn row_image(conn: &Connection, table: &TrackedTable, image: &Map<String, Value>) -> AnyResult<Option<Map<String, Value>>> {
+    let pairs: Vec<String> = table_columns(conn, table)?
+        .iter()
+        .map(|c| format!("'{c}', \"{c}\""))
+        .collect();
+    let (condition, values) = key_clause(table, image, 1)?;
+    let sql = format!("SELECT json_object({}) FROM {} WHERE {}", pairs.join(", "), table.name, condition);
+    let text: Option<String> = conn
+        .query_row(&sql, params_from_iter(values), |row| row.get(0))
+        .optional()?;
+    Ok(text.map(|t| serde_json::from_str(&t)).transpose()?)
+}
+
+// 按整行内容插入（保留原主键）；表中已不存在的列被忽略
+pub fn insert_row(conn: &Connection, table: &TrackedTable, image: &Map<String, Value>) -> AnyResult<()> {
+    let columns = table_columns(conn, table)?;
+    let present: Vec<(&String, &Value)> = image.iter().filter(|(k, _)| columns.contains(k)).collect();
+    let names: Vec<String> = present.iter().map(|(k, _)| format!("\"{}\"", k)).collect();
+    let placeholders: Vec<String> = (1..=present.len()).map(|i| format!("?{}", i)).collect();
+    let sql = format!("INSERT INTO {} ({}) VALUES ({})", table.name, names.join(", "), placeholders.join(", "));
+    conn.execute(&sql, params_from_iter(present.iter().map(|(_, v)| to_sql(v))))?;
+    Ok(())
+}
+
+// 按整行内容更新（按主键定位）
+pub fn update_row(conn: &Connection, table: &TrackedTable, image: &Map<String, Value>) -> AnyResult<()> {
+    let columns = table_columns(conn, table)?;
+    let present: Vec<(&String, &Value)> = image
+        .iter()
+        .filter(|(k, _)| columns.contains(k) && !table.key.contains(&k.as_str()))
+        .collect();
+    if present.is_empty() {
+        return Ok(());
+    }
+    let assignments: Vec<String> = present.iter().enumerate().map(|(i, (k, _))| format!("\"{}\" = ?{}", k, i + 1)).collect();
+    let (condition, key_values) = key_clause(table, image, present.len() + 1)?;
+    let sql = format!("UPDATE {} SET {} WHERE {}", table.name, assignments.join(", "), condition);
+    let mut values: Vec<SqlValue> = present.iter().map(|(_, v)| to_sql(v)).collect();
+    values.extend(key_values);
+    conn.execute(&sql, params_from_iter(values))?;
+    Ok(())
+}
+
+// 删除一行（按 image 中的主键定位）
+pub fn delete_row(conn: &Connection, table: &TrackedTable, image: &Map<String, Value>) -> AnyResult<()> {
+    let (condition, values) = key_clause(table, image, 1)?;
+    conn.execute(&format!("DELETE FROM {} WHERE {}", table.name, condition), params_from_iter(values))?;
+    Ok(())
+}
@@ -0,0 +1,54 @@
+use anyhow::Result as AnyResult;
+use rusqlite::{params, Connection};
+
+// 添加依赖：todo_id 依赖 depends_on_id（被其阻塞），已存在时忽略，返回是否新增
+pub fn add_dependency(conn: &Connection, todo_id: i32, depends_on_id: i32) -> AnyResult<bool> {
+    let rows = conn.execute(
+        "INSERT OR IGNORE INTO todo_dependencies (todo_id, depends_on_id) VALUES (?1, ?2)",
+        params![todo_id, depends_on_id],
+    )?;
+    Ok(rows > 0)
+}
+
+// 删除依赖
+pub fn remove_dependency(conn: &Connection, todo_id: i32, depends_on_id: i32) -> AnyResult<usize> {
+    let rows = conn.execute(
+        "DELETE FROM todo_dependencies WHERE todo_id = ?1 AND depends_on_id = ?2",
+        params![todo_id, depends_on_id],
+    )?;
+    Ok(rows)
+}
+
+// 获取全部依赖关系 (todo_id, depends_on_id)
+pub fn list_dependencies(conn: &Connection) -> AnyResult<Vec<(i32, i32)>> {
+    let mut stmt = conn.prepare("SELECT todo_id, depends_on_id FROM todo_dependencies ORDER BY todo_id, depends_on_id")?;
+    let mut rows = stmt.query([])?;
+    let mut results = Vec::new();
+    while let Some(row) = rows.next()? {
+        results.push((row.get(0)?, row.get(1)?));
+    }
+    Ok(results)
+}
+
+// 获取某个待办事项依赖的待办事项 ID（不包括回收站中的）
+pub fn list_dependencies_of(conn: &Connection, todo_id: i32) -> AnyResult<Vec<i32>> {
+    let mut stmt = conn.prepare(
+        r#"SELECT d.depends_on_id FROM todo_dependencies d JOIN todo_list t ON t.id = d.depends_on_id
+           WHERE d.todo_id = ?1 AND t.deleted_at IS NULL ORDER BY d.depends_on_id"#,
+    )?;
+    let mut rows = stmt.query(params![todo_id])?;
+    let mut results = Vec::new();
+    while let Some(row) = rows.next()? {
+        results.push(row.get(0)?);
+    }
+    Ok(results)
+}
+
+// 删除与某个待办事项相关的全部依赖（无论作为依赖方还是被依赖方）
+pub fn delete_dependencies_by_todo_id(conn: &Connection, todo_id: i32) -> AnyResult<usize> {
+    let rows = conn.execute(
+        "DELETE FROM todo_dependencies WHERE todo_id = ?1 OR depends_on_id = ?1",
+        params![todo_id],
+    )?;
+    Ok(rows)
+}
@@ -0,0 +1,81 @@
+use anyhow::Result as AnyResult;
+use rusqlite::types::Value;
+use rusqlite::{params, Connection};
+
+/// 外键指向不存在的行
+pub struct ForeignKeyViolation {
+    pub table: String,
+    pub row_id: i64,
+    /// 被引用的表
+    pub parent: String,
+    /// 引用列
+    pub column: String,
+    /// 引用列的值
+    pub value: Value,
+}
+
+// 表名、列名加引号
+fn quote(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+// 字符串字面量加引号（表值函数的参数）
+fn quote_literal(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "''"))
+}
+
+// 列出全部外键违例（PRAGMA foreign_key_check），不论外键约束当前是否开启
+pub fn foreign_key_violations(conn: &Connection) -> AnyResult<Vec<ForeignKeyViolation>> {
+    let mut stmt = conn.prepare("PRAGMA foreign_key_check")?;
+    let found: Vec<(String, Option<i64>, String, i64)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))?
+        .collect::<Result<_, _>>()?;
+
+    let mut results = Vec::new();
+    for (table, row_id, parent, fk_id) in found {
+        // WITHOUT ROWID 表没有 rowid，本程序没有这样的表
+        let Some(row_id) = row_id else {
+            continue;
+        };
+        let column: String = conn.query_row(
+            &format!("SELECT \"from\" FROM pragma_foreign_key_list({}) WHERE id = ?1 ORDER BY seq LIMIT 1", quote_literal(&table)),
+            params![fk_id],
+            |row| row.get(0),
+        )?;
+        let value: Value = conn.query_row(
+            &format!("SELECT {} FROM {} WHERE rowid = ?1", quote(&column), quote(&table)),
+            params![row_id],
+            |row| row.get(0),
+        )?;
+        results.push(ForeignKeyViolation { table, row_id, parent, column, value });
+    }
+    Ok(results)
+}
+
+// 某列全部非空的值 (rowid, 值)
+pub fn column_values(conn: &Connection, table: &str, column: &str) -> AnyResult<Vec<(i64, Value)>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT rowid, {0} FROM {1} WHERE {0} IS NOT NULL ORDER BY rowid",
+        quote(column),
+        quote(table)
+    ))?;
+    let values = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<_, _>>()?;
+    Ok(values)
+}
+
+// 按 rowid 删除一行
+pub fn delete_row(conn: &Connection, table: &str, row_id: i64) -> AnyResult<usize> {
+    let rows = conn.execute(&format!("DELETE FROM {} WHERE rowid = ?1", quote(table)), params![row_id])?;
+    Ok(rows)
+}
+
+// 按 rowid 修改一个字段，value 为 None 时置空
+pub fn set_value(conn: &Connection, table: &str, column: &str, row_id: i64, value: Option<&str>) -> AnyResult<usize> {
+    let rows = conn.execute(
+        &format!("UPDATE {} SET {} = ?1 WHERE rowid = ?2", quote(table), quote(column)),
+        params![value, row_id],
+    )?;
+    Ok(rows)
+}
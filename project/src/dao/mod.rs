@@ -3,4 +3,9 @@
 pub mod todo_list_dao;
 pub mod note_dao;
 pub mod reminder_dao;
-
+pub mod tag_dao;
+pub mod search_dao;
+pub mod dependency_dao;
+pub mod change_log_dao;
+pub mod doctor_dao;
+pub mod time_tracking_dao;
@@ -3,6 +3,7 @@ use chrono::{DateTime, Utc};
 use rusqlite::{params, Connection, Row};
 
 use crate::data::note::NoteForm;
+use crate::data::trash::{TrashItem, TrashKind};
 
 // DateTime 和文本互转
 fn datetime_to_text(dt: &DateTime<Utc>) -> String {
@@ -67,11 +68,11 @@ pub fn insert_note(conn: &Connection, form: &NoteForm) -> AnyResult<i64> {
     Ok(conn.last_insert_rowid())
 }
 
-// 根据 ID 获取笔记
+// 根据 ID 获取笔记（不包括回收站中的）
 pub fn get_note_by_id(conn: &Connection, id: i32) -> AnyResult<Option<NoteForm>> {
     let mut stmt = conn.prepare(
         r#"SELECT id, todo_id, note_title, note_content, note_time, noter,
-           note_type, note_status, note_tag, note_priority FROM notes WHERE id = ?1"#,
+           note_type, note_status, note_tag, note_priority FROM notes WHERE id = ?1 AND deleted_at IS NULL"#,
     )?;
     let mut rows = stmt.query(params![id])?;
     if let Some(row) = rows.next()? {
@@ -81,12 +82,12 @@ pub fn get_note_by_id(conn: &Connection, id: i32) -> AnyResult<Option<NoteForm>>
     }
 }
 
-// 获取某个 todo 项目的所有笔记
+// 获取某个 todo 项目的所有笔记（不包括回收站中的）
 pub fn list_notes_by_todo_id(conn: &Connection, todo_id: i32) -> AnyResult<Vec<NoteForm>> {
     let mut stmt = conn.prepare(
         r#"SELECT id, todo_id, note_title, note_content, note_time, noter,
            note_type, note_status, note_tag, note_priority FROM notes 
-           WHERE todo_id = ?1 ORDER BY note_time DESC"#,
+           WHERE todo_id = ?1 AND deleted_at IS NULL ORDER BY note_time DESC"#,
     )?;
     let mut rows = stmt.query(params![todo_id])?;
     let mut results = Vec::new();
@@ -96,6 +97,20 @@ pub fn list_notes_by_todo_id(conn: &Connection, todo_id: i32) -> AnyResult<Vec<N
     Ok(results)
 }
 
+// 获取全部笔记（按 ID 升序，不包括回收站中的）
+pub fn list_notes(conn: &Connection) -> AnyResult<Vec<NoteForm>> {
+    let mut stmt = conn.prepare(
+        r#"SELECT id, todo_id, note_title, note_content, note_time, noter,
+           note_type, note_status, note_tag, note_priority FROM notes WHERE deleted_at IS NULL ORDER BY id"#,
+    )?;
+    let mut rows = stmt.query([])?;
+    let mut results = Vec::new();
+    while let Some(row) = rows.next()? {
+        results.push(map_row(row)?);
+    }
+    Ok(results)
+}
+
 // 更新笔记
 pub fn update_note(conn: &Connection, form: &NoteForm) -> AnyResult<usize> {
     let sql = r#"
@@ -124,19 +139,51 @@ pub fn update_note(conn: &Connection, form: &NoteForm) -> AnyResult<usize> {
             form.id,
         ],
     )?;
-    Ok(rows as usize)
+    Ok(rows)
 }
 
-// 删除笔记
-pub fn delete_note(conn: &Connection, id: i32) -> AnyResult<usize> {
-    let rows = conn.execute("DELETE FROM notes WHERE id = ?1", params![id])?;
-    Ok(rows as usize)
+// 移入回收站，返回是否成功（已在回收站中或不存在时为 false）
+pub fn trash_note(conn: &Connection, id: i32) -> AnyResult<bool> {
+    let rows = conn.execute(
+        "UPDATE notes SET deleted_at = ?1 WHERE id = ?2 AND deleted_at IS NULL",
+        params![datetime_to_text(&Utc::now()), id],
+    )?;
+    Ok(rows > 0)
 }
 
-// 删除某个 todo 项目的所有笔记
-#[allow(dead_code)]
-pub fn delete_notes_by_todo_id(conn: &Connection, todo_id: i32) -> AnyResult<usize> {
-    let rows = conn.execute("DELETE FROM notes WHERE todo_id = ?1", params![todo_id])?;
-    Ok(rows as usize)
+// 从回收站恢复，返回是否成功（不在回收站中时为 false）
+pub fn restore_note(conn: &Connection, id: i32) -> AnyResult<bool> {
+    let rows = conn.execute(
+        "UPDATE notes SET deleted_at = NULL WHERE id = ?1 AND deleted_at IS NOT NULL",
+        params![id],
+    )?;
+    Ok(rows > 0)
+}
+
+// 回收站中单独删除的笔记（最近删除的在前）
+pub fn list_trashed(conn: &Connection) -> AnyResult<Vec<TrashItem>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, todo_id, note_title, deleted_at FROM notes WHERE deleted_at IS NOT NULL ORDER BY deleted_at DESC, id DESC",
+    )?;
+    let mut rows = stmt.query([])?;
+    let mut results = Vec::new();
+    while let Some(row) = rows.next()? {
+        let deleted_at: String = row.get(3)?;
+        results.push(TrashItem {
+            kind: TrashKind::Note,
+            id: row.get(0)?,
+            todo_id: row.get(1)?,
+            title: row.get(2)?,
+            deleted_at: text_to_datetime(&deleted_at)?,
+            purge_at: None,
+        });
+    }
+    Ok(results)
+}
+
+// 彻底删除笔记
+pub fn delete_note(conn: &Connection, id: i32) -> AnyResult<usize> {
+    let rows = conn.execute("DELETE FROM notes WHERE id = ?1", params![id])?;
+    Ok(rows)
 }
 
@@ -1,89 +1,245 @@
-use crate::data::reminder::{ReminderHistory, ReminderType};
-use rusqlite::{Connection, Result};
-use chrono::Utc;
+use crate::data::reminder::{ReminderHistory, ReminderState};
+use rusqlite::{Connection, OptionalExtension, Result, Row};
+use chrono::{DateTime, Utc};
 
-/// 检查是否已经发送过提醒
+const SELECT_COLUMNS: &str = "id, todo_id, reminder_time, reminder_type, notified, occurrence_time, rule_key, trigger_at, \
+     state, snoozed_until, snooze_count, acknowledged_at, message";
+
+fn map_row(row: &Row) -> Result<ReminderHistory> {
+    Ok(ReminderHistory {
+        id: row.get(0)?,
+        todo_id: row.get(1)?,
+        reminder_time: row.get::<_, String>(2)?
+            .parse()
+            .unwrap_or_else(|_| Utc::now()),
+        reminder_type: row.get(3)?,
+        rule_key: row.get(6)?,
+        trigger_time: row.get::<_, Option<i64>>(7)?
+            .and_then(|t| DateTime::from_timestamp(t, 0)),
+        notified: row.get::<_, i32>(4)? == 1,
+        occurrence_time: row.get::<_, Option<String>>(5)?
+            .and_then(|s| s.parse().ok()),
+        state: ReminderState::from_string(&row.get::<_, String>(8)?).unwrap_or(ReminderState::Sent),
+        snoozed_until: row.get::<_, Option<i64>>(9)?
+            .and_then(|t| DateTime::from_timestamp(t, 0)),
+        snooze_count: row.get(10)?,
+        acknowledged_at: row.get::<_, Option<String>>(11)?
+            .and_then(|s| s.parse().ok()),
+        message: row.get(12)?,
+    })
+}
+
+/// 检查某条规则在某个计划触发时间是否已经提醒过
+/// trigger_at 为计划触发时间（Unix 秒），同一规则的重复提醒以它区分
 pub fn has_been_notified(
     conn: &Connection,
     todo_id: i32,
-    reminder_type: &ReminderType,
+    rule_key: &str,
+    trigger_at: i64,
 ) -> Result<bool> {
     let sql = r#"
-        SELECT COUNT(*) 
-        FROM reminder_history 
-        WHERE todo_id = ?1 AND reminder_type = ?2
+        SELECT COUNT(*)
+        FROM reminder_history
+        WHERE todo_id = ?1 AND rule_key = ?2 AND trigger_at = ?3
     "#;
-    
+
     let count: i32 = conn.query_row(
         sql,
-        rusqlite::params![todo_id, reminder_type.to_string()],
+        rusqlite::params![todo_id, rule_key, trigger_at],
         |row| row.get(0),
     )?;
-    
+
     Ok(count > 0)
 }
 
-/// 记录提醒历史
+/// 记录提醒历史，返回记录 ID
+/// occurrence_time 为重复待办事项尚未生成的后续重复的开始时间，普通提醒为 None
 pub fn record_notification(
     conn: &Connection,
     todo_id: i32,
-    reminder_type: &ReminderType,
-) -> Result<()> {
+    rule_type: &str,
+    rule_key: &str,
+    trigger_at: i64,
+    occurrence_time: Option<&DateTime<Utc>>,
+    message: &str,
+) -> Result<i64> {
     let sql = r#"
-        INSERT INTO reminder_history (todo_id, reminder_time, reminder_type, notified)
-        VALUES (?1, ?2, ?3, 1)
+        INSERT INTO reminder_history (todo_id, reminder_time, reminder_type, notified, occurrence_time, rule_key, trigger_at, message)
+        VALUES (?1, ?2, ?3, 1, ?4, ?5, ?6, ?7)
     "#;
-    
+
     conn.execute(
         sql,
         rusqlite::params![
             todo_id,
             Utc::now().to_rfc3339(),
-            reminder_type.to_string(),
+            rule_type,
+            occurrence_time.map(|t| t.to_rfc3339()),
+            rule_key,
+            trigger_at,
+            message,
         ],
     )?;
-    
-    Ok(())
+
+    Ok(conn.last_insert_rowid())
 }
 
-/// 获取所有提醒历史
-pub fn get_all_reminders(conn: &Connection) -> Result<Vec<ReminderHistory>> {
+/// 重复待办事项生成下一次重复后，把 from_todo 上针对尚未生成的重复的提醒记录转移到新生成的 to_todo 上，
+/// 避免同一次重复的提醒在新待办事项上再发一次；针对 occurrence_time 这次重复的记录成为新待办事项的普通提醒
+pub fn move_to_occurrence(
+    conn: &Connection,
+    from_todo: i32,
+    to_todo: i64,
+    occurrence_time: &DateTime<Utc>,
+) -> Result<usize> {
+    conn.execute(
+        r#"
+        UPDATE reminder_history
+        SET todo_id = ?2,
+            occurrence_time = CASE WHEN occurrence_time = ?3 THEN NULL ELSE occurrence_time END
+        WHERE todo_id = ?1 AND occurrence_time IS NOT NULL
+        "#,
+        rusqlite::params![from_todo, to_todo, occurrence_time.to_rfc3339()],
+    )
+}
+
+/// 按 ID 获取提醒记录
+pub fn get_reminder(conn: &Connection, id: i64) -> Result<Option<ReminderHistory>> {
+    let sql = format!("SELECT {} FROM reminder_history WHERE id = ?1", SELECT_COLUMNS);
+    conn.query_row(&sql, [id], map_row).optional()
+}
+
+/// 稍后提醒：到 until（Unix 秒）时再次发送
+pub fn snooze(conn: &Connection, id: i64, until: i64) -> Result<usize> {
+    conn.execute(
+        r#"
+        UPDATE reminder_history
+        SET state = 'snoozed', snoozed_until = ?2, snooze_count = snooze_count + 1, acknowledged_at = NULL
+        WHERE id = ?1
+        "#,
+        rusqlite::params![id, until],
+    )
+}
+
+/// 确认提醒（同时取消尚未到期的稍后提醒）
+pub fn acknowledge(conn: &Connection, id: i64) -> Result<usize> {
+    conn.execute(
+        r#"
+        UPDATE reminder_history
+        SET state = 'acknowledged', snoozed_until = NULL, acknowledged_at = ?2
+        WHERE id = ?1
+        "#,
+        rusqlite::params![id, Utc::now().to_rfc3339()],
+    )
+}
+
+/// 确认全部未确认的提醒，返回确认的条数
+pub fn acknowledge_all(conn: &Connection) -> Result<usize> {
+    conn.execute(
+        r#"
+        UPDATE reminder_history
+        SET state = 'acknowledged', snoozed_until = NULL, acknowledged_at = ?1
+        WHERE state != 'acknowledged'
+        "#,
+        [Utc::now().to_rfc3339()],
+    )
+}
+
+/// 已到稍后提醒时间（不晚于 now，Unix 秒）的提醒
+pub fn due_snoozed(conn: &Connection, now: i64) -> Result<Vec<ReminderHistory>> {
+    let sql = format!(
+        "SELECT {} FROM reminder_history WHERE state = 'snoozed' AND snoozed_until <= ?1 ORDER BY snoozed_until",
+        SELECT_COLUMNS
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map([now], map_row)?;
+    rows.collect()
+}
+
+/// 最早的一个尚未到期的稍后提醒时间（Unix 秒）
+pub fn next_snoozed(conn: &Connection) -> Result<Option<i64>> {
+    conn.query_row(
+        "SELECT MIN(snoozed_until) FROM reminder_history WHERE state = 'snoozed'",
+        [],
+        |row| row.get(0),
+    )
+}
+
+/// 稍后提醒已再次发送（或已不需要发送），恢复为未处理状态
+pub fn finish_snooze(conn: &Connection, id: i64) -> Result<usize> {
+    conn.execute(
+        r#"
+        UPDATE reminder_history
+        SET state = 'sent', snoozed_until = NULL, reminder_time = ?2
+        WHERE id = ?1
+        "#,
+        rusqlite::params![id, Utc::now().to_rfc3339()],
+    )
+}
+
+/// 最近的提醒（不包括回收站中待办事项的），include_acknowledged 为 false 时只返回未确认的
+pub fn recent_reminders(conn: &Connection, limit: usize, include_acknowledged: bool) -> Result<Vec<ReminderHistory>> {
+    let filter = if include_acknowledged { "" } else { "AND state != 'acknowledged'" };
+    let sql = format!(
+        "SELECT {} FROM reminder_history WHERE todo_id NOT IN (SELECT id FROM todo_list WHERE deleted_at IS NOT NULL) {} \
+         ORDER BY reminder_time DESC, id DESC LIMIT ?1",
+        SELECT_COLUMNS, filter
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map([limit as i64], map_row)?;
+    rows.collect()
+}
+
+/// 获取全部提醒历史（按 ID 升序，用于导出）
+pub fn list_all_history(conn: &Connection) -> Result<Vec<ReminderHistory>> {
+    let sql = format!("SELECT {} FROM reminder_history ORDER BY id", SELECT_COLUMNS);
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map([], map_row)?;
+    rows.collect()
+}
+
+/// 写入一条完整的提醒历史（用于导入），返回记录 ID
+pub fn insert_history(conn: &Connection, history: &ReminderHistory) -> Result<i64> {
     let sql = r#"
-        SELECT id, todo_id, reminder_time, reminder_type, notified
-        FROM reminder_history
-        ORDER BY reminder_time DESC
-        LIMIT 100
+        INSERT INTO reminder_history (
+            todo_id, reminder_time, reminder_type, notified, occurrence_time, rule_key, trigger_at,
+            state, snoozed_until, snooze_count, acknowledged_at, message
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
     "#;
-    
-    let mut stmt = conn.prepare(sql)?;
-    let reminder_iter = stmt.query_map([], |row| {
-        Ok(ReminderHistory {
-            id: row.get(0)?,
-            todo_id: row.get(1)?,
-            reminder_time: row.get::<_, String>(2)?
-                .parse()
-                .unwrap_or_else(|_| Utc::now()),
-            reminder_type: row.get(3)?,
-            notified: row.get::<_, i32>(4)? == 1,
-        })
-    })?;
-    
-    let mut reminders = Vec::new();
-    for reminder in reminder_iter {
-        reminders.push(reminder?);
-    }
-    
-    Ok(reminders)
+
+    conn.execute(
+        sql,
+        rusqlite::params![
+            history.todo_id,
+            history.reminder_time.to_rfc3339(),
+            history.reminder_type,
+            history.notified as i32,
+            history.occurrence_time.map(|t| t.to_rfc3339()),
+            history.rule_key,
+            history.trigger_time.map(|t| t.timestamp()),
+            history.state.as_str(),
+            history.snoozed_until.map(|t| t.timestamp()),
+            history.snooze_count,
+            history.acknowledged_at.map(|t| t.to_rfc3339()),
+            history.message,
+        ],
+    )?;
+
+    Ok(conn.last_insert_rowid())
+}
+
+/// 获取所有提醒历史
+pub fn get_all_reminders(conn: &Connection) -> Result<Vec<ReminderHistory>> {
+    recent_reminders(conn, 100, true)
 }
 
 /// 清理旧的提醒历史
 pub fn cleanup_old_history(conn: &Connection, days: i64) -> Result<usize> {
     use chrono::Duration;
     let cutoff_date = Utc::now() - Duration::days(days);
-    
+
     let sql = "DELETE FROM reminder_history WHERE reminder_time <= ?1";
     let deleted = conn.execute(sql, [cutoff_date.to_rfc3339()])?;
-    
+
     Ok(deleted)
 }
-
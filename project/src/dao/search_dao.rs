@@ -0,0 +1,188 @@
+use anyhow::Result as AnyResult;
+use rusqlite::types::Value;
+use rusqlite::{params, Connection};
+
+use crate::data::search::SearchHit;
+
+/// 高亮标记
+const HIGHLIGHT_OPEN: &str = "【";
+const HIGHLIGHT_CLOSE: &str = "】";
+
+/// trigram 分词器只能匹配不少于 3 个字符的词，更短的词退化为 LIKE 扫描
+const TRIGRAM_MIN_CHARS: usize = 3;
+
+/// 片段上下文长度（字符数）
+const SNIPPET_CHARS: usize = 24;
+
+// 转义 LIKE 模式中的通配符
+fn like_pattern(text: &str) -> String {
+    let escaped = text
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{}%", escaped)
+}
+
+// 将关键字包装为 FTS5 短语（双引号内的引号需要重复转义）
+fn fts_phrase(text: &str) -> String {
+    format!("\"{}\"", text.replace('"', "\"\""))
+}
+
+fn uses_fts(text: &str) -> bool {
+    text.chars().count() >= TRIGRAM_MIN_CHARS
+}
+
+// 关键字过滤条件：待办事项自身或其任一笔记包含关键字
+// 供 todo_list_dao::search_todos 使用，要求外层表名为 todo_list
+pub fn text_filter_clause(text: &str, values: &mut Vec<Value>) -> String {
+    if uses_fts(text) {
+        values.push(Value::Text(fts_phrase(text)));
+        "todo_list.id IN (SELECT todo_id FROM search_index WHERE search_index MATCH ?)".to_string()
+    } else {
+        let pattern = like_pattern(text);
+        values.push(Value::Text(pattern.clone()));
+        values.push(Value::Text(pattern));
+        r#"todo_list.id IN (SELECT todo_id FROM search_index
+            WHERE title LIKE ? ESCAPE '\' OR body LIKE ? ESCAPE '\')"#
+            .to_string()
+    }
+}
+
+// 按关键字检索索引，命中任一关键字即返回，按相关度排序
+pub fn search(conn: &Connection, terms: &[String], limit: usize) -> AnyResult<Vec<SearchHit>> {
+    let mut hits: Vec<SearchHit> = Vec::new();
+
+    let fts_terms: Vec<String> = terms.iter().filter(|t| uses_fts(t)).map(|t| fts_phrase(t)).collect();
+    if !fts_terms.is_empty() {
+        let sql = format!(
+            r#"SELECT kind, ref_id, todo_id,
+                   highlight(search_index, 3, '{0}', '{1}'),
+                   snippet(search_index, 4, '{0}', '{1}', '…', {2}),
+                   bm25(search_index)
+               FROM search_index WHERE search_index MATCH ?1
+               ORDER BY bm25(search_index) LIMIT ?2"#,
+            HIGHLIGHT_OPEN, HIGHLIGHT_CLOSE, SNIPPET_CHARS
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let mut rows = stmt.query(params![fts_terms.join(" OR "), limit as i64])?;
+        while let Some(row) = rows.next()? {
+            hits.push(SearchHit {
+                kind: row.get(0)?,
+                ref_id: row.get(1)?,
+                todo_id: row.get(2)?,
+                title: row.get(3)?,
+                snippet: row.get(4)?,
+                rank: row.get(5)?,
+            });
+        }
+    }
+
+    let short_terms: Vec<&String> = terms.iter().filter(|t| !uses_fts(t)).collect();
+    if !short_terms.is_empty() {
+        let conditions: Vec<&str> = short_terms
+            .iter()
+            .map(|_| r#"title LIKE ? ESCAPE '\' OR body LIKE ? ESCAPE '\'"#)
+            .collect();
+        let sql = format!(
+            "SELECT kind, ref_id, todo_id, title, body FROM search_index WHERE {} LIMIT {}",
+            conditions.join(" OR "),
+            limit
+        );
+        let mut values = Vec::new();
+        for term in &short_terms {
+            values.push(like_pattern(term));
+            values.push(like_pattern(term));
+        }
+
+        let mut stmt = conn.prepare(&sql)?;
+        let mut rows = stmt.query(rusqlite::params_from_iter(values))?;
+        while let Some(row) = rows.next()? {
+            let kind: String = row.get(0)?;
+            let ref_id: i32 = row.get(1)?;
+            if hits.iter().any(|h| h.kind == kind && h.ref_id == ref_id) {
+                continue;
+            }
+            let title: String = row.get(3)?;
+            let body: String = row.get(4)?;
+            hits.push(SearchHit {
+                kind,
+                ref_id,
+                todo_id: row.get(2)?,
+                title: highlight_text(&title, &short_terms),
+                snippet: snippet_text(&body, &short_terms),
+                rank: 0.0,
+            });
+        }
+    }
+
+    Ok(hits)
+}
+
+// 重建全文索引，返回索引的记录数
+pub fn rebuild_index(conn: &Connection) -> AnyResult<usize> {
+    conn.execute("DELETE FROM search_index", [])?;
+    let todos = conn.execute(
+        r#"INSERT INTO search_index (rowid, kind, ref_id, todo_id, title, body)
+           SELECT id * 2, 'todo', id, id, title,
+               trim(coalesce(description, '') || ' ' || coalesce(key_message1, '') || ' '
+                   || coalesce(key_message2, '') || ' ' || coalesce(key_message3, ''))
+           FROM todo_list WHERE deleted_at IS NULL"#,
+        [],
+    )?;
+    let notes = conn.execute(
+        r#"INSERT INTO search_index (rowid, kind, ref_id, todo_id, title, body)
+           SELECT id * 2 + 1, 'note', id, todo_id, note_title, note_content
+           FROM notes WHERE deleted_at IS NULL"#,
+        [],
+    )?;
+    Ok(todos + notes)
+}
+
+// 在 text 中查找任一关键字（ASCII 不区分大小写），返回字节区间
+fn find_term(text: &str, from: usize, terms: &[&String]) -> Option<(usize, usize)> {
+    text[from..].char_indices().find_map(|(offset, _)| {
+        let start = from + offset;
+        terms.iter().find_map(|term| {
+            text.get(start..start + term.len())
+                .filter(|s| s.eq_ignore_ascii_case(term))
+                .map(|_| (start, start + term.len()))
+        })
+    })
+}
+
+// 为短关键字手动添加高亮（FTS 命中的记录由 highlight() 处理）
+fn highlight_text(text: &str, terms: &[&String]) -> String {
+    let mut result = String::new();
+    let mut cursor = 0;
+    while let Some((start, end)) = find_term(text, cursor, terms) {
+        result.push_str(&text[cursor..start]);
+        result.push_str(HIGHLIGHT_OPEN);
+        result.push_str(&text[start..end]);
+        result.push_str(HIGHLIGHT_CLOSE);
+        cursor = end;
+    }
+    result.push_str(&text[cursor..]);
+    result
+}
+
+// 截取第一个匹配附近的片段并高亮
+fn snippet_text(text: &str, terms: &[&String]) -> String {
+    let Some((start, _)) = find_term(text, 0, terms) else {
+        return String::new();
+    };
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let center = chars.iter().position(|(i, _)| *i == start).unwrap_or(0);
+    let first = center.saturating_sub(SNIPPET_CHARS / 2);
+    let last = (first + SNIPPET_CHARS).min(chars.len());
+
+    let begin_byte = chars[first].0;
+    let end_byte = chars.get(last).map(|(i, _)| *i).unwrap_or(text.len());
+    let mut snippet = highlight_text(&text[begin_byte..end_byte], terms);
+    if first > 0 {
+        snippet.insert(0, '…');
+    }
+    if last < chars.len() {
+        snippet.push('…');
+    }
+    snippet
+}
@@ -0,0 +1,66 @@
+use anyhow::Result as AnyResult;
+use rusqlite::{params, Connection, OptionalExtension};
+
+/// 标签名称在 SQL 中拼接时使用的分隔符（ASCII 单元分隔符，不会出现在标签名中）
+pub const TAG_SEPARATOR: char = '\u{1f}';
+
+/// 查询某个待办事项全部标签的子查询（按名称排序，以 TAG_SEPARATOR 连接）
+/// 供 todo_list_dao 在 SELECT 中复用，要求外层表名为 todo_list
+pub const TAGS_SUBQUERY: &str = r#"(SELECT group_concat(name, char(31)) FROM (
+        SELECT t.name AS name FROM todo_tags tt JOIN tags t ON t.id = tt.tag_id
+        WHERE tt.todo_id = todo_list.id ORDER BY t.name
+    )) AS tags"#;
+
+/// 将 group_concat 的结果拆分为标签列表
+pub fn split_tags(joined: Option<String>) -> Vec<String> {
+    match joined {
+        Some(s) if !s.is_empty() => s.split(TAG_SEPARATOR).map(|t| t.to_string()).collect(),
+        _ => Vec::new(),
+    }
+}
+
+// 获取标签 ID，不存在时创建
+fn get_or_create_tag(conn: &Connection, name: &str) -> AnyResult<i64> {
+    let existing: Option<i64> = conn
+        .query_row("SELECT id FROM tags WHERE name = ?1", params![name], |row| row.get(0))
+        .optional()?;
+    if let Some(id) = existing {
+        return Ok(id);
+    }
+    conn.execute("INSERT INTO tags (name) VALUES (?1)", params![name])?;
+    Ok(conn.last_insert_rowid())
+}
+
+// 覆盖设置某个待办事项的标签
+pub fn set_tags_for_todo(conn: &Connection, todo_id: i64, tags: &[String]) -> AnyResult<()> {
+    conn.execute("DELETE FROM todo_tags WHERE todo_id = ?1", params![todo_id])?;
+    for tag in tags {
+        let tag_id = get_or_create_tag(conn, tag)?;
+        conn.execute(
+            "INSERT OR IGNORE INTO todo_tags (todo_id, tag_id) VALUES (?1, ?2)",
+            params![todo_id, tag_id],
+        )?;
+    }
+    Ok(())
+}
+
+// 删除某个待办事项的全部标签关联
+pub fn delete_tags_by_todo_id(conn: &Connection, todo_id: i32) -> AnyResult<usize> {
+    let rows = conn.execute("DELETE FROM todo_tags WHERE todo_id = ?1", params![todo_id])?;
+    Ok(rows)
+}
+
+// 列出所有正在使用的标签及其关联的待办数量（按数量降序）
+pub fn list_tag_counts(conn: &Connection) -> AnyResult<Vec<(String, i64)>> {
+    let mut stmt = conn.prepare(
+        r#"SELECT t.name, COUNT(tt.todo_id) AS cnt FROM tags t
+           JOIN todo_tags tt ON tt.tag_id = t.id
+           GROUP BY t.id ORDER BY cnt DESC, t.name"#,
+    )?;
+    let mut rows = stmt.query([])?;
+    let mut results = Vec::new();
+    while let Some(row) = rows.next()? {
+        results.push((row.get(0)?, row.get(1)?));
+    }
+    Ok(results)
+}
@@ -0,0 +1,106 @@
+use anyhow::{Result as AnyResult, anyhow};
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, Row};
+
+use crate::data::time_tracking::{CompletionEvent, CompletionEventKind, TimeSession};
+
+// DateTime 和文本互转
+fn datetime_to_text(dt: &DateTime<Utc>) -> String {
+    dt.to_rfc3339()
+}
+
+fn text_to_datetime(s: &str) -> AnyResult<DateTime<Utc>> {
+    let fixed = DateTime::parse_from_rfc3339(s)?;
+    Ok(fixed.with_timezone(&Utc))
+}
+
+const SESSION_COLUMNS: &str = "s.id, s.todo_id, s.started_at, s.stopped_at";
+
+// 将数据库行映射到 TimeSession
+fn map_session(row: &Row) -> AnyResult<TimeSession> {
+    let started_at: String = row.get("started_at")?;
+    let stopped_at: Option<String> = row.get("stopped_at")?;
+    Ok(TimeSession {
+        id: row.get("id")?,
+        todo_id: row.get("todo_id")?,
+        started_at: text_to_datetime(&started_at)?,
+        stopped_at: stopped_at.as_deref().map(text_to_datetime).transpose()?,
+    })
+}
+
+fn query_sessions(conn: &Connection, sql: &str, values: impl rusqlite::Params) -> AnyResult<Vec<TimeSession>> {
+    let mut stmt = conn.prepare(sql)?;
+    let mut rows = stmt.query(values)?;
+    let mut results = Vec::new();
+    while let Some(row) = rows.next()? {
+        results.push(map_session(row)?);
+    }
+    Ok(results)
+}
+
+// 开始计时，返回计时 ID
+pub fn start_session(conn: &Connection, todo_id: i32, started_at: &DateTime<Utc>) -> AnyResult<i64> {
+    conn.execute(
+        "INSERT INTO time_sessions (todo_id, started_at) VALUES (?1, ?2)",
+        params![todo_id, datetime_to_text(started_at)],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+// 停止计时，返回是否成功（已停止时为 false）
+pub fn stop_session(conn: &Connection, id: i64, stopped_at: &DateTime<Utc>) -> AnyResult<bool> {
+    let rows = conn.execute(
+        "UPDATE time_sessions SET stopped_at = ?1 WHERE id = ?2 AND stopped_at IS NULL",
+        params![datetime_to_text(stopped_at), id],
+    )?;
+    Ok(rows > 0)
+}
+
+// 正在进行的计时（不包括回收站中的待办事项）
+pub fn running_session(conn: &Connection) -> AnyResult<Option<TimeSession>> {
+    let sql = format!(
+        r#"SELECT {} FROM time_sessions s JOIN todo_list t ON t.id = s.todo_id
+           WHERE s.stopped_at IS NULL AND t.deleted_at IS NULL
+           ORDER BY s.started_at DESC LIMIT 1"#,
+        SESSION_COLUMNS
+    );
+    Ok(query_sessions(conn, &sql, [])?.into_iter().next())
+}
+
+// 某个待办事项的全部计时（按开始时间排序）
+pub fn list_sessions_by_todo(conn: &Connection, todo_id: i32) -> AnyResult<Vec<TimeSession>> {
+    let sql = format!(
+        "SELECT {} FROM time_sessions s WHERE s.todo_id = ?1 ORDER BY s.started_at, s.id",
+        SESSION_COLUMNS
+    );
+    query_sessions(conn, &sql, params![todo_id])
+}
+
+// 全部计时（不包括回收站中的待办事项，按开始时间排序）
+pub fn list_sessions(conn: &Connection) -> AnyResult<Vec<TimeSession>> {
+    let sql = format!(
+        r#"SELECT {} FROM time_sessions s JOIN todo_list t ON t.id = s.todo_id
+           WHERE t.deleted_at IS NULL ORDER BY s.started_at, s.id"#,
+        SESSION_COLUMNS
+    );
+    query_sessions(conn, &sql, [])
+}
+
+// 某个待办事项的完成 / 重新打开记录（按时间排序）
+pub fn list_completion_events(conn: &Connection, todo_id: i32) -> AnyResult<Vec<CompletionEvent>> {
+    let mut stmt = conn.prepare(
+        "SELECT todo_id, event, created_at FROM completion_events WHERE todo_id = ?1 ORDER BY created_at, id",
+    )?;
+    let mut rows = stmt.query(params![todo_id])?;
+    let mut results = Vec::new();
+    while let Some(row) = rows.next()? {
+        let event: String = row.get(1)?;
+        let created_at: String = row.get(2)?;
+        results.push(CompletionEvent {
+            todo_id: row.get(0)?,
+            event: CompletionEventKind::from_string(&event).ok_or_else(|| anyhow!("未知的完成记录类型: {}", event))?,
+            created_at: text_to_datetime(&created_at)?,
+        });
+    }
+    Ok(results)
+}
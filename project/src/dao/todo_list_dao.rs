@@ -3,7 +3,13 @@ use anyhow::Result as AnyResult;
 use chrono::{DateTime, Utc};
 use rusqlite::{params, Connection, Row};
 
-use crate::data::todo_list::TodoListForm;
+use crate::dao::{dependency_dao, search_dao, tag_dao};
+use crate::data::local_time;
+use crate::data::query::{CompareOp, HasField, QueryFilter, TimeField, TodoQuery};
+use crate::data::recurrence::RecurrenceRule;
+use crate::data::todo_list::{Priority, TodoListForm, TodoStatus};
+use crate::data::trash::{TrashItem, TrashKind};
+use rusqlite::types::Value;
 
 // 类型转换
 fn datetime_to_text(dt: &DateTime<Utc>) -> String {
@@ -15,23 +21,46 @@ fn text_to_datetime(s: &str) -> AnyResult<DateTime<Utc>> {
     Ok(fixed.with_timezone(&Utc))
 }
 
+// 查询待办事项时使用的列（包含标签子查询）
+fn select_columns() -> String {
+    format!(
+        r#"id, title, description, status, priority, begin_time, end_time,
+           key_message1, key_message2, key_message3, recurrence, series_id, occurrence, parent_id, archived_at, completed_at, {}"#,
+        tag_dao::TAGS_SUBQUERY
+    )
+}
+
+fn text_to_status(s: &str) -> AnyResult<TodoStatus> {
+    TodoStatus::from_string(s).ok_or_else(|| anyhow::anyhow!("未知的待办状态: {}", s))
+}
+
 // 将数据库行映射到TodoListForm
 fn map_row(row: &Row) -> AnyResult<TodoListForm> {
     let id: i32 = row.get("id")?;
     let title: String = row.get("title")?;
     let description: Option<String> = row.get("description").ok();
-    let completed_i: i32 = row.get("completed")?;
+    let status_s: String = row.get("status")?;
+    let priority_s: Option<String> = row.get("priority").ok().flatten();
+    let tags_s: Option<String> = row.get("tags").ok().flatten();
     let begin_time_s: String = row.get("begin_time")?;
     let end_time_s: Option<String> = row.get("end_time").ok();
     let key_message1: Option<String> = row.get("key_message1").ok();
     let key_message2: Option<String> = row.get("key_message2").ok();
     let key_message3: Option<String> = row.get("key_message3").ok();
+    let recurrence_s: Option<String> = row.get("recurrence").ok().flatten();
+    let series_id: Option<i32> = row.get("series_id").ok().flatten();
+    let occurrence: u32 = row.get("occurrence")?;
+    let parent_id: Option<i32> = row.get("parent_id").ok().flatten();
+    let archived_at_s: Option<String> = row.get("archived_at")?;
+    let completed_at_s: Option<String> = row.get("completed_at")?;
 
     Ok(TodoListForm {
         id,
         title,
         description,
-        completed: completed_i != 0,
+        status: text_to_status(&status_s)?,
+        priority: priority_s.as_deref().and_then(Priority::from_string),
+        tags: tag_dao::split_tags(tags_s),
         begin_time: text_to_datetime(&begin_time_s)?,
         end_time: match end_time_s {
             Some(s) => Some(text_to_datetime(&s)?),
@@ -40,41 +69,69 @@ fn map_row(row: &Row) -> AnyResult<TodoListForm> {
         key_message1,
         key_message2,
         key_message3,
+        recurrence: match recurrence_s {
+            Some(s) => Some(RecurrenceRule::parse(&s).map_err(|e| anyhow::anyhow!("重复规则无效: {}", e))?),
+            None => None,
+        },
+        series_id,
+        occurrence,
+        parent_id,
+        archived_at: archived_at_s.as_deref().map(text_to_datetime).transpose()?,
+        completed_at: completed_at_s.as_deref().map(text_to_datetime).transpose()?,
     })
 }
 
+// 重复待办事项所属的系列 ID，新的重复待办事项以自身 ID 作为系列 ID
+fn series_id_of(form: &TodoListForm, id: i64) -> Option<i64> {
+    match form.series_id {
+        Some(series_id) => Some(series_id as i64),
+        None if form.recurrence.is_some() => Some(id),
+        None => None,
+    }
+}
+
 // 插入todo
 pub fn insert_todo(conn: &Connection, form: &TodoListForm) -> AnyResult<i64> {
     let sql = r#"
         INSERT INTO todo_list (
-            title, description, completed, begin_time, end_time,
-            key_message1, key_message2, key_message3
-        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+            title, description, status, priority, begin_time, end_time,
+            key_message1, key_message2, key_message3, recurrence, series_id, occurrence, parent_id, archived_at,
+            completed_at
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)
     "#;
 
-    let completed_i = if form.completed { 1 } else { 0 };
     conn.execute(
         sql,
         params![
             form.title,
             form.description,
-            completed_i,
+            form.status.as_str(),
+            form.priority.map(|p| p.as_str()),
             datetime_to_text(&form.begin_time),
             form.end_time.as_ref().map(datetime_to_text),
             form.key_message1,
             form.key_message2,
             form.key_message3,
+            form.recurrence.as_ref().map(|r| r.to_string()),
+            form.series_id,
+            form.occurrence,
+            form.parent_id,
+            form.archived_at.as_ref().map(datetime_to_text),
+            form.completed_at.filter(|_| form.is_done()).as_ref().map(datetime_to_text),
         ],
     )?;
-    Ok(conn.last_insert_rowid())
+    let id = conn.last_insert_rowid();
+    if form.series_id.is_none() && form.recurrence.is_some() {
+        conn.execute("UPDATE todo_list SET series_id = ?1 WHERE id = ?1", params![id])?;
+    }
+    tag_dao::set_tags_for_todo(conn, id, &form.tags)?;
+    Ok(id)
 }
 
-// 根据id获取todo
+// 根据id获取todo（不包括回收站中的）
 pub fn get_todo_by_id(conn: &Connection, id: i32) -> AnyResult<Option<TodoListForm>> {
-    let mut stmt = conn.prepare(
-        r#"SELECT id, title, description, completed, begin_time, end_time,
-           key_message1, key_message2, key_message3 FROM todo_list WHERE id = ?1"#,
-    )?;
+    let sql = format!("SELECT {} FROM todo_list WHERE id = ?1 AND deleted_at IS NULL", select_columns());
+    let mut stmt = conn.prepare(&sql)?;
     let mut rows = stmt.query(params![id])?;
     if let Some(row) = rows.next()? {
         Ok(Some(map_row(row)?))
@@ -83,12 +140,25 @@ pub fn get_todo_by_id(conn: &Connection, id: i32) -> AnyResult<Option<TodoListFo
     }
 }
 
-// 获取所有todo
+// 获取所有todo（包括已归档的，不包括回收站中的）
 pub fn list_todos(conn: &Connection) -> AnyResult<Vec<TodoListForm>> {
-    let mut stmt = conn.prepare(
-        r#"SELECT id, title, description, completed, begin_time, end_time,
-           key_message1, key_message2, key_message3 FROM todo_list ORDER BY id DESC"#,
-    )?;
+    let sql = format!("SELECT {} FROM todo_list WHERE deleted_at IS NULL ORDER BY id DESC", select_columns());
+    let mut stmt = conn.prepare(&sql)?;
+    let mut rows = stmt.query([])?;
+    let mut results = Vec::new();
+    while let Some(row) = rows.next()? {
+        results.push(map_row(row)?);
+    }
+    Ok(results)
+}
+
+// 获取未归档的todo（不包括回收站中的）
+pub fn list_active_todos(conn: &Connection) -> AnyResult<Vec<TodoListForm>> {
+    let sql = format!(
+        "SELECT {} FROM todo_list WHERE deleted_at IS NULL AND archived_at IS NULL ORDER BY id DESC",
+        select_columns()
+    );
+    let mut stmt = conn.prepare(&sql)?;
     let mut rows = stmt.query([])?;
     let mut results = Vec::new();
     while let Some(row) = rows.next()? {
@@ -97,70 +167,329 @@ pub fn list_todos(conn: &Connection) -> AnyResult<Vec<TodoListForm>> {
     Ok(results)
 }
 
-// 更新todo
+// 将单个查询条件编译为 SQL 片段，参数追加到 values
+fn compile_filter(filter: &QueryFilter, values: &mut Vec<Value>) -> String {
+    match filter {
+        QueryFilter::Status(statuses) => {
+            let placeholders: Vec<&str> = statuses.iter().map(|_| "?").collect();
+            for status in statuses {
+                values.push(Value::Text(status.as_str().to_string()));
+            }
+            format!("status IN ({})", placeholders.join(", "))
+        }
+        QueryFilter::Tag(tag) => {
+            values.push(Value::Text(tag.clone()));
+            r#"EXISTS (SELECT 1 FROM todo_tags tt JOIN tags t ON t.id = tt.tag_id
+                WHERE tt.todo_id = todo_list.id AND t.name = ? COLLATE NOCASE)"#
+                .to_string()
+        }
+        QueryFilter::Priority(Some(priority)) => {
+            values.push(Value::Text(priority.as_str().to_string()));
+            "priority = ?".to_string()
+        }
+        QueryFilter::Priority(None) => "priority IS NULL".to_string(),
+        QueryFilter::Time { field, op, value } => {
+            let column = match field {
+                TimeField::Begin => "begin_time",
+                TimeField::Due => "end_time",
+            };
+            values.push(Value::Text(datetime_to_text(value)));
+            match op {
+                CompareOp::Lt => format!("julianday({}) < julianday(?)", column),
+                CompareOp::Le => format!("julianday({}) <= julianday(?)", column),
+                CompareOp::Gt => format!("julianday({}) > julianday(?)", column),
+                CompareOp::Ge => format!("julianday({}) >= julianday(?)", column),
+                CompareOp::On => {
+                    let next_day = local_time::add_days(value, 1).unwrap_or(*value + chrono::Duration::days(1));
+                    values.push(Value::Text(datetime_to_text(&next_day)));
+                    format!(
+                        "(julianday({0}) >= julianday(?) AND julianday({0}) < julianday(?))",
+                        column
+                    )
+                }
+            }
+        }
+        QueryFilter::Has(HasField::Due) => "end_time IS NOT NULL".to_string(),
+        QueryFilter::Has(HasField::Notes) => {
+            "EXISTS (SELECT 1 FROM notes n WHERE n.todo_id = todo_list.id AND n.deleted_at IS NULL)".to_string()
+        }
+        QueryFilter::Has(HasField::Tags) => {
+            "EXISTS (SELECT 1 FROM todo_tags tt WHERE tt.todo_id = todo_list.id)".to_string()
+        }
+        QueryFilter::Has(HasField::Recurrence) => "recurrence IS NOT NULL".to_string(),
+        QueryFilter::Overdue => {
+            values.push(Value::Text(datetime_to_text(&Utc::now())));
+            "(status IN ('todo', 'in_progress', 'blocked') AND end_time IS NOT NULL AND julianday(end_time) < julianday(?))"
+                .to_string()
+        }
+        QueryFilter::Text(text) => search_dao::text_filter_clause(text, values),
+    }
+}
+
+// 按查询条件检索todo（条件之间为 AND，按 ID 升序，不包括回收站中的）
+pub fn search_todos(conn: &Connection, query: &TodoQuery) -> AnyResult<Vec<TodoListForm>> {
+    let mut values = Vec::new();
+    let mut clauses = vec!["deleted_at IS NULL".to_string()];
+    for term in &query.terms {
+        let clause = compile_filter(&term.filter, &mut values);
+        if term.negated {
+            // NULL 比较视为不满足，取反后应被选中
+            clauses.push(format!("NOT COALESCE({}, 0)", clause));
+        } else {
+            clauses.push(clause);
+        }
+    }
+
+    let sql = format!(
+        "SELECT {} FROM todo_list WHERE {} ORDER BY id",
+        select_columns(),
+        clauses.join(" AND ")
+    );
+
+    let mut stmt = conn.prepare(&sql)?;
+    let mut rows = stmt.query(rusqlite::params_from_iter(values))?;
+    let mut results = Vec::new();
+    while let Some(row) = rows.next()? {
+        results.push(map_row(row)?);
+    }
+    Ok(results)
+}
+
+// 查找重复系列中的第 occurrence 次（回收站中的不算，否则删除一次重复后系列就不再继续）
+pub fn find_occurrence(conn: &Connection, series_id: i32, occurrence: u32) -> AnyResult<Option<i32>> {
+    let mut stmt = conn.prepare(
+        "SELECT id FROM todo_list WHERE series_id = ?1 AND occurrence = ?2 AND deleted_at IS NULL",
+    )?;
+    let mut rows = stmt.query(params![series_id, occurrence])?;
+    match rows.next()? {
+        Some(row) => Ok(Some(row.get(0)?)),
+        None => Ok(None),
+    }
+}
+
+// 更新todo（改为已完成时记录完成时间，重新打开时清空完成时间并取消归档）
 pub fn update_todo(conn: &Connection, form: &TodoListForm) -> AnyResult<usize> {
     let sql = r#"
         UPDATE todo_list SET
             title = ?1,
             description = ?2,
-            completed = ?3,
-            begin_time = ?4,
-            end_time = ?5,
-            key_message1 = ?6,
-            key_message2 = ?7,
-            key_message3 = ?8
-        WHERE id = ?9
+            status = ?3,
+            priority = ?4,
+            begin_time = ?5,
+            end_time = ?6,
+            key_message1 = ?7,
+            key_message2 = ?8,
+            key_message3 = ?9,
+            recurrence = ?10,
+            series_id = ?11,
+            occurrence = ?12,
+            parent_id = ?13,
+            archived_at = CASE WHEN ?3 IN ('done', 'cancelled') THEN archived_at END,
+            completed_at = CASE WHEN ?3 = 'done' THEN (CASE WHEN status = 'done' THEN completed_at ELSE ?15 END) END
+        WHERE id = ?14
     "#;
-    let completed_i = if form.completed { 1 } else { 0 };
     let rows = conn.execute(
         sql,
         params![
             form.title,
             form.description,
-            completed_i,
+            form.status.as_str(),
+            form.priority.map(|p| p.as_str()),
             datetime_to_text(&form.begin_time),
             form.end_time.as_ref().map(datetime_to_text),
             form.key_message1,
             form.key_message2,
             form.key_message3,
+            form.recurrence.as_ref().map(|r| r.to_string()),
+            series_id_of(form, form.id as i64),
+            form.occurrence,
+            form.parent_id,
             form.id,
+            datetime_to_text(&Utc::now()),
         ],
     )?;
-    Ok(rows as usize)
+    if rows > 0 {
+        tag_dao::set_tags_for_todo(conn, form.id as i64, &form.tags)?;
+    }
+    Ok(rows)
+}
+
+// 归档已完成或已取消的待办事项，返回是否成功（未结束、已归档或不存在时为 false）
+pub fn archive_todo(conn: &Connection, id: i32) -> AnyResult<bool> {
+    let rows = conn.execute(
+        r#"UPDATE todo_list SET archived_at = ?1
+           WHERE id = ?2 AND deleted_at IS NULL AND archived_at IS NULL AND status IN ('done', 'cancelled')"#,
+        params![datetime_to_text(&Utc::now()), id],
+    )?;
+    Ok(rows > 0)
+}
+
+// 取消归档，返回是否成功（未归档时为 false）
+pub fn unarchive_todo(conn: &Connection, id: i32) -> AnyResult<bool> {
+    let rows = conn.execute(
+        "UPDATE todo_list SET archived_at = NULL WHERE id = ?1 AND deleted_at IS NULL AND archived_at IS NOT NULL",
+        params![id],
+    )?;
+    Ok(rows > 0)
 }
 
-// 删除todo
+// 未归档的已完成 / 已取消待办事项的结束时间：(ID, 时间)
+// 已完成的取完成时间，其次取修改历史中最后一次改为当前状态的时间；没有修改记录时按截止时间，没有截止时间时按开始时间
+pub fn list_closed_times(conn: &Connection) -> AnyResult<Vec<(i32, DateTime<Utc>)>> {
+    let sql = r#"
+        SELECT t.id, COALESCE(
+            t.completed_at,
+            (SELECT MAX(s.created_at) FROM change_log l JOIN change_sets s ON s.id = l.change_set_id
+             WHERE l.table_name = 'todo_list' AND l.todo_id = t.id
+               AND json_extract(l.after_image, '$.status') = t.status
+               AND (l.before_image IS NULL OR json_extract(l.before_image, '$.status') IS NOT t.status)),
+            t.end_time, t.begin_time)
+        FROM todo_list t
+        WHERE t.deleted_at IS NULL AND t.archived_at IS NULL AND t.status IN ('done', 'cancelled')
+        ORDER BY t.id
+    "#;
+    let mut stmt = conn.prepare(sql)?;
+    let mut rows = stmt.query([])?;
+    let mut results = Vec::new();
+    while let Some(row) = rows.next()? {
+        let closed_at: String = row.get(1)?;
+        results.push((row.get(0)?, text_to_datetime(&closed_at)?));
+    }
+    Ok(results)
+}
+
+// 待办事项的创建时间：(ID, 时间)（不包括回收站中的）
+// 取修改历史中最早新建该行的时间；没有修改记录时按开始时间
+pub fn list_created_times(conn: &Connection) -> AnyResult<Vec<(i32, DateTime<Utc>)>> {
+    let sql = r#"
+        SELECT t.id, COALESCE(
+            (SELECT MIN(s.created_at) FROM change_log l JOIN change_sets s ON s.id = l.change_set_id
+             WHERE l.table_name = 'todo_list' AND l.todo_id = t.id AND l.operation = 'insert'),
+            t.begin_time)
+        FROM todo_list t
+        WHERE t.deleted_at IS NULL
+        ORDER BY t.id
+    "#;
+    let mut stmt = conn.prepare(sql)?;
+    let mut rows = stmt.query([])?;
+    let mut results = Vec::new();
+    while let Some(row) = rows.next()? {
+        let created_at: String = row.get(1)?;
+        results.push((row.get(0)?, text_to_datetime(&created_at)?));
+    }
+    Ok(results)
+}
+
+// 移入回收站，返回是否成功（已在回收站中或不存在时为 false）
+pub fn trash_todo(conn: &Connection, id: i32) -> AnyResult<bool> {
+    let rows = conn.execute(
+        "UPDATE todo_list SET deleted_at = ?1 WHERE id = ?2 AND deleted_at IS NULL",
+        params![datetime_to_text(&Utc::now()), id],
+    )?;
+    Ok(rows > 0)
+}
+
+// 从回收站恢复，返回是否成功（不在回收站中时为 false）
+pub fn restore_todo(conn: &Connection, id: i32) -> AnyResult<bool> {
+    let rows = conn.execute(
+        "UPDATE todo_list SET deleted_at = NULL WHERE id = ?1 AND deleted_at IS NOT NULL",
+        params![id],
+    )?;
+    Ok(rows > 0)
+}
+
+// 回收站中的待办事项（最近删除的在前）
+pub fn list_trashed(conn: &Connection) -> AnyResult<Vec<TrashItem>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, title, deleted_at FROM todo_list WHERE deleted_at IS NOT NULL ORDER BY deleted_at DESC, id DESC",
+    )?;
+    let mut rows = stmt.query([])?;
+    let mut results = Vec::new();
+    while let Some(row) = rows.next()? {
+        let id: i32 = row.get(0)?;
+        let deleted_at: String = row.get(2)?;
+        results.push(TrashItem {
+            kind: TrashKind::Todo,
+            id,
+            todo_id: id,
+            title: row.get(1)?,
+            deleted_at: text_to_datetime(&deleted_at)?,
+            purge_at: None,
+        });
+    }
+    Ok(results)
+}
+
+// 彻底删除todo（子任务提升为顶层任务，相关依赖一并删除，笔记与提醒历史由外键级联删除）
 pub fn delete_todo(conn: &Connection, id: i32) -> AnyResult<usize> {
+    tag_dao::delete_tags_by_todo_id(conn, id)?;
+    dependency_dao::delete_dependencies_by_todo_id(conn, id)?;
+    conn.execute("UPDATE todo_list SET parent_id = NULL WHERE parent_id = ?1", params![id])?;
     let rows = conn.execute("DELETE FROM todo_list WHERE id = ?1", params![id])?;
-    Ok(rows as usize)
+    Ok(rows)
 }
 
 // 标记待办事项为完成
 #[allow(dead_code)]
 pub fn mark_as_completed(conn: &Connection, id: i32) -> AnyResult<usize> {
     let rows = conn.execute(
-        "UPDATE todo_list SET completed = 1 WHERE id = ?1",
-        params![id]
+        "UPDATE todo_list SET status = 'done', completed_at = CASE WHEN status = 'done' THEN completed_at ELSE ?2 END WHERE id = ?1",
+        params![id, datetime_to_text(&Utc::now())]
+    )?;
+    Ok(rows)
+}
+
+// 设置父任务，None 表示取消父任务
+pub fn set_parent(conn: &Connection, id: i32, parent_id: Option<i32>) -> AnyResult<usize> {
+    let rows = conn.execute(
+        "UPDATE todo_list SET parent_id = ?1 WHERE id = ?2",
+        params![parent_id, id]
+    )?;
+    Ok(rows)
+}
+
+// 设置所属重复系列（导入时重新映射系列 ID）
+pub fn set_series_id(conn: &Connection, id: i32, series_id: Option<i32>) -> AnyResult<usize> {
+    let rows = conn.execute(
+        "UPDATE todo_list SET series_id = ?1 WHERE id = ?2",
+        params![series_id, id]
+    )?;
+    Ok(rows)
+}
+
+// 设置待办事项状态（改为已完成时记录完成时间，重新打开时清空完成时间并取消归档）
+pub fn set_status(conn: &Connection, id: i32, status: TodoStatus) -> AnyResult<usize> {
+    let rows = conn.execute(
+        r#"UPDATE todo_list SET
+               status = ?1,
+               archived_at = CASE WHEN ?1 IN ('done', 'cancelled') THEN archived_at END,
+               completed_at = CASE WHEN ?1 = 'done' THEN (CASE WHEN status = 'done' THEN completed_at ELSE ?3 END) END
+           WHERE id = ?2"#,
+        params![status.as_str(), id, datetime_to_text(&Utc::now())]
     )?;
-    Ok(rows as usize)
+    Ok(rows)
 }
 
 // 标记待办事项为未完成
 #[allow(dead_code)]
 pub fn mark_as_pending(conn: &Connection, id: i32) -> AnyResult<usize> {
     let rows = conn.execute(
-        "UPDATE todo_list SET completed = 0 WHERE id = ?1",
+        "UPDATE todo_list SET status = 'todo', archived_at = NULL, completed_at = NULL WHERE id = ?1",
         params![id]
     )?;
-    Ok(rows as usize)
+    Ok(rows)
 }
 
-// 切换待办事项的完成状态
+// 切换待办事项的完成状态（已完成 -> 待办，其余状态 -> 已完成并记录完成时间；重新打开时取消归档）
 pub fn toggle_completed(conn: &Connection, id: i32) -> AnyResult<usize> {
     let rows = conn.execute(
-        "UPDATE todo_list SET completed = 1 - completed WHERE id = ?1",
-        params![id]
+        r#"UPDATE todo_list SET
+               status = CASE WHEN status = 'done' THEN 'todo' ELSE 'done' END,
+               archived_at = CASE WHEN status = 'done' THEN NULL ELSE archived_at END,
+               completed_at = CASE WHEN status = 'done' THEN NULL ELSE ?2 END
+           WHERE id = ?1"#,
+        params![id, datetime_to_text(&Utc::now())]
     )?;
-    Ok(rows as usize)
+    Ok(rows)
 }
 
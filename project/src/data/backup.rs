@@ -0,0 +1,76 @@
+//! 数据库快照
+//!
+//! 快照文件名为 `<数据库名>-YYYYmmdd-HHMMSS-<类型>.db`（本地时间），如 `todo-20261018-090000-auto.db`，
+//! 保存在备份目录（默认为数据库所在目录下的 backups）中。
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::Serialize;
+use std::path::PathBuf;
+
+use crate::data::local_time;
+
+/// 快照类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SnapshotKind {
+    /// 定时自动备份，按保留策略轮换
+    Auto,
+    /// 手动备份，不参与轮换
+    Manual,
+    /// 恢复快照前自动保存的当前数据，不参与轮换
+    PreRestore,
+}
+
+impl SnapshotKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SnapshotKind::Auto => "auto",
+            SnapshotKind::Manual => "manual",
+            SnapshotKind::PreRestore => "pre-restore",
+        }
+    }
+
+    pub fn from_string(s: &str) -> Option<Self> {
+        match s {
+            "auto" => Some(SnapshotKind::Auto),
+            "manual" => Some(SnapshotKind::Manual),
+            "pre-restore" => Some(SnapshotKind::PreRestore),
+            _ => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SnapshotKind::Auto => "自动",
+            SnapshotKind::Manual => "手动",
+            SnapshotKind::PreRestore => "恢复前",
+        }
+    }
+}
+
+/// 一个快照文件
+#[derive(Debug, Clone, Serialize)]
+pub struct Snapshot {
+    pub name: String,
+    pub kind: SnapshotKind,
+    pub created_at: DateTime<Utc>,
+    pub size: u64,
+    #[serde(skip)]
+    pub path: PathBuf,
+}
+
+impl Snapshot {
+    /// 快照文件名
+    pub fn file_name(stem: &str, created_at: &DateTime<Utc>, kind: SnapshotKind) -> String {
+        format!("{}-{}-{}.db", stem, local_time::format(created_at, "%Y%m%d-%H%M%S"), kind.as_str())
+    }
+
+    /// 从文件名解析快照，不是该数据库的快照时返回 None
+    pub fn parse_name(stem: &str, name: &str) -> Option<(DateTime<Utc>, SnapshotKind)> {
+        let rest = name.strip_prefix(stem)?.strip_prefix('-')?.strip_suffix(".db")?;
+        // 时间部分固定为 15 个字符：YYYYmmdd-HHMMSS
+        let (time, kind) = (rest.get(..15)?, rest.get(15..)?.strip_prefix('-')?);
+        let naive = NaiveDateTime::parse_from_str(time, "%Y%m%d-%H%M%S").ok()?;
+        Some((local_time::from_local(&naive)?, SnapshotKind::from_string(kind)?))
+    }
+}
@@ -0,0 +1,153 @@
+//! 变更日志
+//!
+//! 每次修改数据的操作是一个变更集（change set），其中的每一行变化（新增 / 修改 / 删除）
+//! 由触发器写入 change_log，保存修改前后的整行内容（JSON）。
+//! 撤销按相反顺序写回修改前的内容，重做按原顺序写回修改后的内容；撤销与重做本身也作为变更集记录。
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/// 记录变更日志的表
+pub struct TrackedTable {
+    pub name: &'static str,
+    /// 显示名称
+    pub label: &'static str,
+    /// 定位一行的列（主键）
+    pub key: &'static [&'static str],
+    /// 所属待办事项 ID 的列，用于按待办事项查看历史
+    pub todo_column: Option<&'static str>,
+}
+
+/// 记录变更日志的全部表（提醒历史与完成记录由程序自动产生，不记录）
+pub const TRACKED_TABLES: &[TrackedTable] = &[
+    TrackedTable { name: "todo_list", label: "待办事项", key: &["id"], todo_column: Some("id") },
+    TrackedTable { name: "notes", label: "笔记", key: &["id"], todo_column: Some("todo_id") },
+    TrackedTable { name: "tags", label: "标签", key: &["id"], todo_column: None },
+    TrackedTable { name: "todo_tags", label: "标签关联", key: &["todo_id", "tag_id"], todo_column: Some("todo_id") },
+    TrackedTable {
+        name: "todo_dependencies",
+        label: "依赖",
+        key: &["todo_id", "depends_on_id"],
+        todo_column: Some("todo_id"),
+    },
+    TrackedTable { name: "time_sessions", label: "计时", key: &["id"], todo_column: Some("todo_id") },
+];
+
+/// 按表名查找
+pub fn tracked_table(name: &str) -> Option<&'static TrackedTable> {
+    TRACKED_TABLES.iter().find(|t| t.name == name)
+}
+
+/// 变更集的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    /// 普通修改
+    Edit,
+    /// 撤销某个修改
+    Undo,
+    /// 重做某个被撤销的修改
+    Redo,
+}
+
+impl ChangeKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChangeKind::Edit => "edit",
+            ChangeKind::Undo => "undo",
+            ChangeKind::Redo => "redo",
+        }
+    }
+
+    pub fn from_string(s: &str) -> Option<Self> {
+        match s {
+            "edit" => Some(ChangeKind::Edit),
+            "undo" => Some(ChangeKind::Undo),
+            "redo" => Some(ChangeKind::Redo),
+            _ => None,
+        }
+    }
+}
+
+/// 普通修改的状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeState {
+    /// 已生效
+    Applied,
+    /// 已撤销，可以重做
+    Undone,
+    /// 已撤销，之后又有新的修改，不能再重做
+    Discarded,
+}
+
+impl ChangeState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChangeState::Applied => "applied",
+            ChangeState::Undone => "undone",
+            ChangeState::Discarded => "discarded",
+        }
+    }
+
+    pub fn from_string(s: &str) -> Option<Self> {
+        match s {
+            "applied" => Some(ChangeState::Applied),
+            "undone" => Some(ChangeState::Undone),
+            "discarded" => Some(ChangeState::Discarded),
+            _ => None,
+        }
+    }
+}
+
+/// 一次操作（变更集）
+#[derive(Debug, Clone, Serialize)]
+pub struct ChangeSet {
+    pub id: i64,
+    /// 操作说明，如"删除待办事项 #3「周报」"
+    pub action: String,
+    /// 操作者（系统用户名）
+    pub actor: String,
+    /// 操作来源：interactive（交互模式）/ cli（命令行模式）/ scheduled（常驻模式与定时任务）
+    pub source: String,
+    pub kind: ChangeKind,
+    /// 撤销 / 重做的目标变更集
+    pub target_id: Option<i64>,
+    pub state: ChangeState,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 行变化的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeOp {
+    Insert,
+    Update,
+    Delete,
+}
+
+impl ChangeOp {
+    pub fn from_string(s: &str) -> Option<Self> {
+        match s {
+            "insert" => Some(ChangeOp::Insert),
+            "update" => Some(ChangeOp::Update),
+            "delete" => Some(ChangeOp::Delete),
+            _ => None,
+        }
+    }
+}
+
+/// 一行的变化
+#[derive(Debug, Clone, Serialize)]
+pub struct ChangeEntry {
+    pub id: i64,
+    pub change_set_id: i64,
+    pub table_name: String,
+    pub operation: ChangeOp,
+    pub todo_id: Option<i64>,
+    /// 修改前的整行内容（新增时为 None）
+    pub before: Option<Map<String, Value>>,
+    /// 修改后的整行内容（删除时为 None）
+    pub after: Option<Map<String, Value>>,
+}
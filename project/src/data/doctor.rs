@@ -0,0 +1,85 @@
+//! 数据库体检（doctor）
+//!
+//! 检查数据库中程序无法正常读取的数据，并给出修复方式：
+//! - 数据库文件损坏（PRAGMA integrity_check），只能从快照恢复
+//! - 孤立记录：外键指向的行已不存在（如待办事项已删除但笔记、提醒历史仍在）
+//! - 时间字段不是 RFC 3339 格式，读取该行时会出错或被当作当前时间
+
+use serde::Serialize;
+
+/// 记录时间的字段：(表名, 列名)
+pub const TIMESTAMP_COLUMNS: &[(&str, &str)] = &[
+    ("todo_list", "begin_time"),
+    ("todo_list", "end_time"),
+    ("todo_list", "deleted_at"),
+    ("todo_list", "archived_at"),
+    ("todo_list", "completed_at"),
+    ("notes", "note_time"),
+    ("notes", "deleted_at"),
+    ("reminder_history", "reminder_time"),
+    ("reminder_history", "occurrence_time"),
+    ("reminder_history", "acknowledged_at"),
+    ("completion_events", "created_at"),
+    ("time_sessions", "started_at"),
+    ("time_sessions", "stopped_at"),
+    ("change_sets", "created_at"),
+];
+
+/// 问题类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProblemKind {
+    /// 数据库文件损坏
+    Corruption,
+    /// 孤立记录
+    Orphan,
+    /// 时间格式错误
+    BadTimestamp,
+}
+
+impl ProblemKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ProblemKind::Corruption => "数据库损坏",
+            ProblemKind::Orphan => "孤立记录",
+            ProblemKind::BadTimestamp => "时间格式错误",
+        }
+    }
+}
+
+/// 修复方式
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "action", content = "value", rename_all = "snake_case")]
+pub enum Repair {
+    /// 删除该行
+    DeleteRow,
+    /// 把该字段置空
+    SetNull,
+    /// 把该字段改为指定的值
+    SetValue(String),
+    /// 无法自动修复
+    Manual,
+}
+
+impl Repair {
+    pub fn describe(&self) -> String {
+        match self {
+            Repair::DeleteRow => "删除该行".to_string(),
+            Repair::SetNull => "清空该字段".to_string(),
+            Repair::SetValue(value) => format!("改为 {}", value),
+            Repair::Manual => "无法自动修复".to_string(),
+        }
+    }
+}
+
+/// 发现的一个问题
+#[derive(Debug, Clone, Serialize)]
+pub struct Problem {
+    pub kind: ProblemKind,
+    pub table: Option<String>,
+    /// 出问题的行（rowid）
+    pub row_id: Option<i64>,
+    pub column: Option<String>,
+    pub detail: String,
+    pub repair: Repair,
+}
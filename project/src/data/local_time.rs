@@ -0,0 +1,146 @@
+//! 本地时间换算
+//!
+//! 数据库中统一保存 UTC 时刻（RFC 3339），用户输入与界面显示使用本地时区：
+//! config.json 中 `timezone` 指定的 IANA 时区（如 `Asia/Shanghai`），未设置时使用系统时区。
+//!
+//! 夏令时处理：
+//! - 本地时间落在时钟拨快跳过的区间（不存在）时，按跳过的时长顺延，如 02:30 → 03:30
+//! - 本地时间在时钟拨回后重复出现（有歧义）时，取较早的那一次
+
+use chrono::{DateTime, Duration, LocalResult, NaiveDate, NaiveDateTime, Offset, TimeZone, Utc};
+use chrono_tz::Tz;
+use std::sync::RwLock;
+
+/// 本次运行使用的时区，None 表示尚未设置（使用系统时区）
+static ZONE: RwLock<Option<Tz>> = RwLock::new(None);
+
+/// 解析 IANA 时区名称
+pub fn parse_zone(name: &str) -> Option<Tz> {
+    name.trim().parse::<Tz>().ok()
+}
+
+/// 系统时区，无法识别时使用 UTC
+pub fn system_zone() -> Tz {
+    iana_time_zone::get_timezone()
+        .ok()
+        .and_then(|name| parse_zone(&name))
+        .unwrap_or(Tz::UTC)
+}
+
+/// 设置本次运行使用的时区
+pub fn set_zone(zone: Tz) {
+    if let Ok(mut current) = ZONE.write() {
+        *current = Some(zone);
+    }
+}
+
+/// 当前使用的时区
+pub fn zone() -> Tz {
+    ZONE.read().ok().and_then(|current| *current).unwrap_or_else(system_zone)
+}
+
+/// UTC 时刻转换为本地时间
+pub fn to_local(dt: &DateTime<Utc>) -> DateTime<Tz> {
+    dt.with_timezone(&zone())
+}
+
+/// 以本地时间格式化 UTC 时刻
+pub fn format(dt: &DateTime<Utc>, fmt: &str) -> String {
+    to_local(dt).format(fmt).to_string()
+}
+
+/// 当前本地时间
+pub fn now() -> DateTime<Tz> {
+    to_local(&Utc::now())
+}
+
+/// 本地时间转换为 UTC 时刻（夏令时规则见模块说明）
+pub fn from_local(naive: &NaiveDateTime) -> Option<DateTime<Utc>> {
+    from_zone_local(zone(), naive)
+}
+
+/// 指定时区的本地时间转换为 UTC 时刻，规则同 from_local
+pub fn from_zone_local(zone: Tz, naive: &NaiveDateTime) -> Option<DateTime<Utc>> {
+    match zone.from_local_datetime(naive) {
+        LocalResult::Single(dt) => Some(dt.with_timezone(&Utc)),
+        LocalResult::Ambiguous(earliest, _) => Some(earliest.with_timezone(&Utc)),
+        LocalResult::None => {
+            // 用跳变前后的 UTC 偏移之差作为顺延的时长
+            let before = zone.offset_from_utc_datetime(&(*naive - Duration::days(1)));
+            let after = zone.offset_from_utc_datetime(&(*naive + Duration::days(1)));
+            let gap = Duration::seconds(
+                (after.fix().local_minus_utc() - before.fix().local_minus_utc()).abs() as i64,
+            );
+            zone.from_local_datetime(&(*naive + gap))
+                .earliest()
+                .map(|dt| dt.with_timezone(&Utc))
+        }
+    }
+}
+
+/// 本地日期的零点
+pub fn start_of_day(date: NaiveDate) -> Option<DateTime<Utc>> {
+    from_local(&date.and_hms_opt(0, 0, 0)?)
+}
+
+/// 按本地日历加减天数，保持一天中的时刻不变（跨夏令时切换时不是 24 小时的整数倍）
+pub fn add_days(dt: &DateTime<Utc>, days: i64) -> Option<DateTime<Utc>> {
+    let local = to_local(dt).naive_local();
+    from_local(&local.checked_add_signed(Duration::days(days))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono_tz::{America::New_York, Europe::Berlin};
+
+    fn local(y: i32, m: u32, d: u32, h: u32, min: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap().and_hms_opt(h, min, 0).unwrap()
+    }
+
+    fn utc(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+    }
+
+    #[test]
+    fn unambiguous_times() {
+        assert_eq!(from_zone_local(New_York, &local(2026, 1, 15, 9, 0)), Some(utc(2026, 1, 15, 14, 0)));
+        assert_eq!(from_zone_local(New_York, &local(2026, 7, 15, 9, 0)), Some(utc(2026, 7, 15, 13, 0)));
+        assert_eq!(from_zone_local(Berlin, &local(2026, 1, 15, 9, 0)), Some(utc(2026, 1, 15, 8, 0)));
+        assert_eq!(from_zone_local(Berlin, &local(2026, 7, 15, 9, 0)), Some(utc(2026, 7, 15, 7, 0)));
+    }
+
+    #[test]
+    fn spring_forward_gap_is_postponed() {
+        // 纽约 2026-03-08 02:00 EST 拨快到 03:00 EDT，02:30 顺延为 03:30 EDT
+        assert_eq!(from_zone_local(New_York, &local(2026, 3, 8, 2, 30)), Some(utc(2026, 3, 8, 7, 30)));
+        assert_eq!(from_zone_local(New_York, &local(2026, 3, 8, 2, 0)), Some(utc(2026, 3, 8, 7, 0)));
+        assert_eq!(from_zone_local(New_York, &local(2026, 3, 8, 3, 0)), Some(utc(2026, 3, 8, 7, 0)));
+        // 柏林 2026-03-29 02:00 CET 拨快到 03:00 CEST，02:30 顺延为 03:30 CEST
+        assert_eq!(from_zone_local(Berlin, &local(2026, 3, 29, 2, 30)), Some(utc(2026, 3, 29, 1, 30)));
+    }
+
+    #[test]
+    fn fall_back_overlap_takes_earlier() {
+        // 纽约 2026-11-01 02:00 EDT 拨回到 01:00 EST，01:30 出现两次，取 EDT 的那一次
+        assert_eq!(from_zone_local(New_York, &local(2026, 11, 1, 1, 30)), Some(utc(2026, 11, 1, 5, 30)));
+        assert_eq!(from_zone_local(New_York, &local(2026, 11, 1, 2, 30)), Some(utc(2026, 11, 1, 7, 30)));
+        // 柏林 2026-10-25 03:00 CEST 拨回到 02:00 CET，02:30 出现两次，取 CEST 的那一次
+        assert_eq!(from_zone_local(Berlin, &local(2026, 10, 25, 2, 30)), Some(utc(2026, 10, 25, 0, 30)));
+        assert_eq!(from_zone_local(Berlin, &local(2026, 10, 25, 3, 30)), Some(utc(2026, 10, 25, 2, 30)));
+    }
+
+    #[test]
+    fn round_trip_around_transitions() {
+        for zone in [New_York, Berlin] {
+            let mut t = utc(2026, 3, 1, 0, 0);
+            while t < utc(2026, 11, 30, 0, 0) {
+                let naive = t.with_timezone(&zone).naive_local();
+                let back = from_zone_local(zone, &naive).unwrap();
+                // 只有拨回后重复的那一小时会映射到较早的一次
+                assert!(back == t || back == t - Duration::hours(1), "{} {}", zone, t);
+                t += Duration::minutes(30);
+            }
+        }
+    }
+}
@@ -3,4 +3,14 @@
 pub mod todo_list;
 pub mod note;
 pub mod reminder;
-
+pub mod query;
+pub mod recurrence;
+pub mod search;
+pub mod local_time;
+pub mod natural_time;
+pub mod transfer;
+pub mod backup;
+pub mod change_log;
+pub mod trash;
+pub mod doctor;
+pub mod time_tracking;
@@ -0,0 +1,739 @@
+//! 自然语言与相对时间解析
+//!
+//! 在本地时间（不含时区）上计算，由调用方换算为 UTC 时刻。支持的写法：
+//!
+//! - 相对偏移：`+3d`、`-2h`、`in 2 hours`、`3 days later`、`1 week ago`、`3天后`、`半小时后`、`2个月以后`
+//! - 日期：`today` / `tomorrow` / `yesterday` / `day after tomorrow` / `tonight`，
+//!   `今天` / `明天` / `后天` / `大后天` / `昨天` / `前天`
+//! - 星期：`friday`、`this friday`、`next friday`，`周五`、`本周五`、`下周一`、`星期日`
+//! - 区间端点：`end of day` / `eod`、`end of week` / `eow`、`end of month` / `eom`、`next week`、`next month`，
+//!   `月底`、`周末`、`下周`、`下个月`
+//! - 具体日期：`2026-10-20`、`10-20`、`10月20日`、`2026年10月20日`
+//! - 时刻：`9am`、`9:30pm`、`noon`、`midnight`、`14:00`，`下午3点`、`晚上8点半`、`上午10点15分`、`中午`、`今晚`
+//!
+//! 日期与时刻可以组合，顺序不限，如 `tomorrow 9am`、`9am tomorrow`、`明天下午3点`、`下周一 10:00`。
+//! 星期的含义：单独的星期几指今天或之后最近的一天，本周 / this 指本周（周一为一周开始）内的那天，
+//! 下周 / next 指下一周内的那天。只有时刻时指今天；只有日期时为当天 0 点，
+//! 月底、周末结束等区间终点为当天 23:59:59。
+
+use chrono::{Datelike, Duration, Months, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, Timelike, Weekday};
+
+/// 解析自然语言时间，now 为当前本地时间；无法识别时返回 None
+pub fn parse(input: &str, now: NaiveDateTime) -> Option<NaiveDateTime> {
+    let text = normalize(input);
+    if text.is_empty() {
+        return None;
+    }
+    if let Some(dt) = parse_offset(&text, now) {
+        return Some(dt);
+    }
+
+    let today = now.date();
+    // 日期在前或时刻在前均可，中间可以有空格或 at
+    if let Some((date, rest)) = parse_date(&text, today) {
+        let rest = skip_connector(rest);
+        if rest.is_empty() {
+            return Some(date.date.and_time(date.default_time));
+        }
+        if let Some((time, rest)) = parse_time(rest)
+            && skip_connector(rest).is_empty()
+        {
+            return Some(date.date.and_time(time));
+        }
+        return None;
+    }
+    let (time, rest) = parse_time(skip_connector(&text))?;
+    let rest = skip_connector(rest);
+    if rest.is_empty() {
+        return Some(today.and_time(time));
+    }
+    let (date, rest) = parse_date(rest, today)?;
+    skip_connector(rest).is_empty().then(|| date.date.and_time(time))
+}
+
+/// 统一为小写、去掉首尾空白并合并连续空白
+fn normalize(input: &str) -> String {
+    input.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+/// 去掉日期与时刻之间的连接词
+fn skip_connector(s: &str) -> &str {
+    let s = s.trim_start();
+    let s = eat_word(s, &["at"]).map(|(_, rest)| rest).unwrap_or(s);
+    s.trim_start_matches([' ', ',', '，', '的'])
+}
+
+// ---------- 相对偏移 ----------
+
+#[derive(Clone, Copy)]
+enum Unit {
+    Minute,
+    Hour,
+    Day,
+    Week,
+    Month,
+}
+
+/// 在 base 上加减 n 个单位；天、周、月按本地日历计算，保持时刻不变
+/// 偏移量过大（超出可表示的时间范围）时返回 None
+fn shift(base: NaiveDateTime, n: i64, unit: Unit) -> Option<NaiveDateTime> {
+    match unit {
+        Unit::Minute => base.checked_add_signed(TimeDelta::try_minutes(n)?),
+        Unit::Hour => base.checked_add_signed(TimeDelta::try_hours(n)?),
+        Unit::Day => base.checked_add_signed(TimeDelta::try_days(n)?),
+        Unit::Week => base.checked_add_signed(TimeDelta::try_weeks(n)?),
+        Unit::Month => {
+            let months = Months::new(u32::try_from(n.unsigned_abs()).ok()?);
+            if n >= 0 { base.checked_add_months(months) } else { base.checked_sub_months(months) }
+        }
+    }
+}
+
+fn eat_english_unit(s: &str) -> Option<(Unit, &str)> {
+    const UNITS: &[(&[&str], Unit)] = &[
+        (&["minutes", "minute", "mins", "min", "m"], Unit::Minute),
+        (&["hours", "hour", "hrs", "hr", "h"], Unit::Hour),
+        (&["days", "day", "d"], Unit::Day),
+        (&["weeks", "week", "wks", "wk", "w"], Unit::Week),
+        (&["months", "month", "mo"], Unit::Month),
+    ];
+    let s = s.trim_start();
+    UNITS
+        .iter()
+        .find_map(|(words, unit)| eat_word(s, words).map(|(_, rest)| (*unit, rest)))
+}
+
+fn eat_chinese_unit(s: &str) -> Option<(Unit, &str)> {
+    const UNITS: &[(&str, Unit)] = &[
+        ("分钟", Unit::Minute),
+        ("个小时", Unit::Hour),
+        ("小时", Unit::Hour),
+        ("天", Unit::Day),
+        ("日", Unit::Day),
+        ("个星期", Unit::Week),
+        ("个礼拜", Unit::Week),
+        ("星期", Unit::Week),
+        ("礼拜", Unit::Week),
+        ("周", Unit::Week),
+        ("个月", Unit::Month),
+        ("月", Unit::Month),
+    ];
+    UNITS
+        .iter()
+        .find_map(|(word, unit)| s.strip_prefix(word).map(|rest| (*unit, rest)))
+}
+
+/// 相对当前时间的偏移（整句）
+fn parse_offset(s: &str, now: NaiveDateTime) -> Option<NaiveDateTime> {
+    // 以分钟为最小精度
+    let now = now.with_second(0)?.with_nanosecond(0)?;
+
+    // +3d / -2h / +1 week
+    if let Some(sign) = s.chars().next().filter(|c| *c == '+' || *c == '-') {
+        let (n, rest) = eat_number(&s[1..])?;
+        let (unit, rest) = eat_english_unit(rest).or_else(|| eat_chinese_unit(rest.trim_start()))?;
+        let n = if sign == '-' { -n } else { n };
+        return rest.trim().is_empty().then(|| shift(now, n, unit)).flatten();
+    }
+
+    // in 2 hours
+    if let Some((_, rest)) = eat_word(s, &["in"]) {
+        let (n, rest) = eat_number(rest.trim_start())?;
+        let (unit, rest) = eat_english_unit(rest)?;
+        return rest.trim().is_empty().then(|| shift(now, n, unit)).flatten();
+    }
+
+    // 半小时后 / 半小时前
+    if let Some(rest) = s.strip_prefix("半个小时").or_else(|| s.strip_prefix("半小时")) {
+        let sign = chinese_direction(rest)?;
+        return shift(now, 30 * sign, Unit::Minute);
+    }
+
+    // 3 days later / 2 hours ago / 1 week from now；3天后 / 2个月以前
+    let (n, rest) = eat_number(s)?;
+    if let Some((unit, rest)) = eat_english_unit(rest) {
+        let sign = match rest.trim() {
+            "later" | "from now" => 1,
+            "ago" | "before" | "earlier" => -1,
+            _ => return None,
+        };
+        return shift(now, n * sign, unit);
+    }
+    let (unit, rest) = eat_chinese_unit(rest.trim_start())?;
+    let sign = chinese_direction(rest)?;
+    shift(now, n * sign, unit)
+}
+
+/// 解析 "后 / 以后 / 之后 / 前 / 以前 / 之前"，返回方向
+fn chinese_direction(s: &str) -> Option<i64> {
+    match s.trim() {
+        "后" | "以后" | "之后" => Some(1),
+        "前" | "以前" | "之前" => Some(-1),
+        _ => None,
+    }
+}
+
+// ---------- 日期 ----------
+
+/// 解析出的日期，以及未指定时刻时使用的默认时刻
+struct DateSpec {
+    date: NaiveDate,
+    default_time: NaiveTime,
+}
+
+impl DateSpec {
+    fn start(date: NaiveDate) -> Self {
+        DateSpec { date, default_time: NaiveTime::MIN }
+    }
+
+    fn end(date: NaiveDate) -> Self {
+        DateSpec { date, default_time: end_of_day() }
+    }
+
+    fn at(date: NaiveDate, hour: u32) -> Self {
+        DateSpec { date, default_time: NaiveTime::from_hms_opt(hour, 0, 0).unwrap_or(NaiveTime::MIN) }
+    }
+}
+
+fn end_of_day() -> NaiveTime {
+    NaiveTime::from_hms_opt(23, 59, 59).unwrap_or(NaiveTime::MIN)
+}
+
+/// 本周（周一开始）中的某一天
+fn weekday_in_week(today: NaiveDate, weekday: Weekday, weeks_ahead: i64) -> NaiveDate {
+    let monday = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+    monday + Duration::days(weekday.num_days_from_monday() as i64 + weeks_ahead * 7)
+}
+
+/// 今天或之后最近的某个星期几
+fn upcoming_weekday(today: NaiveDate, weekday: Weekday) -> NaiveDate {
+    let diff = (weekday.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+    today + Duration::days(diff as i64)
+}
+
+fn last_day_of_month(date: NaiveDate) -> Option<NaiveDate> {
+    date.with_day(1)?.checked_add_months(Months::new(1))?.pred_opt()
+}
+
+fn first_day_of_next_month(date: NaiveDate) -> Option<NaiveDate> {
+    date.with_day(1)?.checked_add_months(Months::new(1))
+}
+
+fn parse_date(s: &str, today: NaiveDate) -> Option<(DateSpec, &str)> {
+    parse_english_date(s, today)
+        .or_else(|| parse_chinese_date(s, today))
+        .or_else(|| parse_numeric_date(s, today))
+}
+
+fn parse_english_date(s: &str, today: NaiveDate) -> Option<(DateSpec, &str)> {
+    let day = |n: i64| today + Duration::days(n);
+    if let Some((_, rest)) = eat_word(s, &["day after tomorrow"]) {
+        return Some((DateSpec::start(day(2)), rest));
+    }
+    if let Some((word, rest)) = eat_word(s, &["today", "tonight", "tomorrow", "tmr", "yesterday"]) {
+        let spec = match word {
+            "today" => DateSpec::start(today),
+            "tonight" => DateSpec::at(today, 20),
+            "yesterday" => DateSpec::start(day(-1)),
+            _ => DateSpec::start(day(1)),
+        };
+        return Some((spec, rest));
+    }
+    if let Some((phrase, rest)) = eat_word(
+        s,
+        &["end of the day", "end of day", "eod", "end of the week", "end of week", "eow",
+          "end of the month", "end of month", "eom"],
+    ) {
+        let spec = if phrase.ends_with("day") || phrase == "eod" {
+            DateSpec::end(today)
+        } else if phrase.ends_with("week") || phrase == "eow" {
+            DateSpec::end(weekday_in_week(today, Weekday::Sun, 0))
+        } else {
+            DateSpec::end(last_day_of_month(today)?)
+        };
+        return Some((spec, rest));
+    }
+    if let Some((_, rest)) = eat_word(s, &["next"]) {
+        let rest = rest.trim_start();
+        if let Some((_, rest)) = eat_word(rest, &["week"]) {
+            return Some((DateSpec::start(weekday_in_week(today, Weekday::Mon, 1)), rest));
+        }
+        if let Some((_, rest)) = eat_word(rest, &["month"]) {
+            return Some((DateSpec::start(first_day_of_next_month(today)?), rest));
+        }
+        let (weekday, rest) = eat_english_weekday(rest)?;
+        return Some((DateSpec::start(weekday_in_week(today, weekday, 1)), rest));
+    }
+    if let Some((_, rest)) = eat_word(s, &["this"]) {
+        let (weekday, rest) = eat_english_weekday(rest.trim_start())?;
+        return Some((DateSpec::start(weekday_in_week(today, weekday, 0)), rest));
+    }
+    let (weekday, rest) = eat_english_weekday(s)?;
+    Some((DateSpec::start(upcoming_weekday(today, weekday)), rest))
+}
+
+fn eat_english_weekday(s: &str) -> Option<(Weekday, &str)> {
+    const DAYS: &[(&[&str], Weekday)] = &[
+        (&["monday", "mon"], Weekday::Mon),
+        (&["tuesday", "tues", "tue"], Weekday::Tue),
+        (&["wednesday", "wed"], Weekday::Wed),
+        (&["thursday", "thurs", "thur", "thu"], Weekday::Thu),
+        (&["friday", "fri"], Weekday::Fri),
+        (&["saturday", "sat"], Weekday::Sat),
+        (&["sunday", "sun"], Weekday::Sun),
+    ];
+    DAYS.iter()
+        .find_map(|(words, weekday)| eat_word(s, words).map(|(_, rest)| (*weekday, rest)))
+}
+
+fn parse_chinese_date(s: &str, today: NaiveDate) -> Option<(DateSpec, &str)> {
+    let day = |n: i64| today + Duration::days(n);
+    const DAYS: &[(&str, i64)] = &[
+        ("大后天", 3),
+        ("后天", 2),
+        ("明天", 1),
+        ("明日", 1),
+        ("今天", 0),
+        ("今日", 0),
+        ("昨天", -1),
+        ("前天", -2),
+    ];
+    if let Some((offset, rest)) = DAYS.iter().find_map(|(word, n)| s.strip_prefix(word).map(|r| (*n, r))) {
+        return Some((DateSpec::start(day(offset)), rest));
+    }
+    if let Some(rest) = ["本月底", "本月末", "月底", "月末"].iter().find_map(|w| s.strip_prefix(w)) {
+        return Some((DateSpec::end(last_day_of_month(today)?), rest));
+    }
+    if let Some(rest) = ["下个月", "下月"].iter().find_map(|w| s.strip_prefix(w)) {
+        return Some((DateSpec::start(first_day_of_next_month(today)?), rest));
+    }
+    if let Some(rest) = ["本周末", "这周末", "周末"].iter().find_map(|w| s.strip_prefix(w)) {
+        // 周日说 "周末" 指当天
+        let date = if today.weekday() == Weekday::Sun { today } else { upcoming_weekday(today, Weekday::Sat) };
+        return Some((DateSpec::start(date), rest));
+    }
+
+    // 下周一 / 下下周三 / 本周五 / 这周五 / 周五 / 星期日 / 下周
+    let (weeks_ahead, rest) = if let Some(rest) = s.strip_prefix("下下") {
+        (Some(2), rest)
+    } else if let Some(rest) = s.strip_prefix("下") {
+        (Some(1), rest)
+    } else if let Some(rest) = s.strip_prefix("本").or_else(|| s.strip_prefix("这")) {
+        (Some(0), rest)
+    } else {
+        (None, s)
+    };
+    let rest = ["个星期", "个礼拜", "星期", "礼拜", "周"]
+        .iter()
+        .find_map(|w| rest.strip_prefix(w))?;
+    match (eat_chinese_weekday(rest), weeks_ahead) {
+        (Some((weekday, rest)), Some(weeks)) => Some((DateSpec::start(weekday_in_week(today, weekday, weeks)), rest)),
+        (Some((weekday, rest)), None) => Some((DateSpec::start(upcoming_weekday(today, weekday)), rest)),
+        (None, Some(weeks)) if weeks > 0 => Some((DateSpec::start(weekday_in_week(today, Weekday::Mon, weeks)), rest)),
+        _ => None,
+    }
+}
+
+fn eat_chinese_weekday(s: &str) -> Option<(Weekday, &str)> {
+    let mut chars = s.chars();
+    let weekday = match chars.next()? {
+        '一' | '1' => Weekday::Mon,
+        '二' | '2' => Weekday::Tue,
+        '三' | '3' => Weekday::Wed,
+        '四' | '4' => Weekday::Thu,
+        '五' | '5' => Weekday::Fri,
+        '六' | '6' => Weekday::Sat,
+        '日' | '天' | '7' => Weekday::Sun,
+        _ => return None,
+    };
+    Some((weekday, chars.as_str()))
+}
+
+/// 2026-10-20 / 2026/10/20 / 10-20 / 10月20日 / 2026年10月20号（省略年份时取今年）
+fn parse_numeric_date(s: &str, today: NaiveDate) -> Option<(DateSpec, &str)> {
+    let (first, rest) = eat_digits(s)?;
+    let (year, month, rest) = if let Some(rest) = rest.strip_prefix('年') {
+        let (month, rest) = eat_digits(rest)?;
+        (first, month, rest.strip_prefix('月')?)
+    } else if let Some(rest) = rest.strip_prefix('月') {
+        (today.year() as i64, first, rest)
+    } else {
+        let sep = rest.chars().next().filter(|c| *c == '-' || *c == '/')?;
+        let (second, rest) = eat_digits(&rest[1..])?;
+        match rest.strip_prefix(sep).and_then(eat_digits) {
+            Some((day, rest)) => {
+                let date = NaiveDate::from_ymd_opt(i32::try_from(first).ok()?, u32::try_from(second).ok()?, u32::try_from(day).ok()?)?;
+                return Some((DateSpec::start(date), rest));
+            }
+            None => {
+                let date = NaiveDate::from_ymd_opt(today.year(), u32::try_from(first).ok()?, u32::try_from(second).ok()?)?;
+                return Some((DateSpec::start(date), rest));
+            }
+        }
+    };
+    let (day, rest) = eat_digits(rest)?;
+    let rest = rest.strip_prefix('日').or_else(|| rest.strip_prefix('号'))?;
+    let date = NaiveDate::from_ymd_opt(i32::try_from(year).ok()?, u32::try_from(month).ok()?, u32::try_from(day).ok()?)?;
+    Some((DateSpec::start(date), rest))
+}
+
+// ---------- 时刻 ----------
+
+fn parse_time(s: &str) -> Option<(NaiveTime, &str)> {
+    parse_english_time(s).or_else(|| parse_chinese_time(s))
+}
+
+/// 9am / 9:30 pm / 14:00 / 14:00:30 / noon / midnight
+fn parse_english_time(s: &str) -> Option<(NaiveTime, &str)> {
+    if let Some((word, rest)) = eat_word(s, &["noon", "midnight"]) {
+        let hour = if word == "noon" { 12 } else { 0 };
+        return Some((NaiveTime::from_hms_opt(hour, 0, 0)?, rest));
+    }
+
+    let (hour, rest) = eat_digits(s)?;
+    let (minute, second, rest) = match rest.strip_prefix(':') {
+        Some(rest) => {
+            let (minute, rest) = eat_digits(rest)?;
+            match rest.strip_prefix(':') {
+                Some(rest) => {
+                    let (second, rest) = eat_digits(rest)?;
+                    (minute, second, rest)
+                }
+                None => (minute, 0, rest),
+            }
+        }
+        None => (0, 0, rest),
+    };
+    let has_colon = rest.len() < s.len() && s[..s.len() - rest.len()].contains(':');
+
+    let (hour, rest) = match eat_word(rest.trim_start(), &["am", "a.m.", "pm", "p.m."]) {
+        Some((suffix, rest)) => {
+            if !(1..=12).contains(&hour) {
+                return None;
+            }
+            let hour = hour % 12 + if suffix.starts_with('p') { 12 } else { 0 };
+            (hour, rest)
+        }
+        // 没有 am/pm 时必须写成 HH:MM，避免与其他数字混淆
+        None if has_colon => (hour, rest),
+        None => return None,
+    };
+    let time = NaiveTime::from_hms_opt(u32::try_from(hour).ok()?, u32::try_from(minute).ok()?, u32::try_from(second).ok()?)?;
+    Some((time, rest))
+}
+
+/// 下午3点 / 晚上8点半 / 上午10点15分 / 9点一刻 / 中午 / 凌晨1点
+fn parse_chinese_time(s: &str) -> Option<(NaiveTime, &str)> {
+    const PERIODS: &[(&str, i64)] = &[
+        ("凌晨", 0),
+        ("早上", 0),
+        ("早晨", 0),
+        ("上午", 0),
+        ("中午", 12),
+        ("下午", 12),
+        ("傍晚", 12),
+        ("晚上", 12),
+        ("夜里", 12),
+        ("今晚", 12),
+    ];
+    let (period, rest) = match PERIODS.iter().find_map(|(word, add)| s.strip_prefix(word).map(|r| (Some((*word, *add)), r))) {
+        Some(found) => found,
+        None => (None, s),
+    };
+
+    let Some((hour, after_hour)) = eat_number(rest) else {
+        // 只写 "中午" 表示 12 点，"今晚" 表示 20 点
+        return match period {
+            Some(("中午", _)) => Some((NaiveTime::from_hms_opt(12, 0, 0)?, rest)),
+            Some(("今晚", _)) => Some((NaiveTime::from_hms_opt(20, 0, 0)?, rest)),
+            _ => None,
+        };
+    };
+    let Some(after_hour) = after_hour.strip_prefix('点').or_else(|| after_hour.strip_prefix('时')) else {
+        // 下午 3:30 这类写法
+        let (time, rest) = parse_english_time(rest)?;
+        let hour = adjust_chinese_hour(time.hour() as i64, period)?;
+        return Some((time.with_hour(u32::try_from(hour).ok()?)?, rest));
+    };
+
+    let (minute, rest) = if let Some(rest) = after_hour.strip_prefix('半') {
+        (30, rest)
+    } else if let Some(rest) = after_hour.strip_prefix("一刻") {
+        (15, rest)
+    } else if let Some(rest) = after_hour.strip_prefix("三刻") {
+        (45, rest)
+    } else if let Some((minute, rest)) = eat_number(after_hour) {
+        (minute, rest.strip_prefix('分').unwrap_or(rest))
+    } else {
+        (0, after_hour.strip_prefix("整").unwrap_or(after_hour))
+    };
+
+    let hour = adjust_chinese_hour(hour, period)?;
+    let time = NaiveTime::from_hms_opt(u32::try_from(hour).ok()?, u32::try_from(minute).ok()?, 0)?;
+    Some((time, rest))
+}
+
+/// 按时段换算为 24 小时制
+fn adjust_chinese_hour(hour: i64, period: Option<(&str, i64)>) -> Option<i64> {
+    let hour = match period {
+        Some(("凌晨", _)) if hour == 12 => 0,
+        Some(("中午", _)) if hour >= 11 => hour,
+        Some((_, add)) if hour < 12 => hour + add,
+        _ => hour,
+    };
+    (0..24).contains(&hour).then_some(hour)
+}
+
+// ---------- 词法工具 ----------
+
+/// 匹配英文单词或词组（之后不能紧跟字母），返回匹配到的词与剩余部分
+fn eat_word<'a>(s: &'a str, words: &[&'static str]) -> Option<(&'static str, &'a str)> {
+    words.iter().find_map(|word| {
+        let rest = s.strip_prefix(word)?;
+        (!rest.starts_with(|c: char| c.is_ascii_alphabetic())).then_some((*word, rest))
+    })
+}
+
+/// 读取阿拉伯数字
+fn eat_digits(s: &str) -> Option<(i64, &str)> {
+    let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    if end == 0 {
+        return None;
+    }
+    Some((s[..end].parse().ok()?, &s[end..]))
+}
+
+/// 读取阿拉伯数字或 0-99 的中文数字（如 三、十二、二十五、两）
+fn eat_number(s: &str) -> Option<(i64, &str)> {
+    if let Some(found) = eat_digits(s) {
+        return Some(found);
+    }
+
+    let digit = |c: char| match c {
+        '零' => Some(0),
+        '一' => Some(1),
+        '二' | '两' => Some(2),
+        '三' => Some(3),
+        '四' => Some(4),
+        '五' => Some(5),
+        '六' => Some(6),
+        '七' => Some(7),
+        '八' => Some(8),
+        '九' => Some(9),
+        _ => None,
+    };
+    let mut chars = s.char_indices().peekable();
+    let mut value: Option<i64> = None;
+    let mut end = 0;
+    while let Some((i, c)) = chars.next() {
+        if c == '十' {
+            value = Some(value.unwrap_or(1) * 10);
+            end = i + c.len_utf8();
+            if let Some((j, next)) = chars.peek().copied()
+                && let Some(d) = digit(next)
+            {
+                value = value.map(|v| v + d);
+                end = j + next.len_utf8();
+                chars.next();
+            }
+            break;
+        }
+        match digit(c) {
+            Some(d) if value.is_none() => {
+                value = Some(d);
+                end = i + c.len_utf8();
+            }
+            _ => break,
+        }
+    }
+    value.map(|v| (v, &s[end..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 固定的当前时间：2026-10-14 周三 10:30:45
+    fn now() -> NaiveDateTime {
+        at(2026, 10, 14, 10, 30, 45)
+    }
+
+    fn at(y: i32, m: u32, d: u32, h: u32, min: u32, s: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap().and_hms_opt(h, min, s).unwrap()
+    }
+
+    fn assert_parses(cases: &[(&str, NaiveDateTime)]) {
+        for (input, expected) in cases {
+            assert_eq!(parse(input, now()), Some(*expected), "{input}");
+        }
+    }
+
+    #[test]
+    fn relative_offsets() {
+        // 相对偏移以分钟为精度，秒数归零
+        assert_parses(&[
+            ("+3d", at(2026, 10, 17, 10, 30, 0)),
+            ("-2h", at(2026, 10, 14, 8, 30, 0)),
+            ("+90m", at(2026, 10, 14, 12, 0, 0)),
+            ("+1 week", at(2026, 10, 21, 10, 30, 0)),
+            ("in 2 hours", at(2026, 10, 14, 12, 30, 0)),
+            ("In  2  Hours", at(2026, 10, 14, 12, 30, 0)),
+            ("3 days later", at(2026, 10, 17, 10, 30, 0)),
+            ("1 week ago", at(2026, 10, 7, 10, 30, 0)),
+            ("2 hours from now", at(2026, 10, 14, 12, 30, 0)),
+            ("in 1 month", at(2026, 11, 14, 10, 30, 0)),
+            ("3天后", at(2026, 10, 17, 10, 30, 0)),
+            ("两天前", at(2026, 10, 12, 10, 30, 0)),
+            ("半小时后", at(2026, 10, 14, 11, 0, 0)),
+            ("十分钟以后", at(2026, 10, 14, 10, 40, 0)),
+            ("2个月以后", at(2026, 12, 14, 10, 30, 0)),
+            ("-1个月", at(2026, 9, 14, 10, 30, 0)),
+        ]);
+    }
+
+    #[test]
+    fn month_offset_clamps_to_last_day() {
+        let jan_31 = at(2026, 1, 31, 9, 0, 0);
+        assert_eq!(parse("+1 month", jan_31), Some(at(2026, 2, 28, 9, 0, 0)));
+    }
+
+    #[test]
+    fn named_days() {
+        assert_parses(&[
+            ("today", at(2026, 10, 14, 0, 0, 0)),
+            ("tomorrow", at(2026, 10, 15, 0, 0, 0)),
+            ("tmr", at(2026, 10, 15, 0, 0, 0)),
+            ("yesterday", at(2026, 10, 13, 0, 0, 0)),
+            ("day after tomorrow", at(2026, 10, 16, 0, 0, 0)),
+            ("tonight", at(2026, 10, 14, 20, 0, 0)),
+            ("今天", at(2026, 10, 14, 0, 0, 0)),
+            ("明天", at(2026, 10, 15, 0, 0, 0)),
+            ("后天", at(2026, 10, 16, 0, 0, 0)),
+            ("大后天", at(2026, 10, 17, 0, 0, 0)),
+            ("昨天", at(2026, 10, 13, 0, 0, 0)),
+            ("前天", at(2026, 10, 12, 0, 0, 0)),
+        ]);
+    }
+
+    #[test]
+    fn weekdays() {
+        assert_parses(&[
+            // 单独的星期几：今天或之后最近的一天
+            ("wednesday", at(2026, 10, 14, 0, 0, 0)),
+            ("friday", at(2026, 10, 16, 0, 0, 0)),
+            ("mon", at(2026, 10, 19, 0, 0, 0)),
+            // this：本周内，可以在今天之前
+            ("this friday", at(2026, 10, 16, 0, 0, 0)),
+            ("this monday", at(2026, 10, 12, 0, 0, 0)),
+            // next：下一周内
+            ("next friday", at(2026, 10, 23, 0, 0, 0)),
+            ("next monday", at(2026, 10, 19, 0, 0, 0)),
+            ("周五", at(2026, 10, 16, 0, 0, 0)),
+            ("星期日", at(2026, 10, 18, 0, 0, 0)),
+            ("礼拜三", at(2026, 10, 14, 0, 0, 0)),
+            ("本周一", at(2026, 10, 12, 0, 0, 0)),
+            ("这周五", at(2026, 10, 16, 0, 0, 0)),
+            ("下周一", at(2026, 10, 19, 0, 0, 0)),
+            ("下下周三", at(2026, 10, 28, 0, 0, 0)),
+        ]);
+    }
+
+    #[test]
+    fn end_of_period() {
+        assert_parses(&[
+            ("end of day", at(2026, 10, 14, 23, 59, 59)),
+            ("eod", at(2026, 10, 14, 23, 59, 59)),
+            ("end of the week", at(2026, 10, 18, 23, 59, 59)),
+            ("eow", at(2026, 10, 18, 23, 59, 59)),
+            ("end of month", at(2026, 10, 31, 23, 59, 59)),
+            ("eom", at(2026, 10, 31, 23, 59, 59)),
+            ("next week", at(2026, 10, 19, 0, 0, 0)),
+            ("next month", at(2026, 11, 1, 0, 0, 0)),
+            ("月底", at(2026, 10, 31, 23, 59, 59)),
+            ("周末", at(2026, 10, 17, 0, 0, 0)),
+            ("下周", at(2026, 10, 19, 0, 0, 0)),
+            ("下个月", at(2026, 11, 1, 0, 0, 0)),
+        ]);
+        // 周日说周末指当天
+        let sunday = at(2026, 10, 18, 8, 0, 0);
+        assert_eq!(parse("周末", sunday), Some(at(2026, 10, 18, 0, 0, 0)));
+        // 跨年的月底与下个月
+        let december = at(2026, 12, 5, 8, 0, 0);
+        assert_eq!(parse("eom", december), Some(at(2026, 12, 31, 23, 59, 59)));
+        assert_eq!(parse("next month", december), Some(at(2027, 1, 1, 0, 0, 0)));
+    }
+
+    #[test]
+    fn explicit_dates() {
+        assert_parses(&[
+            ("2026-10-20", at(2026, 10, 20, 0, 0, 0)),
+            ("2027/1/5", at(2027, 1, 5, 0, 0, 0)),
+            ("10-20", at(2026, 10, 20, 0, 0, 0)),
+            ("10月20日", at(2026, 10, 20, 0, 0, 0)),
+            ("10月20号", at(2026, 10, 20, 0, 0, 0)),
+            ("2026年10月20日", at(2026, 10, 20, 0, 0, 0)),
+        ]);
+    }
+
+    #[test]
+    fn times_of_day() {
+        // 只有时刻时指今天
+        assert_parses(&[
+            ("9am", at(2026, 10, 14, 9, 0, 0)),
+            ("9:30pm", at(2026, 10, 14, 21, 30, 0)),
+            ("12am", at(2026, 10, 14, 0, 0, 0)),
+            ("12 pm", at(2026, 10, 14, 12, 0, 0)),
+            ("noon", at(2026, 10, 14, 12, 0, 0)),
+            ("midnight", at(2026, 10, 14, 0, 0, 0)),
+            ("14:00", at(2026, 10, 14, 14, 0, 0)),
+            ("14:00:30", at(2026, 10, 14, 14, 0, 30)),
+            ("下午3点", at(2026, 10, 14, 15, 0, 0)),
+            ("晚上8点半", at(2026, 10, 14, 20, 30, 0)),
+            ("上午10点15分", at(2026, 10, 14, 10, 15, 0)),
+            ("9点一刻", at(2026, 10, 14, 9, 15, 0)),
+            ("凌晨1点", at(2026, 10, 14, 1, 0, 0)),
+            ("下午3:30", at(2026, 10, 14, 15, 30, 0)),
+            ("中午", at(2026, 10, 14, 12, 0, 0)),
+            ("今晚", at(2026, 10, 14, 20, 0, 0)),
+        ]);
+    }
+
+    #[test]
+    fn date_and_time_combined() {
+        assert_parses(&[
+            ("tomorrow 9am", at(2026, 10, 15, 9, 0, 0)),
+            ("9am tomorrow", at(2026, 10, 15, 9, 0, 0)),
+            ("tomorrow at 9:30am", at(2026, 10, 15, 9, 30, 0)),
+            ("friday, 14:00", at(2026, 10, 16, 14, 0, 0)),
+            ("2026-10-20 18:00", at(2026, 10, 20, 18, 0, 0)),
+            ("明天下午3点", at(2026, 10, 15, 15, 0, 0)),
+            ("下周一 10:00", at(2026, 10, 19, 10, 0, 0)),
+            ("10月20日晚上8点", at(2026, 10, 20, 20, 0, 0)),
+            ("后天的上午10点", at(2026, 10, 16, 10, 0, 0)),
+        ]);
+    }
+
+    #[test]
+    fn rejects_out_of_range_offsets() {
+        for input in [
+            "+99999999999999d",
+            "in 9999999999999999 weeks",
+            "-9999999999999999999m",
+            "99999999999999 hours later",
+            "9999999999999天后",
+            "+99999999999 months",
+        ] {
+            assert_eq!(parse(input, now()), None, "{input}");
+        }
+    }
+
+    #[test]
+    fn rejects_unrecognised_input() {
+        for input in [
+            "", "   ", "someday", "9", "13pm", "0am", "25:00", "tomorrow later", "9am blah",
+            "next", "this week", "3 days", "in hours", "+d", "2026-02-30", "13月1日", "周八", "下午25点",
+        ] {
+            assert_eq!(parse(input, now()), None, "{input}");
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use chrono::{DateTime, Utc};
+
+use crate::data::todo_list::{Priority, TodoStatus};
+
+/// 待办事项查询（由 query_serv 解析，由 todo_list_dao 编译为 SQL）
+/// 所有条件之间为 AND 关系
+#[derive(Debug, Clone, Default)]
+pub struct TodoQuery {
+    pub terms: Vec<QueryTerm>,
+}
+
+impl TodoQuery {
+    /// 查询中的全文关键字（用于高亮匹配的笔记）
+    pub fn text_terms(&self) -> Vec<&str> {
+        self.terms
+            .iter()
+            .filter(|t| !t.negated)
+            .filter_map(|t| match &t.filter {
+                QueryFilter::Text(s) => Some(s.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// 单个查询条件，negated 为 true 时取反（语法中以 `-` 开头）
+#[derive(Debug, Clone)]
+pub struct QueryTerm {
+    pub negated: bool,
+    pub filter: QueryFilter,
+}
+
+#[derive(Debug, Clone)]
+pub enum QueryFilter {
+    /// status:xxx，状态属于其中之一
+    Status(Vec<TodoStatus>),
+    /// tag:xxx
+    Tag(String),
+    /// priority:xxx，None 表示未设置优先级
+    Priority(Option<Priority>),
+    /// due / begin 与时间比较
+    Time {
+        field: TimeField,
+        op: CompareOp,
+        value: DateTime<Utc>,
+    },
+    /// has:due / has:notes / has:tags / has:repeat
+    Has(HasField),
+    /// is:overdue，截止时间已过且未结束
+    Overdue,
+    /// 关键字，匹配标题、描述与笔记
+    Text(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeField {
+    /// begin_time
+    Begin,
+    /// end_time
+    Due,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    /// 落在同一天（value 为当天 00:00）
+    On,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HasField {
+    Due,
+    Notes,
+    Tags,
+    Recurrence,
+}
@@ -0,0 +1,417 @@
+use chrono::{DateTime, Datelike, Duration, Months, NaiveDate, NaiveDateTime, Utc, Weekday};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::data::local_time;
+
+/// 重复频率
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+impl Frequency {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+        }
+    }
+
+    fn unit_label(&self) -> &'static str {
+        match self {
+            Frequency::Daily => "天",
+            Frequency::Weekly => "周",
+            Frequency::Monthly => "个月",
+        }
+    }
+}
+
+/// 重复规则（RRULE 风格的子集）
+///
+/// 以文本形式存储在 todo_list.recurrence 列中，例如：
+/// `FREQ=WEEKLY;BYDAY=MO,TU,WE,TH,FR;UNTIL=20261231T000000Z`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct RecurrenceRule {
+    pub freq: Frequency,
+    pub interval: u32,            // 间隔（每 N 天/周/月）
+    pub by_weekday: Vec<Weekday>, // 仅 WEEKLY：在一周中的哪几天
+    pub by_month_day: Option<u32>, // 仅 MONTHLY：每月第几天（超过当月天数时取月末）
+    pub until: Option<DateTime<Utc>>, // 最后一次开始时间不晚于该时间
+    pub count: Option<u32>,       // 总次数
+}
+
+/// 寻找下一次重复时最多向后扫描的天数（防止规则异常时死循环）
+const MAX_SCAN_DAYS: i64 = 366 * 4;
+
+impl RecurrenceRule {
+    fn new(freq: Frequency) -> Self {
+        RecurrenceRule {
+            freq,
+            interval: 1,
+            by_weekday: Vec::new(),
+            by_month_day: None,
+            until: None,
+            count: None,
+        }
+    }
+
+    /// 解析重复规则
+    ///
+    /// 以 `;` 分隔的若干部分，每部分为 RRULE 键值（FREQ / INTERVAL / BYDAY /
+    /// BYMONTHDAY / UNTIL / COUNT，不区分大小写），或以下简写：
+    /// daily、weekly、weekdays（工作日）、monthly，及对应的中文 每天/每周/工作日/每月
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let mut rule: Option<RecurrenceRule> = None;
+        let mut interval = None;
+        let mut by_weekday = None;
+        let mut by_month_day = None;
+        let mut until = None;
+        let mut count = None;
+
+        for part in input.split(';').map(str::trim).filter(|p| !p.is_empty()) {
+            let Some((key, value)) = part.split_once('=') else {
+                let preset = match part.to_lowercase().as_str() {
+                    "daily" | "每天" => RecurrenceRule::new(Frequency::Daily),
+                    "weekly" | "每周" => RecurrenceRule::new(Frequency::Weekly),
+                    "monthly" | "每月" => RecurrenceRule::new(Frequency::Monthly),
+                    "weekdays" | "工作日" => RecurrenceRule {
+                        by_weekday: vec![Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri],
+                        ..RecurrenceRule::new(Frequency::Weekly)
+                    },
+                    _ => return Err(format!("无法识别的重复规则: {}", part)),
+                };
+                if rule.replace(preset).is_some() {
+                    return Err("重复频率只能指定一次".to_string());
+                }
+                continue;
+            };
+
+            let value = value.trim();
+            match key.trim().to_uppercase().as_str() {
+                "FREQ" => {
+                    let freq = match value.to_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        _ => return Err(format!("不支持的重复频率: {}", value)),
+                    };
+                    if rule.replace(RecurrenceRule::new(freq)).is_some() {
+                        return Err("重复频率只能指定一次".to_string());
+                    }
+                }
+                "INTERVAL" => {
+                    let n = value.parse::<u32>().ok().filter(|n| *n >= 1);
+                    interval = Some(n.ok_or_else(|| format!("INTERVAL 必须为正整数: {}", value))?);
+                }
+                "BYDAY" => {
+                    let days = value
+                        .split(',')
+                        .map(|d| parse_weekday(d.trim()).ok_or_else(|| format!("无法识别的星期: {}", d)))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    by_weekday = Some(days);
+                }
+                "BYMONTHDAY" => {
+                    let n = value.parse::<u32>().ok().filter(|n| (1..=31).contains(n));
+                    by_month_day = Some(n.ok_or_else(|| format!("BYMONTHDAY 必须为 1-31: {}", value))?);
+                }
+                "UNTIL" => {
+                    until = Some(parse_until(value).ok_or_else(|| format!("无法识别的截止日期: {}", value))?);
+                }
+                "COUNT" => {
+                    let n = value.parse::<u32>().ok().filter(|n| *n >= 1);
+                    count = Some(n.ok_or_else(|| format!("COUNT 必须为正整数: {}", value))?);
+                }
+                other => return Err(format!("不支持的重复规则字段: {}", other)),
+            }
+        }
+
+        let mut rule = rule.ok_or_else(|| "缺少重复频率（daily / weekly / weekdays / monthly 或 FREQ=..）".to_string())?;
+        if let Some(interval) = interval {
+            rule.interval = interval;
+        }
+        if let Some(days) = by_weekday {
+            if rule.freq != Frequency::Weekly {
+                return Err("BYDAY 仅适用于每周重复".to_string());
+            }
+            rule.by_weekday = days;
+        }
+        if by_month_day.is_some() {
+            if rule.freq != Frequency::Monthly {
+                return Err("BYMONTHDAY 仅适用于每月重复".to_string());
+            }
+            rule.by_month_day = by_month_day;
+        }
+        rule.until = until;
+        rule.count = count;
+
+        rule.by_weekday.sort_by_key(|d| d.num_days_from_monday());
+        rule.by_weekday.dedup();
+        Ok(rule)
+    }
+
+    /// 以首次开始时间补全规则中隐含的部分（每月重复默认固定在首次的本地日期）
+    pub fn anchored(mut self, begin: DateTime<Utc>) -> Self {
+        if self.freq == Frequency::Monthly && self.by_month_day.is_none() {
+            self.by_month_day = Some(local_time::to_local(&begin).day());
+        }
+        self
+    }
+
+    /// 不考虑 COUNT / UNTIL 时，begin 之后的下一次开始时间
+    /// 按本地日历推算并保持本地时刻不变，跨夏令时切换时也不会偏移一小时
+    fn step(&self, begin: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.step_in(local_time::zone(), begin)
+    }
+
+    /// 同 step，按指定时区的日历推算
+    fn step_in(&self, zone: Tz, begin: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let interval = self.interval.max(1);
+        let local = begin.with_timezone(&zone).naive_local();
+        let next = match self.freq {
+            Frequency::Daily => local.checked_add_signed(Duration::days(interval as i64))?,
+            Frequency::Weekly if self.by_weekday.is_empty() => {
+                local.checked_add_signed(Duration::weeks(interval as i64))?
+            }
+            Frequency::Weekly => {
+                // 以周一为一周的开始，仅在与首周相隔 interval 整数倍的周内取值
+                let week_start = |dt: NaiveDateTime| {
+                    dt.date() - Duration::days(dt.weekday().num_days_from_monday() as i64)
+                };
+                let base_week = week_start(local);
+                (1..=MAX_SCAN_DAYS)
+                    .filter_map(|d| local.checked_add_signed(Duration::days(d)))
+                    .find(|candidate| {
+                        let weeks = (week_start(*candidate) - base_week).num_days() / 7;
+                        weeks % interval as i64 == 0 && self.by_weekday.contains(&candidate.weekday())
+                    })?
+            }
+            Frequency::Monthly => {
+                let day = self.by_month_day.unwrap_or(local.day());
+                let first_of_month = local.date().with_day(1)?;
+                let target_month = first_of_month.checked_add_months(Months::new(interval))?;
+                let date = target_month.with_day(day.min(days_in_month(target_month)))?;
+                date.and_time(local.time())
+            }
+        };
+        local_time::from_zone_local(zone, &next)
+    }
+
+    /// 第 occurrence 次（从 1 开始）开始于 begin 时，下一次的开始时间
+    /// 已达到 COUNT 或超过 UNTIL 时返回 None
+    pub fn next_occurrence(&self, begin: DateTime<Utc>, occurrence: u32) -> Option<DateTime<Utc>> {
+        if self.count.is_some_and(|count| occurrence >= count) {
+            return None;
+        }
+        let next = self.step(begin)?;
+        if self.until.is_some_and(|until| next > until) {
+            return None;
+        }
+        Some(next)
+    }
+
+    /// 从第 occurrence 次（开始于 begin）往后，第一次开始时间晚于 now 的重复
+    /// 返回（开始时间, 次数）
+    pub fn upcoming(&self, begin: DateTime<Utc>, occurrence: u32, now: DateTime<Utc>) -> Option<(DateTime<Utc>, u32)> {
+        let mut current = (begin, occurrence);
+        while current.0 <= now {
+            let next = self.next_occurrence(current.0, current.1)?;
+            current = (next, current.1 + 1);
+        }
+        Some(current)
+    }
+
+    /// 中文描述，例如 "每 2 周的周一、周三，共 5 次"
+    pub fn describe(&self) -> String {
+        let mut text = if self.interval > 1 {
+            format!("每 {} {}", self.interval, self.freq.unit_label())
+        } else {
+            match self.freq {
+                Frequency::Daily => "每天".to_string(),
+                Frequency::Weekly => "每周".to_string(),
+                Frequency::Monthly => "每月".to_string(),
+            }
+        };
+        if !self.by_weekday.is_empty() {
+            let days: Vec<&str> = self.by_weekday.iter().map(|d| weekday_label(*d)).collect();
+            text.push_str(&format!("的{}", days.join("、")));
+        }
+        if let Some(day) = self.by_month_day {
+            text.push_str(&format!("的 {} 日", day));
+        }
+        if let Some(count) = self.count {
+            text.push_str(&format!("，共 {} 次", count));
+        }
+        if let Some(until) = self.until {
+            text.push_str(&format!("，截止 {}", local_time::format(&until, "%Y-%m-%d")));
+        }
+        text
+    }
+}
+
+impl fmt::Display for RecurrenceRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FREQ={}", self.freq.as_str())?;
+        if self.interval > 1 {
+            write!(f, ";INTERVAL={}", self.interval)?;
+        }
+        if !self.by_weekday.is_empty() {
+            let days: Vec<&str> = self.by_weekday.iter().map(|d| weekday_code(*d)).collect();
+            write!(f, ";BYDAY={}", days.join(","))?;
+        }
+        if let Some(day) = self.by_month_day {
+            write!(f, ";BYMONTHDAY={}", day)?;
+        }
+        if let Some(until) = self.until {
+            write!(f, ";UNTIL={}", until.format("%Y%m%dT%H%M%SZ"))?;
+        }
+        if let Some(count) = self.count {
+            write!(f, ";COUNT={}", count)?;
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<String> for RecurrenceRule {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        RecurrenceRule::parse(&value)
+    }
+}
+
+impl From<RecurrenceRule> for String {
+    fn from(rule: RecurrenceRule) -> Self {
+        rule.to_string()
+    }
+}
+
+fn parse_weekday(s: &str) -> Option<Weekday> {
+    match s.to_uppercase().as_str() {
+        "MO" | "MON" | "周一" => Some(Weekday::Mon),
+        "TU" | "TUE" | "周二" => Some(Weekday::Tue),
+        "WE" | "WED" | "周三" => Some(Weekday::Wed),
+        "TH" | "THU" | "周四" => Some(Weekday::Thu),
+        "FR" | "FRI" | "周五" => Some(Weekday::Fri),
+        "SA" | "SAT" | "周六" => Some(Weekday::Sat),
+        "SU" | "SUN" | "周日" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+fn weekday_code(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU",
+    }
+}
+
+fn weekday_label(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Mon => "周一",
+        Weekday::Tue => "周二",
+        Weekday::Wed => "周三",
+        Weekday::Thu => "周四",
+        Weekday::Fri => "周五",
+        Weekday::Sat => "周六",
+        Weekday::Sun => "周日",
+    }
+}
+
+// UNTIL 支持 RRULE 格式（20261231T000000Z / 20261231）和 YYYY-MM-DD；只写日期时包含本地时间的当天全天
+fn parse_until(s: &str) -> Option<DateTime<Utc>> {
+    if let Ok(dt) = NaiveDateTime::parse_from_str(s, "%Y%m%dT%H%M%SZ") {
+        return Some(dt.and_utc());
+    }
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Some(dt.with_timezone(&Utc));
+    }
+    let date = NaiveDate::parse_from_str(s, "%Y%m%d")
+        .or_else(|_| NaiveDate::parse_from_str(s, "%Y-%m-%d"))
+        .ok()?;
+    local_time::from_local(&date.and_hms_opt(23, 59, 59)?)
+}
+
+fn days_in_month(first_of_month: NaiveDate) -> u32 {
+    first_of_month
+        .checked_add_months(Months::new(1))
+        .and_then(|next| next.pred_opt())
+        .map(|last| last.day())
+        .unwrap_or(28)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use chrono_tz::{America::New_York, Europe::Berlin};
+
+    fn utc(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+    }
+
+    fn rule(input: &str) -> RecurrenceRule {
+        RecurrenceRule::parse(input).unwrap()
+    }
+
+    #[test]
+    fn daily_keeps_local_time_across_spring_forward() {
+        // 纽约 2026-03-08 02:00 拨快到 03:00：09:00 EST (14:00Z) 的下一天是 09:00 EDT (13:00Z)，只隔 23 小时
+        let next = rule("daily").step_in(New_York, utc(2026, 3, 7, 14, 0)).unwrap();
+        assert_eq!(next, utc(2026, 3, 8, 13, 0));
+    }
+
+    #[test]
+    fn daily_keeps_local_time_across_fall_back() {
+        // 纽约 2026-11-01 02:00 拨回到 01:00：09:00 EDT (13:00Z) 的下一天是 09:00 EST (14:00Z)，相隔 25 小时
+        let next = rule("daily").step_in(New_York, utc(2026, 10, 31, 13, 0)).unwrap();
+        assert_eq!(next, utc(2026, 11, 1, 14, 0));
+    }
+
+    #[test]
+    fn daily_into_skipped_hour_is_postponed() {
+        // 02:30 EST (07:30Z) 的下一天 02:30 不存在，顺延到 03:30 EDT (07:30Z)
+        let next = rule("daily").step_in(New_York, utc(2026, 3, 7, 7, 30)).unwrap();
+        assert_eq!(next, utc(2026, 3, 8, 7, 30));
+    }
+
+    #[test]
+    fn daily_into_repeated_hour_takes_earlier() {
+        // 01:30 EDT (05:30Z) 的下一天 01:30 出现两次，取较早的 EDT 那一次 (05:30Z)
+        let next = rule("daily").step_in(New_York, utc(2026, 10, 31, 5, 30)).unwrap();
+        assert_eq!(next, utc(2026, 11, 1, 5, 30));
+    }
+
+    #[test]
+    fn weekly_keeps_local_time_across_transitions() {
+        // 柏林 2026-03-29 拨快：周一 10:00 CET (09:00Z) → 下周一 10:00 CEST (08:00Z)
+        let next = rule("weekly").step_in(Berlin, utc(2026, 3, 23, 9, 0)).unwrap();
+        assert_eq!(next, utc(2026, 3, 30, 8, 0));
+
+        // 柏林 2026-10-25 拨回：周五 18:00 CEST (16:00Z) → 周一 18:00 CET (17:00Z)
+        let by_day = rule("FREQ=WEEKLY;BYDAY=MO,FR");
+        let next = by_day.step_in(Berlin, utc(2026, 10, 23, 16, 0)).unwrap();
+        assert_eq!(next, utc(2026, 10, 26, 17, 0));
+    }
+
+    #[test]
+    fn weekly_on_transition_day() {
+        // 每周日 02:30：柏林 2026-03-29 的 02:30 不存在，顺延到 03:30 CEST (01:30Z)
+        let next = rule("weekly").step_in(Berlin, utc(2026, 3, 22, 1, 30)).unwrap();
+        assert_eq!(next, utc(2026, 3, 29, 1, 30));
+
+        // 2026-10-25 的 02:30 出现两次，取较早的 CEST 那一次 (00:30Z)
+        let next = rule("weekly").step_in(Berlin, utc(2026, 10, 18, 0, 30)).unwrap();
+        assert_eq!(next, utc(2026, 10, 25, 0, 30));
+    }
+}
@@ -1,38 +1,227 @@
 use chrono::{DateTime, Utc};
 use serde::{Serialize, Deserialize};
+use std::fmt;
+
+use crate::data::todo_list::Priority;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReminderHistory {
-    pub id: i32,
+    pub id: i64,
     pub todo_id: i32,
     pub reminder_time: DateTime<Utc>,
-    pub reminder_type: String,
+    pub reminder_type: String,  // 触发的规则类型（如 before_start）
+    pub rule_key: Option<String>,  // 触发的规则标识
+    pub trigger_time: Option<DateTime<Utc>>,  // 本次提醒对应的计划触发时间
     pub notified: bool,
+    pub occurrence_time: Option<DateTime<Utc>>,  // 针对重复待办事项后续某次重复的提醒
+    #[serde(default)]
+    pub state: ReminderState,
+    pub snoozed_until: Option<DateTime<Utc>>,  // 稍后提醒的时间（state 为 snoozed 时有效）
+    #[serde(default)]
+    pub snooze_count: i32,  // 已稍后提醒的次数
+    pub acknowledged_at: Option<DateTime<Utc>>,
+    pub message: Option<String>,  // 发送时的提醒内容，再次提醒时沿用
+}
+
+/// 提醒的处理状态
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReminderState {
+    /// 已发送，尚未处理
+    #[default]
+    Sent,
+    /// 稍后提醒：到 snoozed_until 时再次发送
+    Snoozed,
+    /// 已确认（"知道了"）
+    Acknowledged,
+}
+
+impl ReminderState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReminderState::Sent => "sent",
+            ReminderState::Snoozed => "snoozed",
+            ReminderState::Acknowledged => "acknowledged",
+        }
+    }
+
+    pub fn from_string(s: &str) -> Option<Self> {
+        match s.trim() {
+            "sent" => Some(ReminderState::Sent),
+            "snoozed" => Some(ReminderState::Snoozed),
+            "acknowledged" => Some(ReminderState::Acknowledged),
+            _ => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ReminderState::Sent => "🔔 未处理",
+            ReminderState::Snoozed => "💤 稍后提醒",
+            ReminderState::Acknowledged => "✅ 已确认",
+        }
+    }
 }
 
-#[derive(Debug, Clone, PartialEq)]
-pub enum ReminderType {
-    OneDayBefore,
-    OneHourBefore,
-    Overdue,
+/// 一条待发送的提醒
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub history_id: i64,  // 对应的提醒历史记录，稍后提醒 / 确认时使用
+    pub todo_id: i32,
+    pub todo_title: String,
+    pub priority: Option<Priority>,
+    pub kind: ReminderKind,
+    pub rule_key: String,
+    pub trigger_at: i64,  // 计划触发时间（Unix 秒），与提醒历史中的记录对应
+    pub message: String,
 }
 
-impl ReminderType {
-    pub fn to_string(&self) -> String {
+/// 通知的紧急程度（对应 freedesktop 通知规范中的 urgency）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Urgency {
+    Normal,
+    Critical,
+}
+
+impl Urgency {
+    pub fn as_str(&self) -> &'static str {
         match self {
-            ReminderType::OneDayBefore => "1_day_before".to_string(),
-            ReminderType::OneHourBefore => "1_hour_before".to_string(),
-            ReminderType::Overdue => "overdue".to_string(),
+            Urgency::Normal => "normal",
+            Urgency::Critical => "critical",
+        }
+    }
+}
+
+impl Notification {
+    /// 开始后 / 截止后仍未完成的提醒为紧急，其余为普通
+    pub fn urgency(&self) -> Urgency {
+        if self.kind.is_before() {
+            Urgency::Normal
+        } else {
+            Urgency::Critical
         }
     }
-    
+}
+
+impl fmt::Display for Notification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// 提醒规则类型：以开始时间或截止时间为参照，在其之前或之后触发
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReminderKind {
+    BeforeStart,
+    BeforeEnd,
+    AfterStart,
+    AfterEnd,
+}
+
+impl fmt::Display for ReminderKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            ReminderKind::BeforeStart => "before_start",
+            ReminderKind::BeforeEnd => "before_end",
+            ReminderKind::AfterStart => "after_start",
+            ReminderKind::AfterEnd => "after_end",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl ReminderKind {
+    /// 解析规则类型，兼容旧配置中的 overdue（= after_start）
     pub fn from_string(s: &str) -> Option<Self> {
-        match s {
-            "1_day_before" => Some(ReminderType::OneDayBefore),
-            "1_hour_before" => Some(ReminderType::OneHourBefore),
-            "overdue" => Some(ReminderType::Overdue),
+        match s.trim().to_lowercase().as_str() {
+            "before_start" => Some(ReminderKind::BeforeStart),
+            "before_end" | "before_due" => Some(ReminderKind::BeforeEnd),
+            "after_start" | "overdue" => Some(ReminderKind::AfterStart),
+            "after_end" | "after_due" | "overdue_end" => Some(ReminderKind::AfterEnd),
             _ => None,
         }
     }
+
+    /// 是否在参照时间之前触发
+    pub fn is_before(&self) -> bool {
+        matches!(self, ReminderKind::BeforeStart | ReminderKind::BeforeEnd)
+    }
+
+    /// 是否以截止时间为参照
+    pub fn uses_end_time(&self) -> bool {
+        matches!(self, ReminderKind::BeforeEnd | ReminderKind::AfterEnd)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ReminderKind::BeforeStart => "开始前",
+            ReminderKind::BeforeEnd => "截止前",
+            ReminderKind::AfterStart => "开始后",
+            ReminderKind::AfterEnd => "截止后",
+        }
+    }
 }
 
+/// 将秒数格式化为 "1天2小时" 形式
+pub fn format_duration(seconds: i64) -> String {
+    if seconds == 0 {
+        return "0秒".to_string();
+    }
+    let units = [(86400, "天"), (3600, "小时"), (60, "分钟"), (1, "秒")];
+    let mut rest = seconds.abs();
+    let mut text = String::new();
+    for (size, name) in units {
+        if rest >= size {
+            text.push_str(&format!("{}{}", rest / size, name));
+            rest %= size;
+        }
+    }
+    text
+}
+
+/// 解析时长，如 `90`（秒）、`30m`、`2h`、`1d12h`，也接受中文单位 天/小时/分钟/秒
+pub fn parse_duration(input: &str) -> Option<i64> {
+    let input = input.trim().to_lowercase();
+    if input.is_empty() {
+        return None;
+    }
+    if let Ok(seconds) = input.parse::<i64>() {
+        return (seconds >= 0).then_some(seconds);
+    }
+
+    let mut total: i64 = 0;
+    let mut number = String::new();
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        if c.is_whitespace() {
+            continue;
+        }
+        let mut unit = c.to_string();
+        // 多字符中文单位
+        while let Some(next) = chars.peek() {
+            if next.is_ascii_digit() || next.is_whitespace() {
+                break;
+            }
+            unit.push(*next);
+            chars.next();
+        }
+        let size = match unit.as_str() {
+            "d" | "day" | "days" | "天" => 86400,
+            "h" | "hour" | "hours" | "小时" | "时" => 3600,
+            "m" | "min" | "mins" | "分钟" | "分" => 60,
+            "s" | "sec" | "secs" | "秒" => 1,
+            _ => return None,
+        };
+        let n: i64 = number.parse().ok()?;
+        total = total.checked_add(n.checked_mul(size)?)?;
+        number.clear();
+    }
+    if !number.is_empty() {
+        return None;
+    }
+    Some(total)
+}
@@ -0,0 +1,12 @@
+use serde::Serialize;
+
+/// 全文检索命中的记录（待办事项或笔记）
+#[derive(Debug, Serialize, Clone)]
+pub struct SearchHit {
+    pub kind: String,      // "todo" 或 "note"
+    pub ref_id: i32,       // 待办事项 ID 或笔记 ID
+    pub todo_id: i32,      // 所属待办事项 ID
+    pub title: String,     // 高亮后的标题
+    pub snippet: String,   // 高亮后的正文片段
+    pub rank: f64,         // bm25 相关度，越小越相关
+}
@@ -0,0 +1,76 @@
+//! 完成时间与计时
+//!
+//! - 待办事项改为已完成时记录完成时间（completed_at），改回其他状态时清空；
+//!   每次完成与重新打开另外追加一条记录（completion_events），保留完整的过程
+//! - 计时（time_sessions）：对某个待办事项开始计时，停止后得到一段实际用时；
+//!   同一时间只有一个正在进行的计时，开始新的计时时自动停止上一个
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+
+/// 一段计时
+#[derive(Debug, Clone, Serialize)]
+pub struct TimeSession {
+    pub id: i64,
+    pub todo_id: i32,
+    pub started_at: DateTime<Utc>,
+    /// 停止时间，正在计时时为空
+    pub stopped_at: Option<DateTime<Utc>>,
+}
+
+impl TimeSession {
+    /// 计时长度，正在计时的算到 now 为止
+    pub fn duration(&self, now: DateTime<Utc>) -> Duration {
+        (self.stopped_at.unwrap_or(now) - self.started_at).max(Duration::zero())
+    }
+}
+
+/// 完成记录的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompletionEventKind {
+    /// 改为已完成
+    Completed,
+    /// 由已完成改回其他状态
+    Reopened,
+}
+
+impl CompletionEventKind {
+    pub fn from_string(s: &str) -> Option<Self> {
+        match s {
+            "completed" => Some(CompletionEventKind::Completed),
+            "reopened" => Some(CompletionEventKind::Reopened),
+            _ => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            CompletionEventKind::Completed => "完成",
+            CompletionEventKind::Reopened => "重新打开",
+        }
+    }
+}
+
+/// 一条完成 / 重新打开记录
+#[derive(Debug, Clone, Serialize)]
+pub struct CompletionEvent {
+    pub todo_id: i32,
+    pub event: CompletionEventKind,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 格式化时长：x 天 y 小时 / x 小时 y 分钟（不足一分钟时为 z 秒）
+pub fn format_duration(duration: Duration) -> String {
+    let minutes = duration.num_minutes();
+    let hours = minutes / 60;
+    if hours >= 24 {
+        format!("{} 天 {} 小时", hours / 24, hours % 24)
+    } else if hours > 0 {
+        format!("{} 小时 {} 分钟", hours, minutes % 60)
+    } else if minutes > 0 {
+        format!("{} 分钟", minutes)
+    } else {
+        format!("{} 秒", duration.num_seconds().max(0))
+    }
+}
@@ -1,17 +1,189 @@
 use chrono::{DateTime, Utc};
 use serde::{Serialize, Deserialize};
+use std::fmt;
+
+use crate::data::recurrence::RecurrenceRule;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TodoListForm {
     pub id: i32,
     pub title: String,
     pub description: Option<String>,
-    pub completed: bool,
+    pub status: TodoStatus,
+    pub priority: Option<Priority>,
+    #[serde(default)]
+    pub tags: Vec<String>,  // 标签（存储在 tags / todo_tags 表中）
     pub begin_time: DateTime<Utc>,
     pub end_time: Option<DateTime<Utc>>,
     pub key_message1: Option<String>,
     pub key_message2: Option<String>,
     pub key_message3: Option<String>,
+    #[serde(default)]
+    pub recurrence: Option<RecurrenceRule>,  // 重复规则
+    #[serde(default)]
+    pub series_id: Option<i32>,  // 所属重复系列（系列中第一个待办事项的 ID）
+    #[serde(default = "first_occurrence")]
+    pub occurrence: u32,  // 在重复系列中是第几次，从 1 开始
+    #[serde(default)]
+    pub parent_id: Option<i32>,  // 父任务 ID（子任务）
+    #[serde(default)]
+    pub archived_at: Option<DateTime<Utc>>,  // 归档时间（已归档的不出现在默认列表与提醒中）
+    #[serde(default)]
+    pub completed_at: Option<DateTime<Utc>>,  // 完成时间（改回未完成时清空）
+}
+
+fn first_occurrence() -> u32 {
+    1
 }
 
+impl TodoListForm {
+    /// 是否已完成
+    pub fn is_done(&self) -> bool {
+        self.status == TodoStatus::Done
+    }
+
+    /// 是否已归档
+    pub fn is_archived(&self) -> bool {
+        self.archived_at.is_some()
+    }
+}
+
+/// 待办事项状态
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TodoStatus {
+    Todo,
+    InProgress,
+    Blocked,
+    Done,
+    Cancelled,
+}
 
+impl TodoStatus {
+    pub const ALL: [TodoStatus; 5] = [
+        TodoStatus::Todo,
+        TodoStatus::InProgress,
+        TodoStatus::Blocked,
+        TodoStatus::Done,
+        TodoStatus::Cancelled,
+    ];
+
+    /// 数据库中存储的取值
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TodoStatus::Todo => "todo",
+            TodoStatus::InProgress => "in_progress",
+            TodoStatus::Blocked => "blocked",
+            TodoStatus::Done => "done",
+            TodoStatus::Cancelled => "cancelled",
+        }
+    }
+
+    /// 解析用户输入或数据库取值
+    pub fn from_string(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "todo" | "pending" | "open" | "待办" | "未完成" => Some(TodoStatus::Todo),
+            "in_progress" | "in-progress" | "doing" | "wip" | "进行中" => Some(TodoStatus::InProgress),
+            "blocked" | "受阻" | "阻塞" => Some(TodoStatus::Blocked),
+            "done" | "completed" | "完成" | "已完成" => Some(TodoStatus::Done),
+            "cancelled" | "canceled" | "取消" | "已取消" => Some(TodoStatus::Cancelled),
+            _ => None,
+        }
+    }
+
+    /// 是否仍未结束（待办 / 进行中 / 受阻）
+    pub fn is_open(&self) -> bool {
+        !matches!(self, TodoStatus::Done | TodoStatus::Cancelled)
+    }
+
+    pub fn icon(&self) -> &'static str {
+        match self {
+            TodoStatus::Todo => "⬜",
+            TodoStatus::InProgress => "🔄",
+            TodoStatus::Blocked => "⛔",
+            TodoStatus::Done => "✅",
+            TodoStatus::Cancelled => "🚫",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TodoStatus::Todo => "待办",
+            TodoStatus::InProgress => "进行中",
+            TodoStatus::Blocked => "受阻",
+            TodoStatus::Done => "已完成",
+            TodoStatus::Cancelled => "已取消",
+        }
+    }
+}
+
+impl fmt::Display for TodoStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// 优先级（排序时高优先级在前）
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Priority {
+    High,
+    Medium,
+    Low,
+}
+
+impl Priority {
+    /// 数据库中存储的取值
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Priority::High => "high",
+            Priority::Medium => "medium",
+            Priority::Low => "low",
+        }
+    }
+
+    /// 解析用户输入，兼容中文与数字写法
+    pub fn from_string(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "高" | "high" | "1" | "urgent" | "紧急" => Some(Priority::High),
+            "中" | "medium" | "2" | "normal" | "普通" => Some(Priority::Medium),
+            "低" | "low" | "3" | "minor" | "次要" => Some(Priority::Low),
+            _ => None,
+        }
+    }
+
+    /// 排序权重，数字越小越靠前
+    pub fn rank(&self) -> i32 {
+        match self {
+            Priority::High => 1,
+            Priority::Medium => 2,
+            Priority::Low => 3,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Priority::High => "高",
+            Priority::Medium => "中",
+            Priority::Low => "低",
+        }
+    }
+
+    /// 自由文本优先级（如笔记的 note_priority）的排序权重
+    /// 无优先级排在最后；无法识别的文本尝试按数字解析
+    pub fn rank_of_text(priority: &Option<String>) -> i32 {
+        match priority {
+            None => 999,
+            Some(p) => match Priority::from_string(p) {
+                Some(parsed) => parsed.rank(),
+                None => p.trim().parse::<i32>().unwrap_or(999),
+            },
+        }
+    }
+}
+
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
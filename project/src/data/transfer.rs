@@ -0,0 +1,178 @@
+//! 导入 / 导出的数据格式
+//!
+//! JSON 导出文件（当前版本 1）的顶层结构：
+//!
+//! ```json
+//! {
+//!   "format": "todolist-export",
+//!   "version": 1,
+//!   "exported_at": "2026-10-18T08:00:00Z",
+//!   "timezone": "Asia/Shanghai",
+//!   "todos": [ ... ],
+//!   "dependencies": [ { "todo_id": 5, "depends_on_id": 3 } ],
+//!   "notes": [ ... ],
+//!   "reminder_history": [ ... ]
+//! }
+//! ```
+//!
+//! todos / notes / reminder_history 中每条记录的字段与 `TodoListForm`、`NoteForm`、
+//! `ReminderHistory` 的序列化结果一致，时间均为 RFC 3339（UTC）。
+//! 文件中的 ID 只用于表示记录之间的引用（父任务、重复系列、依赖、笔记所属待办等），
+//! 导入时全部重新分配。新增字段时保持向后兼容并沿用版本号，不兼容的修改才递增版本号。
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::data::note::NoteForm;
+use crate::data::reminder::ReminderHistory;
+use crate::data::todo_list::TodoListForm;
+
+/// 导出文件的格式标识
+pub const EXPORT_FORMAT: &str = "todolist-export";
+/// 导出文件的结构版本
+pub const EXPORT_VERSION: u32 = 1;
+
+/// JSON 导出文件
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportDocument {
+    pub format: String,
+    pub version: u32,
+    pub exported_at: DateTime<Utc>,
+    #[serde(default)]
+    pub timezone: Option<String>,  // 导出时配置的时区，仅供参考
+    #[serde(default)]
+    pub todos: Vec<TodoListForm>,
+    #[serde(default)]
+    pub dependencies: Vec<Dependency>,
+    #[serde(default)]
+    pub notes: Vec<NoteForm>,
+    #[serde(default)]
+    pub reminder_history: Vec<ReminderHistory>,
+    /// 读取导入文件时产生的警告（如 iCalendar 中无法转换的组件），不写入导出文件
+    #[serde(skip)]
+    pub read_warnings: Vec<String>,
+}
+
+impl ExportDocument {
+    pub fn new(timezone: Option<String>) -> Self {
+        Self {
+            format: EXPORT_FORMAT.to_string(),
+            version: EXPORT_VERSION,
+            exported_at: Utc::now(),
+            timezone,
+            todos: Vec::new(),
+            dependencies: Vec::new(),
+            notes: Vec::new(),
+            reminder_history: Vec::new(),
+            read_warnings: Vec::new(),
+        }
+    }
+
+    /// 检查格式标识与版本
+    pub fn validate(&self) -> Result<(), String> {
+        if self.format != EXPORT_FORMAT {
+            return Err(format!("不是 TodoList 导出文件（format 应为 \"{}\"）", EXPORT_FORMAT));
+        }
+        if self.version == 0 || self.version > EXPORT_VERSION {
+            return Err(format!(
+                "不支持的导出文件版本 {}（当前程序支持的最高版本为 {}）",
+                self.version, EXPORT_VERSION
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// 任务依赖：todo_id 依赖 depends_on_id
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Dependency {
+    pub todo_id: i32,
+    pub depends_on_id: i32,
+}
+
+/// 导入 / 导出的文件格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferFormat {
+    /// 完整的 JSON 导出文件
+    Json,
+    /// 单张表的 CSV，便于用表格软件编辑
+    Csv,
+    /// Markdown 待办清单（仅导出）
+    Markdown,
+    /// iCalendar：导出为 VTODO，导入 VTODO 与 VEVENT
+    Ics,
+    /// todo.txt，每行一个待办事项
+    TodoTxt,
+    /// Taskwarrior `task export` 输出的 JSON（仅导入）
+    Taskwarrior,
+}
+
+impl TransferFormat {
+    pub fn from_string(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "json" => Some(TransferFormat::Json),
+            "csv" => Some(TransferFormat::Csv),
+            "md" | "markdown" => Some(TransferFormat::Markdown),
+            "ics" | "ical" | "icalendar" => Some(TransferFormat::Ics),
+            "todotxt" | "todo.txt" | "txt" => Some(TransferFormat::TodoTxt),
+            "taskwarrior" | "task" | "tw" => Some(TransferFormat::Taskwarrior),
+            _ => None,
+        }
+    }
+
+    /// 按文件扩展名推断格式
+    pub fn from_path(path: &Path) -> Option<Self> {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(Self::from_string)
+    }
+
+    /// 是否支持导出
+    pub fn can_export(&self) -> bool {
+        *self != TransferFormat::Taskwarrior
+    }
+
+    /// 是否支持导入
+    pub fn can_import(&self) -> bool {
+        *self != TransferFormat::Markdown
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            TransferFormat::Json => "json",
+            TransferFormat::Csv => "csv",
+            TransferFormat::Markdown => "md",
+            TransferFormat::Ics => "ics",
+            TransferFormat::TodoTxt => "txt",
+            TransferFormat::Taskwarrior => "json",
+        }
+    }
+}
+
+/// CSV 导入 / 导出针对的表
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferTable {
+    Todos,
+    Notes,
+    Reminders,
+}
+
+impl TransferTable {
+    pub fn from_string(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "todos" | "todo" | "todo_list" => Some(TransferTable::Todos),
+            "notes" | "note" => Some(TransferTable::Notes),
+            "reminders" | "reminder" | "reminder_history" => Some(TransferTable::Reminders),
+            _ => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TransferTable::Todos => "待办事项",
+            TransferTable::Notes => "笔记",
+            TransferTable::Reminders => "提醒历史",
+        }
+    }
+}
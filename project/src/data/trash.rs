@@ -0,0 +1,39 @@
+//! 回收站
+//!
+//! 删除待办事项或笔记时只记录删除时间（deleted_at），数据保留在原表中，
+//! 在回收站中可以恢复；超过保留天数后自动彻底删除。
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// 回收站中项目的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrashKind {
+    /// 待办事项（连同其笔记、标签、依赖与提醒历史）
+    Todo,
+    /// 单独删除的笔记
+    Note,
+}
+
+impl TrashKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            TrashKind::Todo => "待办事项",
+            TrashKind::Note => "笔记",
+        }
+    }
+}
+
+/// 回收站中的一项
+#[derive(Debug, Clone, Serialize)]
+pub struct TrashItem {
+    pub kind: TrashKind,
+    pub id: i32,
+    /// 所属待办事项 ID（笔记）；待办事项为自身 ID
+    pub todo_id: i32,
+    pub title: String,
+    pub deleted_at: DateTime<Utc>,
+    /// 自动彻底删除的时间，未开启自动清理时为空
+    pub purge_at: Option<DateTime<Utc>>,
+}
@@ -27,7 +27,7 @@ fn load_config() -> Value {
     let config: Value = toml::from_str(&config_content)
                 .expect("Failed to parse config file");
 
-    return config;
+    config
 }
 
 // 获取对应条目的信息，并返回数据库文件的完整路径
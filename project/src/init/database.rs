@@ -1,8 +1,21 @@
-use rusqlite::{Connection};
-use anyhow::{Result as AnyResult};
+use rusqlite::{Connection, OpenFlags};
+use rusqlite::backup::{Backup, StepResult};
+use anyhow::{Result as AnyResult, bail};
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+use crate::init::migration;
+use crate::service::logger::Logger;
+
+/// 在线备份每一步复制的页数
+const BACKUP_PAGES_PER_STEP: i32 = 128;
+/// 数据库被其他连接锁定时重试的次数与间隔（共约 10 秒）
+const BACKUP_BUSY_RETRIES: u32 = 100;
+const BACKUP_BUSY_PAUSE: Duration = Duration::from_millis(100);
 
 pub struct Database {
     conn: Connection,
+    path: String,
 }
 
 impl Database {
@@ -10,15 +23,25 @@ impl Database {
     /// Self的意思是返回类型自身
     pub fn new(db_path: &str) -> AnyResult<Self> {
         let conn = Connection::open(db_path)?;
+        // 开启外键约束：拒绝指向不存在待办事项的笔记、提醒历史等，删除待办事项时级联删除
+        conn.execute_batch("PRAGMA foreign_keys = ON")?;
+        let enabled: bool = conn.query_row("PRAGMA foreign_keys", [], |row| row.get(0))?;
+        if !enabled {
+            bail!("当前 SQLite 不支持外键约束");
+        }
 
-        Ok(Self { conn })
+        Ok(Self { conn, path: db_path.to_string() })
     }
 
-    // 初始化数据库表结构
+    // 初始化数据库表结构（执行尚未应用的迁移）
     pub fn initialize_tables(&self) -> AnyResult<()> {
-        self.create_todo_list_table()?;
-        self.create_notes_table()?;
-        self.create_reminder_history_table()?;
+        let applied = migration::run_migrations(&self.conn)?;
+        if applied > 0 {
+            Logger::log(
+                "INFO",
+                &format!("数据库已迁移到版本 {}（执行 {} 个迁移）", migration::latest_version(), applied),
+            );
+        }
         Ok(())
     }
 
@@ -27,59 +50,119 @@ impl Database {
         &self.conn
     }
 
-    // 创建todo_list表
-    pub fn create_todo_list_table(&self) -> AnyResult<()> {
-        let sql = r#"
-        CREATE TABLE IF NOT EXISTS todo_list (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            title TEXT NOT NULL,
-            description TEXT,
-            completed INTEGER NOT NULL DEFAULT 0,
-            begin_time TEXT NOT NULL,
-            end_time TEXT,
-            key_message1 TEXT,
-            key_message2 TEXT,
-            key_message3 TEXT
-        )
-        "#;
-        self.conn.execute(sql, [])?;
-        Ok(())
+    // 数据库文件路径
+    pub fn path(&self) -> &str {
+        &self.path
     }
 
-    // 创建notes表
-    pub fn create_notes_table(&self) -> AnyResult<()> {
-        let sql = r#"
-        CREATE TABLE IF NOT EXISTS notes (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            todo_id INTEGER NOT NULL,
-            note_title TEXT NOT NULL,
-            note_content TEXT NOT NULL,
-            note_time TEXT NOT NULL,
-            noter TEXT,
-            note_type TEXT,
-            note_status TEXT,
-            note_tag TEXT,
-            note_priority TEXT,
-            FOREIGN KEY (todo_id) REFERENCES todo_list(id) ON DELETE CASCADE
-        )
-        "#;
-        self.conn.execute(sql, [])?;
-        Ok(())
+    // 数据库的修改计数：其他连接（其他进程）提交修改后会变化，用于发现外部修改
+    pub fn data_version(&self) -> AnyResult<i64> {
+        Ok(self.conn.query_row("PRAGMA data_version", [], |row| row.get(0))?)
+    }
+
+    // 用 SQLite 在线备份 API 把当前数据库复制到 dst（覆盖已有内容），复制期间其他连接仍可读写
+    pub fn backup_to(&self, dst: &Path) -> AnyResult<()> {
+        let mut target = Connection::open(dst)?;
+        copy_database(&self.conn, &mut target)
+    }
+
+    // 用 src 的内容整体替换当前数据库：在同一个写事务中完成，其他连接看到的要么是旧数据要么是新数据
+    pub fn restore_from(&self, src: &Path) -> AnyResult<()> {
+        let source = Connection::open_with_flags(src, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        let mut target = Connection::open(&self.path)?;
+        copy_database(&source, &mut target)
+    }
+
+    // 完整性检查，返回发现的问题（为空表示正常）
+    pub fn integrity_check(&self) -> AnyResult<Vec<String>> {
+        integrity_check(&self.conn)
     }
-    
-    // 创建提醒历史表
-    pub fn create_reminder_history_table(&self) -> AnyResult<()> {
-        let sql = r#"
-        CREATE TABLE IF NOT EXISTS reminder_history (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            todo_id INTEGER NOT NULL,
-            reminder_time TEXT NOT NULL,
-            reminder_type TEXT NOT NULL,
-            notified INTEGER NOT NULL DEFAULT 1,
-            FOREIGN KEY (todo_id) REFERENCES todo_list(id) ON DELETE CASCADE
+}
+
+/// 只读打开数据库文件并执行 PRAGMA integrity_check，返回发现的问题（为空表示正常）
+pub fn check_file(path: &Path) -> AnyResult<Vec<String>> {
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    integrity_check(&conn)
+}
+
+/// 只读打开数据库文件，读取其结构版本（PRAGMA user_version）
+pub fn file_version(path: &Path) -> AnyResult<i64> {
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    Ok(conn.query_row("PRAGMA user_version", [], |row| row.get(0))?)
+}
+
+fn integrity_check(conn: &Connection) -> AnyResult<Vec<String>> {
+    let mut stmt = conn.prepare("PRAGMA integrity_check")?;
+    let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+    let mut problems = Vec::new();
+    for row in rows {
+        let row = row?;
+        if row != "ok" {
+            problems.push(row);
+        }
+    }
+    Ok(problems)
+}
+
+/// 逐步复制整个数据库；遇到其他连接的锁时稍后重试，长时间无法获得锁时报错
+fn copy_database(from: &Connection, to: &mut Connection) -> AnyResult<()> {
+    let backup = Backup::new(from, to)?;
+    let mut retries = 0;
+    loop {
+        match backup.step(BACKUP_PAGES_PER_STEP)? {
+            StepResult::Done => return Ok(()),
+            StepResult::More => {}
+            _ => {
+                retries += 1;
+                if retries > BACKUP_BUSY_RETRIES {
+                    bail!("数据库正被其他连接锁定，请稍后重试");
+                }
+                thread::sleep(BACKUP_BUSY_PAUSE);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn memory_database() -> Database {
+        let database = Database::new(":memory:").unwrap();
+        database.initialize_tables().unwrap();
+        database
+    }
+
+    #[test]
+    fn foreign_keys_are_enforced() {
+        let database = memory_database();
+        let conn = database.get_connection();
+        conn.execute(
+            "INSERT INTO todo_list (id, title, begin_time) VALUES (1, '写周报', '2026-03-01T09:00:00+00:00')",
+            [],
         )
-        "#;
-        self.conn.execute(sql, [])?;
-        Ok(())
+        .unwrap();
+
+        // 指向不存在的待办事项的笔记、提醒历史、父任务都会被拒绝
+        let orphans = [
+            "INSERT INTO notes (todo_id, note_title, note_content, note_time) VALUES (99, 't', 'c', '2026-03-01T09:00:00+00:00')",
+            "INSERT INTO reminder_history (todo_id, reminder_time, reminder_type) VALUES (99, '2026-03-01T09:00:00+00:00', 'overdue')",
+            "INSERT INTO todo_list (title, begin_time, parent_id) VALUES ('子任务', '2026-03-01T09:00:00+00:00', 99)",
+            "INSERT INTO todo_dependencies (todo_id, depends_on_id) VALUES (1, 99)",
+        ];
+        for sql in orphans {
+            let error = conn.execute(sql, []).unwrap_err();
+            assert!(error.to_string().contains("FOREIGN KEY constraint failed"), "{}: {}", sql, error);
+        }
+
+        // 删除待办事项时级联删除它的笔记
+        conn.execute(
+            "INSERT INTO notes (todo_id, note_title, note_content, note_time) VALUES (1, 't', 'c', '2026-03-01T09:00:00+00:00')",
+            [],
+        )
+        .unwrap();
+        conn.execute("DELETE FROM todo_list WHERE id = 1", []).unwrap();
+        let notes: i64 = conn.query_row("SELECT count(*) FROM notes", [], |row| row.get(0)).unwrap();
+        assert_eq!(notes, 0);
     }
-}
\ No newline at end of file
+}
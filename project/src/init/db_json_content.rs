@@ -1,11 +1,127 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::data::reminder::{Notification, ReminderKind, Urgency, format_duration};
+use crate::data::todo_list::Priority;
 
 /// JSON数据结构（仅用于序列化/反序列化）
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct JsonDataContent {
     pub mode: String,
     #[serde(default)]
+    pub timezone: Option<String>,  // IANA 时区名称（如 Asia/Shanghai），为空时使用系统时区
+    #[serde(default)]
     pub reminder: ReminderConfig,
+    #[serde(default)]
+    pub backup: BackupConfig,
+    #[serde(default)]
+    pub trash: TrashConfig,
+    #[serde(default)]
+    pub archive: ArchiveConfig,
+}
+
+/// 自动备份配置
+///
+/// - interval_hours: 距上一次自动备份超过该时长时，常驻模式、定时任务与交互模式启动时自动备份
+/// - keep_daily / keep_weekly: 自动快照保留最近 N 天每天最新的一个、最近 M 周每周最新的一个，
+///   两者都为 0 时不轮换
+/// - directory: 备份目录，相对路径以数据库所在目录为基准，缺省为 backups
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BackupConfig {
+    pub enabled: bool,
+    #[serde(default = "default_backup_interval_hours")]
+    pub interval_hours: u32,
+    #[serde(default = "default_keep_daily")]
+    pub keep_daily: u32,
+    #[serde(default = "default_keep_weekly")]
+    pub keep_weekly: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub directory: Option<String>,
+}
+
+fn default_backup_interval_hours() -> u32 {
+    24
+}
+
+fn default_keep_daily() -> u32 {
+    7
+}
+
+fn default_keep_weekly() -> u32 {
+    4
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_hours: default_backup_interval_hours(),
+            keep_daily: default_keep_daily(),
+            keep_weekly: default_keep_weekly(),
+            directory: None,
+        }
+    }
+}
+
+/// 天数类配置（归档、回收站）允许的最大值（一百年）
+pub const MAX_DAYS: u32 = 36500;
+
+/// 回收站配置
+///
+/// - retention_days: 移入回收站超过该天数的待办事项与笔记自动彻底删除，0 表示不自动清理
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TrashConfig {
+    #[serde(default = "default_trash_retention_days")]
+    pub retention_days: u32,
+}
+
+fn default_trash_retention_days() -> u32 {
+    30
+}
+
+impl TrashConfig {
+    /// 检查配置是否有效，返回问题描述
+    pub fn validate(&self) -> Result<(), String> {
+        if self.retention_days > MAX_DAYS {
+            return Err(format!("trash.retention_days 不能超过 {}", MAX_DAYS));
+        }
+        Ok(())
+    }
+}
+
+impl Default for TrashConfig {
+    fn default() -> Self {
+        Self { retention_days: default_trash_retention_days() }
+    }
+}
+
+/// 归档配置
+///
+/// - after_days: 已完成或已取消超过该天数的待办事项自动归档，0 表示不自动归档
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ArchiveConfig {
+    #[serde(default = "default_archive_after_days")]
+    pub after_days: u32,
+}
+
+fn default_archive_after_days() -> u32 {
+    30
+}
+
+impl ArchiveConfig {
+    /// 检查配置是否有效，返回问题描述
+    pub fn validate(&self) -> Result<(), String> {
+        if self.after_days > MAX_DAYS {
+            return Err(format!("archive.after_days 不能超过 {}", MAX_DAYS));
+        }
+        Ok(())
+    }
+}
+
+impl Default for ArchiveConfig {
+    fn default() -> Self {
+        Self { after_days: default_archive_after_days() }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -15,20 +131,368 @@ pub struct ReminderConfig {
     pub notification_type: String,
     #[serde(default = "default_is_changed")]
     pub is_changed: bool,
+    #[serde(default = "default_action_wait_seconds")]
+    pub action_wait_seconds: u64,  // 桌面通知带操作按钮时等待用户点击的秒数，0 表示不显示按钮
+    #[serde(default = "default_snooze_minutes")]
+    pub snooze_minutes: u32,  // 通知上点击"稍后提醒"时推迟的分钟数
     pub rules: Vec<ReminderRule>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sinks: Vec<SinkConfig>,  // 通知渠道，非空时取代 notification_type
+}
+
+impl ReminderConfig {
+    /// 实际使用的通知渠道：未配置 sinks 时按 notification_type 生成
+    pub fn effective_sinks(&self) -> Vec<SinkConfig> {
+        if !self.sinks.is_empty() {
+            return self.sinks.clone();
+        }
+        let desktop = if cfg!(windows) { "windows" } else { "linux" };
+        let types: &[&str] = match self.notification_type.as_str() {
+            "windows" => &["windows"],
+            "linux" => &["linux"],
+            "both" if cfg!(any(windows, target_os = "linux")) => &["console", desktop],
+            _ => &["console"],
+        };
+        types.iter().map(|t| SinkConfig::new(t)).collect()
+    }
 }
 
 fn default_is_changed() -> bool {
     true
 }
 
+fn default_action_wait_seconds() -> u64 {
+    120
+}
+
+fn default_snooze_minutes() -> u32 {
+    30
+}
+
+/// 提醒规则
+///
+/// - rule_type: before_start / before_end / after_start / after_end（旧配置的 overdue 等同 after_start）
+/// - seconds_before: before_* 规则提前的秒数
+/// - seconds_after: after_* 规则延后的秒数，缺省为 0
+/// - repeat_seconds: 触发后在有效期内每隔多少秒重复提醒（如逾期后每天提醒）
+/// - id: 规则标识，用于提醒去重；缺省时由类型与时间偏移生成
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ReminderRule {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
     pub rule_type: String,
+    #[serde(default)]
     pub seconds_before: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seconds_after: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repeat_seconds: Option<i64>,
     pub message_template: String,
 }
 
+impl ReminderRule {
+    pub fn kind(&self) -> Option<ReminderKind> {
+        ReminderKind::from_string(&self.rule_type)
+    }
+
+    /// 相对参照时间的偏移秒数（before_* 为提前量，after_* 为延后量）
+    pub fn offset_seconds(&self) -> i64 {
+        match self.kind() {
+            Some(kind) if kind.is_before() => self.seconds_before.unwrap_or(0),
+            _ => self.seconds_after.unwrap_or(0),
+        }
+    }
+
+    /// 规则标识：提醒历史以它区分不同规则，修改规则的类型或偏移会被视为新规则
+    pub fn key(&self) -> String {
+        if let Some(id) = self.id.as_ref().filter(|id| !id.trim().is_empty()) {
+            return id.trim().to_string();
+        }
+        let mut key = format!("{}:{}", self.rule_type.trim().to_lowercase(), self.offset_seconds());
+        if let Some(repeat) = self.repeat_seconds {
+            key.push_str(&format!(":every{}", repeat));
+        }
+        key
+    }
+
+    /// 检查规则是否有效，返回问题描述
+    pub fn validate(&self) -> Result<(), String> {
+        let kind = self
+            .kind()
+            .ok_or_else(|| format!("未知的规则类型 '{}'", self.rule_type))?;
+        if kind.is_before() && self.seconds_before.is_none_or(|s| s <= 0) {
+            return Err(format!("{} 规则需要设置大于 0 的 seconds_before", kind));
+        }
+        if self.seconds_before.is_some_and(|s| s < 0) || self.seconds_after.is_some_and(|s| s < 0) {
+            return Err("时间偏移不能为负数".to_string());
+        }
+        if self.repeat_seconds.is_some_and(|s| s <= 0) {
+            return Err("repeat_seconds 必须大于 0".to_string());
+        }
+        Ok(())
+    }
+
+    /// 中文描述，例如 "截止前 2小时" / "截止后，每 1天 重复"
+    pub fn describe(&self) -> String {
+        let Some(kind) = self.kind() else {
+            return format!("未知规则 {}", self.rule_type);
+        };
+        let offset = self.offset_seconds();
+        let mut text = if offset == 0 {
+            if kind.is_before() { kind.label().to_string() } else { format!("{}立即", kind.label()) }
+        } else {
+            format!("{} {}", kind.label(), format_duration(offset))
+        };
+        if let Some(repeat) = self.repeat_seconds {
+            text.push_str(&format!("，每 {} 重复", format_duration(repeat)));
+        }
+        text
+    }
+}
+
+/// 通知渠道（sink）配置
+///
+/// - type: console / windows / linux / log / webhook / email / command
+/// - name: 渠道名称，用于日志和 `reminder sink-test`，缺省为类型名
+/// - enabled: 是否启用，缺省为 true
+/// - filter: 只接收符合条件的提醒，缺省接收全部
+/// - 其余字段为各类型的参数，见 validate()
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SinkConfig {
+    #[serde(rename = "type")]
+    pub sink_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default, skip_serializing_if = "SinkFilter::is_empty")]
+    pub filter: SinkFilter,
+    // log
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,  // text / json
+    // webhook
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    // email
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub smtp_host: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub smtp_port: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub to: Vec<String>,
+    // command
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    // webhook / email / command 的超时秒数
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_seconds: Option<u64>,
+    // linux：覆盖全局的 action_wait_seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub action_wait_seconds: Option<u64>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl SinkConfig {
+    /// 只指定类型、其余取默认值的渠道
+    pub fn new(sink_type: &str) -> Self {
+        Self {
+            sink_type: sink_type.to_string(),
+            enabled: true,
+            ..Default::default()
+        }
+    }
+
+    /// 渠道名称，未设置时为类型名
+    pub fn display_name(&self) -> String {
+        self.name
+            .as_ref()
+            .map(|n| n.trim())
+            .filter(|n| !n.is_empty())
+            .unwrap_or(self.sink_type.trim())
+            .to_string()
+    }
+
+    /// 超时时长，缺省 30 秒
+    pub fn timeout_seconds(&self) -> u64 {
+        self.timeout_seconds.filter(|s| *s > 0).unwrap_or(30)
+    }
+
+    /// 检查渠道配置是否完整有效，返回问题描述
+    pub fn validate(&self) -> Result<(), String> {
+        let missing = |field: &str| format!("{} 渠道需要设置 {}", self.sink_type, field);
+        let is_blank = |value: &Option<String>| value.as_deref().is_none_or(|v| v.trim().is_empty());
+        match self.sink_type.as_str() {
+            "console" | "windows" | "linux" => {}
+            "log" => {
+                if let Some(format) = &self.format
+                    && !matches!(format.as_str(), "text" | "json")
+                {
+                    return Err(format!("未知的日志格式 '{}'（可选 text / json）", format));
+                }
+            }
+            "webhook" => {
+                let url = self.url.as_deref().unwrap_or("").trim();
+                if url.is_empty() {
+                    return Err(missing("url"));
+                }
+                if !url.starts_with("http://") && !url.starts_with("https://") {
+                    return Err(format!("url 必须以 http:// 或 https:// 开头: {}", url));
+                }
+            }
+            "email" => {
+                if is_blank(&self.smtp_host) {
+                    return Err(missing("smtp_host"));
+                }
+                if is_blank(&self.from) {
+                    return Err(missing("from"));
+                }
+                if self.to.is_empty() {
+                    return Err(missing("to"));
+                }
+                if self.username.is_some() != self.password.is_some() {
+                    return Err("username 与 password 需要同时设置".to_string());
+                }
+            }
+            "command" => {
+                if is_blank(&self.command) {
+                    return Err(missing("command"));
+                }
+            }
+            other => return Err(format!("未知的通知渠道类型 '{}'", other)),
+        }
+        self.filter.validate()
+    }
+
+    /// 中文描述，例如 "webhook → https://example.com/hook"
+    pub fn describe(&self) -> String {
+        let target = match self.sink_type.as_str() {
+            "console" => "控制台".to_string(),
+            "windows" => "Windows 通知".to_string(),
+            "linux" => "Linux 桌面通知".to_string(),
+            "log" => self.path.clone().unwrap_or_else(|| DEFAULT_SINK_LOG.to_string()),
+            "webhook" => self.url.clone().unwrap_or_default(),
+            "email" => self.to.join(", "),
+            "command" => self.command.clone().unwrap_or_default(),
+            _ => String::new(),
+        };
+        let mut text = format!("{} → {}", self.sink_type, target);
+        if !self.filter.is_empty() {
+            text.push_str(&format!("（{}）", self.filter.describe()));
+        }
+        if !self.enabled {
+            text.push_str(" [已停用]");
+        }
+        text
+    }
+}
+
+/// log 渠道的缺省文件
+pub const DEFAULT_SINK_LOG: &str = "database/notifications.log";
+
+/// 通知渠道的过滤条件，各条件之间为"且"，同一条件内的多个值为"或"
+///
+/// - kinds: 规则类型，另可用 overdue 表示所有开始后 / 截止后的提醒
+/// - rules: 规则标识（如 before_end:7200）
+/// - priorities: 待办事项优先级（高 / 中 / 低），没有优先级的待办事项不匹配
+/// - urgency: critical 表示只接收紧急提醒
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SinkFilter {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub kinds: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub priorities: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub urgency: Option<String>,
+}
+
+impl SinkFilter {
+    pub fn is_empty(&self) -> bool {
+        self.kinds.is_empty() && self.rules.is_empty() && self.priorities.is_empty() && self.urgency.is_none()
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        for kind in &self.kinds {
+            if !Self::is_overdue(kind) && ReminderKind::from_string(kind).is_none() {
+                return Err(format!("过滤条件中未知的规则类型 '{}'", kind));
+            }
+        }
+        for priority in &self.priorities {
+            if Priority::from_string(priority).is_none() {
+                return Err(format!("过滤条件中未知的优先级 '{}'", priority));
+            }
+        }
+        if let Some(urgency) = &self.urgency
+            && !matches!(urgency.as_str(), "normal" | "critical")
+        {
+            return Err(format!("未知的紧急程度 '{}'（可选 normal / critical）", urgency));
+        }
+        Ok(())
+    }
+
+    /// 提醒是否符合过滤条件
+    pub fn matches(&self, notification: &Notification) -> bool {
+        let kind_ok = self.kinds.is_empty()
+            || self.kinds.iter().any(|kind| {
+                if Self::is_overdue(kind) {
+                    !notification.kind.is_before()
+                } else {
+                    ReminderKind::from_string(kind) == Some(notification.kind)
+                }
+            });
+        let rule_ok = self.rules.is_empty() || self.rules.iter().any(|r| r.trim() == notification.rule_key);
+        let priority_ok = self.priorities.is_empty()
+            || self.priorities.iter().any(|p| {
+                Priority::from_string(p).is_some_and(|p| notification.priority == Some(p))
+            });
+        let urgency_ok = self.urgency.as_deref() != Some("critical")
+            || notification.urgency() == Urgency::Critical;
+        kind_ok && rule_ok && priority_ok && urgency_ok
+    }
+
+    /// 中文描述，例如 "类型: overdue；优先级: 高"
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if !self.kinds.is_empty() {
+            parts.push(format!("类型: {}", self.kinds.join("/")));
+        }
+        if !self.rules.is_empty() {
+            parts.push(format!("规则: {}", self.rules.join("/")));
+        }
+        if !self.priorities.is_empty() {
+            parts.push(format!("优先级: {}", self.priorities.join("/")));
+        }
+        if self.urgency.as_deref() == Some("critical") {
+            parts.push("仅紧急".to_string());
+        }
+        if parts.is_empty() {
+            "全部提醒".to_string()
+        } else {
+            parts.join("；")
+        }
+    }
+
+    fn is_overdue(kind: &str) -> bool {
+        kind.trim().eq_ignore_ascii_case("overdue")
+    }
+}
+
 impl Default for ReminderConfig {
     fn default() -> Self {
         Self {
@@ -36,22 +500,50 @@ impl Default for ReminderConfig {
             check_interval_minutes: 15,
             notification_type: "both".to_string(),
             is_changed: true,
+            action_wait_seconds: default_action_wait_seconds(),
+            snooze_minutes: default_snooze_minutes(),
+            sinks: Vec::new(),
             rules: vec![
                 ReminderRule {
+                    id: None,
                     rule_type: "before_start".to_string(),
                     seconds_before: Some(86400),
+                    seconds_after: None,
+                    repeat_seconds: None,
                     message_template: "📅 任务「{title}」(ID:{id}) 将在1天后开始".to_string(),
                 },
                 ReminderRule {
+                    id: None,
                     rule_type: "before_start".to_string(),
                     seconds_before: Some(3600),
+                    seconds_after: None,
+                    repeat_seconds: None,
                     message_template: "⏰ 任务「{title}」(ID:{id}) 将在1小时后开始！".to_string(),
                 },
                 ReminderRule {
+                    id: None,
                     rule_type: "overdue".to_string(),
                     seconds_before: None,
+                    seconds_after: None,
+                    repeat_seconds: None,
                     message_template: "❌ 任务「{title}」(ID:{id}) 已超过开始时间！".to_string(),
                 },
+                ReminderRule {
+                    id: None,
+                    rule_type: "before_end".to_string(),
+                    seconds_before: Some(7200),
+                    seconds_after: None,
+                    repeat_seconds: None,
+                    message_template: "⏳ 任务「{title}」(ID:{id}) 将在2小时后到期（{end}）".to_string(),
+                },
+                ReminderRule {
+                    id: None,
+                    rule_type: "after_end".to_string(),
+                    seconds_before: None,
+                    seconds_after: None,
+                    repeat_seconds: Some(86400),
+                    message_template: "⚠️ 任务「{title}」(ID:{id}) 已超过截止时间 {end}，仍未完成".to_string(),
+                },
             ],
         }
     }
@@ -62,7 +554,11 @@ impl JsonDataContent {
     pub fn default() -> Self {
         Self {
             mode: "memo".to_string(),
+            timezone: None,
             reminder: ReminderConfig::default(),
+            backup: BackupConfig::default(),
+            trash: TrashConfig::default(),
+            archive: ArchiveConfig::default(),
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,742 @@
+//! 数据库结构迁移
+//!
+//! 使用 SQLite 的 `PRAGMA user_version` 记录当前数据库的结构版本：
+//! - 每个迁移有一个递增的版本号，按顺序执行
+//! - 每个迁移在独立事务中执行，失败时整体回滚，版本号不变
+//! - 数据库版本高于程序支持的最新版本时拒绝打开，避免旧程序破坏新数据
+//!
+//! 新增字段或表时，在 `MIGRATIONS` 末尾追加一项即可，不要修改已发布的迁移。
+//! 连接上始终开启外键约束，需要重建表（DROP 后重新创建）的迁移要注意会级联删除引用它的行。
+
+use anyhow::{Result as AnyResult, anyhow, Context};
+use rusqlite::Connection;
+
+use crate::data::change_log::TRACKED_TABLES;
+
+/// 单个迁移步骤
+pub struct Migration {
+    /// 迁移完成后的结构版本号（从 1 开始连续递增）
+    pub version: i64,
+    /// 迁移说明（写入日志）
+    pub description: &'static str,
+    /// 迁移内容，在事务中执行
+    pub up: fn(&Connection) -> AnyResult<()>,
+}
+
+/// 所有迁移（按版本号升序）
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "初始表结构（todo_list / notes / reminder_history）",
+        up: migrate_v1_initial_tables,
+    },
+    Migration {
+        version: 2,
+        description: "待办事项状态、优先级与标签",
+        up: migrate_v2_status_priority_tags,
+    },
+    Migration {
+        version: 3,
+        description: "全文检索索引（FTS5）",
+        up: migrate_v3_search_index,
+    },
+    Migration {
+        version: 4,
+        description: "重复待办事项",
+        up: migrate_v4_recurrence,
+    },
+    Migration {
+        version: 5,
+        description: "子任务与任务依赖",
+        up: migrate_v5_subtasks_dependencies,
+    },
+    Migration {
+        version: 6,
+        description: "提醒历史按规则标识去重",
+        up: migrate_v6_reminder_rule_keys,
+    },
+    Migration {
+        version: 7,
+        description: "提醒的稍后提醒与确认状态",
+        up: migrate_v7_reminder_snooze_ack,
+    },
+    Migration {
+        version: 8,
+        description: "变更日志（撤销 / 重做与修改历史）",
+        up: migrate_v8_change_log,
+    },
+    Migration {
+        version: 9,
+        description: "回收站（软删除）",
+        up: migrate_v9_trash,
+    },
+    Migration {
+        version: 10,
+        description: "归档已完成的待办事项",
+        up: migrate_v10_archive,
+    },
+    Migration {
+        version: 11,
+        description: "完成时间、完成 / 重新打开记录与计时",
+        up: migrate_v11_completion_time_tracking,
+    },
+];
+
+/// 程序支持的最新结构版本
+pub fn latest_version() -> i64 {
+    MIGRATIONS.last().map(|m| m.version).unwrap_or(0)
+}
+
+/// 读取数据库当前的结构版本
+pub fn current_version(conn: &Connection) -> AnyResult<i64> {
+    let version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    Ok(version)
+}
+
+/// 将数据库升级到最新版本，返回本次执行的迁移数量
+pub fn run_migrations(conn: &Connection) -> AnyResult<usize> {
+    apply_migrations(conn, MIGRATIONS)
+}
+
+/// 按顺序执行 migrations 中版本号高于数据库当前版本的迁移
+fn apply_migrations(conn: &Connection, migrations: &[Migration]) -> AnyResult<usize> {
+    let latest = migrations.last().map(|m| m.version).unwrap_or(0);
+    let current = current_version(conn)?;
+
+    if current > latest {
+        return Err(anyhow!(
+            "数据库结构版本为 {}，高于当前程序支持的版本 {}，请升级程序后再打开",
+            current,
+            latest
+        ));
+    }
+
+    let mut applied = 0;
+    for migration in migrations.iter().filter(|m| m.version > current) {
+        let tx = conn.unchecked_transaction()?;
+        (migration.up)(&tx).with_context(|| {
+            format!("执行数据库迁移 v{} 失败: {}", migration.version, migration.description)
+        })?;
+        // PRAGMA 不支持参数绑定，版本号来自常量，直接拼接是安全的
+        tx.execute_batch(&format!("PRAGMA user_version = {}", migration.version))?;
+        tx.commit()?;
+        applied += 1;
+    }
+
+    Ok(applied)
+}
+
+/// v1: 初始表结构
+///
+/// 使用 IF NOT EXISTS，使得在引入迁移机制之前创建的数据库（版本号为 0）
+/// 可以直接被接管，而不会重复建表。
+fn migrate_v1_initial_tables(conn: &Connection) -> AnyResult<()> {
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS todo_list (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            title TEXT NOT NULL,
+            description TEXT,
+            completed INTEGER NOT NULL DEFAULT 0,
+            begin_time TEXT NOT NULL,
+            end_time TEXT,
+            key_message1 TEXT,
+            key_message2 TEXT,
+            key_message3 TEXT
+        );
+
+        CREATE TABLE IF NOT EXISTS notes (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            todo_id INTEGER NOT NULL,
+            note_title TEXT NOT NULL,
+            note_content TEXT NOT NULL,
+            note_time TEXT NOT NULL,
+            noter TEXT,
+            note_type TEXT,
+            note_status TEXT,
+            note_tag TEXT,
+            note_priority TEXT,
+            FOREIGN KEY (todo_id) REFERENCES todo_list(id) ON DELETE CASCADE
+        );
+
+        CREATE TABLE IF NOT EXISTS reminder_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            todo_id INTEGER NOT NULL,
+            reminder_time TEXT NOT NULL,
+            reminder_type TEXT NOT NULL,
+            notified INTEGER NOT NULL DEFAULT 1,
+            FOREIGN KEY (todo_id) REFERENCES todo_list(id) ON DELETE CASCADE
+        );
+        "#,
+    )?;
+    Ok(())
+}
+
+/// v2: 用 status 取代 completed，新增 priority 字段与标签表
+///
+/// 已完成的事项迁移为 done，其余迁移为 todo。
+fn migrate_v2_status_priority_tags(conn: &Connection) -> AnyResult<()> {
+    conn.execute_batch(
+        r#"
+        ALTER TABLE todo_list ADD COLUMN status TEXT NOT NULL DEFAULT 'todo';
+        UPDATE todo_list SET status = CASE WHEN completed != 0 THEN 'done' ELSE 'todo' END;
+        ALTER TABLE todo_list DROP COLUMN completed;
+
+        ALTER TABLE todo_list ADD COLUMN priority TEXT;
+
+        CREATE TABLE IF NOT EXISTS tags (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE COLLATE NOCASE
+        );
+
+        CREATE TABLE IF NOT EXISTS todo_tags (
+            todo_id INTEGER NOT NULL,
+            tag_id INTEGER NOT NULL,
+            PRIMARY KEY (todo_id, tag_id),
+            FOREIGN KEY (todo_id) REFERENCES todo_list(id) ON DELETE CASCADE,
+            FOREIGN KEY (tag_id) REFERENCES tags(id) ON DELETE CASCADE
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_todo_list_status ON todo_list(status);
+        CREATE INDEX IF NOT EXISTS idx_todo_tags_tag_id ON todo_tags(tag_id);
+        "#,
+    )?;
+    Ok(())
+}
+
+/// v3: FTS5 全文检索索引，覆盖待办事项与笔记，由触发器保持同步
+///
+/// 使用 trigram 分词器以支持中文子串检索。rowid 约定：
+/// 待办事项为 id * 2，笔记为 id * 2 + 1，便于触发器按 rowid 精确删除。
+fn migrate_v3_search_index(conn: &Connection) -> AnyResult<()> {
+    conn.execute_batch(
+        r#"
+        CREATE VIRTUAL TABLE IF NOT EXISTS search_index USING fts5(
+            kind UNINDEXED,
+            ref_id UNINDEXED,
+            todo_id UNINDEXED,
+            title,
+            body,
+            tokenize = 'trigram'
+        );
+
+        CREATE TRIGGER IF NOT EXISTS search_index_todo_ai AFTER INSERT ON todo_list BEGIN
+            INSERT INTO search_index (rowid, kind, ref_id, todo_id, title, body)
+            VALUES (new.id * 2, 'todo', new.id, new.id, new.title,
+                trim(coalesce(new.description, '') || ' ' || coalesce(new.key_message1, '') || ' '
+                    || coalesce(new.key_message2, '') || ' ' || coalesce(new.key_message3, '')));
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS search_index_todo_au
+        AFTER UPDATE OF title, description, key_message1, key_message2, key_message3 ON todo_list BEGIN
+            DELETE FROM search_index WHERE rowid = old.id * 2;
+            INSERT INTO search_index (rowid, kind, ref_id, todo_id, title, body)
+            VALUES (new.id * 2, 'todo', new.id, new.id, new.title,
+                trim(coalesce(new.description, '') || ' ' || coalesce(new.key_message1, '') || ' '
+                    || coalesce(new.key_message2, '') || ' ' || coalesce(new.key_message3, '')));
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS search_index_todo_ad AFTER DELETE ON todo_list BEGIN
+            DELETE FROM search_index WHERE rowid = old.id * 2;
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS search_index_note_ai AFTER INSERT ON notes BEGIN
+            INSERT INTO search_index (rowid, kind, ref_id, todo_id, title, body)
+            VALUES (new.id * 2 + 1, 'note', new.id, new.todo_id, new.note_title, new.note_content);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS search_index_note_au
+        AFTER UPDATE OF todo_id, note_title, note_content ON notes BEGIN
+            DELETE FROM search_index WHERE rowid = old.id * 2 + 1;
+            INSERT INTO search_index (rowid, kind, ref_id, todo_id, title, body)
+            VALUES (new.id * 2 + 1, 'note', new.id, new.todo_id, new.note_title, new.note_content);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS search_index_note_ad AFTER DELETE ON notes BEGIN
+            DELETE FROM search_index WHERE rowid = old.id * 2 + 1;
+        END;
+
+        -- 为已有数据建立索引
+        INSERT INTO search_index (rowid, kind, ref_id, todo_id, title, body)
+        SELECT id * 2, 'todo', id, id, title,
+            trim(coalesce(description, '') || ' ' || coalesce(key_message1, '') || ' '
+                || coalesce(key_message2, '') || ' ' || coalesce(key_message3, ''))
+        FROM todo_list;
+
+        INSERT INTO search_index (rowid, kind, ref_id, todo_id, title, body)
+        SELECT id * 2 + 1, 'note', id, todo_id, note_title, note_content
+        FROM notes;
+        "#,
+    )?;
+    Ok(())
+}
+
+/// v4: 重复规则、重复系列，以及按重复次数区分的提醒历史
+fn migrate_v4_recurrence(conn: &Connection) -> AnyResult<()> {
+    conn.execute_batch(
+        r#"
+        ALTER TABLE todo_list ADD COLUMN recurrence TEXT;
+        ALTER TABLE todo_list ADD COLUMN series_id INTEGER;
+        ALTER TABLE todo_list ADD COLUMN occurrence INTEGER NOT NULL DEFAULT 1;
+        CREATE INDEX IF NOT EXISTS idx_todo_list_series ON todo_list(series_id, occurrence);
+
+        ALTER TABLE reminder_history ADD COLUMN occurrence_time TEXT;
+        "#,
+    )?;
+    Ok(())
+}
+
+/// v5: 父子任务层级（parent_id）与任务依赖关系表
+fn migrate_v5_subtasks_dependencies(conn: &Connection) -> AnyResult<()> {
+    conn.execute_batch(
+        r#"
+        ALTER TABLE todo_list ADD COLUMN parent_id INTEGER REFERENCES todo_list(id) ON DELETE SET NULL;
+        CREATE INDEX IF NOT EXISTS idx_todo_list_parent ON todo_list(parent_id);
+
+        CREATE TABLE IF NOT EXISTS todo_dependencies (
+            todo_id INTEGER NOT NULL,
+            depends_on_id INTEGER NOT NULL,
+            PRIMARY KEY (todo_id, depends_on_id),
+            FOREIGN KEY (todo_id) REFERENCES todo_list(id) ON DELETE CASCADE,
+            FOREIGN KEY (depends_on_id) REFERENCES todo_list(id) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS idx_todo_dependencies_depends_on ON todo_dependencies(depends_on_id);
+        "#,
+    )?;
+    Ok(())
+}
+
+/// v6: 提醒历史记录触发的规则标识（rule_key）与计划触发时间（trigger_at，Unix 秒）
+///
+/// 旧记录按原有的三种固定类型换算为对应默认规则的标识，避免升级后重复提醒。
+fn migrate_v6_reminder_rule_keys(conn: &Connection) -> AnyResult<()> {
+    conn.execute_batch(
+        r#"
+        ALTER TABLE reminder_history ADD COLUMN rule_key TEXT;
+        ALTER TABLE reminder_history ADD COLUMN trigger_at INTEGER;
+
+        UPDATE reminder_history SET
+            rule_key = CASE reminder_type
+                WHEN '1_day_before' THEN 'before_start:86400'
+                WHEN '1_hour_before' THEN 'before_start:3600'
+                WHEN 'overdue' THEN 'overdue:0'
+                ELSE reminder_type
+            END,
+            trigger_at = (
+                SELECT CAST(strftime('%s', coalesce(reminder_history.occurrence_time, t.begin_time)) AS INTEGER)
+                FROM todo_list t WHERE t.id = reminder_history.todo_id
+            ) - CASE reminder_type
+                WHEN '1_day_before' THEN 86400
+                WHEN '1_hour_before' THEN 3600
+                ELSE 0
+            END;
+
+        UPDATE reminder_history SET reminder_type = CASE reminder_type
+            WHEN '1_day_before' THEN 'before_start'
+            WHEN '1_hour_before' THEN 'before_start'
+            ELSE reminder_type
+        END;
+
+        CREATE INDEX IF NOT EXISTS idx_reminder_history_rule ON reminder_history(todo_id, rule_key, trigger_at);
+        "#,
+    )?;
+    Ok(())
+}
+
+/// v7: 提醒状态（sent / snoozed / acknowledged）、稍后提醒时间（snoozed_until，Unix 秒）与提醒内容
+fn migrate_v7_reminder_snooze_ack(conn: &Connection) -> AnyResult<()> {
+    conn.execute_batch(
+        r#"
+        ALTER TABLE reminder_history ADD COLUMN state TEXT NOT NULL DEFAULT 'sent';
+        ALTER TABLE reminder_history ADD COLUMN snoozed_until INTEGER;
+        ALTER TABLE reminder_history ADD COLUMN snooze_count INTEGER NOT NULL DEFAULT 0;
+        ALTER TABLE reminder_history ADD COLUMN acknowledged_at TEXT;
+        ALTER TABLE reminder_history ADD COLUMN message TEXT;
+
+        CREATE INDEX IF NOT EXISTS idx_reminder_history_snoozed ON reminder_history(state, snoozed_until);
+        "#,
+    )?;
+    Ok(())
+}
+
+/// v8: 变更日志
+///
+/// - change_sets: 每次操作一行（操作说明、操作者、来源、时间，以及撤销 / 重做的状态）
+/// - change_log: 每行数据的变化，由触发器写入修改前后的整行内容；只能追加，不能修改或删除
+/// - change_context: 只有一行，记录当前正在进行的操作；为空时（如迁移、外部工具修改）不记录
+fn migrate_v8_change_log(conn: &Connection) -> AnyResult<()> {
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS change_sets (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            action TEXT NOT NULL,
+            actor TEXT NOT NULL,
+            source TEXT NOT NULL,
+            kind TEXT NOT NULL DEFAULT 'edit',
+            target_id INTEGER,
+            state TEXT NOT NULL DEFAULT 'applied',
+            created_at TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_change_sets_state ON change_sets(kind, state);
+
+        CREATE TABLE IF NOT EXISTS change_log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            change_set_id INTEGER NOT NULL REFERENCES change_sets(id),
+            table_name TEXT NOT NULL,
+            operation TEXT NOT NULL,
+            todo_id INTEGER,
+            before_image TEXT,
+            after_image TEXT
+        );
+        CREATE INDEX IF NOT EXISTS idx_change_log_set ON change_log(change_set_id);
+        CREATE INDEX IF NOT EXISTS idx_change_log_todo ON change_log(todo_id);
+
+        CREATE TRIGGER IF NOT EXISTS change_log_no_update BEFORE UPDATE ON change_log BEGIN
+            SELECT RAISE(ABORT, 'change_log 只能追加');
+        END;
+        CREATE TRIGGER IF NOT EXISTS change_log_no_delete BEFORE DELETE ON change_log BEGIN
+            SELECT RAISE(ABORT, 'change_log 只能追加');
+        END;
+
+        CREATE TABLE IF NOT EXISTS change_context (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            change_set_id INTEGER
+        );
+        INSERT OR IGNORE INTO change_context (id, change_set_id) VALUES (1, NULL);
+        "#,
+    )?;
+    create_change_log_triggers(conn)
+}
+
+/// v9: 回收站
+///
+/// 待办事项与笔记增加 deleted_at（移入回收站的时间），不为空的行不再显示。
+/// 全文索引只收录不在回收站中的行：更新触发器同时监听 deleted_at，移入回收站时从索引中移除，恢复时重新加入。
+fn migrate_v9_trash(conn: &Connection) -> AnyResult<()> {
+    conn.execute_batch(
+        r#"
+        ALTER TABLE todo_list ADD COLUMN deleted_at TEXT;
+        ALTER TABLE notes ADD COLUMN deleted_at TEXT;
+        CREATE INDEX IF NOT EXISTS idx_todo_list_deleted ON todo_list(deleted_at);
+        CREATE INDEX IF NOT EXISTS idx_notes_deleted ON notes(deleted_at);
+
+        DROP TRIGGER IF EXISTS search_index_todo_ai;
+        CREATE TRIGGER search_index_todo_ai AFTER INSERT ON todo_list WHEN new.deleted_at IS NULL BEGIN
+            INSERT INTO search_index (rowid, kind, ref_id, todo_id, title, body)
+            VALUES (new.id * 2, 'todo', new.id, new.id, new.title,
+                trim(coalesce(new.description, '') || ' ' || coalesce(new.key_message1, '') || ' '
+                    || coalesce(new.key_message2, '') || ' ' || coalesce(new.key_message3, '')));
+        END;
+
+        DROP TRIGGER IF EXISTS search_index_note_ai;
+        CREATE TRIGGER search_index_note_ai AFTER INSERT ON notes WHEN new.deleted_at IS NULL BEGIN
+            INSERT INTO search_index (rowid, kind, ref_id, todo_id, title, body)
+            VALUES (new.id * 2 + 1, 'note', new.id, new.todo_id, new.note_title, new.note_content);
+        END;
+
+        DROP TRIGGER IF EXISTS search_index_todo_au;
+        CREATE TRIGGER search_index_todo_au
+        AFTER UPDATE OF title, description, key_message1, key_message2, key_message3, deleted_at ON todo_list BEGIN
+            DELETE FROM search_index WHERE rowid = old.id * 2;
+            INSERT INTO search_index (rowid, kind, ref_id, todo_id, title, body)
+            SELECT new.id * 2, 'todo', new.id, new.id, new.title,
+                trim(coalesce(new.description, '') || ' ' || coalesce(new.key_message1, '') || ' '
+                    || coalesce(new.key_message2, '') || ' ' || coalesce(new.key_message3, ''))
+            WHERE new.deleted_at IS NULL;
+        END;
+
+        DROP TRIGGER IF EXISTS search_index_note_au;
+        CREATE TRIGGER search_index_note_au
+        AFTER UPDATE OF todo_id, note_title, note_content, deleted_at ON notes BEGIN
+            DELETE FROM search_index WHERE rowid = old.id * 2 + 1;
+            INSERT INTO search_index (rowid, kind, ref_id, todo_id, title, body)
+            SELECT new.id * 2 + 1, 'note', new.id, new.todo_id, new.note_title, new.note_content
+            WHERE new.deleted_at IS NULL;
+        END;
+        "#,
+    )?;
+    create_change_log_triggers(conn)
+}
+
+/// v10: 归档时间（archived_at），不为空的待办事项默认不在列表中显示
+fn migrate_v10_archive(conn: &Connection) -> AnyResult<()> {
+    conn.execute_batch(
+        r#"
+        ALTER TABLE todo_list ADD COLUMN archived_at TEXT;
+        CREATE INDEX IF NOT EXISTS idx_todo_list_archived ON todo_list(archived_at);
+        "#,
+    )?;
+    create_change_log_triggers(conn)
+}
+
+/// v11: 完成时间（completed_at）、完成记录表（completion_events）与计时表（time_sessions）
+///
+/// 已完成的待办事项的 completed_at 从 v8 起的修改历史回填：取最后一次由其他状态改为已完成的操作时间；
+/// v8 之前完成、修改历史中没有记录的保持为空。
+fn migrate_v11_completion_time_tracking(conn: &Connection) -> AnyResult<()> {
+    conn.execute_batch(
+        r#"
+        ALTER TABLE todo_list ADD COLUMN completed_at TEXT;
+
+        -- 已完成的待办事项取修改历史中最后一次改为已完成的时间，没有记录时留空
+        UPDATE todo_list SET completed_at = (
+            SELECT MAX(s.created_at) FROM change_log l JOIN change_sets s ON s.id = l.change_set_id
+            WHERE l.table_name = 'todo_list' AND l.todo_id = todo_list.id
+              AND json_extract(l.after_image, '$.status') = 'done'
+              AND (l.before_image IS NULL OR json_extract(l.before_image, '$.status') IS NOT 'done')
+        )
+        WHERE status = 'done';
+
+        -- 完成与重新打开的记录，只追加，由触发器在状态变化时写入（撤销 / 重做也会记录）
+        CREATE TABLE IF NOT EXISTS completion_events (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            todo_id INTEGER NOT NULL,
+            event TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (todo_id) REFERENCES todo_list(id) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS idx_completion_events_todo ON completion_events(todo_id);
+
+        CREATE TRIGGER IF NOT EXISTS completion_events_au AFTER UPDATE OF status ON todo_list
+        WHEN old.status IS NOT new.status AND 'done' IN (old.status, new.status) BEGIN
+            INSERT INTO completion_events (todo_id, event, created_at)
+            VALUES (
+                new.id,
+                CASE WHEN new.status = 'done' THEN 'completed' ELSE 'reopened' END,
+                strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now')
+            );
+        END;
+
+        -- 计时：stopped_at 为空表示正在计时
+        CREATE TABLE IF NOT EXISTS time_sessions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            todo_id INTEGER NOT NULL,
+            started_at TEXT NOT NULL,
+            stopped_at TEXT,
+            FOREIGN KEY (todo_id) REFERENCES todo_list(id) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS idx_time_sessions_todo ON time_sessions(todo_id);
+        "#,
+    )?;
+    create_change_log_triggers(conn)
+}
+
+/// 为记录变更日志的表（重新）创建触发器
+///
+/// 触发器按表当前的全部列生成，之后的迁移给这些表增加列时需要再调用一次
+pub fn create_change_log_triggers(conn: &Connection) -> AnyResult<()> {
+    const CURRENT_SET: &str = "(SELECT change_set_id FROM change_context WHERE id = 1)";
+
+    for table in TRACKED_TABLES {
+        let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table.name))?;
+        let columns: Vec<String> = stmt
+            .query_map([], |row| row.get::<_, String>(1))?
+            .collect::<Result<_, _>>()?;
+        // 表在之后的迁移中才创建，届时再生成触发器
+        if columns.is_empty() {
+            continue;
+        }
+        let image = |prefix: &str| {
+            let pairs: Vec<String> = columns.iter().map(|c| format!("'{c}', {prefix}.\"{c}\"")).collect();
+            format!("json_object({})", pairs.join(", "))
+        };
+        let todo_id = |prefix: &str| match table.todo_column {
+            Some(column) => format!("{prefix}.\"{column}\""),
+            None => "NULL".to_string(),
+        };
+        let changed: Vec<String> = columns.iter().map(|c| format!("old.\"{c}\" IS NOT new.\"{c}\"")).collect();
+        let name = table.name;
+
+        conn.execute_batch(&format!(
+            r#"
+            DROP TRIGGER IF EXISTS change_log_{name}_ai;
+            DROP TRIGGER IF EXISTS change_log_{name}_au;
+            DROP TRIGGER IF EXISTS change_log_{name}_ad;
+
+            CREATE TRIGGER change_log_{name}_ai AFTER INSERT ON {name}
+            WHEN {CURRENT_SET} IS NOT NULL BEGIN
+                INSERT INTO change_log (change_set_id, table_name, operation, todo_id, before_image, after_image)
+                VALUES ({CURRENT_SET}, '{name}', 'insert', {new_todo}, NULL, {new_image});
+            END;
+
+            CREATE TRIGGER change_log_{name}_au AFTER UPDATE ON {name}
+            WHEN {CURRENT_SET} IS NOT NULL AND ({changed}) BEGIN
+                INSERT INTO change_log (change_set_id, table_name, operation, todo_id, before_image, after_image)
+                VALUES ({CURRENT_SET}, '{name}', 'update', {new_todo}, {old_image}, {new_image});
+            END;
+
+            CREATE TRIGGER change_log_{name}_ad AFTER DELETE ON {name}
+            WHEN {CURRENT_SET} IS NOT NULL BEGIN
+                INSERT INTO change_log (change_set_id, table_name, operation, todo_id, before_image, after_image)
+                VALUES ({CURRENT_SET}, '{name}', 'delete', {old_todo}, {old_image}, NULL);
+            END;
+            "#,
+            new_todo = todo_id("new"),
+            old_todo = todo_id("old"),
+            new_image = image("new"),
+            old_image = image("old"),
+            changed = changed.join(" OR "),
+        ))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::init::db_json_content::{ReminderConfig, ReminderRule};
+
+    fn memory_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("PRAGMA foreign_keys = ON").unwrap();
+        conn
+    }
+
+    /// 只执行到 version 为止的迁移，得到该版本的数据库
+    fn database_at(version: i64) -> Connection {
+        let conn = memory_db();
+        let steps: Vec<Migration> = MIGRATIONS
+            .iter()
+            .filter(|m| m.version <= version)
+            .map(|m| Migration { version: m.version, description: m.description, up: m.up })
+            .collect();
+        apply_migrations(&conn, &steps).unwrap();
+        assert_eq!(current_version(&conn).unwrap(), version);
+        conn
+    }
+
+    /// 数据库结构（表、索引、触发器的建表语句）
+    fn schema(conn: &Connection) -> Vec<String> {
+        let mut stmt = conn
+            .prepare("SELECT type || ' ' || name || ': ' || coalesce(sql, '') FROM sqlite_master ORDER BY type, name")
+            .unwrap();
+        stmt.query_map([], |row| row.get(0)).unwrap().collect::<Result<_, _>>().unwrap()
+    }
+
+    #[test]
+    fn versions_are_consecutive() {
+        for (index, migration) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(migration.version, index as i64 + 1, "{}", migration.description);
+        }
+    }
+
+    #[test]
+    fn upgrades_every_old_version_to_latest() {
+        let fresh = schema(&database_at(latest_version()));
+        for version in 0..latest_version() {
+            let conn = database_at(version);
+            let applied = run_migrations(&conn).unwrap();
+            assert_eq!(applied as i64, latest_version() - version, "从 v{} 升级", version);
+            assert_eq!(current_version(&conn).unwrap(), latest_version());
+            assert_eq!(schema(&conn), fresh, "从 v{} 升级后的结构与新建的不同", version);
+            assert_eq!(run_migrations(&conn).unwrap(), 0);
+        }
+    }
+
+    #[test]
+    fn upgrade_keeps_existing_data() {
+        // 引入迁移机制之前的程序写入的数据（reminder_type 为旧版本的取值）
+        let conn = database_at(1);
+        conn.execute_batch(
+            r#"
+            INSERT INTO todo_list (id, title, completed, begin_time) VALUES (1, '写周报', 1, '2025-01-06T09:00:00+00:00');
+            INSERT INTO todo_list (id, title, completed, begin_time) VALUES (2, '买菜', 0, '2025-01-07T09:00:00+00:00');
+            INSERT INTO notes (todo_id, note_title, note_content, note_time)
+            VALUES (1, '进展', '已完成初稿', '2025-01-06T10:00:00+00:00');
+            INSERT INTO reminder_history (id, todo_id, reminder_time, reminder_type) VALUES
+                (1, 2, '2025-01-06T09:00:00+00:00', '1_day_before'),
+                (2, 2, '2025-01-07T08:00:00+00:00', '1_hour_before'),
+                (3, 2, '2025-01-07T09:00:00+00:00', 'overdue');
+            "#,
+        )
+        .unwrap();
+
+        run_migrations(&conn).unwrap();
+
+        let count = |table: &str| -> i64 {
+            conn.query_row(&format!("SELECT count(*) FROM {}", table), [], |row| row.get(0)).unwrap()
+        };
+        assert_eq!((count("todo_list"), count("notes"), count("reminder_history")), (2, 1, 3));
+
+        let statuses: Vec<(i32, String)> = conn
+            .prepare("SELECT id, status FROM todo_list ORDER BY id")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(statuses, vec![(1, "done".to_string()), (2, "todo".to_string())]);
+
+        // v3 为已有数据建立全文索引
+        let hits: i64 = conn
+            .query_row("SELECT count(*) FROM search_index WHERE search_index MATCH '完成初稿'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(hits, 1);
+
+        // v6 将旧的提醒类型换算为规则标识与计划触发时间，与默认提醒规则的标识一致，升级后不会重复提醒
+        let begin = 1736240400; // 2025-01-07T09:00:00Z
+        let reminders: Vec<(String, String, i64)> = conn
+            .prepare("SELECT reminder_type, rule_key, trigger_at FROM reminder_history ORDER BY id")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            reminders,
+            vec![
+                ("before_start".to_string(), "before_start:86400".to_string(), begin - 86400),
+                ("before_start".to_string(), "before_start:3600".to_string(), begin - 3600),
+                ("overdue".to_string(), "overdue:0".to_string(), begin),
+            ]
+        );
+        let default_keys: Vec<String> = ReminderConfig::default().rules.iter().map(ReminderRule::key).collect();
+        for (_, rule_key, _) in &reminders {
+            assert!(default_keys.contains(rule_key), "{} 不是默认规则的标识", rule_key);
+        }
+    }
+
+    #[test]
+    fn refuses_newer_version() {
+        let conn = database_at(latest_version());
+        conn.execute_batch(&format!("PRAGMA user_version = {}", latest_version() + 1)).unwrap();
+
+        let error = run_migrations(&conn).unwrap_err();
+        assert!(error.to_string().contains("高于当前程序支持的版本"), "{}", error);
+        assert_eq!(current_version(&conn).unwrap(), latest_version() + 1);
+    }
+
+    #[test]
+    fn failed_step_is_rolled_back() {
+        fn create_table(conn: &Connection) -> AnyResult<()> {
+            conn.execute_batch("CREATE TABLE first (id INTEGER PRIMARY KEY)")?;
+            Ok(())
+        }
+        fn create_then_fail(conn: &Connection) -> AnyResult<()> {
+            conn.execute_batch("CREATE TABLE second (id INTEGER PRIMARY KEY); INSERT INTO first VALUES (1);")?;
+            conn.execute_batch("INSERT INTO missing_table VALUES (1)")?;
+            Ok(())
+        }
+        let migrations = [
+            Migration { version: 1, description: "first", up: create_table },
+            Migration { version: 2, description: "second", up: create_then_fail },
+        ];
+
+        let conn = memory_db();
+        let error = apply_migrations(&conn, &migrations).unwrap_err();
+        assert!(format!("{:#}", error).contains("v2"), "{:#}", error);
+
+        // v1 已提交，v2 的修改全部回滚，版本号停在 1
+        assert_eq!(current_version(&conn).unwrap(), 1);
+        let tables: Vec<String> = conn
+            .prepare("SELECT name FROM sqlite_master WHERE type = 'table' ORDER BY name")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(tables, vec!["first".to_string()]);
+        let rows: i64 = conn.query_row("SELECT count(*) FROM first", [], |row| row.get(0)).unwrap();
+        assert_eq!(rows, 0);
+    }
+}
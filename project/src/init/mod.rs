@@ -3,4 +3,5 @@ pub mod config_load;
 pub mod db_json;
 pub mod db_json_content;
 pub mod config_reset;
-
+pub mod migration;
+pub mod timezone;
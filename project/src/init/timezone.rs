@@ -0,0 +1,31 @@
+/// 时区初始化模块
+/// 负责在程序启动时读取配置中的时区
+use crate::data::local_time;
+use crate::init::db_json::JsonConfig;
+use crate::service::logger::Logger;
+
+/// 读取 config.json 中的 timezone 并设为本次运行使用的时区
+///
+/// 未设置时使用系统时区；名称无法识别时给出警告并回退到系统时区，
+/// 避免配置写错后程序无法启动
+pub fn load_timezone(json_config: &JsonConfig) {
+    let configured = json_config
+        .get_value("timezone")
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .filter(|name| !name.trim().is_empty());
+
+    let zone = match configured {
+        Some(name) => match local_time::parse_zone(&name) {
+            Some(zone) => zone,
+            None => {
+                let message = format!("无法识别的时区 '{}'，已改用系统时区", name);
+                eprintln!("⚠️  {}", message);
+                Logger::log("WARN", &message);
+                local_time::system_zone()
+            }
+        },
+        None => local_time::system_zone(),
+    };
+    local_time::set_zone(zone);
+}
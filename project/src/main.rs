@@ -24,6 +24,21 @@ fn main() -> AnyResult<()> {
         // 后台模式：直接执行提醒检查
         return runner::reminder::run_check_mode();
     }
+
+    // 常驻模式：进程常驻，按提醒时间自行检查
+    if args.len() > 1 && args[1] == "--daemon" {
+        if let Err(e) = runner::daemon::run_daemon_mode() {
+            eprintln!("❌ {:#}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    // 带子命令的非交互模式（供脚本调用），退出码见 args_cli
+    if args.len() > 1 {
+        let code = cli::args_cli::run(&args[1..]);
+        std::process::exit(code);
+    }
     
     // 正常的交互式模式
     run_interactive_mode()
@@ -41,9 +56,42 @@ fn run_interactive_mode() -> AnyResult<()> {
     // 获取JSON配置文件路径并初始化
     let json_path = config_load::get_config_value("json", Some("path"));
     let json_config = db_json::JsonConfig::new(&json_path)?;
+
+    // 读取配置中的时区（未设置时使用系统时区）
+    init::timezone::load_timezone(&json_config);
     
     // 重置提醒配置的 is_changed 标记（标记配置已同步）
     init::config_reset::reset_reminder_changed_flag(&json_config)?;
+
+    // 到了自动备份的时间时创建快照，失败时只提示，不影响使用
+    match service::backup_serv::run_scheduled(&db, &json_config) {
+        Ok(Some(snapshot)) => println!("💾 已创建自动快照: {}", snapshot.name),
+        Ok(None) => {}
+        Err(e) => {
+            println!("⚠️  自动备份失败: {:#}", e);
+            service::logger::Logger::log("WARN", &format!("自动备份失败: {:#}", e));
+        }
+    }
+
+    // 彻底删除回收站中过期的项目
+    match service::trash_serv::run_scheduled(&db, &json_config) {
+        Ok(0) => {}
+        Ok(count) => println!("🗑️  已彻底删除回收站中过期的 {} 项", count),
+        Err(e) => {
+            println!("⚠️  清理回收站失败: {:#}", e);
+            service::logger::Logger::log("WARN", &format!("清理回收站失败: {:#}", e));
+        }
+    }
+
+    // 自动归档已结束超过 archive.after_days 天的待办事项
+    match service::archive_serv::run_scheduled(&db, &json_config) {
+        Ok(0) => {}
+        Ok(count) => println!("📦 已自动归档 {} 个待办事项", count),
+        Err(e) => {
+            println!("⚠️  自动归档失败: {:#}", e);
+            service::logger::Logger::log("WARN", &format!("自动归档失败: {:#}", e));
+        }
+    }
     
     // 启动时检查一次提醒（可选功能）
     // runner::reminder::check_on_startup(&db, &json_config)?;
@@ -0,0 +1,255 @@
+//! 常驻模式
+//!
+//! 不依赖系统定时任务：进程常驻，休眠到下一个提醒的触发时间再检查。
+//! - 休眠期间每秒查看一次信号，以及 config.json、数据库是否被修改，有修改时立即重新计算
+//! - 单次休眠不超过 check_interval_minutes；稍后提醒到期的时间同样参与计算
+//! - 启动时写入 pid 文件（与数据库同目录的 todolist.pid），已有实例在运行时拒绝启动
+//! - SIGTERM / SIGINT：删除 pid 文件后退出；SIGHUP（仅 Unix）：立即重新加载配置并检查
+//! - 自动备份同样在这里执行：下一次自动备份的时间也参与休眠时间的计算
+
+use anyhow::{Result as AnyResult, bail};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use crate::data::local_time;
+use crate::init::{self, config_load, database, db_json};
+use crate::runner::reminder;
+use crate::service::{archive_serv, backup_serv, change_log_serv, trash_serv};
+use crate::service::logger::Logger;
+use crate::service::reminder_serv::ReminderService;
+
+/// pid 文件名（与数据库文件同目录）
+const PID_FILE: &str = "todolist.pid";
+/// 休眠期间查看信号和文件变化的间隔
+const TICK: Duration = Duration::from_secs(1);
+
+/// 常驻模式入口
+///
+/// 使用方式：
+/// ```bash
+/// project --daemon
+/// ```
+pub fn run_daemon_mode() -> AnyResult<()> {
+    change_log_serv::set_source("scheduled");
+    let db_path = config_load::get_config_value("database", Some("path"));
+    let db = database::Database::new(&db_path)?;
+    db.initialize_tables()?;
+
+    let json_path = config_load::get_config_value("json", Some("path"));
+    let json_config = db_json::JsonConfig::new(&json_path)?;
+
+    let _pid_file = PidFile::acquire(&Path::new(&db_path).with_file_name(PID_FILE))?;
+    let signals = Signals::register()?;
+    log("INFO", &format!("常驻模式已启动 (PID {})", process::id()));
+
+    let mut config_stamp = modified_time(&json_path);
+    loop {
+        // 每轮都重新读取配置，时区、规则、通知渠道的修改随之生效
+        init::timezone::load_timezone(&json_config);
+        let config = match ReminderService::get_reminder_config(&json_config) {
+            Ok(config) => Some(config),
+            Err(e) => {
+                log("WARN", &format!("读取提醒配置失败，等待配置修改: {:#}", e));
+                None
+            }
+        };
+        let enabled = config.as_ref().is_some_and(|c| c.enabled);
+
+        let mut wake_at = None;
+        if let Some(config) = config.as_ref().filter(|c| c.enabled) {
+            match reminder::check_and_send(&db, &json_config) {
+                Ok(0) => {}
+                Ok(count) => log("INFO", &format!("已发送 {} 条提醒", count)),
+                Err(e) => log("WARN", &format!("提醒检查失败: {:#}", e)),
+            }
+            let cap = Utc::now() + ChronoDuration::minutes(config.check_interval_minutes.max(1) as i64);
+            let next_due = ReminderService::next_due(&db, &json_config).unwrap_or_else(|e| {
+                log("WARN", &format!("计算下一次提醒时间失败: {:#}", e));
+                None
+            });
+            wake_at = Some(next_due.map_or(cap, |due| due.min(cap)));
+            match next_due {
+                Some(due) if due <= cap => println!(
+                    "💤 下一个提醒: {}",
+                    local_time::format(&due, "%Y-%m-%d %H:%M:%S")
+                ),
+                _ => println!(
+                    "💤 暂无临近的提醒，{} 再次检查",
+                    local_time::format(&cap, "%Y-%m-%d %H:%M:%S")
+                ),
+            }
+        } else if config.is_some() && !enabled {
+            println!("💤 提醒功能已禁用，等待配置修改");
+        }
+
+        match backup_serv::run_scheduled(&db, &json_config) {
+            Ok(Some(snapshot)) => log("INFO", &format!("已创建自动快照 {}", snapshot.name)),
+            Ok(None) => {}
+            Err(e) => log("WARN", &format!("自动备份失败: {:#}", e)),
+        }
+        match trash_serv::run_scheduled(&db, &json_config) {
+            Ok(0) => {}
+            Ok(count) => log("INFO", &format!("已彻底删除回收站中过期的 {} 项", count)),
+            Err(e) => log("WARN", &format!("清理回收站失败: {:#}", e)),
+        }
+        match archive_serv::run_scheduled(&db, &json_config) {
+            Ok(0) => {}
+            Ok(count) => log("INFO", &format!("已自动归档 {} 个待办事项", count)),
+            Err(e) => log("WARN", &format!("自动归档失败: {:#}", e)),
+        }
+        let next_backup = backup_serv::get_backup_config(&json_config)
+            .and_then(|config| backup_serv::next_scheduled(&db, &config))
+            .unwrap_or_else(|e| {
+                log("WARN", &format!("计算下一次自动备份时间失败: {:#}", e));
+                None
+            });
+        // 备份失败时 next_scheduled 仍是过去的时间，至少推迟 10 分钟再试，避免反复重试
+        let next_backup = next_backup.map(|at| at.max(Utc::now() + ChronoDuration::minutes(10)));
+        wake_at = match (wake_at, next_backup) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+
+        match wait(wake_at, &signals, &db, &json_path, &mut config_stamp)? {
+            Wake::Due => {}
+            Wake::Terminate => break,
+            Wake::Reload => log("INFO", "收到 SIGHUP，重新加载配置"),
+            Wake::ConfigChanged => log("INFO", "配置文件已修改，重新加载配置"),
+            Wake::DataChanged => {}
+        }
+    }
+
+    log("INFO", "常驻模式已退出");
+    Ok(())
+}
+
+/// 结束休眠的原因
+enum Wake {
+    /// 到达预定的检查时间
+    Due,
+    /// 收到 SIGTERM / SIGINT
+    Terminate,
+    /// 收到 SIGHUP
+    Reload,
+    /// config.json 被修改
+    ConfigChanged,
+    /// 数据库被其他进程修改（新增、修改待办事项等）
+    DataChanged,
+}
+
+/// 休眠到 wake_at（为 None 时一直等待），期间有信号或修改时提前返回
+fn wait(
+    wake_at: Option<DateTime<Utc>>,
+    signals: &Signals,
+    db: &database::Database,
+    json_path: &str,
+    config_stamp: &mut Option<SystemTime>,
+) -> AnyResult<Wake> {
+    let data_version = db.data_version()?;
+    loop {
+        if signals.terminate.load(Ordering::SeqCst) {
+            return Ok(Wake::Terminate);
+        }
+        if signals.reload.swap(false, Ordering::SeqCst) {
+            return Ok(Wake::Reload);
+        }
+        let stamp = modified_time(json_path);
+        if stamp != *config_stamp {
+            *config_stamp = stamp;
+            return Ok(Wake::ConfigChanged);
+        }
+        if db.data_version()? != data_version {
+            return Ok(Wake::DataChanged);
+        }
+        // 每次都与当前时刻比较，系统休眠唤醒后也能及时检查
+        if wake_at.is_some_and(|at| Utc::now() >= at) {
+            return Ok(Wake::Due);
+        }
+        thread::sleep(TICK);
+    }
+}
+
+fn modified_time(path: &str) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// 同时输出到终端和 app.log
+fn log(level: &str, message: &str) {
+    println!("[{}] {}", local_time::now().format("%Y-%m-%d %H:%M:%S"), message);
+    Logger::log(level, message);
+}
+
+/// 收到的信号
+struct Signals {
+    terminate: Arc<AtomicBool>,
+    reload: Arc<AtomicBool>,
+}
+
+impl Signals {
+    fn register() -> AnyResult<Self> {
+        use signal_hook::consts::{SIGINT, SIGTERM};
+
+        let signals = Self {
+            terminate: Arc::new(AtomicBool::new(false)),
+            reload: Arc::new(AtomicBool::new(false)),
+        };
+        signal_hook::flag::register(SIGTERM, Arc::clone(&signals.terminate))?;
+        signal_hook::flag::register(SIGINT, Arc::clone(&signals.terminate))?;
+        #[cfg(unix)]
+        signal_hook::flag::register(signal_hook::consts::SIGHUP, Arc::clone(&signals.reload))?;
+        Ok(signals)
+    }
+}
+
+/// pid 文件，退出时自动删除
+struct PidFile {
+    path: PathBuf,
+}
+
+impl PidFile {
+    /// 写入当前进程的 pid；文件中记录的进程仍在运行时返回错误
+    fn acquire(path: &Path) -> AnyResult<Self> {
+        if let Ok(content) = fs::read_to_string(path)
+            && let Ok(pid) = content.trim().parse::<u32>()
+            && pid != process::id()
+            && process_alive(pid)
+        {
+            bail!("常驻进程已在运行 (PID {})，pid 文件: {}", pid, path.display());
+        }
+        fs::write(path, format!("{}\n", process::id()))?;
+        Ok(Self { path: path.to_path_buf() })
+    }
+}
+
+impl Drop for PidFile {
+    fn drop(&mut self) {
+        // 只删除自己写入的 pid 文件
+        let own = fs::read_to_string(&self.path)
+            .is_ok_and(|content| content.trim() == process::id().to_string());
+        if own {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+#[cfg(unix)]
+fn process_alive(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    // 信号 0 只检查进程是否存在；没有权限（EPERM）说明进程存在但属于其他用户
+    let result = unsafe { libc::kill(pid, 0) };
+    result == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(not(unix))]
+fn process_alive(_pid: u32) -> bool {
+    // 无法可靠判断时视为残留的 pid 文件
+    false
+}
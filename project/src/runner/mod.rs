@@ -1,8 +1,12 @@
-/// 应用程序的不同运行模式
-/// 
-/// 该模块包含应用程序的各种运行模式：
-/// - reminder: 提醒检查模式（定时任务、启动检查等）
-/// - 未来可扩展：backup（备份）、sync（同步）等
+//! 应用程序的不同运行模式
+//!
+//! 该模块包含应用程序的各种运行模式：
+//! - reminder: 提醒检查模式（定时任务、启动检查等）
+//! - daemon: 常驻模式（休眠到下一个提醒时间，不依赖系统定时任务）
+//! - 两种模式都会顺带执行自动备份、回收站清理与自动归档，见 `service::backup_serv`、`service::trash_serv`、
+//!   `service::archive_serv`
+//! - 未来可扩展：sync（同步）等
 
 pub mod reminder;
+pub mod daemon;
 
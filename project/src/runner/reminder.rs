@@ -1,11 +1,11 @@
-/// 提醒功能的运行器
-/// 
-/// 该模块负责协调提醒功能的运行，包括：
-/// - 定时任务模式：由系统调度器调用，执行提醒检查
-/// - 启动检查模式：在程序启动时检查并显示提醒
+//! 提醒功能的运行器
+//!
+//! 该模块负责协调提醒功能的运行，包括：
+//! - 定时任务模式：由系统调度器调用，执行提醒检查
+//! - 启动检查模式：在程序启动时检查并显示提醒
 
-use crate::init::{database, db_json, config_load};
-use crate::service::{reminder_serv, notifier};
+use crate::init::{self, database, db_json, config_load};
+use crate::service::{archive_serv, backup_serv, change_log_serv, logger::Logger, reminder_serv, notifier, trash_serv};
 use anyhow::Result as AnyResult;
 
 /// 提醒检查模式（由系统定时任务调用）
@@ -20,6 +20,7 @@ use anyhow::Result as AnyResult;
 /// project.exe --check-reminders
 /// ```
 pub fn run_check_mode() -> AnyResult<()> {
+    change_log_serv::set_source("scheduled");
     // 初始化数据库
     let db_path = config_load::get_config_value("database", Some("path"));
     let db = database::Database::new(&db_path)?;
@@ -28,33 +29,71 @@ pub fn run_check_mode() -> AnyResult<()> {
     // 初始化 JSON 配置
     let json_path = config_load::get_config_value("json", Some("path"));
     let json_config = db_json::JsonConfig::new(&json_path)?;
+    init::timezone::load_timezone(&json_config);
     
-    // 检查提醒
-    let notifications = reminder_serv::ReminderService::check_and_notify(&db, &json_config)?;
-    
-    if notifications.is_empty() {
+    if check_and_send(&db, &json_config)? == 0 {
         println!("✓ 没有需要提醒的事项");
-        return Ok(());
     }
-    
-    // 读取通知类型配置
-    let notification_type = match json_config.get_value("reminder") {
-        Ok(value) => {
-            value.get("notification_type")
-                .and_then(|v| v.as_str())
-                .unwrap_or("console")
-                .to_string()
+
+    // 定时任务顺带执行自动备份，备份失败不影响提醒
+    match backup_serv::run_scheduled(&db, &json_config) {
+        Ok(Some(snapshot)) => println!("💾 已创建自动快照: {}", snapshot.name),
+        Ok(None) => {}
+        Err(e) => {
+            println!("⚠️  自动备份失败: {:#}", e);
+            Logger::log("WARN", &format!("自动备份失败: {:#}", e));
+        }
+    }
+
+    // 顺带清理回收站中过期的项目
+    match trash_serv::run_scheduled(&db, &json_config) {
+        Ok(0) => {}
+        Ok(count) => println!("🗑️  已彻底删除回收站中过期的 {} 项", count),
+        Err(e) => {
+            println!("⚠️  清理回收站失败: {:#}", e);
+            Logger::log("WARN", &format!("清理回收站失败: {:#}", e));
+        }
+    }
+
+    // 以及自动归档已结束较久的待办事项
+    match archive_serv::run_scheduled(&db, &json_config) {
+        Ok(0) => {}
+        Ok(count) => println!("📦 已自动归档 {} 个待办事项", count),
+        Err(e) => {
+            println!("⚠️  自动归档失败: {:#}", e);
+            Logger::log("WARN", &format!("自动归档失败: {:#}", e));
         }
-        Err(_) => "console".to_string(),
-    };
+    }
+    Ok(())
+}
+
+/// 检查一次提醒并发送到各通知渠道，返回发送的提醒条数
+///
+/// 定时任务模式与常驻模式共用
+pub fn check_and_send(db: &database::Database, json_config: &db_json::JsonConfig) -> AnyResult<usize> {
+    let notifications = reminder_serv::ReminderService::check_and_notify(db, json_config)?;
+    if notifications.is_empty() {
+        return Ok(0);
+    }
     
-    // 发送通知
-    for notification in notifications {
-        notifier::Notifier::send(&notification, &notification_type)?;
+    // 按配置的通知渠道发送，单个渠道失败不影响其他渠道
+    let reminder_config = reminder_serv::ReminderService::get_reminder_config(json_config)?;
+    let mut notifier = notifier::Notifier::from_config(&reminder_config);
+    let report = notifier.send(&notifications);
+    for notification in &notifications {
         println!("✓ 已发送提醒: {}", notification);
     }
+    if !report.failed.is_empty() {
+        let names: Vec<&str> = report.failed.iter().map(|(name, _)| name.as_str()).collect();
+        println!("⚠️  {} 个通知渠道发送失败: {}", names.len(), names.join(", "));
+    }
+
+    // 处理用户在桌面通知上点击的按钮
+    for action in &report.actions {
+        reminder_serv::ReminderService::handle_action(db, &reminder_config, action)?;
+    }
     
-    Ok(())
+    Ok(notifications.len())
 }
 
 /// 启动时检查提醒
@@ -0,0 +1,365 @@
+//! 趋势报告（查看模式的 trends / `review trends`）
+//!
+//! 统计最近 N 天（含今天，按本地日期）内的：
+//! - 每天 / 每周新建与完成的数量（终端中以迷你折线与条形图显示）
+//! - 平均交付周期：窗口内完成的待办事项从新建到完成的平均时长
+//! - 逾期率：截止时间落在窗口内且已过去的待办事项中，到截止时间仍未完成的比例（已取消的不计）
+//! - 按时完成率：窗口内完成且有截止时间的待办事项中，在截止时间前完成的比例
+//! - 最活跃的标签：窗口内新建与完成数量最多的标签
+//! - 连续完成：每天至少完成一项的连续天数；今天还没有完成时从昨天算起
+//!
+//! 新建时间取修改历史中新建该行的时间，没有记录时按开始时间；完成时间取 completed_at，
+//! 升级前完成、没有完成时间的待办事项不参与完成相关的统计。回收站中的待办事项不计入，已归档的计入。
+
+use anyhow::{Result as AnyResult, bail};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+
+use crate::dao::{time_tracking_dao, todo_list_dao};
+use crate::data::local_time;
+use crate::data::time_tracking;
+use crate::data::todo_list::TodoStatus;
+use crate::init::database::Database;
+use crate::service::chart;
+use crate::service::output::{self, OutputFormat};
+
+/// 默认统计的天数
+pub const DEFAULT_DAYS: u32 = 30;
+
+/// 最多统计的天数（十年）
+pub const MAX_DAYS: u32 = 3660;
+
+/// 最活跃的标签显示的个数
+const TOP_TAGS: usize = 5;
+
+/// 条形图的最大宽度（字符）
+const BAR_WIDTH: usize = 24;
+
+/// 趋势的统计粒度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrendPeriod {
+    Day,
+    Week,
+}
+
+impl TrendPeriod {
+    pub fn from_string(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "day" | "daily" | "d" | "天" | "日" => Some(TrendPeriod::Day),
+            "week" | "weekly" | "w" | "周" => Some(TrendPeriod::Week),
+            _ => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TrendPeriod::Day => "按天",
+            TrendPeriod::Week => "按周",
+        }
+    }
+
+    /// 日期所在统计区间的第一天（按周时为周一）
+    fn bucket_start(&self, date: NaiveDate) -> NaiveDate {
+        match self {
+            TrendPeriod::Day => date,
+            TrendPeriod::Week => date - Duration::days(date.weekday().num_days_from_monday() as i64),
+        }
+    }
+
+    fn step(&self) -> Duration {
+        match self {
+            TrendPeriod::Day => Duration::days(1),
+            TrendPeriod::Week => Duration::days(7),
+        }
+    }
+}
+
+/// 一个统计区间（一天或一周）
+#[derive(Debug, Serialize, Clone)]
+pub struct TrendPoint {
+    /// 区间的第一天（按周时为周一，可能早于窗口的第一天）
+    pub start: NaiveDate,
+    pub created: usize,
+    pub completed: usize,
+}
+
+/// 标签在窗口内的活跃程度
+#[derive(Debug, Serialize, Clone)]
+pub struct TagActivity {
+    pub tag: String,
+    pub created: usize,
+    pub completed: usize,
+}
+
+/// 趋势报告（文本与 JSON 输出共用）
+#[derive(Debug, Serialize, Clone)]
+pub struct TrendReport {
+    pub days: u32,
+    pub period: TrendPeriod,
+    /// 窗口的第一天与最后一天（今天），本地日期
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub points: Vec<TrendPoint>,
+    pub created: usize,
+    pub completed: usize,
+    /// 平均交付周期（新建到完成），窗口内没有完成的待办事项时为空
+    pub average_lead_time_seconds: Option<i64>,
+    /// 截止时间在窗口内且已过去的待办事项数，以及其中逾期的数量
+    pub due: usize,
+    pub overdue: usize,
+    pub overdue_rate: Option<f64>,
+    /// 窗口内完成且有截止时间的待办事项数，以及其中按时完成的数量
+    pub completed_with_due: usize,
+    pub completed_on_time: usize,
+    pub on_time_completion_rate: Option<f64>,
+    /// 窗口内的计时时长（跨窗口的计时只算窗口内的部分）
+    pub tracked_seconds: i64,
+    pub busiest_tags: Vec<TagActivity>,
+    pub current_streak_days: u32,
+    pub longest_streak_days: u32,
+}
+
+fn rate(part: usize, total: usize) -> Option<f64> {
+    (total > 0).then(|| part as f64 / total as f64 * 100.0)
+}
+
+fn local_date(dt: &DateTime<Utc>) -> NaiveDate {
+    local_time::to_local(dt).date_naive()
+}
+
+/// 计算最近 days 天的趋势报告
+pub fn collect_trends(database: &Database, days: u32, period: TrendPeriod) -> AnyResult<TrendReport> {
+    if !(1..=MAX_DAYS).contains(&days) {
+        bail!("统计天数应在 1 到 {} 之间", MAX_DAYS);
+    }
+    let conn = database.get_connection();
+    let now = Utc::now();
+    let to = local_date(&now);
+    let (Some(from), Some(fallback_start)) = (
+        to.checked_sub_signed(Duration::days(days as i64 - 1)),
+        now.checked_sub_signed(Duration::days(days as i64)),
+    ) else {
+        bail!("统计天数 {} 超出可表示的日期范围", days);
+    };
+    let window_start = local_time::start_of_day(from).unwrap_or(fallback_start);
+    let in_window = |dt: &DateTime<Utc>| (from..=to).contains(&local_date(dt));
+
+    let todos = todo_list_dao::list_todos(conn)?;
+    let created_times: HashMap<i32, DateTime<Utc>> = todo_list_dao::list_created_times(conn)?.into_iter().collect();
+
+    // 各统计区间
+    let mut points = Vec::new();
+    let mut start = period.bucket_start(from);
+    while start <= to {
+        points.push(TrendPoint { start, created: 0, completed: 0 });
+        start += period.step();
+    }
+    let point_index = |date: NaiveDate| {
+        let first = period.bucket_start(from);
+        ((period.bucket_start(date) - first).num_days() / period.step().num_days()) as usize
+    };
+
+    let mut created = 0;
+    let mut completed = 0;
+    let mut lead_times = Vec::new();
+    let mut due = 0;
+    let mut overdue = 0;
+    let mut completed_with_due = 0;
+    let mut completed_on_time = 0;
+    let mut tags: HashMap<String, TagActivity> = HashMap::new();
+    let mut completion_days: BTreeSet<NaiveDate> = BTreeSet::new();
+
+    for todo in &todos {
+        let created_at = created_times.get(&todo.id).copied().unwrap_or(todo.begin_time);
+        let completed_at = todo.completed_at.filter(|_| todo.is_done());
+        if let Some(completed_at) = &completed_at {
+            completion_days.insert(local_date(completed_at));
+        }
+
+        let created_here = in_window(&created_at);
+        let completed_here = completed_at.as_ref().is_some_and(in_window);
+        if created_here {
+            created += 1;
+            points[point_index(local_date(&created_at))].created += 1;
+        }
+        if let Some(completed_at) = completed_at.filter(|_| completed_here) {
+            completed += 1;
+            points[point_index(local_date(&completed_at))].completed += 1;
+            lead_times.push((completed_at - created_at).max(Duration::zero()));
+            if let Some(end_time) = todo.end_time {
+                completed_with_due += 1;
+                if completed_at <= end_time {
+                    completed_on_time += 1;
+                }
+            }
+        }
+        if created_here || completed_here {
+            for tag in &todo.tags {
+                let activity = tags.entry(tag.to_lowercase()).or_insert_with(|| TagActivity {
+                    tag: tag.clone(),
+                    created: 0,
+                    completed: 0,
+                });
+                activity.created += created_here as usize;
+                activity.completed += completed_here as usize;
+            }
+        }
+
+        // 逾期：到截止时间仍未完成（完成时间未知的已完成待办事项无法判断，不计入）
+        if let Some(end_time) = todo.end_time
+            && end_time <= now
+            && in_window(&end_time)
+            && todo.status != TodoStatus::Cancelled
+            && (!todo.is_done() || completed_at.is_some())
+        {
+            due += 1;
+            if completed_at.is_none_or(|c| c > end_time) {
+                overdue += 1;
+            }
+        }
+    }
+
+    let mut busiest_tags: Vec<TagActivity> = tags.into_values().collect();
+    busiest_tags.sort_by(|a, b| {
+        (b.created + b.completed)
+            .cmp(&(a.created + a.completed))
+            .then(b.completed.cmp(&a.completed))
+            .then(a.tag.cmp(&b.tag))
+    });
+    busiest_tags.truncate(TOP_TAGS);
+
+    let tracked_seconds = time_tracking_dao::list_sessions(conn)?
+        .iter()
+        .map(|s| (s.stopped_at.unwrap_or(now) - s.started_at.max(window_start)).num_seconds().max(0))
+        .sum();
+
+    let (current_streak_days, longest_streak_days) = streaks(&completion_days, to);
+
+    Ok(TrendReport {
+        days,
+        period,
+        from,
+        to,
+        points,
+        created,
+        completed,
+        average_lead_time_seconds: (!lead_times.is_empty())
+            .then(|| lead_times.iter().map(|d| d.num_seconds()).sum::<i64>() / lead_times.len() as i64),
+        due,
+        overdue,
+        overdue_rate: rate(overdue, due),
+        completed_with_due,
+        completed_on_time,
+        on_time_completion_rate: rate(completed_on_time, completed_with_due),
+        tracked_seconds,
+        busiest_tags,
+        current_streak_days,
+        longest_streak_days,
+    })
+}
+
+/// 连续完成的天数：(当前, 最长)；今天还没有完成时当前连续从昨天算起
+fn streaks(days: &BTreeSet<NaiveDate>, today: NaiveDate) -> (u32, u32) {
+    let mut longest = 0;
+    let mut run = 0;
+    let mut previous: Option<NaiveDate> = None;
+    for &day in days {
+        run = match previous {
+            Some(p) if day - p == Duration::days(1) => run + 1,
+            _ => 1,
+        };
+        longest = longest.max(run);
+        previous = Some(day);
+    }
+
+    let mut current = 0;
+    let mut day = if days.contains(&today) { today } else { today - Duration::days(1) };
+    while days.contains(&day) {
+        current += 1;
+        day -= Duration::days(1);
+    }
+    (current, longest)
+}
+
+fn weekday_label(date: NaiveDate) -> &'static str {
+    const LABELS: [&str; 7] = ["周一", "周二", "周三", "周四", "周五", "周六", "周日"];
+    LABELS[date.weekday().num_days_from_monday() as usize]
+}
+
+/// 输出趋势报告
+pub fn print_trends(database: &Database, days: u32, period: TrendPeriod, format: OutputFormat) -> AnyResult<()> {
+    let report = collect_trends(database, days, period)?;
+    if format != OutputFormat::Text {
+        return output::print_records(&[report], format);
+    }
+
+    let percent = |rate: Option<f64>| rate.map(|r| format!("{:.0}%", r)).unwrap_or_else(|| "-".to_string());
+
+    println!(
+        "\n📈 趋势报告（最近 {} 天，{}）{} ~ {}",
+        report.days,
+        report.period.label(),
+        report.from.format("%Y-%m-%d"),
+        report.to.format("%Y-%m-%d")
+    );
+    println!("{:=<80}", "");
+    let created: Vec<usize> = report.points.iter().map(|p| p.created).collect();
+    let completed: Vec<usize> = report.points.iter().map(|p| p.completed).collect();
+    println!("新建: {:<4} {}", report.created, chart::sparkline(&created));
+    println!("完成: {:<4} {}", report.completed, chart::sparkline(&completed));
+    if let Some(seconds) = report.average_lead_time_seconds {
+        println!("平均交付周期（新建到完成）: {}", time_tracking::format_duration(Duration::seconds(seconds)));
+    }
+    println!("逾期率: {}（到期 {} 项，逾期 {} 项）", percent(report.overdue_rate), report.due, report.overdue);
+    println!(
+        "按时完成率: {}（有截止时间的完成 {} 项，按时 {} 项）",
+        percent(report.on_time_completion_rate),
+        report.completed_with_due,
+        report.completed_on_time
+    );
+    if report.tracked_seconds > 0 {
+        println!("计时: {}", time_tracking::format_duration(Duration::seconds(report.tracked_seconds)));
+    }
+    println!("连续完成: 当前 {} 天，最长 {} 天", report.current_streak_days, report.longest_streak_days);
+
+    println!("{:-<80}", "");
+    println!("{}明细（▓ 新建  █ 完成）:", report.period.label());
+    let max = report.points.iter().map(|p| p.created.max(p.completed)).max().unwrap_or(0);
+    for point in &report.points {
+        let label = match report.period {
+            TrendPeriod::Day => format!("{} {}", point.start.format("%m-%d"), weekday_label(point.start)),
+            TrendPeriod::Week => format!("{} 起", point.start.format("%m-%d")),
+        };
+        println!(
+            "  {:<10} {:>3} {:<w$} {:>3} {}",
+            label,
+            point.created,
+            chart::bar(point.created, max, BAR_WIDTH).replace('█', "▓"),
+            point.completed,
+            chart::bar(point.completed, max, BAR_WIDTH),
+            w = BAR_WIDTH
+        );
+    }
+
+    if !report.busiest_tags.is_empty() {
+        println!("{:-<80}", "");
+        println!("🏷️  最活跃的标签:");
+        let max = report.busiest_tags.iter().map(|t| t.created + t.completed).max().unwrap_or(0);
+        let width = report.busiest_tags.iter().map(|t| t.tag.chars().count()).max().unwrap_or(0);
+        for tag in &report.busiest_tags {
+            println!(
+                "  {:<width$} {:<w$} 新建 {}，完成 {}",
+                tag.tag,
+                chart::bar(tag.created + tag.completed, max, BAR_WIDTH),
+                tag.created,
+                tag.completed,
+                width = width,
+                w = BAR_WIDTH
+            );
+        }
+    }
+    println!("{:=<80}", "");
+    Ok(())
+}
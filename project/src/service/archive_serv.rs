@@ -0,0 +1,169 @@
+//! 归档
+//!
+//! 已完成或已取消的待办事项可以归档（记录 archived_at）：
+//! - 归档的待办事项不出现在默认列表（list / todo list）与提醒检查中，查看模式的报表、条件查询与导出中仍然可见
+//! - 归档时子任务一并归档，子任务都已结束才能归档；重新打开（改回未完成状态）时自动取消归档
+//! - 已结束超过 after_days 天的待办事项在常驻模式、定时任务与交互模式启动时自动归档，
+//!   结束时间取完成时间（已取消的取修改历史中最后一次改为已取消的时间）
+//!
+//! 归档与取消归档都记入修改历史，可以撤销。
+
+use anyhow::{Result as AnyResult, anyhow, bail};
+use chrono::{Duration, Utc};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use crate::dao::todo_list_dao;
+use crate::data::local_time;
+use crate::data::todo_list::TodoListForm;
+use crate::init::database::Database;
+use crate::init::db_json::JsonConfig;
+use crate::init::db_json_content::ArchiveConfig;
+use crate::service::change_log_serv;
+use crate::service::output::{self, OutputFormat};
+use crate::service::relation_serv::TodoTree;
+
+/// 读取归档配置，没有配置时使用默认值
+pub fn get_archive_config(json_config: &JsonConfig) -> AnyResult<ArchiveConfig> {
+    let config: ArchiveConfig = match json_config.get_value("archive") {
+        Ok(value) => serde_json::from_value(value)?,
+        Err(_) => ArchiveConfig::default(),
+    };
+    config.validate().map_err(|e| anyhow!(e))?;
+    Ok(config)
+}
+
+/// 已归档的待办事项（最近归档的在前）
+pub fn list_archived(database: &Database) -> AnyResult<Vec<TodoListForm>> {
+    let mut todos: Vec<TodoListForm> = todo_list_dao::list_todos(database.get_connection())?
+        .into_iter()
+        .filter(|t| t.is_archived())
+        .collect();
+    todos.sort_by_key(|t| Reverse(t.archived_at));
+    Ok(todos)
+}
+
+/// 归档待办事项及其子任务，返回归档的待办事项；不存在时返回 None
+///
+/// 待办事项与全部子任务都已完成或已取消才能归档
+pub fn archive(database: &Database, id: i32) -> AnyResult<Option<Vec<TodoListForm>>> {
+    let tree = TodoTree::load(database)?;
+    let Some(todo) = tree.get(id) else {
+        return Ok(None);
+    };
+    if todo.is_archived() {
+        bail!("待办事项 #{}「{}」已归档", id, todo.title);
+    }
+    if todo.status.is_open() {
+        bail!("只能归档已完成或已取消的待办事项，#{}「{}」当前为{}", id, todo.title, todo.status.label());
+    }
+    if let Some(open) = tree.descendants(id).into_iter().find(|t| t.status.is_open()) {
+        bail!("子任务 #{}「{}」尚未结束，不能归档", open.id, open.title);
+    }
+
+    let mut todos = vec![todo.clone()];
+    todos.extend(tree.descendants(id).into_iter().filter(|t| !t.is_archived()).cloned());
+    let action = format!("归档待办事项 #{}「{}」", id, todo.title);
+    archive_todos(database, &todos, &action)?;
+    Ok(Some(todos))
+}
+
+/// 取消归档待办事项及其已归档的子任务，返回取消归档的待办事项；不存在时返回 None
+pub fn unarchive(database: &Database, id: i32) -> AnyResult<Option<Vec<TodoListForm>>> {
+    let tree = TodoTree::load(database)?;
+    let Some(todo) = tree.get(id) else {
+        return Ok(None);
+    };
+    if !todo.is_archived() {
+        bail!("待办事项 #{}「{}」未归档", id, todo.title);
+    }
+
+    let mut todos = vec![todo.clone()];
+    todos.extend(tree.descendants(id).into_iter().filter(|t| t.is_archived()).cloned());
+    let conn = database.get_connection();
+    let action = format!("取消归档待办事项 #{}「{}」", id, todo.title);
+    change_log_serv::record(database, &action, || {
+        for todo in &todos {
+            todo_list_dao::unarchive_todo(conn, todo.id)?;
+        }
+        Ok(())
+    })?;
+    Ok(Some(todos))
+}
+
+/// 归档已结束超过 days 天的待办事项，返回归档的待办事项；dry_run 时只返回将要归档的待办事项
+///
+/// 有子任务时，子任务也都满足条件（或已归档）才一并归档
+pub fn archive_expired(database: &Database, days: u32, dry_run: bool) -> AnyResult<Vec<TodoListForm>> {
+    // 天数大到超出可表示的时间范围时，没有待办事项结束得那么早
+    let Some(cutoff) = Utc::now().checked_sub_signed(Duration::days(days as i64)) else {
+        return Ok(Vec::new());
+    };
+    let closed: HashMap<i32, bool> = todo_list_dao::list_closed_times(database.get_connection())?
+        .into_iter()
+        .map(|(id, closed_at)| (id, closed_at <= cutoff))
+        .collect();
+    let tree = TodoTree::load(database)?;
+    let mut todos: Vec<TodoListForm> = closed
+        .iter()
+        .filter(|(_, expired)| **expired)
+        .filter_map(|(id, _)| tree.get(*id))
+        .filter(|todo| {
+            tree.descendants(todo.id)
+                .iter()
+                .all(|t| t.is_archived() || closed.get(&t.id).copied().unwrap_or(false))
+        })
+        .cloned()
+        .collect();
+    todos.sort_by_key(|t| t.id);
+
+    if !dry_run && !todos.is_empty() {
+        let action = format!("自动归档已结束超过 {} 天的 {} 个待办事项", days, todos.len());
+        archive_todos(database, &todos, &action)?;
+    }
+    Ok(todos)
+}
+
+fn archive_todos(database: &Database, todos: &[TodoListForm], action: &str) -> AnyResult<()> {
+    let conn = database.get_connection();
+    change_log_serv::record(database, action, || {
+        for todo in todos {
+            todo_list_dao::archive_todo(conn, todo.id)?;
+        }
+        Ok(())
+    })
+}
+
+/// 自动归档，返回归档的数量
+///
+/// 常驻模式、定时任务模式与交互模式启动时调用
+pub fn run_scheduled(database: &Database, json_config: &JsonConfig) -> AnyResult<usize> {
+    let config = get_archive_config(json_config)?;
+    if config.after_days == 0 {
+        return Ok(0);
+    }
+    Ok(archive_expired(database, config.after_days, false)?.len())
+}
+
+/// 输出已归档的待办事项
+pub fn print_archive(database: &Database, format: OutputFormat) -> AnyResult<()> {
+    let todos = list_archived(database)?;
+    if format != OutputFormat::Text {
+        return output::print_records(&todos, format);
+    }
+
+    if todos.is_empty() {
+        println!("📦 没有已归档的待办事项");
+        return Ok(());
+    }
+    println!("\n📦 已归档的待办事项 ({} 项):", todos.len());
+    println!("{}", "=".repeat(80));
+    for todo in &todos {
+        println!("  {} [ID: {}] {} ({})", todo.status.icon(), todo.id, todo.title, todo.status.label());
+        if let Some(archived_at) = &todo.archived_at {
+            println!("      归档于 {}", local_time::format(archived_at, "%Y-%m-%d %H:%M:%S"));
+        }
+    }
+    println!("{}", "=".repeat(80));
+    Ok(())
+}
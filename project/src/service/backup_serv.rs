@@ -0,0 +1,326 @@
+//! 数据库备份与恢复
+//!
+//! 使用 SQLite 的在线备份 API 复制数据库：复制期间其他连接仍可读写，得到的是某一时刻一致的副本。
+//!
+//! - 快照先写入临时文件（.partial），通过 PRAGMA integrity_check 后才改为正式文件名，
+//!   检查失败的副本会被删除，不会出现在快照列表中
+//! - 自动快照按 keep_daily / keep_weekly 轮换；手动快照与恢复前快照不会被自动删除
+//! - 恢复前检查快照的完整性与结构版本，先把当前数据保存为恢复前快照，
+//!   再在一个写事务中整体替换，最后按需执行迁移
+
+use anyhow::{Context, Result as AnyResult, anyhow, bail};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::data::backup::{Snapshot, SnapshotKind};
+use crate::data::local_time;
+use crate::init::database::{self, Database};
+use crate::init::db_json::JsonConfig;
+use crate::init::db_json_content::BackupConfig;
+use crate::init::migration;
+use crate::service::logger::Logger;
+use crate::service::output::{self, OutputFormat};
+
+/// 缺省的备份目录（相对数据库所在目录）
+const DEFAULT_DIRECTORY: &str = "backups";
+
+/// 读取备份配置，没有配置时使用默认值
+pub fn get_backup_config(json_config: &JsonConfig) -> AnyResult<BackupConfig> {
+    match json_config.get_value("backup") {
+        Ok(value) => Ok(serde_json::from_value(value)?),
+        Err(_) => Ok(BackupConfig::default()),
+    }
+}
+
+/// 备份目录：相对路径以数据库所在目录为基准
+pub fn backup_dir(db: &Database, config: &BackupConfig) -> PathBuf {
+    let directory = Path::new(config.directory.as_deref().unwrap_or(DEFAULT_DIRECTORY));
+    if directory.is_absolute() {
+        return directory.to_path_buf();
+    }
+    Path::new(db.path()).parent().unwrap_or(Path::new(".")).join(directory)
+}
+
+/// 快照文件名的前缀（数据库文件名去掉扩展名）
+fn db_stem(db: &Database) -> String {
+    Path::new(db.path())
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "todo".to_string())
+}
+
+/// 列出全部快照，最新的在前
+pub fn list_snapshots(db: &Database, config: &BackupConfig) -> AnyResult<Vec<Snapshot>> {
+    let dir = backup_dir(db, config);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let stem = db_stem(db);
+    let mut snapshots = Vec::new();
+    for entry in fs::read_dir(&dir).with_context(|| format!("无法读取备份目录 {}", dir.display()))? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let Some((created_at, kind)) = Snapshot::parse_name(&stem, &name) else {
+            continue;
+        };
+        snapshots.push(Snapshot {
+            name,
+            kind,
+            created_at,
+            size: entry.metadata()?.len(),
+            path: entry.path(),
+        });
+    }
+    snapshots.sort_by(|a, b| b.created_at.cmp(&a.created_at).then_with(|| b.name.cmp(&a.name)));
+    Ok(snapshots)
+}
+
+/// 按序号（list 中的编号，从 1 开始）或文件名查找快照
+pub fn find_snapshot(db: &Database, config: &BackupConfig, reference: &str) -> AnyResult<Option<Snapshot>> {
+    let snapshots = list_snapshots(db, config)?;
+    if let Ok(index) = reference.parse::<usize>() {
+        return Ok(index.checked_sub(1).and_then(|i| snapshots.get(i)).cloned());
+    }
+    Ok(snapshots.into_iter().find(|s| s.name == reference || s.name.strip_suffix(".db") == Some(reference)))
+}
+
+/// 创建一个快照
+///
+/// 恢复前快照即使没有通过完整性检查也会保留（当前数据可能本就已损坏，它是恢复前数据的唯一副本）
+pub fn create_snapshot(db: &Database, config: &BackupConfig, kind: SnapshotKind) -> AnyResult<Snapshot> {
+    let dir = backup_dir(db, config);
+    fs::create_dir_all(&dir).with_context(|| format!("无法创建备份目录 {}", dir.display()))?;
+
+    // 文件名精确到秒，同一秒内已有同类快照时顺延一秒
+    let stem = db_stem(db);
+    let mut created_at = Utc::now();
+    while dir.join(Snapshot::file_name(&stem, &created_at, kind)).exists() {
+        created_at += Duration::seconds(1);
+    }
+    let name = Snapshot::file_name(&stem, &created_at, kind);
+    let path = dir.join(&name);
+    let partial = dir.join(format!("{}.partial", name));
+    let _ = fs::remove_file(&partial);
+
+    if let Err(e) = db.backup_to(&partial) {
+        let _ = fs::remove_file(&partial);
+        return Err(e.context("复制数据库失败"));
+    }
+    let problems = match database::check_file(&partial) {
+        Ok(problems) => problems,
+        Err(e) => vec![format!("{:#}", e)],
+    };
+    if !problems.is_empty() {
+        if kind != SnapshotKind::PreRestore {
+            let _ = fs::remove_file(&partial);
+            bail!("快照未通过完整性检查，已删除: {}", problems.join("; "));
+        }
+        Logger::log("WARN", &format!("恢复前快照 {} 未通过完整性检查: {}", name, problems.join("; ")));
+    }
+    fs::rename(&partial, &path)?;
+
+    Logger::log("INFO", &format!("已创建{}快照 {}", kind.label(), name));
+    Ok(Snapshot {
+        name,
+        kind,
+        created_at,
+        size: fs::metadata(&path)?.len(),
+        path,
+    })
+}
+
+/// 按保留策略删除过期的自动快照，返回删除（试运行时为将要删除）的快照
+pub fn prune(db: &Database, config: &BackupConfig, dry_run: bool) -> AnyResult<Vec<Snapshot>> {
+    let snapshots = list_snapshots(db, config)?;
+    let autos: Vec<&Snapshot> = snapshots.iter().filter(|s| s.kind == SnapshotKind::Auto).collect();
+    let keep = retained(&autos, config);
+
+    let mut removed = Vec::new();
+    for snapshot in autos.into_iter().filter(|s| !keep.contains(&s.name)) {
+        if !dry_run {
+            fs::remove_file(&snapshot.path).with_context(|| format!("无法删除快照 {}", snapshot.name))?;
+            Logger::log("INFO", &format!("按保留策略删除快照 {}", snapshot.name));
+        }
+        removed.push(snapshot.clone());
+    }
+    Ok(removed)
+}
+
+/// 需要保留的自动快照（autos 按时间从新到旧排列）
+///
+/// 最近 keep_daily 个有快照的日子各保留当天最新的一个，最近 keep_weekly 个有快照的周各保留该周最新的一个；
+/// 两者都为 0 时全部保留
+fn retained(autos: &[&Snapshot], config: &BackupConfig) -> HashSet<String> {
+    if config.keep_daily == 0 && config.keep_weekly == 0 {
+        return autos.iter().map(|s| s.name.clone()).collect();
+    }
+    let mut keep = HashSet::new();
+    let mut days: Vec<NaiveDate> = Vec::new();
+    let mut weeks: Vec<(i32, u32)> = Vec::new();
+    for snapshot in autos {
+        let date = local_time::to_local(&snapshot.created_at).date_naive();
+        if !days.contains(&date) && days.len() < config.keep_daily as usize {
+            days.push(date);
+            keep.insert(snapshot.name.clone());
+        }
+        let week = (date.iso_week().year(), date.iso_week().week());
+        if !weeks.contains(&week) && weeks.len() < config.keep_weekly as usize {
+            weeks.push(week);
+            keep.insert(snapshot.name.clone());
+        }
+    }
+    keep
+}
+
+/// 下一次自动备份的时间；自动备份已禁用时返回 None，从未备份过时返回当前时间
+pub fn next_scheduled(db: &Database, config: &BackupConfig) -> AnyResult<Option<DateTime<Utc>>> {
+    if !config.enabled {
+        return Ok(None);
+    }
+    let last = list_snapshots(db, config)?
+        .into_iter()
+        .find(|s| s.kind == SnapshotKind::Auto)
+        .map(|s| s.created_at);
+    Ok(Some(match last {
+        Some(last) => last + Duration::hours(config.interval_hours.max(1) as i64),
+        None => Utc::now(),
+    }))
+}
+
+/// 到了自动备份的时间时创建快照并轮换，返回新建的快照
+///
+/// 常驻模式、定时任务模式与交互模式启动时调用
+pub fn run_scheduled(db: &Database, json_config: &JsonConfig) -> AnyResult<Option<Snapshot>> {
+    let config = get_backup_config(json_config)?;
+    match next_scheduled(db, &config)? {
+        Some(at) if at <= Utc::now() => {}
+        _ => return Ok(None),
+    }
+    let snapshot = create_snapshot(db, &config, SnapshotKind::Auto)?;
+    prune(db, &config, false)?;
+    Ok(Some(snapshot))
+}
+
+/// 完整性检查的结果
+pub struct VerifyResult {
+    pub name: String,
+    pub problems: Vec<String>,
+}
+
+/// 检查当前数据库，以及指定的快照（为 None 时检查全部快照）
+pub fn verify(db: &Database, config: &BackupConfig, snapshot: Option<&Snapshot>) -> AnyResult<Vec<VerifyResult>> {
+    let mut results = vec![VerifyResult {
+        name: format!("当前数据库 ({})", db.path()),
+        problems: db.integrity_check()?,
+    }];
+    let snapshots = match snapshot {
+        Some(snapshot) => vec![snapshot.clone()],
+        None => list_snapshots(db, config)?,
+    };
+    for snapshot in snapshots {
+        results.push(VerifyResult {
+            problems: database::check_file(&snapshot.path).unwrap_or_else(|e| vec![format!("{:#}", e)]),
+            name: snapshot.name,
+        });
+    }
+    Ok(results)
+}
+
+/// 用快照替换当前数据库，返回恢复前自动保存的快照
+pub fn restore(db: &Database, config: &BackupConfig, snapshot: &Snapshot) -> AnyResult<Snapshot> {
+    let problems = database::check_file(&snapshot.path).unwrap_or_else(|e| vec![format!("{:#}", e)]);
+    if !problems.is_empty() {
+        bail!("快照 {} 未通过完整性检查，不能恢复: {}", snapshot.name, problems.join("; "));
+    }
+    let version = database::file_version(&snapshot.path)?;
+    if version > migration::latest_version() {
+        bail!(
+            "快照 {} 的数据库版本为 {}，高于当前程序支持的版本 {}，请升级程序后再恢复",
+            snapshot.name,
+            version,
+            migration::latest_version()
+        );
+    }
+
+    let pre_restore = create_snapshot(db, config, SnapshotKind::PreRestore)
+        .map_err(|e| anyhow!("保存当前数据失败，已取消恢复: {:#}", e))?;
+    db.restore_from(&snapshot.path)
+        .map_err(|e| anyhow!("恢复失败（恢复前的数据保存在 {}）: {:#}", pre_restore.name, e))?;
+    // 较旧的快照需要迁移到当前的表结构
+    db.initialize_tables()?;
+
+    Logger::log(
+        "INFO",
+        &format!("已从快照 {} 恢复数据库，恢复前的数据保存在 {}", snapshot.name, pre_restore.name),
+    );
+    Ok(pre_restore)
+}
+
+/// 输出快照列表
+pub fn print_snapshots(db: &Database, config: &BackupConfig, format: OutputFormat) -> AnyResult<()> {
+    let snapshots = list_snapshots(db, config)?;
+    if format != OutputFormat::Text {
+        return output::print_records(&snapshots, format);
+    }
+
+    let dir = backup_dir(db, config);
+    if snapshots.is_empty() {
+        println!("📭 暂无快照（备份目录: {}）", dir.display());
+        return Ok(());
+    }
+    println!("\n💾 数据库快照（备份目录: {}）", dir.display());
+    println!("{}", "=".repeat(60));
+    for (index, snapshot) in snapshots.iter().enumerate() {
+        println!(
+            "{:>3}. {}  {}{:>10}  {}",
+            index + 1,
+            local_time::format(&snapshot.created_at, "%Y-%m-%d %H:%M:%S"),
+            pad_label(snapshot.kind.label()),
+            format_size(snapshot.size),
+            snapshot.name
+        );
+    }
+    match next_scheduled(db, config)? {
+        Some(at) => println!(
+            "\n⏰ 下一次自动备份: {}（每 {} 小时，保留 {} 天 / {} 周）",
+            local_time::format(&at.max(Utc::now()), "%Y-%m-%d %H:%M"),
+            config.interval_hours.max(1),
+            config.keep_daily,
+            config.keep_weekly
+        ),
+        None => println!("\n⏸️  自动备份已禁用"),
+    }
+    Ok(())
+}
+
+/// 输出完整性检查结果，返回是否全部通过
+pub fn print_verify(results: &[VerifyResult]) -> bool {
+    let mut all_ok = true;
+    for result in results {
+        if result.problems.is_empty() {
+            println!("✅ {}: ok", result.name);
+        } else {
+            all_ok = false;
+            println!("❌ {}:", result.name);
+            for problem in &result.problems {
+                println!("   - {}", problem);
+            }
+        }
+    }
+    all_ok
+}
+
+/// 类型标签按显示宽度（每个汉字占两列）补齐到 8 列
+fn pad_label(label: &str) -> String {
+    format!("{}{}", label, " ".repeat(8usize.saturating_sub(label.chars().count() * 2)))
+}
+
+fn format_size(bytes: u64) -> String {
+    match bytes {
+        b if b >= 1024 * 1024 => format!("{:.1} MB", b as f64 / (1024.0 * 1024.0)),
+        b if b >= 1024 => format!("{:.1} KB", b as f64 / 1024.0),
+        b => format!("{} B", b),
+    }
+}
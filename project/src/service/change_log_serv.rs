@@ -0,0 +1,472 @@
+//! 撤销 / 重做与修改历史
+//!
+//! 修改数据的服务函数通过 `record` 把一次操作中的全部修改记为一个变更集，
+//! 各表的行变化由触发器写入变更日志（见 `data::change_log`）。
+//!
+//! - 撤销：最近一个未撤销的操作，按相反顺序写回修改前的内容
+//! - 重做：最近一次撤销的操作，按原顺序写回修改后的内容；撤销后又有新的操作时不能再重做
+//! - 写回前逐行核对当前内容是否与日志一致，之后被其他程序修改过时拒绝撤销 / 重做，整个操作回滚
+
+use anyhow::{Result as AnyResult, anyhow, bail};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+use std::env;
+use std::sync::RwLock;
+
+use crate::dao::change_log_dao;
+use crate::data::change_log::{ChangeEntry, ChangeKind, ChangeOp, ChangeSet, ChangeState, tracked_table};
+use crate::data::local_time;
+use crate::data::todo_list::{Priority, TodoStatus};
+use crate::init::database::Database;
+use crate::service::logger::Logger;
+use rusqlite::Connection;
+
+/// 本次运行的操作来源
+static SOURCE: RwLock<&'static str> = RwLock::new("interactive");
+
+/// 事务保存点名称
+const SAVEPOINT: &str = "change_set";
+
+/// 设置操作来源（interactive / cli / scheduled）
+pub fn set_source(source: &'static str) {
+    if let Ok(mut current) = SOURCE.write() {
+        *current = source;
+    }
+}
+
+fn source() -> &'static str {
+    SOURCE.read().map(|s| *s).unwrap_or("interactive")
+}
+
+/// 操作来源的显示名称
+pub fn source_label(source: &str) -> &str {
+    match source {
+        "interactive" => "交互模式",
+        "cli" => "命令行",
+        "scheduled" => "定时任务",
+        other => other,
+    }
+}
+
+/// 操作者：系统用户名
+fn actor() -> String {
+    env::var("USER")
+        .or_else(|_| env::var("USERNAME"))
+        .ok()
+        .filter(|name| !name.trim().is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// 把 f 中的全部修改记录为一个操作；f 出错时全部回滚
+///
+/// 已在某个操作中时（如导入）直接并入该操作
+pub fn record<T>(database: &Database, action: &str, f: impl FnOnce() -> AnyResult<T>) -> AnyResult<T> {
+    let conn = database.get_connection();
+    if change_log_dao::active_change_set(conn)?.is_some() {
+        return f();
+    }
+    conn.execute_batch(&format!("SAVEPOINT {}", SAVEPOINT))?;
+    let result = (|| {
+        let id = change_log_dao::begin_change_set(conn, action, &actor(), source(), ChangeKind::Edit, None)?;
+        let value = f()?;
+        change_log_dao::end_change_set(conn, id, ChangeKind::Edit)?;
+        Ok(value)
+    })();
+    finish(conn, result)
+}
+
+/// 在调用方已开启的事务中开始记录一个操作（导入等），返回变更集 ID
+pub fn begin(conn: &Connection, action: &str) -> AnyResult<i64> {
+    change_log_dao::begin_change_set(conn, action, &actor(), source(), ChangeKind::Edit, None)
+}
+
+/// 结束 `begin` 开始的操作，action 为最终的操作说明
+pub fn end(conn: &Connection, id: i64, action: &str) -> AnyResult<()> {
+    change_log_dao::set_action(conn, id, action)?;
+    change_log_dao::end_change_set(conn, id, ChangeKind::Edit)?;
+    Ok(())
+}
+
+fn finish<T>(conn: &Connection, result: AnyResult<T>) -> AnyResult<T> {
+    match result {
+        Ok(value) => {
+            conn.execute_batch(&format!("RELEASE {}", SAVEPOINT))?;
+            Ok(value)
+        }
+        Err(e) => {
+            let _ = conn.execute_batch(&format!("ROLLBACK TO {0}; RELEASE {0}", SAVEPOINT));
+            Err(e)
+        }
+    }
+}
+
+/// 撤销最近一个操作，返回被撤销的操作；没有可撤销的操作时返回 None
+pub fn undo(database: &Database) -> AnyResult<Option<ChangeSet>> {
+    let conn = database.get_connection();
+    conn.execute_batch(&format!("SAVEPOINT {}", SAVEPOINT))?;
+    let result = (|| {
+        let Some(target) = change_log_dao::latest_applied_edit(conn)? else {
+            return Ok(None);
+        };
+        let action = format!("撤销: {}", target.action);
+        let id = change_log_dao::begin_change_set(conn, &action, &actor(), source(), ChangeKind::Undo, Some(target.id))?;
+        for entry in change_log_dao::list_entries(conn, target.id)?.iter().rev() {
+            revert(conn, entry).map_err(|e| anyhow!("无法撤销「{}」: {}", target.action, e))?;
+        }
+        change_log_dao::end_change_set(conn, id, ChangeKind::Undo)?;
+        change_log_dao::set_state(conn, target.id, ChangeState::Undone)?;
+        Ok(Some(target))
+    })();
+    let result = finish(conn, result);
+    if let Ok(Some(target)) = &result {
+        Logger::log("INFO", &format!("撤销操作 #{}: {}", target.id, target.action));
+    }
+    result
+}
+
+/// 重做最近一次撤销的操作，返回被重做的操作；没有可重做的操作时返回 None
+pub fn redo(database: &Database) -> AnyResult<Option<ChangeSet>> {
+    let conn = database.get_connection();
+    conn.execute_batch(&format!("SAVEPOINT {}", SAVEPOINT))?;
+    let result = (|| {
+        let Some(target) = change_log_dao::latest_undone_edit(conn)? else {
+            return Ok(None);
+        };
+        let action = format!("重做: {}", target.action);
+        let id = change_log_dao::begin_change_set(conn, &action, &actor(), source(), ChangeKind::Redo, Some(target.id))?;
+        for entry in change_log_dao::list_entries(conn, target.id)? {
+            reapply(conn, &entry).map_err(|e| anyhow!("无法重做「{}」: {}", target.action, e))?;
+        }
+        change_log_dao::end_change_set(conn, id, ChangeKind::Redo)?;
+        change_log_dao::set_state(conn, target.id, ChangeState::Applied)?;
+        Ok(Some(target))
+    })();
+    let result = finish(conn, result);
+    if let Ok(Some(target)) = &result {
+        Logger::log("INFO", &format!("重做操作 #{}: {}", target.id, target.action));
+    }
+    result
+}
+
+/// 写回修改前的内容
+fn revert(conn: &Connection, entry: &ChangeEntry) -> AnyResult<()> {
+    let table = tracked_table(&entry.table_name).ok_or_else(|| anyhow!("未知的表: {}", entry.table_name))?;
+    let image = entry.after.as_ref().or(entry.before.as_ref()).ok_or_else(|| anyhow!("变更日志 #{} 缺少内容", entry.id))?;
+    ensure_unchanged(conn, entry, image, entry.after.as_ref())?;
+    match (entry.operation, &entry.before, &entry.after) {
+        (ChangeOp::Insert, _, Some(after)) => change_log_dao::delete_row(conn, table, after),
+        (ChangeOp::Update, Some(before), _) => change_log_dao::update_row(conn, table, before),
+        (ChangeOp::Delete, Some(before), _) => change_log_dao::insert_row(conn, table, before),
+        _ => bail!("变更日志 #{} 的内容不完整", entry.id),
+    }
+}
+
+/// 写回修改后的内容
+fn reapply(conn: &Connection, entry: &ChangeEntry) -> AnyResult<()> {
+    let table = tracked_table(&entry.table_name).ok_or_else(|| anyhow!("未知的表: {}", entry.table_name))?;
+    let image = entry.before.as_ref().or(entry.after.as_ref()).ok_or_else(|| anyhow!("变更日志 #{} 缺少内容", entry.id))?;
+    ensure_unchanged(conn, entry, image, entry.before.as_ref())?;
+    match (entry.operation, &entry.before, &entry.after) {
+        (ChangeOp::Insert, _, Some(after)) => change_log_dao::insert_row(conn, table, after),
+        (ChangeOp::Update, _, Some(after)) => change_log_dao::update_row(conn, table, after),
+        (ChangeOp::Delete, Some(before), _) => change_log_dao::delete_row(conn, table, before),
+        _ => bail!("变更日志 #{} 的内容不完整", entry.id),
+    }
+}
+
+/// 核对一行的当前内容与预期一致（expected 为 None 表示该行应不存在）
+///
+/// 只比较日志中记录的列，之后迁移新增的列不参与比较
+fn ensure_unchanged(
+    conn: &Connection,
+    entry: &ChangeEntry,
+    key_image: &Map<String, Value>,
+    expected: Option<&Map<String, Value>>,
+) -> AnyResult<()> {
+    let table = tracked_table(&entry.table_name).ok_or_else(|| anyhow!("未知的表: {}", entry.table_name))?;
+    let current = change_log_dao::row_image(conn, table, key_image)?;
+    let matches = match (&current, expected) {
+        (None, None) => true,
+        (Some(current), Some(expected)) => expected
+            .iter()
+            .all(|(column, value)| current.get(column).unwrap_or(&Value::Null) == value),
+        _ => false,
+    };
+    if !matches {
+        let key: Vec<String> = table
+            .key
+            .iter()
+            .map(|column| key_image.get(*column).map(Value::to_string).unwrap_or_default())
+            .collect();
+        bail!("{} {} 之后又被修改过", table.label, key.join("/"));
+    }
+    Ok(())
+}
+
+// ---- 修改历史 ----
+
+/// 修改历史中的一条记录（用于 JSON / CSV 输出）
+#[derive(Debug, Serialize)]
+pub struct HistoryRecord {
+    pub change_set_id: i64,
+    pub created_at: DateTime<Utc>,
+    pub actor: String,
+    pub source: String,
+    pub action: String,
+    pub kind: ChangeKind,
+    pub state: ChangeState,
+    pub table_name: String,
+    pub operation: ChangeOp,
+    pub before: Option<Map<String, Value>>,
+    pub after: Option<Map<String, Value>>,
+}
+
+/// 某个待办事项的修改历史，按操作分组（按时间顺序）
+pub fn todo_history(database: &Database, todo_id: i32) -> AnyResult<Vec<(ChangeSet, Vec<ChangeEntry>)>> {
+    let conn = database.get_connection();
+    let mut groups: Vec<(ChangeSet, Vec<ChangeEntry>)> = Vec::new();
+    for entry in change_log_dao::list_entries_for_todo(conn, todo_id)? {
+        match groups.last_mut() {
+            Some((set, entries)) if set.id == entry.change_set_id => entries.push(entry),
+            _ => {
+                let set = change_log_dao::get_change_set(conn, entry.change_set_id)?
+                    .ok_or_else(|| anyhow!("变更集 #{} 不存在", entry.change_set_id))?;
+                groups.push((set, vec![entry]));
+            }
+        }
+    }
+    Ok(groups)
+}
+
+/// 某个待办事项的修改历史（逐行，用于 JSON / CSV 输出）
+pub fn collect_todo_history(database: &Database, todo_id: i32) -> AnyResult<Vec<HistoryRecord>> {
+    let mut records = Vec::new();
+    for (set, entries) in todo_history(database, todo_id)? {
+        for entry in entries {
+            records.push(HistoryRecord {
+                change_set_id: set.id,
+                created_at: set.created_at,
+                actor: set.actor.clone(),
+                source: set.source.clone(),
+                action: set.action.clone(),
+                kind: set.kind,
+                state: set.state,
+                table_name: entry.table_name,
+                operation: entry.operation,
+                before: entry.before,
+                after: entry.after,
+            });
+        }
+    }
+    Ok(records)
+}
+
+/// 最近的操作（最新的在前）
+pub fn recent_changes(database: &Database, limit: usize) -> AnyResult<Vec<ChangeSet>> {
+    change_log_dao::recent_change_sets(database.get_connection(), limit)
+}
+
+/// 显示某个待办事项的修改历史
+pub fn show_todo_history(database: &Database, todo_id: i32) -> AnyResult<()> {
+    let groups = todo_history(database, todo_id)?;
+    if groups.is_empty() {
+        println!("📭 待办事项 #{} 没有修改记录", todo_id);
+        return Ok(());
+    }
+
+    let conn = database.get_connection();
+    println!("\n📜 待办事项 #{} 的修改历史:", todo_id);
+    println!("{}", "=".repeat(80));
+    for (set, entries) in &groups {
+        println!("{}", set_line(set));
+        for line in describe_entries(conn, entries)? {
+            println!("      {}", line);
+        }
+    }
+    Ok(())
+}
+
+/// 显示最近的操作
+pub fn show_recent_changes(database: &Database, limit: usize) -> AnyResult<()> {
+    let sets = recent_changes(database, limit)?;
+    if sets.is_empty() {
+        println!("📭 暂无修改记录");
+        return Ok(());
+    }
+    println!("\n📜 最近 {} 次操作（最新的在前）:", sets.len());
+    println!("{}", "=".repeat(80));
+    for set in &sets {
+        println!("{}", set_line(set));
+    }
+    println!("\n💡 使用 'history <待办ID>' 查看某个待办事项的详细修改");
+    Ok(())
+}
+
+fn set_line(set: &ChangeSet) -> String {
+    let marker = match (set.kind, set.state) {
+        (ChangeKind::Edit, ChangeState::Undone) => "  [已撤销，可重做]",
+        (ChangeKind::Edit, ChangeState::Discarded) => "  [已撤销]",
+        _ => "",
+    };
+    format!(
+        "  #{} {}  {}（{}）  {}{}",
+        set.id,
+        local_time::format(&set.created_at, "%Y-%m-%d %H:%M:%S"),
+        set.actor,
+        source_label(&set.source),
+        set.action,
+        marker
+    )
+}
+
+/// 把一个操作中的行变化描述为可读的文字
+fn describe_entries(conn: &Connection, entries: &[ChangeEntry]) -> AnyResult<Vec<String>> {
+    let mut lines = Vec::new();
+    // 更新标签时先删除全部关联再重新插入，按最终结果汇总增减
+    let mut tags_added: BTreeSet<i64> = BTreeSet::new();
+    let mut tags_removed: BTreeSet<i64> = BTreeSet::new();
+
+    for entry in entries {
+        let image = entry.after.as_ref().or(entry.before.as_ref());
+        let field = |name: &str| image.and_then(|i| i.get(name)).cloned().unwrap_or(Value::Null);
+        match (entry.table_name.as_str(), entry.operation) {
+            ("todo_list", ChangeOp::Insert) => lines.push(format!("+ 新建「{}」", text(&field("title")))),
+            ("todo_list", ChangeOp::Delete) => lines.push(format!("- 删除「{}」", text(&field("title")))),
+            ("todo_list", ChangeOp::Update) => {
+                let (Some(before), Some(after)) = (&entry.before, &entry.after) else {
+                    continue;
+                };
+                for (column, new_value) in after {
+                    let old_value = before.get(column).unwrap_or(&Value::Null);
+                    if column == "deleted_at" && old_value != new_value {
+                        lines.push(if new_value.is_null() { "♻️ 从回收站恢复" } else { "🗑️ 移到回收站" }.to_string());
+                    } else if column == "archived_at" && old_value != new_value {
+                        lines.push(if new_value.is_null() { "📤 取消归档" } else { "📦 归档" }.to_string());
+                    } else if column == "completed_at" {
+                        // 随状态变化，状态一行已经说明
+                        continue;
+                    } else if old_value != new_value {
+                        lines.push(format!(
+                            "{}: {} → {}",
+                            column_label(column),
+                            format_value(column, old_value),
+                            format_value(column, new_value)
+                        ));
+                    }
+                }
+            }
+            ("notes", op) => {
+                let trashed = |image: &Option<Map<String, Value>>| {
+                    image.as_ref().and_then(|i| i.get("deleted_at")).is_some_and(|v| !v.is_null())
+                };
+                let verb = match op {
+                    ChangeOp::Insert => "+ 添加笔记",
+                    ChangeOp::Update if !trashed(&entry.before) && trashed(&entry.after) => "🗑️ 笔记移到回收站",
+                    ChangeOp::Update if trashed(&entry.before) && !trashed(&entry.after) => "♻️ 从回收站恢复笔记",
+                    ChangeOp::Update => "~ 修改笔记",
+                    ChangeOp::Delete => "- 删除笔记",
+                };
+                lines.push(format!("{} #{}「{}」", verb, text(&field("id")), text(&field("note_title"))));
+            }
+            ("todo_tags", op) => {
+                let Some(tag_id) = field("tag_id").as_i64() else {
+                    continue;
+                };
+                match op {
+                    ChangeOp::Insert if !tags_removed.remove(&tag_id) => {
+                        tags_added.insert(tag_id);
+                    }
+                    ChangeOp::Delete if !tags_added.remove(&tag_id) => {
+                        tags_removed.insert(tag_id);
+                    }
+                    _ => {}
+                }
+            }
+            ("todo_dependencies", ChangeOp::Insert) => {
+                lines.push(format!("+ 依赖 #{}", text(&field("depends_on_id"))));
+            }
+            ("todo_dependencies", ChangeOp::Delete) => {
+                lines.push(format!("- 依赖 #{}", text(&field("depends_on_id"))));
+            }
+            ("time_sessions", op) => {
+                let verb = match op {
+                    ChangeOp::Insert => "⏱️ 开始计时",
+                    ChangeOp::Update => "⏹️ 停止计时",
+                    ChangeOp::Delete => "- 删除计时",
+                };
+                lines.push(verb.to_string());
+            }
+            _ => {}
+        }
+    }
+
+    if !tags_added.is_empty() || !tags_removed.is_empty() {
+        let mut changes = Vec::new();
+        for tag_id in &tags_added {
+            changes.push(format!("+{}", tag_label(conn, *tag_id)?));
+        }
+        for tag_id in &tags_removed {
+            changes.push(format!("-{}", tag_label(conn, *tag_id)?));
+        }
+        lines.push(format!("标签: {}", changes.join(" ")));
+    }
+    Ok(lines)
+}
+
+fn tag_label(conn: &Connection, tag_id: i64) -> AnyResult<String> {
+    Ok(change_log_dao::tag_name(conn, tag_id)?.unwrap_or_else(|| format!("#{}", tag_id)))
+}
+
+fn column_label(column: &str) -> &str {
+    match column {
+        "title" => "标题",
+        "description" => "描述",
+        "status" => "状态",
+        "priority" => "优先级",
+        "begin_time" => "开始时间",
+        "end_time" => "截止时间",
+        "key_message1" => "关键信息1",
+        "key_message2" => "关键信息2",
+        "key_message3" => "关键信息3",
+        "recurrence" => "重复",
+        "series_id" => "重复系列",
+        "occurrence" => "第几次",
+        "parent_id" => "父任务",
+        other => other,
+    }
+}
+
+fn format_value(column: &str, value: &Value) -> String {
+    if value.is_null() {
+        return "（空）".to_string();
+    }
+    let raw = text(value);
+    match column {
+        "status" => TodoStatus::from_string(&raw).map(|s| s.label().to_string()).unwrap_or(raw),
+        "priority" => Priority::from_string(&raw).map(|p| p.label().to_string()).unwrap_or(raw),
+        "begin_time" | "end_time" => DateTime::parse_from_rfc3339(&raw)
+            .map(|dt| local_time::format(&dt.with_timezone(&Utc), "%Y-%m-%d %H:%M"))
+            .unwrap_or(raw),
+        "parent_id" | "series_id" => format!("#{}", raw),
+        _ => shorten(&raw, 40),
+    }
+}
+
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+fn shorten(s: &str, max_chars: usize) -> String {
+    let single_line = s.replace(['\r', '\n'], " ");
+    if single_line.chars().count() <= max_chars {
+        single_line
+    } else {
+        format!("{}…", single_line.chars().take(max_chars).collect::<String>())
+    }
+}
@@ -0,0 +1,22 @@
+//! 终端字符图表（迷你折线与横向条形图）
+
+/// 迷你折线使用的字符，从低到高
+const SPARK_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// 迷你折线：每个值一个字符，按最大值缩放；全为 0 时为最低一档
+pub fn sparkline(values: &[usize]) -> String {
+    let max = values.iter().copied().max().unwrap_or(0);
+    values
+        .iter()
+        .map(|&value| SPARK_LEVELS[(value * (SPARK_LEVELS.len() - 1)).checked_div(max).unwrap_or(0)])
+        .collect()
+}
+
+/// 横向条形：按 max 缩放到 width 个字符，非 0 的值至少占一格
+pub fn bar(value: usize, max: usize, width: usize) -> String {
+    if value == 0 || max == 0 {
+        return String::new();
+    }
+    let cells = (value * width).div_ceil(max).clamp(1, width);
+    "█".repeat(cells)
+}
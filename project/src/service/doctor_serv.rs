@@ -0,0 +1,287 @@
+//! 数据库体检与修复（doctor / fsck）
+//!
+//! - 孤立记录：笔记、提醒历史、标签关联与依赖删除；父任务已不存在的待办事项变为顶层任务；
+//!   变更日志只能追加，只报告不修复
+//! - 时间字段：能按常见格式（不带时区的日期时间按本地时区、Unix 秒）理解的改写为 RFC 3339，
+//!   无法理解的只报告，需要手动修改，避免丢失原来的内容
+//! - 数据库文件损坏无法在原地修复，需要从快照恢复
+//!
+//! 修复记入修改历史。只改了时间字段时可以撤销；处理过孤立记录时撤销会重新引入孤立记录，会被外键约束拒绝。
+
+use anyhow::Result as AnyResult;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use rusqlite::types::Value;
+
+use crate::dao::doctor_dao;
+use crate::data::doctor::{Problem, ProblemKind, Repair, TIMESTAMP_COLUMNS};
+use crate::data::local_time;
+use crate::init::database::Database;
+use crate::service::change_log_serv;
+use crate::service::output::{self, OutputFormat};
+
+/// 不带时区的日期时间格式（按本地时区理解）
+const NAIVE_DATETIME_FORMATS: &[&str] = &[
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M",
+    "%Y-%m-%dT%H:%M",
+    "%Y/%m/%d %H:%M:%S",
+    "%Y/%m/%d %H:%M",
+];
+
+/// 只有日期的格式（按本地时区当天零点理解）
+const DATE_FORMATS: &[&str] = &["%Y-%m-%d", "%Y/%m/%d"];
+
+/// 检查数据库，返回发现的全部问题
+pub fn diagnose(database: &Database) -> AnyResult<Vec<Problem>> {
+    let conn = database.get_connection();
+    let mut problems = Vec::new();
+
+    for issue in database.integrity_check()? {
+        problems.push(Problem {
+            kind: ProblemKind::Corruption,
+            table: None,
+            row_id: None,
+            column: None,
+            detail: issue,
+            repair: Repair::Manual,
+        });
+    }
+
+    for violation in doctor_dao::foreign_key_violations(conn)? {
+        let repair = match violation.table.as_str() {
+            // 父任务已不存在，变为顶层任务
+            "todo_list" => Repair::SetNull,
+            // 变更日志只能追加
+            "change_log" => Repair::Manual,
+            _ => Repair::DeleteRow,
+        };
+        problems.push(Problem {
+            kind: ProblemKind::Orphan,
+            detail: format!(
+                "{} = {} 指向不存在的 {}",
+                violation.column,
+                display_value(&violation.value),
+                violation.parent
+            ),
+            table: Some(violation.table),
+            row_id: Some(violation.row_id),
+            column: Some(violation.column),
+            repair,
+        });
+    }
+
+    for (table, column) in TIMESTAMP_COLUMNS {
+        for (row_id, value) in doctor_dao::column_values(conn, table, column)? {
+            if let Value::Text(text) = &value
+                && DateTime::parse_from_rfc3339(text).is_ok()
+            {
+                continue;
+            }
+            let (repair, note) = match recover_timestamp(&value) {
+                Some(dt) => (Repair::SetValue(dt.to_rfc3339()), ""),
+                None => (Repair::Manual, "，无法推断原时间，请手动修改"),
+            };
+            problems.push(Problem {
+                kind: ProblemKind::BadTimestamp,
+                table: Some(table.to_string()),
+                row_id: Some(row_id),
+                column: Some(column.to_string()),
+                detail: format!("{} = {} 不是 RFC 3339 时间{}", column, display_value(&value), note),
+                repair,
+            });
+        }
+    }
+
+    Ok(problems)
+}
+
+/// 尝试按常见格式理解时间：纯数字按 Unix 秒，不带时区的日期时间按本地时区
+fn recover_timestamp(value: &Value) -> Option<DateTime<Utc>> {
+    let text = match value {
+        Value::Integer(secs) => return DateTime::from_timestamp(*secs, 0),
+        Value::Real(secs) => return DateTime::from_timestamp(*secs as i64, 0),
+        Value::Text(text) => text.trim(),
+        _ => return None,
+    };
+    if let Ok(secs) = text.parse::<i64>() {
+        return DateTime::from_timestamp(secs, 0);
+    }
+    // 带时区但不完全符合 RFC 3339 的写法（如用空格分隔日期与时间）
+    if let Ok(dt) = text.parse::<DateTime<Utc>>() {
+        return Some(dt);
+    }
+    for format in NAIVE_DATETIME_FORMATS {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(text, format) {
+            return local_time::from_local(&naive);
+        }
+    }
+    for format in DATE_FORMATS {
+        if let Ok(date) = NaiveDate::parse_from_str(text, format) {
+            return local_time::start_of_day(date);
+        }
+    }
+    None
+}
+
+/// 按问题中给出的方式修复，返回实际修改的行数
+pub fn repair(database: &Database, problems: &[Problem]) -> AnyResult<usize> {
+    // 先改字段再删行，避免删除时级联删掉的行再被修改
+    let mut fixable: Vec<&Problem> = problems.iter().filter(|p| p.repair != Repair::Manual).collect();
+    if fixable.is_empty() {
+        return Ok(0);
+    }
+    fixable.sort_by_key(|p| p.repair == Repair::DeleteRow);
+
+    let conn = database.get_connection();
+    let action = format!("数据库体检修复 {} 个问题", fixable.len());
+    change_log_serv::record(database, &action, || {
+        let mut fixed = 0;
+        for problem in &fixable {
+            let (Some(table), Some(row_id)) = (&problem.table, problem.row_id) else {
+                continue;
+            };
+            let column = problem.column.as_deref().unwrap_or_default();
+            fixed += match &problem.repair {
+                Repair::DeleteRow => doctor_dao::delete_row(conn, table, row_id)?,
+                Repair::SetNull => doctor_dao::set_value(conn, table, column, row_id, None)?,
+                Repair::SetValue(value) => doctor_dao::set_value(conn, table, column, row_id, Some(value))?,
+                Repair::Manual => 0,
+            };
+        }
+        Ok(fixed)
+    })
+}
+
+/// 输出体检结果
+pub fn print_problems(problems: &[Problem], format: OutputFormat) -> AnyResult<()> {
+    if format != OutputFormat::Text {
+        return output::print_records(problems, format);
+    }
+
+    if problems.is_empty() {
+        println!("✅ 数据库体检通过，没有发现问题");
+        return Ok(());
+    }
+    let fixable = problems.iter().filter(|p| p.repair != Repair::Manual).count();
+    println!("\n🩺 发现 {} 个问题（{} 个可以自动修复）:", problems.len(), fixable);
+    println!("{}", "=".repeat(80));
+    for (index, problem) in problems.iter().enumerate() {
+        let location = match (&problem.table, problem.row_id) {
+            (Some(table), Some(row_id)) => format!("{} #{}: ", table, row_id),
+            (Some(table), None) => format!("{}: ", table),
+            _ => String::new(),
+        };
+        println!("{:>3}. [{}] {}{}", index + 1, problem.kind.label(), location, problem.detail);
+        println!("       → {}", problem.repair.describe());
+    }
+    println!("{}", "=".repeat(80));
+    if problems.iter().any(|p| p.kind == ProblemKind::Corruption) {
+        println!("⚠️  数据库文件已损坏，请使用 'backup verify' 找到完好的快照后用 'restore' 恢复");
+    }
+    Ok(())
+}
+
+fn display_value(value: &Value) -> String {
+    match value {
+        Value::Null => "NULL".to_string(),
+        Value::Integer(n) => n.to_string(),
+        Value::Real(f) => f.to_string(),
+        Value::Text(text) => format!("'{}'", text),
+        Value::Blob(bytes) => format!("<{} 字节的二进制数据>", bytes.len()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 关闭外键约束后写入孤立记录与格式错误的时间，模拟旧版本或外部工具留下的数据
+    fn seeded_database() -> Database {
+        let database = Database::new(":memory:").unwrap();
+        database.initialize_tables().unwrap();
+        database
+            .get_connection()
+            .execute_batch(
+                r#"
+                PRAGMA foreign_keys = OFF;
+                INSERT INTO todo_list (id, title, begin_time) VALUES (1, '正常', '2026-03-01T09:00:00+00:00');
+                INSERT INTO todo_list (id, title, begin_time, parent_id) VALUES (2, '父任务已删除', '2026-03-01T09:00:00+00:00', 99);
+                INSERT INTO notes (id, todo_id, note_title, note_content, note_time)
+                VALUES (1, 99, '孤立笔记', '内容', '2026-03-01T09:00:00+00:00');
+                INSERT INTO reminder_history (id, todo_id, reminder_time, reminder_type)
+                VALUES (1, 99, '2026-03-01T09:00:00+00:00', 'overdue');
+
+                INSERT INTO todo_list (id, title, begin_time, end_time)
+                VALUES (3, 'Unix 秒', '1772355600', 1772445600);
+                INSERT INTO todo_list (id, title, begin_time, end_time) VALUES (4, '无法理解', 'someday', 'never');
+                PRAGMA foreign_keys = ON;
+                "#,
+            )
+            .unwrap();
+        database
+    }
+
+    fn find<'a>(problems: &'a [Problem], table: &str, row_id: i64, column: &str) -> &'a Problem {
+        problems
+            .iter()
+            .find(|p| p.table.as_deref() == Some(table) && p.row_id == Some(row_id) && p.column.as_deref() == Some(column))
+            .unwrap_or_else(|| panic!("没有报告 {} #{} 的 {}: {:?}", table, row_id, column, problems))
+    }
+
+    #[test]
+    fn reports_orphans_and_bad_timestamps() {
+        let database = seeded_database();
+        let problems = diagnose(&database).unwrap();
+
+        let orphans: Vec<&Problem> = problems.iter().filter(|p| p.kind == ProblemKind::Orphan).collect();
+        assert_eq!(orphans.len(), 3, "{:?}", orphans);
+        assert_eq!(find(&problems, "todo_list", 2, "parent_id").repair, Repair::SetNull);
+        assert_eq!(find(&problems, "notes", 1, "todo_id").repair, Repair::DeleteRow);
+        assert_eq!(find(&problems, "reminder_history", 1, "todo_id").repair, Repair::DeleteRow);
+
+        let timestamps: Vec<&Problem> = problems.iter().filter(|p| p.kind == ProblemKind::BadTimestamp).collect();
+        assert_eq!(timestamps.len(), 4, "{:?}", timestamps);
+        assert_eq!(
+            find(&problems, "todo_list", 3, "begin_time").repair,
+            Repair::SetValue("2026-03-01T09:00:00+00:00".to_string())
+        );
+        assert_eq!(
+            find(&problems, "todo_list", 3, "end_time").repair,
+            Repair::SetValue("2026-03-02T10:00:00+00:00".to_string())
+        );
+        // 无法理解的只报告，不论是否必填
+        assert_eq!(find(&problems, "todo_list", 4, "begin_time").repair, Repair::Manual);
+        assert_eq!(find(&problems, "todo_list", 4, "end_time").repair, Repair::Manual);
+
+        assert!(problems.iter().all(|p| p.kind != ProblemKind::Corruption));
+    }
+
+    #[test]
+    fn fix_repairs_everything_recoverable() {
+        let database = seeded_database();
+        let problems = diagnose(&database).unwrap();
+        assert_eq!(repair(&database, &problems).unwrap(), problems.len() - 2);
+
+        // 只剩需要手动处理的时间，原值保持不变
+        let remaining: Vec<(i64, String)> = diagnose(&database)
+            .unwrap()
+            .iter()
+            .map(|p| (p.row_id.unwrap(), p.column.clone().unwrap()))
+            .collect();
+        assert_eq!(remaining, vec![(4, "begin_time".to_string()), (4, "end_time".to_string())]);
+
+        let conn = database.get_connection();
+        let count = |sql: &str| -> i64 { conn.query_row(sql, [], |row| row.get(0)).unwrap() };
+        assert_eq!(count("SELECT count(*) FROM notes"), 0);
+        assert_eq!(count("SELECT count(*) FROM reminder_history"), 0);
+        assert_eq!(count("SELECT count(*) FROM todo_list"), 4);
+        assert_eq!(count("SELECT count(*) FROM todo_list WHERE id = 4 AND begin_time = 'someday' AND end_time = 'never'"), 1);
+        assert_eq!(count("SELECT count(*) FROM todo_list WHERE id = 2 AND parent_id IS NULL"), 1);
+        let begin: String = conn.query_row("SELECT begin_time FROM todo_list WHERE id = 3", [], |row| row.get(0)).unwrap();
+        assert_eq!(begin, "2026-03-01T09:00:00+00:00");
+
+        // 修复记入修改历史
+        assert_eq!(count("SELECT count(*) FROM change_sets WHERE action LIKE '数据库体检修复%'"), 1);
+    }
+}
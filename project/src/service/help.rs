@@ -10,9 +10,21 @@ pub fn print_help(json_config: &db_json::JsonConfig) -> AnyResult<()> {
     println!();
 
     println!("📋 可用命令列表:");
-    println!("  help   - 显示此帮助信息");
-    println!("  switch - 切换应用模式");
-    println!("  exit   - 退出程序");
+    println!("  help     - 显示此帮助信息");
+    println!("  switch   - 切换应用模式");
+    println!("  timezone - 查看或修改时区");
+    println!("  export   - 导出数据（JSON / CSV / Markdown 清单 / iCalendar / todo.txt）");
+    println!("  import   - 导入数据（JSON / CSV / iCalendar / todo.txt / Taskwarrior，先显示试运行报告，确认后写入）");
+    println!("  backup   - 数据库备份（立即备份 / 查看快照 / 完整性检查 / 清理过期快照）");
+    println!("  restore  - 从快照恢复数据库（当前数据先保存为恢复前快照）");
+    println!("  trash    - 回收站（查看、恢复或彻底删除已删除的待办事项与笔记）");
+    println!("  archive  - 归档（查看、归档或取消归档已结束的待办事项）");
+    println!("  track    - 计时（开始 / 停止对待办事项计时，查看实际用时）");
+    println!("  doctor   - 数据库体检（查找并修复孤立记录与格式错误的时间，别名 fsck）");
+    println!("  undo     - 撤销最近一次修改");
+    println!("  redo     - 重做最近一次撤销的修改");
+    println!("  history  - 查看最近的操作记录；history <ID> 查看某个待办事项的修改历史");
+    println!("  exit     - 退出程序");
     println!();
 
     // 根据模式显示特定命令
@@ -31,12 +43,19 @@ pub fn print_help(json_config: &db_json::JsonConfig) -> AnyResult<()> {
 // 打印 memo 模式可用指令
 pub fn print_memo_help() {
     println!("📝 Memo 模式专用命令:");
-    println!("  list   - 显示所有待办事项");
+    println!("  list   - 显示所有待办事项（不含已归档的）");
     println!("  new    - 创建新的待办事项");
     println!("  update - 更新待办事项");
-    println!("  delete - 删除待办事项");
+    println!("  delete - 删除待办事项（移到回收站）");
     println!("  toggle - 切换待办事项完成状态");
+    println!("  status - 设置待办事项状态（待办/进行中/受阻/已完成/已取消）");
     println!("  note   - 管理待办事项的笔记");
+    println!("  parent - 设置父任务（子任务在 list 中缩进显示，父任务需在子任务都完成后才能完成）");
+    println!("  depend - 添加或移除任务依赖（被依赖的任务未完成时显示为等待中）");
+    println!("  search - 按条件查询待办事项（也可写作 search <条件> / filter <条件>）");
+    println!("  reindex - 重建全文检索索引");
+    println!();
+    print_query_help();
     println!();
     println!("💡 提示: 使用 'switch' 切换到其他模式 (review/reminder)");
 }
@@ -46,8 +65,22 @@ pub fn print_review_help() {
     println!("📊 Review 模式专用命令:");
     println!("  stats     - 显示整体统计信息");
     println!("  completed - 显示已完成的待办事项");
-    println!("  pending   - 显示未完成的待办事项");
+    println!("  pending   - 显示未完成的待办事项（按优先级排序）");
+    println!("  tags      - 显示所有标签及使用次数");
     println!("  notes     - 显示最近的笔记（最多10条）");
+    println!("  trends    - 趋势报告（新建 / 完成趋势、交付周期、逾期率、活跃标签、连续完成），可写作 trends [天数] [day|week]");
+    println!("  search    - 按条件查询待办事项（也可写作 search <条件> / filter <条件>）");
+    println!();
+    print_query_help();
+}
+
+// 打印查询语法说明
+pub fn print_query_help() {
+    println!("🔍 查询语法（多个条件为 AND 关系，前加 - 表示取反）:");
+    println!("  status:pending|todo|in_progress|blocked|done|cancelled|closed");
+    println!("  tag:work   priority:high|medium|low|none   has:due|notes|tags|repeat   is:overdue");
+    println!("  due<2026-11-01   begin>=2026-10-01   due:2026-10-20（同一天）");
+    println!("  关键字 或 \"带空格的短语\" - 全文检索标题、描述、关键信息和笔记，按相关度排序");
 }
 
 // 打印 reminder 模式可用指令
@@ -55,15 +88,112 @@ pub fn print_reminder_help() {
     println!("⏰ Reminder 模式专用命令:");
     println!("  reminder         - 提醒功能开关设置（可设置检查间隔）");
     println!("  reminder-status  - 查看提醒功能状态（包括检查间隔和通知类型）");
-    println!("  reminder-type    - 设置通知类型（控制台/Windows/双重通知）");
+    println!("  reminder-type    - 设置通知类型（控制台/Windows/Linux 桌面/双重通知）");
     println!("  reminder-history - 查看提醒历史记录");
+    println!("  reminder-recent [all]         - 查看最近未确认的提醒（all 包含已确认的）");
+    println!("  reminder-snooze [<ID> [时长]] - 稍后提醒，到时再次发送（如 reminder-snooze 5 2h）");
+    println!("  reminder-ack [<ID>|all]       - 确认提醒");
     println!("  reminder-cleanup - 清理旧提醒历史");
     println!("  test-reminder    - 测试提醒功能（触发命令行提醒）");
+    println!("  reminder-rules   - 查看、添加、删除提醒规则（开始前/截止前/开始后/截止后，可重复）");
     println!();
     println!("💡 提示:");
     println!("   • 提醒基于任务的开始时间(begin_time)，会在任务开始前1天/1小时提醒");
     println!("   • 默认检查间隔为15分钟，可自定义（1-1440分钟）");
-    println!("   • 默认通知类型为双重通知（控制台+Windows）");
-    println!("   • 定时任务建议仅使用 Windows 通知，避免弹出控制台窗口");
-    println!("   • 修改检查间隔后，需重启程序以应用新设置");
-}
\ No newline at end of file
+    println!("   • 默认通知类型为双重通知（控制台+当前平台的系统通知）");
+    println!("   • 定时任务建议仅使用系统通知（Windows / Linux），避免弹出控制台窗口");
+    println!("   • Linux 桌面通知带有 \"完成\" / \"稍后提醒\" / \"知道了\" 按钮，等待时长由 action_wait_seconds 设置");
+    println!("   • 稍后提醒的默认时长由 snooze_minutes 设置（默认 30 分钟）");
+    println!("   • 修改检查间隔后，需重启程序以应用新设置（常驻模式 --daemon 会自动重新加载）");
+}
+
+// 打印命令行（非交互）模式用法
+pub fn print_cli_usage() {
+    println!("用法: project [子命令] [参数]");
+    println!("不带参数启动时进入交互模式");
+    println!();
+    println!("📝 待办事项:");
+    println!("  todo list [--all] [--format ..]    --all 时包含已归档的待办事项");
+    println!("  todo add --title <标题> --begin <时间> [--end <时间>] [--desc <描述>]");
+    println!("           [--priority 高|中|低] [--tags a,b] [--status <状态>] [--repeat <重复规则>]");
+    println!("           [--parent <父任务ID>]");
+    println!("           [--key1 <信息>] [--key2 <信息>] [--key3 <信息>]");
+    println!("  todo update <ID> [--title ..] [--desc ..] [--begin ..] [--end ..] [--priority ..]");
+    println!("           [--tags ..] [--status ..] [--repeat ..] [--parent ..] [--key1 ..] [--cascade]");
+    println!("  todo delete <ID>");
+    println!("  todo toggle <ID> [--cascade]");
+    println!("  todo status <ID> <todo|in_progress|blocked|done|cancelled> [--cascade]");
+    println!("  todo parent <ID> <父任务ID|none>");
+    println!("  todo depend|undepend <ID> <依赖的ID>");
+    println!("  todo search <查询条件..> [--format ..]   例: todo search status:pending tag:work");
+    println!("  todo reindex                            重建全文检索索引");
+    println!();
+    println!("🗒️  笔记:");
+    println!("  note list --todo <待办ID> [--format ..]");
+    println!("  note add --todo <待办ID> --title <标题> --content <内容> [--priority ..] [--tag ..]");
+    println!("  note update <笔记ID> [--title ..] [--content ..] [--priority ..] [--tag ..]");
+    println!("  note delete <笔记ID>");
+    println!();
+    println!("📊 查看:");
+    println!("  review stats | completed | pending | tags [--format ..]");
+    println!("  review notes [--limit <数量>] [--format ..]");
+    println!("  review trends [--days N] [--by day|week] [--format ..]   最近 N 天（默认 30）的趋势报告");
+    println!();
+    println!("⏰ 提醒:");
+    println!("  reminder history [--format ..]");
+    println!("  reminder recent [--all] [--format ..]");
+    println!("  reminder snooze <提醒ID> [--for 30m]");
+    println!("  reminder ack <提醒ID|all>");
+    println!("  reminder rules");
+    println!("  reminder rule-add --type <before_start|before_end|after_start|after_end> [--offset 2h]");
+    println!("           [--repeat 1d] [--message <模板>] [--id <规则标识>]");
+    println!("  reminder rule-remove <序号|规则标识>");
+    println!("  reminder sinks                     查看通知渠道");
+    println!("  reminder sink-test [<渠道名称>]     向通知渠道发送一条测试提醒");
+    println!("  timezone [<IANA 时区>|system]     查看或设置时区");
+    println!("  --check-reminders   执行一次提醒检查（供定时任务调用）");
+    println!("  --daemon            常驻运行，在提醒到期时自动检查（SIGHUP 重新加载配置，SIGTERM 退出）");
+    println!();
+    println!("📦 导入导出:");
+    println!("  export [--format json|csv|md|ics|todotxt] [--table todos|notes|reminders] [--output <文件>]");
+    println!("  import <文件> [--format json|csv|ics|todotxt|taskwarrior] [--table todos|notes] [--dry-run]");
+    println!("           [--on-conflict skip|duplicate]");
+    println!();
+    println!("💾 备份与恢复:");
+    println!("  backup create                      立即生成一个手动快照");
+    println!("  backup list [--format ..]          列出快照");
+    println!("  backup verify [<序号|快照名>]       对当前数据库和快照执行完整性检查");
+    println!("  backup prune [--dry-run]           按保留策略删除过期的自动快照");
+    println!("  restore [<序号|快照名>]             无参数时列出快照，否则用该快照替换当前数据库");
+    println!();
+    println!("🗑️  回收站:");
+    println!("  trash [list] [--format ..]         查看回收站（todo delete / note delete 删除的项目）");
+    println!("  trash restore <ID> [--note]        恢复待办事项（连同笔记、标签、依赖与提醒历史）或笔记");
+    println!("  trash purge <ID> [--note]          彻底删除回收站中的一项");
+    println!("  trash purge [--all] [--dry-run]    彻底删除超过保留天数的项目，--all 清空回收站");
+    println!();
+    println!("📦 归档:");
+    println!("  archive [list] [--format ..]       查看已归档的待办事项");
+    println!("  archive add <ID>                   归档已完成或已取消的待办事项（连同子任务）");
+    println!("  archive restore <ID>               取消归档（连同子任务）");
+    println!("  archive auto [--days N] [--dry-run]  归档已结束超过 N 天的待办事项（默认 archive.after_days）");
+    println!();
+    println!("⏱️  计时:");
+    println!("  track start <ID>                   开始计时（自动停止正在进行的计时）");
+    println!("  track stop                         停止正在进行的计时");
+    println!("  track [status] [ID] [--format ..]  查看正在进行的计时，指定 ID 时列出其计时与完成记录");
+    println!();
+    println!("🩺 数据库体检:");
+    println!("  doctor [--fix] [--format ..]       查找孤立记录与格式错误的时间，--fix 修复（别名 fsck）");
+    println!();
+    println!("↩️  撤销与历史:");
+    println!("  undo                               撤销最近一次修改");
+    println!("  redo                               重做最近一次撤销的修改");
+    println!("  history [<ID>] [--limit N] [--format ..]  最近的操作记录，或某个待办事项的修改历史");
+    println!();
+    println!("时间格式: YYYY-MM-DD [HH[:MM[:SS]]]（按本地时区理解）、带偏移的 RFC 3339，");
+    println!("          或自然语言：tomorrow 9am、next friday、+3d、in 2 hours、end of month、明天下午3点、下周一、3天后、月底");
+    println!("选项也可写作 --key=value");
+    println!("输出格式: --format text（默认）| json | ndjson | csv");
+    println!("退出码: 0 成功 / 1 运行错误 / 2 参数错误 / 3 目标不存在");
+}
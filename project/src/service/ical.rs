@@ -0,0 +1,899 @@
+//! iCalendar（RFC 5545）导入导出
+//!
+//! 导出：每个待办事项为一个 VTODO，开始 / 截止 / 完成时间对应 DTSTART / DUE / COMPLETED，重复规则原样写为 RRULE，
+//! 父任务与依赖写为 RELATED-TO；未结束的待办按当前的提醒规则附带 VALARM。
+//! 日历中没有的信息（受阻状态、关键信息）写在 `X-TODOLIST-*` 扩展属性中，导入时还原。
+//!
+//! 导入：读取 VTODO 与 VEVENT（会议等日程），转换为与 JSON 导出文件相同的结构，
+//! 之后的 ID 分配、重复检测与试运行都与其他格式一致。VALARM、VTIMEZONE 等其他组件被忽略，
+//! 提醒仍由提醒规则统一产生。
+
+use anyhow::{Result as AnyResult, bail};
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, TimeDelta, Utc};
+use std::collections::HashMap;
+
+use crate::data::local_time;
+use crate::data::todo_list::{Priority, TodoListForm, TodoStatus};
+use crate::data::transfer::{Dependency, ExportDocument};
+use crate::init::db_json_content::{ReminderConfig, ReminderRule};
+use crate::service::reminder_serv::ReminderService;
+use crate::service::todo_list_serv;
+
+const PRODID: &str = "-//todoList//TodoList//ZH";
+/// 内容行的最大长度（字节），超出部分折行
+const LINE_LIMIT: usize = 75;
+
+/// 导出的 UID，导入时用于还原 RELATED-TO 引用
+fn todo_uid(id: i32) -> String {
+    format!("todo-{}@todolist", id)
+}
+
+/// 生成 iCalendar 文件内容；提醒功能关闭时不附带 VALARM
+pub fn render_calendar(document: &ExportDocument, reminder: &ReminderConfig) -> String {
+    let rules: Vec<&ReminderRule> = if reminder.enabled {
+        reminder.rules.iter().filter(|rule| rule.validate().is_ok()).collect()
+    } else {
+        Vec::new()
+    };
+    let mut depends_on: HashMap<i32, Vec<i32>> = HashMap::new();
+    for dependency in &document.dependencies {
+        depends_on.entry(dependency.todo_id).or_default().push(dependency.depends_on_id);
+    }
+
+    let mut calendar = Calendar::default();
+    calendar.line("BEGIN", "VCALENDAR");
+    calendar.line("VERSION", "2.0");
+    calendar.line("PRODID", PRODID);
+    calendar.line("CALSCALE", "GREGORIAN");
+    calendar.line("METHOD", "PUBLISH");
+    calendar.line("X-WR-CALNAME", "TodoList");
+    if let Some(timezone) = &document.timezone {
+        calendar.line("X-WR-TIMEZONE", &escape_text(timezone));
+    }
+    for todo in &document.todos {
+        let dependencies = depends_on.get(&todo.id).map(Vec::as_slice).unwrap_or_default();
+        render_todo(&mut calendar, todo, dependencies, &rules, document.exported_at);
+    }
+    calendar.line("END", "VCALENDAR");
+    calendar.text
+}
+
+fn render_todo(
+    calendar: &mut Calendar,
+    todo: &TodoListForm,
+    depends_on: &[i32],
+    rules: &[&ReminderRule],
+    stamp: DateTime<Utc>,
+) {
+    // DUE 必须晚于 DTSTART
+    let due = todo.end_time.filter(|end| *end > todo.begin_time);
+
+    calendar.line("BEGIN", "VTODO");
+    calendar.line("UID", &todo_uid(todo.id));
+    calendar.line("DTSTAMP", &format_utc(&stamp));
+    calendar.line("DTSTART", &format_utc(&todo.begin_time));
+    if let Some(due) = &due {
+        calendar.line("DUE", &format_utc(due));
+    }
+    calendar.line("SUMMARY", &escape_text(&todo.title));
+    if let Some(description) = todo.description.as_deref().filter(|d| !d.trim().is_empty()) {
+        calendar.line("DESCRIPTION", &escape_text(description));
+    }
+    let status = match todo.status {
+        TodoStatus::Todo | TodoStatus::Blocked => "NEEDS-ACTION",
+        TodoStatus::InProgress => "IN-PROCESS",
+        TodoStatus::Done => "COMPLETED",
+        TodoStatus::Cancelled => "CANCELLED",
+    };
+    calendar.line("STATUS", status);
+    if todo.status == TodoStatus::Blocked {
+        calendar.line("X-TODOLIST-STATUS", TodoStatus::Blocked.as_str());
+    }
+    if todo.is_done() {
+        if let Some(completed_at) = &todo.completed_at {
+            calendar.line("COMPLETED", &format_utc(completed_at));
+        }
+        calendar.line("PERCENT-COMPLETE", "100");
+    }
+    if let Some(priority) = todo.priority {
+        let value = match priority {
+            Priority::High => "1",
+            Priority::Medium => "5",
+            Priority::Low => "9",
+        };
+        calendar.line("PRIORITY", value);
+    }
+    if !todo.tags.is_empty() {
+        let tags: Vec<String> = todo.tags.iter().map(|t| escape_text(t)).collect();
+        calendar.line("CATEGORIES", &tags.join(","));
+    }
+    // 已结束的重复待办不再展开，下一次由系列中未结束的那一个代表
+    if todo.status.is_open()
+        && let Some(recurrence) = &todo.recurrence
+    {
+        calendar.line("RRULE", &recurrence.to_string());
+    }
+    if let Some(parent_id) = todo.parent_id {
+        calendar.line("RELATED-TO;RELTYPE=PARENT", &todo_uid(parent_id));
+    }
+    for depends_on_id in depends_on {
+        calendar.line("RELATED-TO;RELTYPE=DEPENDS-ON", &todo_uid(*depends_on_id));
+    }
+    let key_messages = [&todo.key_message1, &todo.key_message2, &todo.key_message3];
+    for (index, message) in key_messages.into_iter().enumerate() {
+        if let Some(message) = message.as_deref().filter(|m| !m.trim().is_empty()) {
+            calendar.line(&format!("X-TODOLIST-KEY{}", index + 1), &escape_text(message));
+        }
+    }
+
+    if todo.status.is_open() {
+        for rule in rules {
+            render_alarm(calendar, todo, due, rule, rules);
+        }
+    }
+    calendar.line("END", "VTODO");
+}
+
+/// 按提醒规则生成 VALARM：before_* / after_* 对应负 / 正的 TRIGGER，*_end 以 DUE 为参照
+fn render_alarm(
+    calendar: &mut Calendar,
+    todo: &TodoListForm,
+    due: Option<DateTime<Utc>>,
+    rule: &ReminderRule,
+    rules: &[&ReminderRule],
+) {
+    let Some(kind) = rule.kind() else {
+        return;
+    };
+    if kind.uses_end_time() && due.is_none() {
+        return;
+    }
+    let offset = if kind.is_before() { -rule.offset_seconds() } else { rule.offset_seconds() };
+    let trigger = if kind.uses_end_time() { "TRIGGER;RELATED=END" } else { "TRIGGER" };
+
+    calendar.line("BEGIN", "VALARM");
+    calendar.line("ACTION", "DISPLAY");
+    let message = ReminderService::render_message(rule, todo, todo.begin_time, due);
+    calendar.line("DESCRIPTION", &escape_text(&message));
+    calendar.line(trigger, &format_duration(offset));
+    // 重复提醒：有效期有结束时间时换算为次数，after_* 规则一直有效，日历中无法表达，只提醒一次
+    if let Some(repeat) = rule.repeat_seconds.filter(|r| *r > 0)
+        && let Some((start, Some(end))) = ReminderService::trigger_window(rule, rules, todo.begin_time, due)
+    {
+        let count = ((end - start).num_seconds() - 1) / repeat;
+        if count > 0 {
+            calendar.line("REPEAT", &count.to_string());
+            calendar.line("DURATION", &format_duration(repeat));
+        }
+    }
+    calendar.line("END", "VALARM");
+}
+
+/// 按行写出 iCalendar 内容，负责折行与 CRLF
+#[derive(Default)]
+struct Calendar {
+    text: String,
+}
+
+impl Calendar {
+    fn line(&mut self, name: &str, value: &str) {
+        let line = format!("{}:{}", name, value);
+        let mut width = 0;
+        for c in line.chars() {
+            // 折行后的续行以一个空格开头，占用一个字节
+            if width + c.len_utf8() > LINE_LIMIT {
+                self.text.push_str("\r\n ");
+                width = 1;
+            }
+            self.text.push(c);
+            width += c.len_utf8();
+        }
+        self.text.push_str("\r\n");
+    }
+}
+
+fn format_utc(dt: &DateTime<Utc>) -> String {
+    dt.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// 秒数格式化为 RFC 5545 时长，如 -P1D、PT1H30M
+fn format_duration(seconds: i64) -> String {
+    let sign = if seconds < 0 { "-" } else { "" };
+    let mut rest = seconds.abs();
+    let days = rest / 86400;
+    rest %= 86400;
+    let mut text = format!("{}P", sign);
+    if days > 0 {
+        text.push_str(&format!("{}D", days));
+    }
+    if rest > 0 || days == 0 {
+        text.push('T');
+        let (hours, minutes, secs) = (rest / 3600, rest % 3600 / 60, rest % 60);
+        if hours > 0 {
+            text.push_str(&format!("{}H", hours));
+        }
+        if minutes > 0 {
+            text.push_str(&format!("{}M", minutes));
+        }
+        if secs > 0 || rest == 0 {
+            text.push_str(&format!("{}S", secs));
+        }
+    }
+    text
+}
+
+/// 解析 RFC 5545 时长，如 PT1H30M、-P1D、P2W
+fn parse_duration(value: &str) -> Option<i64> {
+    let value = value.trim();
+    let (sign, value) = match value.strip_prefix('-') {
+        Some(rest) => (-1, rest),
+        None => (1, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let value = value.strip_prefix('P')?;
+    let mut total = 0i64;
+    let mut number = String::new();
+    let mut in_time = false;
+    for c in value.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' if number.is_empty() => in_time = true,
+            _ => {
+                let n: i64 = number.parse().ok()?;
+                number.clear();
+                let unit = match (c, in_time) {
+                    ('W', false) => 7 * 86400,
+                    ('D', false) => 86400,
+                    ('H', true) => 3600,
+                    ('M', true) => 60,
+                    ('S', true) => 1,
+                    _ => return None,
+                };
+                total = total.checked_add(n.checked_mul(unit)?)?;
+            }
+        }
+    }
+    number.is_empty().then_some(sign * total)
+}
+
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+fn unescape_text(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => result.push('\n'),
+            Some(other) => result.push(other),
+            None => result.push('\\'),
+        }
+    }
+    result
+}
+
+/// 按未转义的逗号拆分多值文本（如 CATEGORIES）
+fn split_text_list(value: &str) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut current = String::new();
+    let mut escaped = false;
+    for c in value.chars() {
+        match c {
+            ',' if !escaped => items.push(std::mem::take(&mut current)),
+            _ => {
+                escaped = c == '\\' && !escaped;
+                current.push(c);
+            }
+        }
+    }
+    items.push(current);
+    items.iter()
+        .map(|item| unescape_text(item.trim()))
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+/// 内容行：名称、参数与值
+struct Property {
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+impl Property {
+    /// 解析一行内容，参数值可以用双引号包含 `:` 与 `;`
+    fn parse(line: &str) -> Option<Property> {
+        let mut in_quotes = false;
+        let mut split = None;
+        for (index, c) in line.char_indices() {
+            match c {
+                '"' => in_quotes = !in_quotes,
+                ':' if !in_quotes => {
+                    split = Some(index);
+                    break;
+                }
+                _ => {}
+            }
+        }
+        let (head, value) = line.split_at(split?);
+
+        let mut parts = Vec::new();
+        let mut current = String::new();
+        in_quotes = false;
+        for c in head.chars() {
+            match c {
+                '"' => in_quotes = !in_quotes,
+                ';' if !in_quotes => parts.push(std::mem::take(&mut current)),
+                _ => current.push(c),
+            }
+        }
+        parts.push(current);
+
+        let mut parts = parts.into_iter();
+        let name = parts.next()?.trim().to_uppercase();
+        let params = parts
+            .filter_map(|p| p.split_once('=').map(|(k, v)| (k.trim().to_uppercase(), v.trim().to_string())))
+            .collect();
+        Some(Property { name, params, value: value[1..].to_string() })
+    }
+
+    fn param(&self, key: &str) -> Option<&str> {
+        self.params.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+
+    fn text(&self) -> String {
+        unescape_text(self.value.trim())
+    }
+
+    fn is_date(&self) -> bool {
+        self.param("VALUE").is_some_and(|v| v.eq_ignore_ascii_case("DATE")) || self.value.trim().len() == 8
+    }
+
+    /// 解析日期时间：UTC（以 Z 结尾）、带 TZID 的本地时间、浮动时间（按本地时区）或全天日期
+    fn time(&self, warnings: &mut Vec<String>, label: &str) -> Option<DateTime<Utc>> {
+        let value = self.value.trim();
+        if self.is_date() {
+            let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+            return local_time::start_of_day(date);
+        }
+        if let Some(utc) = value.strip_suffix('Z') {
+            return NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok().map(|dt| dt.and_utc());
+        }
+        let naive = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+        match self.param("TZID") {
+            Some(tzid) => {
+                let name = tzid.trim_matches('"').trim_start_matches('/');
+                match local_time::parse_zone(name) {
+                    Some(zone) => local_time::from_zone_local(zone, &naive),
+                    None => {
+                        warnings.push(format!("{}：无法识别的时区 {}，按本地时区处理", label, name));
+                        local_time::from_local(&naive)
+                    }
+                }
+            }
+            None => local_time::from_local(&naive),
+        }
+    }
+}
+
+/// VTODO / VEVENT 组件
+struct Component {
+    kind: String,
+    /// BEGIN 所在的行号，作为导入时的临时 ID
+    line: usize,
+    properties: Vec<Property>,
+}
+
+impl Component {
+    fn get(&self, name: &str) -> Option<&Property> {
+        self.properties.iter().find(|p| p.name == name)
+    }
+
+    fn all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Property> + 'a {
+        self.properties.iter().filter(move |p| p.name == name)
+    }
+
+    fn text(&self, name: &str) -> Option<String> {
+        self.get(name).map(Property::text).filter(|t| !t.trim().is_empty())
+    }
+
+    fn label(&self) -> String {
+        let kind = if self.kind == "VEVENT" { "日程" } else { "待办" };
+        match self.text("SUMMARY") {
+            Some(summary) => format!("第 {} 行的{}「{}」", self.line, kind, summary),
+            None => format!("第 {} 行的{}", self.line, kind),
+        }
+    }
+}
+
+/// 展开折行，返回（起始行号, 内容行）
+fn unfold(content: &str) -> Vec<(usize, String)> {
+    let mut lines: Vec<(usize, String)> = Vec::new();
+    for (index, raw) in content.lines().enumerate() {
+        let raw = raw.trim_end_matches('\r');
+        match raw.strip_prefix([' ', '\t']) {
+            Some(rest) if !lines.is_empty() => {
+                if let Some((_, last)) = lines.last_mut() {
+                    last.push_str(rest);
+                }
+            }
+            _ if raw.trim().is_empty() => {}
+            _ => lines.push((index + 1, raw.to_string())),
+        }
+    }
+    lines
+}
+
+/// 读取 VCALENDAR 中的 VTODO 与 VEVENT，嵌套组件（如 VALARM）的属性被忽略
+fn parse_components(content: &str) -> AnyResult<Vec<Component>> {
+    let lines = unfold(content);
+    if !lines.first().is_some_and(|(_, l)| l.trim().eq_ignore_ascii_case("BEGIN:VCALENDAR")) {
+        bail!("不是有效的 iCalendar 文件（应以 BEGIN:VCALENDAR 开头）");
+    }
+
+    let mut components = Vec::new();
+    let mut stack: Vec<String> = Vec::new();
+    let mut current: Option<Component> = None;
+    for (line, text) in lines {
+        let Some(property) = Property::parse(&text) else {
+            continue;
+        };
+        match property.name.as_str() {
+            "BEGIN" => {
+                let kind = property.value.trim().to_uppercase();
+                if stack.len() == 1 && (kind == "VTODO" || kind == "VEVENT") {
+                    current = Some(Component { kind: kind.clone(), line, properties: Vec::new() });
+                }
+                stack.push(kind);
+            }
+            "END" => {
+                let kind = property.value.trim().to_uppercase();
+                if stack.pop().as_deref() != Some(kind.as_str()) {
+                    bail!("第 {} 行的 END:{} 与 BEGIN 不匹配", line, kind);
+                }
+                if stack.len() == 1
+                    && let Some(component) = current.take()
+                {
+                    components.push(component);
+                }
+            }
+            _ if stack.len() == 2 => {
+                if let Some(component) = current.as_mut() {
+                    component.properties.push(property);
+                }
+            }
+            _ => {}
+        }
+    }
+    if !stack.is_empty() {
+        bail!("iCalendar 文件不完整，缺少 END:{}", stack.last().cloned().unwrap_or_default());
+    }
+    Ok(components)
+}
+
+/// 解析 iCalendar 文件为导入文档
+///
+/// - VTODO 的 DTSTART / DUE 对应开始 / 截止时间，没有 DTSTART 时以 DUE 作为开始时间
+/// - VEVENT 的 DTSTART / DTEND（或 DURATION）对应开始 / 截止时间；已经结束的日程导入为已完成，
+///   重复日程从下一次尚未开始的重复导入
+/// - RELATED-TO 引用文件中其他组件的 UID 时还原为父任务或依赖
+pub fn parse_calendar(content: &str) -> AnyResult<ExportDocument> {
+    let components = parse_components(content)?;
+    let mut document = ExportDocument::new(None);
+    let now = Utc::now();
+
+    // 临时 ID 使用负的行号，导入报告中显示为行号
+    let uids: HashMap<String, i32> = components
+        .iter()
+        .filter_map(|c| c.get("UID").map(|uid| (uid.value.trim().to_string(), -(c.line as i32))))
+        .collect();
+    let mut children: Vec<(String, i32)> = Vec::new();
+
+    for component in &components {
+        let id = -(component.line as i32);
+        let label = component.label();
+        if component.get("RECURRENCE-ID").is_some() {
+            document.read_warnings.push(format!("{}是重复日程中单独修改的一次，已忽略", label));
+            continue;
+        }
+        let Some(mut todo) = component_todo(component, id, now, &mut document.read_warnings) else {
+            continue;
+        };
+
+        for related in component.all("RELATED-TO") {
+            let uid = related.value.trim();
+            let reltype = related.param("RELTYPE").unwrap_or("PARENT").to_uppercase();
+            match reltype.as_str() {
+                "CHILD" => children.push((uid.to_string(), id)),
+                _ => match uids.get(uid) {
+                    Some(&other) if reltype == "PARENT" => todo.parent_id = Some(other),
+                    Some(&other) if reltype == "DEPENDS-ON" => {
+                        document.dependencies.push(Dependency { todo_id: id, depends_on_id: other })
+                    }
+                    Some(_) => {}
+                    None => document.read_warnings.push(format!("{}引用的 {} 不在文件中，已忽略", label, uid)),
+                },
+            }
+        }
+        document.todos.push(todo);
+    }
+
+    for (child_uid, parent_id) in children {
+        if let Some(&child_id) = uids.get(&child_uid)
+            && let Some(child) = document.todos.iter_mut().find(|t| t.id == child_id)
+        {
+            child.parent_id.get_or_insert(parent_id);
+        }
+    }
+    Ok(document)
+}
+
+/// 将一个组件转换为待办事项，缺少必要信息时记录警告并返回 None
+fn component_todo(
+    component: &Component,
+    id: i32,
+    now: DateTime<Utc>,
+    warnings: &mut Vec<String>,
+) -> Option<TodoListForm> {
+    let label = component.label();
+    let is_event = component.kind == "VEVENT";
+    let start = component.get("DTSTART");
+    let mut time_of = |name: &str| {
+        let property = component.get(name)?;
+        let time = property.time(warnings, &label);
+        if time.is_none() {
+            warnings.push(format!("{}的 {} 无法识别: {}", label, name, property.value.trim()));
+        }
+        time
+    };
+
+    let begin = time_of("DTSTART");
+    let explicit_end = if is_event { time_of("DTEND") } else { time_of("DUE") };
+    let completed = time_of("COMPLETED");
+    let Some(begin_time) = begin.or(explicit_end) else {
+        warnings.push(format!("{}没有开始时间，已忽略", label));
+        return None;
+    };
+    let duration_end = component.get("DURATION").and_then(|d| {
+        let end = parse_duration(&d.value)
+            .and_then(TimeDelta::try_seconds)
+            .and_then(|d| begin_time.checked_add_signed(d));
+        if end.is_none() {
+            warnings.push(format!("{}的 DURATION 无法识别或超出范围: {}", label, d.value.trim()));
+        }
+        end
+    });
+    let end_time = explicit_end
+        .or(duration_end)
+        // 全天日程没有 DTEND 时持续一天
+        .or_else(|| (is_event && start.is_some_and(Property::is_date)).then(|| begin_time.checked_add_signed(Duration::days(1))).flatten())
+        .filter(|end| *end > begin_time);
+
+    let x_status = component.text("X-TODOLIST-STATUS").and_then(|s| TodoStatus::from_string(&s));
+    let status = component.text("STATUS").map(|s| s.to_uppercase());
+    let mut status = x_status.unwrap_or(match status.as_deref() {
+        Some("COMPLETED") => TodoStatus::Done,
+        Some("IN-PROCESS") => TodoStatus::InProgress,
+        Some("CANCELLED") => TodoStatus::Cancelled,
+        _ => TodoStatus::Todo,
+    });
+
+    let mut recurrence = None;
+    if let Some(rrule) = component.get("RRULE") {
+        match todo_list_serv::parse_recurrence_input(rrule.value.trim(), begin_time) {
+            Ok(rule) => recurrence = rule,
+            Err(e) => warnings.push(format!("{}的重复规则不受支持（{}），按单次导入", label, e)),
+        }
+    }
+
+    let mut todo = TodoListForm {
+        id,
+        title: component.text("SUMMARY").unwrap_or_else(|| "（无标题）".to_string()),
+        description: component.text("DESCRIPTION"),
+        status,
+        priority: component.get("PRIORITY").and_then(|p| match p.value.trim().parse::<u8>().ok()? {
+            1..=4 => Some(Priority::High),
+            5 => Some(Priority::Medium),
+            6..=9 => Some(Priority::Low),
+            _ => None,
+        }),
+        tags: component.all("CATEGORIES").flat_map(|c| split_text_list(&c.value)).collect(),
+        begin_time,
+        end_time,
+        key_message1: component.text("X-TODOLIST-KEY1")
+            .or_else(|| component.text("LOCATION").map(|l| format!("地点: {}", l))),
+        key_message2: component.text("X-TODOLIST-KEY2"),
+        key_message3: component.text("X-TODOLIST-KEY3"),
+        recurrence: None,
+        series_id: None,
+        occurrence: 1,
+        parent_id: None,
+        archived_at: None,
+        completed_at: None,
+    };
+
+    if is_event && status.is_open() {
+        match &recurrence {
+            // 重复日程从下一次尚未开始的重复导入，已全部结束的视为已完成
+            Some(rule) => match rule.upcoming(begin_time, 1, now) {
+                Some((next, occurrence)) => {
+                    todo.end_time = end_time.map(|end| next + (end - begin_time));
+                    todo.begin_time = next;
+                    todo.occurrence = occurrence;
+                }
+                None => status = TodoStatus::Done,
+            },
+            None if end_time.unwrap_or(begin_time) <= now => status = TodoStatus::Done,
+            None => {}
+        }
+        todo.status = status;
+    }
+    todo.recurrence = recurrence;
+    todo.completed_at = completed.filter(|_| todo.is_done());
+    Some(todo)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn utc(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+    }
+
+    fn calendar(components: &str) -> String {
+        format!("BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:-//test//EN\r\n{}END:VCALENDAR\r\n", components)
+    }
+
+    /// 导出为 iCalendar 后重新导入
+    fn round_trip(document: &ExportDocument) -> (String, ExportDocument) {
+        let text = render_calendar(document, &ReminderConfig::default());
+        let document = parse_calendar(&text).unwrap();
+        (text, document)
+    }
+
+    fn find<'a>(document: &'a ExportDocument, title: &str) -> &'a TodoListForm {
+        document.todos.iter().find(|t| t.title == title).unwrap()
+    }
+
+    #[test]
+    fn vtodo_round_trip() {
+        let imported = parse_calendar(&calendar(
+            "BEGIN:VTODO\r\n\
+             UID:report@test\r\n\
+             DTSTART:20300105T090000Z\r\n\
+             DUE:20300106T170000Z\r\n\
+             SUMMARY:写周报\\, 发给组长\r\n\
+             DESCRIPTION:第一行\\n第二行\r\n\
+             STATUS:IN-PROCESS\r\n\
+             PRIORITY:1\r\n\
+             CATEGORIES:work,urgent\r\n\
+             RRULE:FREQ=WEEKLY\r\n\
+             X-TODOLIST-KEY1:周五前\r\n\
+             END:VTODO\r\n\
+             BEGIN:VTODO\r\n\
+             UID:data@test\r\n\
+             DTSTART:20300105T100000Z\r\n\
+             SUMMARY:整理数据\r\n\
+             STATUS:NEEDS-ACTION\r\n\
+             X-TODOLIST-STATUS:blocked\r\n\
+             PRIORITY:9\r\n\
+             RELATED-TO;RELTYPE=PARENT:report@test\r\n\
+             RELATED-TO;RELTYPE=DEPENDS-ON:check@test\r\n\
+             END:VTODO\r\n\
+             BEGIN:VTODO\r\n\
+             UID:check@test\r\n\
+             DTSTART:20300104T080000Z\r\n\
+             SUMMARY:核对口径\r\n\
+             STATUS:CANCELLED\r\n\
+             END:VTODO\r\n",
+        ))
+        .unwrap();
+        assert!(imported.read_warnings.is_empty(), "{:?}", imported.read_warnings);
+
+        let (text, exported) = round_trip(&imported);
+        // 未结束且有截止时间的待办附带提醒
+        assert!(text.contains("BEGIN:VALARM"));
+        assert!(exported.read_warnings.is_empty(), "{:?}", exported.read_warnings);
+        assert_eq!(exported.todos.len(), 3);
+
+        for document in [&imported, &exported] {
+            let report = find(document, "写周报, 发给组长");
+            assert_eq!(report.description.as_deref(), Some("第一行\n第二行"));
+            assert_eq!(report.status, TodoStatus::InProgress);
+            assert_eq!(report.priority, Some(Priority::High));
+            assert_eq!(report.tags, vec!["work", "urgent"]);
+            assert_eq!(report.begin_time, utc(2030, 1, 5, 9, 0));
+            assert_eq!(report.end_time, Some(utc(2030, 1, 6, 17, 0)));
+            assert_eq!(report.key_message1.as_deref(), Some("周五前"));
+            assert_eq!(report.recurrence.as_ref().map(ToString::to_string), Some("FREQ=WEEKLY".to_string()));
+
+            let data = find(document, "整理数据");
+            assert_eq!(data.status, TodoStatus::Blocked);
+            assert_eq!(data.priority, Some(Priority::Low));
+            assert_eq!(data.end_time, None);
+            assert_eq!(data.parent_id, Some(report.id));
+
+            let check = find(document, "核对口径");
+            assert_eq!(check.status, TodoStatus::Cancelled);
+            assert_eq!(document.dependencies.len(), 1);
+            assert_eq!(document.dependencies[0].todo_id, data.id);
+            assert_eq!(document.dependencies[0].depends_on_id, check.id);
+        }
+    }
+
+    #[test]
+    fn vevent_round_trip() {
+        let imported = parse_calendar(&calendar(
+            "BEGIN:VEVENT\r\n\
+             UID:meeting@test\r\n\
+             DTSTART:20300110T020000Z\r\n\
+             DTEND:20300110T030000Z\r\n\
+             SUMMARY:评审会\r\n\
+             LOCATION:三楼会议室\r\n\
+             BEGIN:VALARM\r\n\
+             TRIGGER:-PT15M\r\n\
+             ACTION:DISPLAY\r\n\
+             DESCRIPTION:忽略\r\n\
+             END:VALARM\r\n\
+             END:VEVENT\r\n\
+             BEGIN:VEVENT\r\n\
+             UID:past@test\r\n\
+             DTSTART:20200110T020000Z\r\n\
+             DTEND:20200110T030000Z\r\n\
+             SUMMARY:去年的会\r\n\
+             END:VEVENT\r\n",
+        ))
+        .unwrap();
+        let meeting = find(&imported, "评审会");
+        // VALARM 中的 DESCRIPTION 不覆盖日程本身
+        assert_eq!(meeting.description, None);
+        assert_eq!(meeting.status, TodoStatus::Todo);
+        assert_eq!(meeting.key_message1.as_deref(), Some("地点: 三楼会议室"));
+        // 已经结束的日程导入为已完成
+        assert_eq!(find(&imported, "去年的会").status, TodoStatus::Done);
+
+        // 日程导出为 VTODO，开始与截止时间保持不变
+        let (text, exported) = round_trip(&imported);
+        assert!(!text.contains("BEGIN:VEVENT"));
+        let meeting = find(&exported, "评审会");
+        assert_eq!(meeting.begin_time, utc(2030, 1, 10, 2, 0));
+        assert_eq!(meeting.end_time, Some(utc(2030, 1, 10, 3, 0)));
+        assert_eq!(meeting.key_message1.as_deref(), Some("地点: 三楼会议室"));
+        assert_eq!(find(&exported, "去年的会").status, TodoStatus::Done);
+    }
+
+    #[test]
+    fn date_and_date_time_values() {
+        let imported = parse_calendar(&calendar(
+            "BEGIN:VEVENT\r\n\
+             UID:holiday@test\r\n\
+             DTSTART;VALUE=DATE:20300201\r\n\
+             SUMMARY:全天\r\n\
+             END:VEVENT\r\n\
+             BEGIN:VTODO\r\n\
+             UID:date@test\r\n\
+             DUE;VALUE=DATE:20300203\r\n\
+             SUMMARY:只有截止日期\r\n\
+             END:VTODO\r\n",
+        ))
+        .unwrap();
+        let start = |d: u32| local_time::start_of_day(NaiveDate::from_ymd_opt(2030, 2, d).unwrap());
+
+        // 全天日程没有 DTEND 时持续一天
+        let holiday = find(&imported, "全天");
+        assert_eq!(Some(holiday.begin_time), start(1));
+        assert_eq!(holiday.end_time, start(2));
+        // 没有 DTSTART 时以 DUE 作为开始时间，DUE 不晚于开始时间时不保留
+        let due_only = find(&imported, "只有截止日期");
+        assert_eq!(Some(due_only.begin_time), start(3));
+        assert_eq!(due_only.end_time, None);
+
+        // 导出统一写为 UTC 的日期时间，导入后时刻不变
+        let (text, exported) = round_trip(&imported);
+        assert!(!text.contains("VALUE=DATE"));
+        let holiday = find(&exported, "全天");
+        assert_eq!(Some(holiday.begin_time), start(1));
+        assert_eq!(holiday.end_time, start(2));
+    }
+
+    #[test]
+    fn tzid_values() {
+        let imported = parse_calendar(&calendar(
+            "BEGIN:VTODO\r\n\
+             UID:shanghai@test\r\n\
+             DTSTART;TZID=Asia/Shanghai:20300301T090000\r\n\
+             DUE;TZID=\"/America/New_York\":20300308T030000\r\n\
+             SUMMARY:跨时区\r\n\
+             END:VTODO\r\n\
+             BEGIN:VTODO\r\n\
+             UID:unknown@test\r\n\
+             DTSTART;TZID=Mars/Olympus:20300301T090000\r\n\
+             SUMMARY:未知时区\r\n\
+             END:VTODO\r\n",
+        ))
+        .unwrap();
+        let todo = find(&imported, "跨时区");
+        assert_eq!(todo.begin_time, utc(2030, 3, 1, 1, 0));
+        // 纽约 2030-03-10 才切换夏令时，此时仍为 EST (UTC-5)
+        assert_eq!(todo.end_time, Some(utc(2030, 3, 8, 8, 0)));
+        // 无法识别的时区按本地时区处理并给出警告
+        let naive = NaiveDate::from_ymd_opt(2030, 3, 1).unwrap().and_hms_opt(9, 0, 0).unwrap();
+        assert_eq!(Some(find(&imported, "未知时区").begin_time), local_time::from_local(&naive));
+        assert_eq!(imported.read_warnings.len(), 1);
+        assert!(imported.read_warnings[0].contains("Mars/Olympus"));
+
+        let (_, exported) = round_trip(&imported);
+        let todo = find(&exported, "跨时区");
+        assert_eq!(todo.begin_time, utc(2030, 3, 1, 1, 0));
+        assert_eq!(todo.end_time, Some(utc(2030, 3, 8, 8, 0)));
+    }
+
+    #[test]
+    fn duration_values() {
+        assert_eq!(parse_duration("PT1H30M"), Some(5400));
+        assert_eq!(parse_duration("-P1D"), Some(-86400));
+        assert_eq!(parse_duration("+P2W"), Some(14 * 86400));
+        assert_eq!(parse_duration("P1DT2H3M4S"), Some(86400 + 7384));
+        assert_eq!(parse_duration("PT"), Some(0));
+        for invalid in ["", "1H", "P1H", "PT1D", "P1", "PTX", "P9999999999999999999D", "P99999999999999W", "P1DT9223372036854775807S"] {
+            assert_eq!(parse_duration(invalid), None, "{invalid}");
+        }
+        for seconds in [0, 45, 5400, 86400, -900, 90061] {
+            assert_eq!(parse_duration(&format_duration(seconds)), Some(seconds));
+        }
+
+        let imported = parse_calendar(&calendar(
+            "BEGIN:VEVENT\r\n\
+             UID:call@test\r\n\
+             DTSTART:20300401T060000Z\r\n\
+             DURATION:PT45M\r\n\
+             SUMMARY:电话会\r\n\
+             END:VEVENT\r\n",
+        ))
+        .unwrap();
+        let call = find(&imported, "电话会");
+        assert_eq!(call.end_time, Some(utc(2030, 4, 1, 6, 45)));
+
+        // 导出时 DURATION 换算为 DUE
+        let (text, exported) = round_trip(&imported);
+        assert!(text.contains("DUE:20300401T064500Z"));
+        assert_eq!(find(&exported, "电话会").end_time, Some(utc(2030, 4, 1, 6, 45)));
+    }
+    #[test]
+    fn out_of_range_durations_become_warnings() {
+        let imported = parse_calendar(&calendar(
+            "BEGIN:VTODO\r\n\
+             UID:huge@test\r\n\
+             DTSTART:20300401T060000Z\r\n\
+             DURATION:P9999999999999D\r\n\
+             SUMMARY:很长的任务\r\n\
+             END:VTODO\r\n\
+             BEGIN:VEVENT\r\n\
+             UID:broken@test\r\n\
+             DTSTART:20300402T060000Z\r\n\
+             DURATION:soon\r\n\
+             SUMMARY:时长无效\r\n\
+             END:VEVENT\r\n",
+        ))
+        .unwrap();
+
+        // 仍然导入，只是没有截止时间
+        for title in ["很长的任务", "时长无效"] {
+            let todo = find(&imported, title);
+            assert_eq!(todo.end_time, None);
+        }
+        assert_eq!(imported.read_warnings.len(), 2, "{:?}", imported.read_warnings);
+        assert!(imported.read_warnings[0].contains("P9999999999999D"));
+        assert!(imported.read_warnings[1].contains("soon"));
+    }
+}
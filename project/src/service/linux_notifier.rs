@@ -0,0 +1,339 @@
+//! Linux 桌面通知
+//!
+//! 通过会话总线调用 freedesktop 通知规范（`org.freedesktop.Notifications`），
+//! GNOME、KDE、dunst、mako 等通知服务都实现了该接口。
+//! 总线地址取自环境变量 `DBUS_SESSION_BUS_ADDRESS`，因此也可以连接到本地的模拟总线进行测试
+//! （见 examples/mock_notification_daemon.rs）。
+
+use anyhow::{Context, Result as AnyResult};
+use std::collections::HashMap;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+use zbus::blocking::{Connection, MessageIterator};
+use zbus::zvariant::Value;
+use zbus::{MatchRule, message::Type as MessageType, proxy};
+
+use crate::data::reminder::{Notification, ReminderKind, Urgency};
+use crate::service::notifier::{ACTION_ACK, ACTION_DONE, ACTION_SNOOZE};
+
+const APP_NAME: &str = "TodoList";
+const INTERFACE: &str = "org.freedesktop.Notifications";
+
+#[proxy(
+    interface = "org.freedesktop.Notifications",
+    default_service = "org.freedesktop.Notifications",
+    default_path = "/org/freedesktop/Notifications"
+)]
+trait Notifications {
+    #[allow(clippy::too_many_arguments)]
+    fn notify(
+        &self,
+        app_name: &str,
+        replaces_id: u32,
+        app_icon: &str,
+        summary: &str,
+        body: &str,
+        actions: &[&str],
+        hints: HashMap<&str, Value<'_>>,
+        expire_timeout: i32,
+    ) -> zbus::Result<u32>;
+
+    fn get_capabilities(&self) -> zbus::Result<Vec<String>>;
+}
+
+/// 用户在通知上点击的操作
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvokedAction {
+    pub notification_id: u32,
+    pub action: String,
+}
+
+/// 信号中的通知事件：通知 ID 与点击的操作（通知被关闭时为 None）
+type NotificationEvent = (u32, Option<String>);
+
+/// 连接到会话总线的通知发送器
+///
+/// 持有一条连接，可以多次发送；支持操作按钮时，由一个后台线程持续读取信号，
+/// 连接关闭（通知发送器被丢弃）后该线程随之结束
+pub struct LinuxNotifier {
+    connection: Connection,
+    proxy: NotificationsProxyBlocking<'static>,
+    // 在发送通知前就订阅信号，避免错过用户很快的点击
+    events: Option<mpsc::Receiver<NotificationEvent>>,
+}
+
+impl LinuxNotifier {
+    /// 连接会话总线并查询通知服务的能力
+    pub fn connect() -> AnyResult<Self> {
+        Self::with_connection(Connection::session().context("无法连接 D-Bus 会话总线")?)
+    }
+
+    /// 使用已建立的总线连接
+    pub fn with_connection(connection: Connection) -> AnyResult<Self> {
+        let proxy = NotificationsProxyBlocking::new(&connection)
+            .context("无法创建 org.freedesktop.Notifications 代理")?;
+        let capabilities = proxy.get_capabilities().unwrap_or_default();
+        let events = if capabilities.iter().any(|c| c == "actions") {
+            let rule = MatchRule::builder()
+                .msg_type(MessageType::Signal)
+                .interface(INTERFACE)?
+                .build();
+            let messages = MessageIterator::for_match_rule(rule, &connection, None)?;
+            // 信号迭代器会阻塞，放到后台线程读取，等待时按超时接收
+            let (sender, receiver) = mpsc::channel();
+            thread::spawn(move || listen(messages, sender));
+            Some(receiver)
+        } else {
+            None
+        };
+        Ok(Self { connection, proxy, events })
+    }
+
+    /// 通知服务是否支持操作按钮
+    pub fn supports_actions(&self) -> bool {
+        self.events.is_some()
+    }
+
+    /// 发送一条提醒，with_actions 为 true 且服务支持时附带 "完成" / "稍后提醒" / "知道了" 按钮
+    /// 返回通知 ID
+    pub fn send(&self, notification: &Notification, with_actions: bool) -> AnyResult<u32> {
+        let urgency: u8 = match notification.urgency() {
+            Urgency::Normal => 1,
+            Urgency::Critical => 2,
+        };
+        let icon = match notification.kind {
+            ReminderKind::BeforeStart | ReminderKind::BeforeEnd => "appointment-soon",
+            ReminderKind::AfterStart | ReminderKind::AfterEnd => "dialog-warning",
+        };
+
+        let mut hints: HashMap<&str, Value<'_>> = HashMap::new();
+        hints.insert("urgency", Value::U8(urgency));
+        hints.insert("category", Value::from("x-todolist.reminder"));
+        hints.insert("desktop-entry", Value::from("todolist"));
+
+        let actions: &[&str] = if with_actions && self.supports_actions() {
+            &[ACTION_DONE, "完成", ACTION_SNOOZE, "稍后提醒", ACTION_ACK, "知道了"]
+        } else {
+            &[]
+        };
+
+        let summary = format!("TodoList 提醒 · {}", notification.todo_title);
+        let id = self
+            .proxy
+            .notify(APP_NAME, 0, icon, &summary, &notification.message, actions, hints, -1)
+            .context("发送桌面通知失败")?;
+        Ok(id)
+    }
+
+    /// 等待用户点击通知上的按钮，直到 ids 中的通知都已处理（点击或关闭）或超时
+    pub fn wait_for_actions(&self, ids: &[u32], timeout: Duration) -> Vec<InvokedAction> {
+        let mut pending: Vec<u32> = ids.to_vec();
+        let mut invoked = Vec::new();
+        let Some(events) = &self.events else {
+            return invoked;
+        };
+        if pending.is_empty() || timeout.is_zero() {
+            return invoked;
+        }
+
+        let deadline = Instant::now() + timeout;
+        while !pending.is_empty() {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let Ok((id, action)) = events.recv_timeout(remaining) else {
+                break;
+            };
+            // 之前批次的通知的事件直接忽略
+            if !pending.contains(&id) {
+                continue;
+            }
+            pending.retain(|p| *p != id);
+            if let Some(action) = action.filter(|a| a != "default") {
+                invoked.push(InvokedAction { notification_id: id, action });
+            }
+        }
+        invoked
+    }
+}
+
+impl Drop for LinuxNotifier {
+    fn drop(&mut self) {
+        // 关闭连接，后台线程中的信号迭代器随之结束
+        let _ = self.connection.clone().close();
+    }
+}
+
+/// 读取通知服务的信号，转发点击与关闭事件，直到连接关闭或接收端被丢弃
+fn listen(messages: MessageIterator, sender: mpsc::Sender<NotificationEvent>) {
+    for message in messages.flatten() {
+        let header = message.header();
+        let member = header.member().map(|m| m.to_string()).unwrap_or_default();
+        let event = match member.as_str() {
+            "ActionInvoked" => message
+                .body()
+                .deserialize::<(u32, String)>()
+                .ok()
+                .map(|(id, action)| (id, Some(action))),
+            "NotificationClosed" => message
+                .body()
+                .deserialize::<(u32, u32)>()
+                .ok()
+                .map(|(id, _reason)| (id, None)),
+            _ => None,
+        };
+        if let Some(event) = event
+            && sender.send(event).is_err()
+        {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+    use std::path::PathBuf;
+    use std::process::{Child, Command, Stdio};
+
+    /// 独立的会话总线与运行在其上的模拟通知服务（examples/mock_notification_daemon.rs）
+    struct MockBus {
+        bus: Child,
+        daemon: Child,
+        address: String,
+        output: mpsc::Receiver<String>,
+    }
+
+    impl MockBus {
+        /// 启动总线与模拟服务，action 为模拟用户点击的按钮；没有 dbus-daemon 或示例程序时返回 None
+        fn start(action: &str) -> Option<Self> {
+            // cargo test 会一并构建示例，示例程序在 target/<profile>/examples 下
+            let example: PathBuf = std::env::current_exe()
+                .ok()?
+                .parent()?
+                .parent()?
+                .join("examples/mock_notification_daemon");
+            if !example.exists() {
+                eprintln!("跳过: 未找到 {}", example.display());
+                return None;
+            }
+            let mut bus = match Command::new("dbus-daemon")
+                .args(["--session", "--nofork", "--print-address=1"])
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .spawn()
+            {
+                Ok(bus) => bus,
+                Err(_) => {
+                    eprintln!("跳过: 没有可用的 dbus-daemon");
+                    return None;
+                }
+            };
+            let mut address = String::new();
+            BufReader::new(bus.stdout.take()?).read_line(&mut address).ok()?;
+            let address = address.trim().to_string();
+
+            let mut daemon = Command::new(example)
+                .env("DBUS_SESSION_BUS_ADDRESS", &address)
+                .env("MOCK_NOTIFY_ACTION", action)
+                .stdout(Stdio::piped())
+                .spawn()
+                .ok()?;
+            let (sender, output) = mpsc::channel();
+            let stdout = BufReader::new(daemon.stdout.take()?);
+            thread::spawn(move || {
+                for line in stdout.lines().map_while(Result::ok) {
+                    if sender.send(line).is_err() {
+                        break;
+                    }
+                }
+            });
+            let mock = Self { bus, daemon, address, output };
+            mock.wait_for_line(|line| line.contains("ready"))?;
+            Some(mock)
+        }
+
+        fn notifier(&self) -> LinuxNotifier {
+            let connection = zbus::blocking::connection::Builder::address(self.address.as_str())
+                .and_then(|builder| builder.build())
+                .expect("无法连接模拟总线");
+            LinuxNotifier::with_connection(connection).expect("无法创建通知发送器")
+        }
+
+        /// 等待模拟服务输出满足条件的一行
+        fn wait_for_line(&self, matches: impl Fn(&str) -> bool) -> Option<String> {
+            let deadline = Instant::now() + Duration::from_secs(10);
+            loop {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                let line = self.output.recv_timeout(remaining).ok()?;
+                if matches(&line) {
+                    return Some(line);
+                }
+            }
+        }
+    }
+
+    impl Drop for MockBus {
+        fn drop(&mut self) {
+            let _ = self.daemon.kill();
+            let _ = self.daemon.wait();
+            let _ = self.bus.kill();
+            let _ = self.bus.wait();
+        }
+    }
+
+    fn notification(kind: ReminderKind) -> Notification {
+        Notification {
+            history_id: 1,
+            todo_id: 7,
+            todo_title: "写周报".to_string(),
+            priority: None,
+            kind,
+            rule_key: "after_end:0".to_string(),
+            trigger_at: 0,
+            message: "任务已超过截止时间".to_string(),
+        }
+    }
+
+    #[test]
+    fn sends_hints_and_receives_invoked_action() {
+        let Some(mock) = MockBus::start(ACTION_DONE) else {
+            return;
+        };
+        let notifier = mock.notifier();
+        assert!(notifier.supports_actions());
+
+        let id = notifier.send(&notification(ReminderKind::AfterEnd), true).unwrap();
+        let line = mock.wait_for_line(|line| line.starts_with(&format!("[{}]", id))).unwrap();
+        assert!(line.contains("app=TodoList"), "{}", line);
+        assert!(line.contains("icon=dialog-warning"), "{}", line);
+        assert!(line.contains("urgency=Some(2)"), "{}", line);
+        assert!(line.contains(r#"category=Some("x-todolist.reminder")"#), "{}", line);
+        assert!(line.contains(r#"desktop-entry=Some("todolist")"#), "{}", line);
+        assert!(line.contains(r#""done", "完成", "snooze", "稍后提醒", "ack", "知道了""#), "{}", line);
+
+        let invoked = notifier.wait_for_actions(&[id], Duration::from_secs(10));
+        assert_eq!(invoked, vec![InvokedAction { notification_id: id, action: ACTION_DONE.to_string() }]);
+    }
+
+    #[test]
+    fn reuses_connection_and_ignores_stale_events() {
+        let Some(mock) = MockBus::start(ACTION_SNOOZE) else {
+            return;
+        };
+        let notifier = mock.notifier();
+
+        // 第一条不等待，它的点击事件留在队列中，之后等待第二条时应被忽略
+        let first = notifier.send(&notification(ReminderKind::BeforeStart), true).unwrap();
+        let second = notifier.send(&notification(ReminderKind::BeforeStart), true).unwrap();
+        let line = mock.wait_for_line(|line| line.starts_with(&format!("[{}]", first))).unwrap();
+        assert!(line.contains("urgency=Some(1)"), "{}", line);
+
+        let invoked = notifier.wait_for_actions(&[second], Duration::from_secs(10));
+        assert_eq!(invoked, vec![InvokedAction { notification_id: second, action: ACTION_SNOOZE.to_string() }]);
+
+        // 不带按钮时不发送操作，也不等待
+        notifier.send(&notification(ReminderKind::BeforeStart), false).unwrap();
+        assert!(mock.wait_for_line(|line| line.contains("actions=[]")).is_some());
+    }
+}
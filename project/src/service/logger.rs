@@ -1,6 +1,6 @@
 use std::fs::OpenOptions;
 use std::io::Write;
-use chrono::Local;
+use crate::data::local_time;
 
 pub struct Logger;
 
@@ -8,7 +8,7 @@ impl Logger {
     /// 记录提醒日志
     pub fn log_reminder(message: &str) {
         let log_file = "database/reminder.log";
-        let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S");
+        let timestamp = local_time::now().format("%Y-%m-%d %H:%M:%S");
         let log_message = format!("[{}] {}\n", timestamp, message);
         
         if let Ok(mut file) = OpenOptions::new()
@@ -23,7 +23,7 @@ impl Logger {
     /// 记录一般日志
     pub fn log(level: &str, message: &str) {
         let log_file = "database/app.log";
-        let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S");
+        let timestamp = local_time::now().format("%Y-%m-%d %H:%M:%S");
         let log_message = format!("[{}] [{}] {}\n", timestamp, level, message);
         
         if let Ok(mut file) = OpenOptions::new()
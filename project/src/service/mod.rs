@@ -7,5 +7,24 @@ pub mod review_serv;
 pub mod help;
 pub mod reminder_serv;
 pub mod notifier;
+pub mod sinks;
+#[cfg(target_os = "linux")]
+pub mod linux_notifier;
 pub mod logger;
+pub mod output;
+pub mod query_serv;
+pub mod relation_serv;
+pub mod transfer_serv;
+pub mod ical;
+pub mod todo_txt;
+pub mod taskwarrior;
+pub mod backup_serv;
+pub mod change_log_serv;
+pub mod trash_serv;
+pub mod doctor_serv;
+pub mod archive_serv;
+pub mod time_tracking_serv;
+pub mod analytics_serv;
+pub mod chart;
 
+pub mod timezone_serv;
@@ -1,56 +1,227 @@
-use anyhow::Result as AnyResult;
+//! 提醒通知的分发
+//!
+//! 每种通知方式实现 `NotificationSink`，由 `SinkRegistry` 按配置中的类型名创建。
+//! `Notifier` 持有本次启用的全部渠道，按各自的过滤条件分发提醒；
+//! 某个渠道失败只记录警告，不影响其他渠道；逐条发送的渠道中某一条失败也不影响同批的其他提醒。
 
-pub struct Notifier;
+use anyhow::{Result as AnyResult, anyhow};
+use std::fmt;
+
+use crate::data::reminder::Notification;
+use crate::init::db_json_content::{ReminderConfig, SinkConfig, SinkFilter};
+use crate::service::logger::Logger;
+use crate::service::sinks;
+
+/// 通知上的操作按钮：完成任务
+pub const ACTION_DONE: &str = "done";
+/// 通知上的操作按钮：稍后再提醒（推迟 snooze_minutes 分钟）
+pub const ACTION_SNOOZE: &str = "snooze";
+/// 通知上的操作按钮：知道了（确认提醒）
+pub const ACTION_ACK: &str = "ack";
+
+/// 用户在桌面通知上点击的操作（如完成、稍后提醒）
+#[derive(Debug, Clone)]
+pub struct NotificationAction {
+    pub notification: Notification,
+    pub action: String,
+}
+
+/// 通知渠道
+pub trait NotificationSink {
+    /// 发送一批提醒，返回用户在通知上点击的操作（不支持交互的渠道返回空）
+    fn send(&mut self, notifications: &[&Notification]) -> AnyResult<Vec<NotificationAction>>;
+}
+
+/// 一批提醒中有部分发送失败，其余的已经送达
+#[derive(Debug)]
+pub struct PartialSendError {
+    /// 本批提醒的数量
+    pub total: usize,
+    /// 失败的提醒在本批中的下标与原因
+    pub failures: Vec<(usize, String)>,
+}
+
+impl fmt::Display for PartialSendError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reasons: Vec<&str> = self.failures.iter().map(|(_, reason)| reason.as_str()).collect();
+        write!(f, "{}/{} 条提醒发送失败: {}", self.failures.len(), self.total, reasons.join("; "))
+    }
+}
+
+impl std::error::Error for PartialSendError {}
+
+/// 逐条发送提醒：某一条失败时继续发送其余的，最后一并返回失败的提醒（PartialSendError）
+pub fn send_each(
+    notifications: &[&Notification],
+    mut send: impl FnMut(&Notification) -> AnyResult<()>,
+) -> AnyResult<()> {
+    let failures: Vec<(usize, String)> = notifications
+        .iter()
+        .enumerate()
+        .filter_map(|(i, notification)| send(notification).err().map(|e| (i, format!("{:#}", e))))
+        .collect();
+    if failures.is_empty() {
+        return Ok(());
+    }
+    Err(PartialSendError { total: notifications.len(), failures }.into())
+}
+
+/// 根据渠道配置创建渠道
+pub type SinkFactory = fn(&SinkConfig, &ReminderConfig) -> AnyResult<Box<dyn NotificationSink>>;
+
+/// 渠道类型名到创建函数的映射
+pub struct SinkRegistry {
+    factories: Vec<(&'static str, SinkFactory)>,
+}
+
+impl SinkRegistry {
+    /// 内置的全部渠道类型
+    pub fn builtin() -> Self {
+        let mut registry = Self { factories: Vec::new() };
+        registry.register("console", sinks::console::create);
+        registry.register("windows", sinks::desktop::create_windows);
+        registry.register("linux", sinks::desktop::create_linux);
+        registry.register("log", sinks::log_file::create);
+        registry.register("webhook", sinks::webhook::create);
+        registry.register("email", sinks::email::create);
+        registry.register("command", sinks::command::create);
+        registry
+    }
+
+    /// 注册（或替换）一种渠道类型
+    pub fn register(&mut self, sink_type: &'static str, factory: SinkFactory) {
+        self.factories.retain(|(name, _)| *name != sink_type);
+        self.factories.push((sink_type, factory));
+    }
+
+    /// 按配置创建渠道
+    pub fn create(
+        &self,
+        config: &SinkConfig,
+        reminder: &ReminderConfig,
+    ) -> AnyResult<Box<dyn NotificationSink>> {
+        config.validate().map_err(|e| anyhow!(e))?;
+        let (_, factory) = self
+            .factories
+            .iter()
+            .find(|(name, _)| *name == config.sink_type)
+            .ok_or_else(|| anyhow!("未注册的通知渠道类型 '{}'", config.sink_type))?;
+        factory(config, reminder)
+    }
+}
+
+/// 已启用的渠道
+struct ActiveSink {
+    name: String,
+    filter: SinkFilter,
+    sink: Box<dyn NotificationSink>,
+}
+
+/// 一次分发的结果
+#[derive(Debug, Default)]
+pub struct SendReport {
+    /// 用户在通知上点击的操作
+    pub actions: Vec<NotificationAction>,
+    /// 发送成功的渠道名称
+    pub succeeded: Vec<String>,
+    /// 发送失败的渠道名称与原因
+    pub failed: Vec<(String, String)>,
+}
+
+pub struct Notifier {
+    sinks: Vec<ActiveSink>,
+}
 
 impl Notifier {
-    /// 发送控制台通知
-    pub fn send_console(message: &str) {
-        println!("\n{}", "=".repeat(60));
-        println!("⏰ 提醒通知");
-        println!("{}", "=".repeat(60));
-        println!("{}", message);
-        println!("{}", "=".repeat(60));
-        println!();
-    }
-    
-    /// 发送 Windows 系统通知
-    #[cfg(windows)]
-    pub fn send_windows(title: &str, message: &str) -> AnyResult<()> {
-        use winrt_notification::{Toast, Duration as ToastDuration};
-        
-        Toast::new(Toast::POWERSHELL_APP_ID)
-            .title(title)
-            .text1(message)
-            .duration(ToastDuration::Short)
-            .show()?;
-        
-        Ok(())
-    }
-    
-    #[cfg(not(windows))]
-    pub fn send_windows(_title: &str, _message: &str) -> AnyResult<()> {
-        // 非 Windows 系统不支持
-        Ok(())
-    }
-    
-    /// 根据配置发送通知
-    pub fn send(message: &str, notification_type: &str) -> AnyResult<()> {
-        match notification_type {
-            "console" => {
-                Self::send_console(message);
-            }
-            "windows" => {
-                Self::send_windows("TodoList 提醒", message)?;
-            }
-            "both" => {
-                Self::send_console(message);
-                Self::send_windows("TodoList 提醒", message)?;
-            }
-            _ => {
-                Self::send_console(message);
+    /// 按提醒配置创建全部启用的渠道，配置无效的渠道给出警告后跳过
+    pub fn from_config(reminder: &ReminderConfig) -> Self {
+        Self::with_registry(&SinkRegistry::builtin(), reminder)
+    }
+
+    pub fn with_registry(registry: &SinkRegistry, reminder: &ReminderConfig) -> Self {
+        let mut sinks = Vec::new();
+        for config in reminder.effective_sinks() {
+            if !config.enabled {
+                continue;
+            }
+            let name = config.display_name();
+            match registry.create(&config, reminder) {
+                Ok(sink) => sinks.push(ActiveSink { name, filter: config.filter.clone(), sink }),
+                Err(e) => {
+                    eprintln!("⚠️  通知渠道「{}」无法启用: {:#}", name, e);
+                    Logger::log("WARN", &format!("通知渠道「{}」无法启用: {:#}", name, e));
+                }
             }
         }
-        Ok(())
+        Self { sinks }
     }
-}
 
+    /// 将提醒分发到各渠道，每个渠道只收到符合其过滤条件的提醒
+    ///
+    /// 某条提醒匹配的渠道全部失败时，改为在控制台输出，避免提醒丢失
+    pub fn send(&mut self, notifications: &[Notification]) -> SendReport {
+        let mut report = SendReport::default();
+        let mut delivered = vec![false; notifications.len()];
+        let mut attempted = vec![false; notifications.len()];
+
+        for active in &mut self.sinks {
+            let selected: Vec<usize> = (0..notifications.len())
+                .filter(|&i| active.filter.matches(&notifications[i]))
+                .collect();
+            if selected.is_empty() {
+                continue;
+            }
+            let batch: Vec<&Notification> = selected.iter().map(|&i| &notifications[i]).collect();
+            for &i in &selected {
+                attempted[i] = true;
+            }
+            match active.sink.send(&batch) {
+                Ok(actions) => {
+                    for &i in &selected {
+                        delivered[i] = true;
+                    }
+                    report.actions.extend(actions);
+                    report.succeeded.push(active.name.clone());
+                }
+                Err(e) => {
+                    // 部分失败时，其余提醒已经送达
+                    if let Some(partial) = e.downcast_ref::<PartialSendError>() {
+                        for (position, &i) in selected.iter().enumerate() {
+                            if !partial.failures.iter().any(|(failed, _)| *failed == position) {
+                                delivered[i] = true;
+                            }
+                        }
+                    }
+                    eprintln!("⚠️  通知渠道「{}」发送失败: {:#}", active.name, e);
+                    Logger::log("WARN", &format!("通知渠道「{}」发送失败: {:#}", active.name, e));
+                    report.failed.push((active.name.clone(), format!("{:#}", e)));
+                }
+            }
+        }
+
+        for (i, notification) in notifications.iter().enumerate() {
+            if attempted[i] && !delivered[i] {
+                sinks::console::print(&notification.message);
+            }
+        }
+        report
+    }
+
+    /// 不经过滤条件，向指定名称（或全部）渠道发送，用于测试渠道配置
+    pub fn send_test(&mut self, name: Option<&str>, notification: &Notification) -> SendReport {
+        let mut report = SendReport::default();
+        for active in &mut self.sinks {
+            if name.is_some_and(|n| n != active.name) {
+                continue;
+            }
+            match active.sink.send(&[notification]) {
+                Ok(actions) => {
+                    report.actions.extend(actions);
+                    report.succeeded.push(active.name.clone());
+                }
+                Err(e) => report.failed.push((active.name.clone(), format!("{:#}", e))),
+            }
+        }
+        report
+    }
+}
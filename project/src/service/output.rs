@@ -0,0 +1,88 @@
+use anyhow::{Result as AnyResult, anyhow};
+use serde::Serialize;
+use serde_json::Value;
+use std::io::{self, Write};
+
+/// 列表类命令的输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// 默认的人类可读文本
+    Text,
+    /// 单个 JSON 数组
+    Json,
+    /// 每行一个 JSON 对象（newline-delimited JSON）
+    Ndjson,
+    /// 带表头的 CSV
+    Csv,
+}
+
+impl OutputFormat {
+    pub fn from_string(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "text" => Some(OutputFormat::Text),
+            "json" => Some(OutputFormat::Json),
+            "ndjson" | "jsonl" => Some(OutputFormat::Ndjson),
+            "csv" => Some(OutputFormat::Csv),
+            _ => None,
+        }
+    }
+}
+
+/// 以机器可读格式输出记录列表到标准输出
+///
+/// 仅处理 Json / Ndjson / Csv，文本格式由各业务模块自行打印。
+/// CSV 中字符串数组（如标签）以 `;` 连接，其他嵌套结构以 JSON 文本写入单元格。
+pub fn print_records<T: Serialize>(records: &[T], format: OutputFormat) -> AnyResult<()> {
+    write_records(io::stdout().lock(), records, format)
+}
+
+/// 以机器可读格式将记录列表写入 out（如导出文件）
+pub fn write_records<T: Serialize, W: Write>(mut out: W, records: &[T], format: OutputFormat) -> AnyResult<()> {
+    match format {
+        OutputFormat::Json => {
+            serde_json::to_writer_pretty(&mut out, records)?;
+            writeln!(out)?;
+        }
+        OutputFormat::Ndjson => {
+            for record in records {
+                serde_json::to_writer(&mut out, record)?;
+                writeln!(out)?;
+            }
+        }
+        OutputFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(out);
+            let mut header_written = false;
+            for record in records {
+                let value = serde_json::to_value(record)?;
+                let obj = value
+                    .as_object()
+                    .ok_or_else(|| anyhow!("CSV 输出要求记录为对象"))?;
+                if !header_written {
+                    writer.write_record(obj.keys())?;
+                    header_written = true;
+                }
+                writer.write_record(obj.values().map(csv_cell))?;
+            }
+            writer.flush()?;
+        }
+        OutputFormat::Text => {
+            return Err(anyhow!("文本格式应由调用方直接打印"));
+        }
+    }
+
+    Ok(())
+}
+
+/// 将 JSON 值转换为 CSV 单元格文本
+fn csv_cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::Array(items) if items.iter().all(|v| !v.is_array() && !v.is_object()) => {
+            items.iter().map(csv_cell).collect::<Vec<_>>().join(";")
+        }
+        other => other.to_string(),
+    }
+}
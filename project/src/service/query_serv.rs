@@ -0,0 +1,355 @@
+//! 查询语言解析
+//!
+//! 语法示例：`status:pending tag:work due<2026-11-01 "release notes" -priority:low`
+//!
+//! - 以空白分隔的多个条件之间为 AND 关系，条件前加 `-` 表示取反
+//! - `status:` todo / in_progress / blocked / done / cancelled，以及 pending（=未结束）、closed（=已结束）
+//! - `tag:` 标签名（不区分大小写）
+//! - `priority:` high / medium / low / none（兼容 高/中/低）
+//! - `due` / `begin` 支持 `<` `<=` `>` `>=`，`:` 或 `=` 表示同一天
+//! - `has:` due / notes / tags / repeat；`is:overdue` 表示已超过截止时间且未结束
+//! - 其他词语或双引号包围的短语作为关键字，通过全文索引匹配标题、描述、关键信息与笔记，
+//!   含关键字时结果按相关度排序
+
+use anyhow::Result as AnyResult;
+use std::collections::HashMap;
+
+use crate::dao::{search_dao, todo_list_dao};
+use crate::data::local_time;
+use crate::data::query::{CompareOp, HasField, QueryFilter, QueryTerm, TimeField, TodoQuery};
+use crate::data::search::SearchHit;
+use crate::data::todo_list::{Priority, TodoListForm, TodoStatus};
+use crate::init::database::Database;
+use crate::service::todo_list_serv;
+
+/// 将查询字符串切分为词（双引号内的空白不切分，引号本身保留）
+fn tokenize(input: &str) -> Result<Vec<String>, String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+
+    for c in input.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                current.push(c);
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            _ => current.push(c),
+        }
+    }
+
+    if in_quotes {
+        return Err("引号未闭合".to_string());
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    Ok(tokens)
+}
+
+/// 去掉值两端的双引号
+fn unquote(value: &str) -> String {
+    value.replace('"', "")
+}
+
+/// 拆分 `字段 运算符 值`，字段必须是已知字段名
+fn split_field(token: &str) -> Option<(&str, &str, &str)> {
+    const FIELDS: &[&str] = &["status", "tag", "priority", "due", "begin", "has", "is"];
+    // 两字符运算符必须先于单字符匹配
+    const OPS: &[&str] = &["<=", ">=", "<", ">", ":", "="];
+
+    let field_end = token.find(|c: char| !c.is_ascii_alphabetic())?;
+    let field = &token[..field_end];
+    if !FIELDS.contains(&field.to_lowercase().as_str()) {
+        return None;
+    }
+    let rest = &token[field_end..];
+    OPS.iter()
+        .find(|op| rest.starts_with(*op))
+        .map(|op| (field, *op, &rest[op.len()..]))
+}
+
+fn parse_status_values(value: &str) -> Result<Vec<TodoStatus>, String> {
+    match value.to_lowercase().as_str() {
+        "pending" | "open" | "未完成" => Ok(TodoStatus::ALL.iter().copied().filter(|s| s.is_open()).collect()),
+        "closed" | "已结束" => Ok(TodoStatus::ALL.iter().copied().filter(|s| !s.is_open()).collect()),
+        _ => TodoStatus::from_string(value)
+            .map(|s| vec![s])
+            .ok_or_else(|| format!("未知的状态: {}", value)),
+    }
+}
+
+fn parse_term(token: &str) -> Result<QueryTerm, String> {
+    let (negated, body) = match token.strip_prefix('-') {
+        Some(rest) if !rest.is_empty() => (true, rest),
+        _ => (false, token),
+    };
+
+    let filter = match split_field(body) {
+        Some((field, op, raw_value)) => {
+            let value = unquote(raw_value);
+            if value.trim().is_empty() {
+                return Err(format!("条件 '{}' 缺少取值", token));
+            }
+            let field = field.to_lowercase();
+            let value = value.trim();
+
+            match (field.as_str(), op) {
+                ("status", ":" | "=") => QueryFilter::Status(parse_status_values(value)?),
+                ("tag", ":" | "=") => QueryFilter::Tag(value.to_string()),
+                ("priority", ":" | "=") => match value.to_lowercase().as_str() {
+                    "none" | "无" => QueryFilter::Priority(None),
+                    _ => QueryFilter::Priority(Some(
+                        Priority::from_string(value).ok_or_else(|| format!("未知的优先级: {}", value))?,
+                    )),
+                },
+                ("due" | "begin", _) => {
+                    let time_field = if field == "due" { TimeField::Due } else { TimeField::Begin };
+                    let op = match op {
+                        "<" => CompareOp::Lt,
+                        "<=" => CompareOp::Le,
+                        ">" => CompareOp::Gt,
+                        ">=" => CompareOp::Ge,
+                        _ => CompareOp::On,
+                    };
+                    let mut time = todo_list_serv::parse_datetime(value)
+                        .map_err(|_| format!("无法识别的时间: {}", value))?;
+                    if op == CompareOp::On {
+                        // 同一天按本地日历计算
+                        let date = local_time::to_local(&time).date_naive();
+                        time = local_time::start_of_day(date).unwrap_or(time);
+                    }
+                    QueryFilter::Time { field: time_field, op, value: time }
+                }
+                ("has", ":" | "=") => match value.to_lowercase().as_str() {
+                    "due" => QueryFilter::Has(HasField::Due),
+                    "notes" | "note" => QueryFilter::Has(HasField::Notes),
+                    "tags" | "tag" => QueryFilter::Has(HasField::Tags),
+                    "repeat" | "recurrence" => QueryFilter::Has(HasField::Recurrence),
+                    _ => return Err(format!("未知的 has 条件: {}", value)),
+                },
+                ("is", ":" | "=") => match value.to_lowercase().as_str() {
+                    "overdue" | "逾期" => QueryFilter::Overdue,
+                    "open" | "pending" => QueryFilter::Status(parse_status_values("pending")?),
+                    "done" => QueryFilter::Status(vec![TodoStatus::Done]),
+                    _ => return Err(format!("未知的 is 条件: {}", value)),
+                },
+                _ => return Err(format!("字段 '{}' 不支持运算符 '{}'", field, op)),
+            }
+        }
+        None => QueryFilter::Text(unquote(body)),
+    };
+
+    Ok(QueryTerm { negated, filter })
+}
+
+/// 解析查询字符串
+pub fn parse_query(input: &str) -> Result<TodoQuery, String> {
+    let terms = tokenize(input)?
+        .iter()
+        .map(|t| parse_term(t))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(TodoQuery { terms })
+}
+
+/// 检索结果返回的全文索引命中数上限
+const MAX_HITS: usize = 500;
+
+/// 按待办事项 ID 分组的全文命中记录
+type HitsByTodo = HashMap<i32, Vec<SearchHit>>;
+
+/// 按查询条件检索待办事项，并返回每个待办事项的全文命中记录
+/// 含关键字时按最佳命中的相关度排序，否则按 ID 排序
+fn search_with_hits(
+    database: &Database,
+    query: &TodoQuery,
+) -> AnyResult<(Vec<TodoListForm>, HitsByTodo)> {
+    let conn = database.get_connection();
+    let mut todos = todo_list_dao::search_todos(conn, query)?;
+
+    let terms: Vec<String> = query.text_terms().iter().map(|t| t.to_string()).collect();
+    let mut hits_by_todo = HitsByTodo::new();
+    if terms.is_empty() {
+        return Ok((todos, hits_by_todo));
+    }
+
+    for hit in search_dao::search(conn, &terms, MAX_HITS)? {
+        hits_by_todo.entry(hit.todo_id).or_default().push(hit);
+    }
+    let best_rank = |id: i32| {
+        hits_by_todo
+            .get(&id)
+            .and_then(|hits| hits.iter().map(|h| h.rank).reduce(f64::min))
+            .unwrap_or(f64::MAX)
+    };
+    todos.sort_by(|a, b| best_rank(a.id).total_cmp(&best_rank(b.id)));
+
+    Ok((todos, hits_by_todo))
+}
+
+/// 按查询条件检索待办事项
+pub fn search_todos(database: &Database, query: &TodoQuery) -> AnyResult<Vec<TodoListForm>> {
+    let (todos, _) = search_with_hits(database, query)?;
+    Ok(todos)
+}
+
+/// 打印查询结果（关键字命中的标题、描述与笔记以【】高亮显示）
+pub fn show_search_results(database: &Database, query_text: &str) -> AnyResult<()> {
+    let query = parse_query(query_text).map_err(|e| anyhow::anyhow!("查询语法错误: {}", e))?;
+    let (todos, hits_by_todo) = search_with_hits(database, &query)?;
+
+    if todos.is_empty() {
+        println!("🔍 没有符合条件的待办事项");
+        return Ok(());
+    }
+
+    println!("\n🔍 查询结果 ({} 项):", todos.len());
+    println!("{:=<80}", "");
+    for (index, todo) in todos.iter().enumerate() {
+        let archived = if todo.is_archived() { " · 已归档" } else { "" };
+        println!("{}. {} [ID: {}] {} ({}{})", index + 1, todo.status.icon(), todo.id, todo.title, todo.status.label(), archived);
+        if let Some(priority) = &todo.priority {
+            println!("   优先级: {}", priority.label());
+        }
+        if !todo.tags.is_empty() {
+            println!("   标签: {}", todo.tags.join(", "));
+        }
+        println!("   开始时间: {}", local_time::format(&todo.begin_time, "%Y-%m-%d %H:%M:%S"));
+        if let Some(end_time) = &todo.end_time {
+            println!("   截止时间: {}", local_time::format(end_time, "%Y-%m-%d %H:%M:%S"));
+        }
+
+        for hit in hits_by_todo.get(&todo.id).map(|h| h.as_slice()).unwrap_or_default() {
+            if hit.kind == "note" {
+                println!("   📝 [笔记ID: {}] {}: {}", hit.ref_id, hit.title, hit.snippet);
+            } else if hit.snippet.is_empty() {
+                println!("   🔎 {}", hit.title);
+            } else {
+                println!("   🔎 {}: {}", hit.title, hit.snippet);
+            }
+        }
+        println!("{:-<80}", "");
+    }
+
+    Ok(())
+}
+
+/// 重建全文索引（索引由触发器自动维护，仅在索引异常时需要手动重建）
+pub fn rebuild_search_index(database: &Database) -> AnyResult<()> {
+    let conn = database.get_connection();
+    let count = search_dao::rebuild_index(conn)?;
+    println!("✅ 全文索引已重建，共索引 {} 条记录", count);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+
+    /// 解析只含一个条件的查询
+    fn term(input: &str) -> QueryTerm {
+        let mut query = parse_query(input).unwrap();
+        assert_eq!(query.terms.len(), 1, "{input}");
+        query.terms.remove(0)
+    }
+
+    /// 解析时间条件，返回 (字段, 运算符, 时间)
+    fn time_term(input: &str) -> (TimeField, CompareOp, chrono::DateTime<Utc>) {
+        match term(input).filter {
+            QueryFilter::Time { field, op, value } => (field, op, value),
+            other => panic!("{input}: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn field_operators() {
+        let open: Vec<TodoStatus> = TodoStatus::ALL.iter().copied().filter(|s| s.is_open()).collect();
+        assert!(matches!(term("status:pending").filter, QueryFilter::Status(s) if s == open));
+        assert!(matches!(term("STATUS=done").filter, QueryFilter::Status(s) if s == vec![TodoStatus::Done]));
+        assert!(matches!(term("tag:work").filter, QueryFilter::Tag(t) if t == "work"));
+        assert!(matches!(term(r#"tag:"my tag""#).filter, QueryFilter::Tag(t) if t == "my tag"));
+        assert!(matches!(term("priority:high").filter, QueryFilter::Priority(Some(Priority::High))));
+        assert!(matches!(term("priority:低").filter, QueryFilter::Priority(Some(Priority::Low))));
+        assert!(matches!(term("priority:none").filter, QueryFilter::Priority(None)));
+        assert!(matches!(term("has:notes").filter, QueryFilter::Has(HasField::Notes)));
+        assert!(matches!(term("has:repeat").filter, QueryFilter::Has(HasField::Recurrence)));
+        assert!(matches!(term("is:overdue").filter, QueryFilter::Overdue));
+
+        let expected = todo_list_serv::parse_datetime("2026-11-01").unwrap();
+        for (input, op) in [
+            ("due<2026-11-01", CompareOp::Lt),
+            ("due<=2026-11-01", CompareOp::Le),
+            ("due>2026-11-01", CompareOp::Gt),
+            ("due>=2026-11-01", CompareOp::Ge),
+        ] {
+            assert_eq!(time_term(input), (TimeField::Due, op, expected), "{input}");
+        }
+        assert_eq!(time_term(r#"begin="2026-11-01 15:30""#), (TimeField::Begin, CompareOp::On, expected));
+    }
+
+    #[test]
+    fn negation_and_text() {
+        let query = parse_query(r#"-tag:work "release notes" -draft status:todo"#).unwrap();
+        let terms: Vec<(bool, String)> = query.terms.iter().map(|t| (t.negated, format!("{:?}", t.filter))).collect();
+        assert_eq!(
+            terms,
+            vec![
+                (true, r#"Tag("work")"#.to_string()),
+                (false, r#"Text("release notes")"#.to_string()),
+                (true, r#"Text("draft")"#.to_string()),
+                (false, "Status([Todo])".to_string()),
+            ]
+        );
+        // 被取反的关键字不参与高亮
+        assert_eq!(query.text_terms(), vec!["release notes"]);
+
+        // 单独的 `-` 与未知字段按关键字处理
+        let t = term("-");
+        assert!(!t.negated && matches!(t.filter, QueryFilter::Text(s) if s == "-"));
+        assert!(matches!(term("title:foo").filter, QueryFilter::Text(s) if s == "title:foo"));
+        assert!(parse_query("   ").unwrap().terms.is_empty());
+    }
+
+    #[test]
+    fn relative_dates() {
+        let before = Utc::now();
+        let (field, op, value) = time_term("due<+3d");
+        let after = Utc::now();
+        assert_eq!((field, op), (TimeField::Due, CompareOp::Lt));
+        // 相对时间精确到分钟
+        assert!(value > before + Duration::days(3) - Duration::minutes(1) && value <= after + Duration::days(3));
+
+        // `:` 表示同一天，取本地当天 0 点
+        let today = local_time::now().date_naive();
+        let (field, op, value) = time_term("begin:today");
+        assert_eq!((field, op), (TimeField::Begin, CompareOp::On));
+        assert_eq!(Some(value), local_time::start_of_day(today));
+    }
+
+    #[test]
+    fn rejects_invalid_input() {
+        for input in [
+            r#"tag:"unterminated"#,
+            "status:someday",
+            "priority:extreme",
+            "has:attachments",
+            "is:late",
+            "tag<work",
+            "priority>=high",
+            "due<nonsense",
+            "due:",
+            r#"tag:"""#,
+            "due<+9999999999999999m",
+            r#"begin>"in 9999999999999999 weeks""#,
+            "due>=+99999999999999d",
+        ] {
+            assert!(parse_query(input).is_err(), "{input}");
+        }
+    }
+}
@@ -0,0 +1,341 @@
+//! 子任务层级与任务依赖
+//!
+//! 父子关系保存在 todo_list.parent_id，依赖关系保存在 todo_dependencies 表。
+//! 父任务要等子任务完成，依赖方要等被依赖的任务完成；两种关系合在一起不允许形成环，
+//! 例如父任务不能是自身的子孙，依赖链不能回到自身，子任务也不能依赖自己的祖先。
+
+use anyhow::{Result as AnyResult, anyhow, bail};
+use std::collections::{HashMap, HashSet};
+
+use crate::dao::{dependency_dao, todo_list_dao};
+use crate::data::todo_list::{TodoListForm, TodoStatus};
+use crate::init::database::Database;
+use crate::service::change_log_serv;
+
+/// 全部待办事项及其父子、依赖关系（用于树形展示、进度汇总与环检测）
+pub struct TodoTree {
+    todos: Vec<TodoListForm>,
+    index: HashMap<i32, usize>,
+    children: HashMap<i32, Vec<usize>>,
+    dependencies: HashMap<i32, Vec<i32>>,
+}
+
+impl TodoTree {
+    /// 从数据库加载（待办事项按 ID 升序）
+    pub fn load(database: &Database) -> AnyResult<Self> {
+        let conn = database.get_connection();
+        let mut todos = todo_list_dao::list_todos(conn)?;
+        todos.reverse();
+        let edges = dependency_dao::list_dependencies(conn)?;
+        Ok(Self::build(todos, edges))
+    }
+
+    fn build(todos: Vec<TodoListForm>, edges: Vec<(i32, i32)>) -> Self {
+        let index: HashMap<i32, usize> = todos.iter().enumerate().map(|(i, t)| (t.id, i)).collect();
+        let mut children: HashMap<i32, Vec<usize>> = HashMap::new();
+        for (i, todo) in todos.iter().enumerate() {
+            if let Some(parent_id) = todo.parent_id.filter(|p| index.contains_key(p)) {
+                children.entry(parent_id).or_default().push(i);
+            }
+        }
+        let mut dependencies: HashMap<i32, Vec<i32>> = HashMap::new();
+        for (todo_id, depends_on_id) in edges {
+            dependencies.entry(todo_id).or_default().push(depends_on_id);
+        }
+        TodoTree { todos, index, children, dependencies }
+    }
+
+    pub fn get(&self, id: i32) -> Option<&TodoListForm> {
+        self.index.get(&id).map(|i| &self.todos[*i])
+    }
+
+    /// 顶层任务（没有父任务，或父任务已不存在）
+    pub fn roots(&self) -> Vec<&TodoListForm> {
+        self.todos
+            .iter()
+            .filter(|t| t.parent_id.is_none_or(|p| !self.index.contains_key(&p)))
+            .collect()
+    }
+
+    /// 未归档的顶层任务（没有父任务，或父任务已归档 / 不存在）
+    pub fn active_roots(&self) -> Vec<&TodoListForm> {
+        self.todos
+            .iter()
+            .filter(|t| !t.is_archived())
+            .filter(|t| t.parent_id.is_none_or(|p| self.get(p).is_none_or(|parent| parent.is_archived())))
+            .collect()
+    }
+
+    /// 已归档的待办事项数量
+    pub fn archived_count(&self) -> usize {
+        self.todos.iter().filter(|t| t.is_archived()).count()
+    }
+
+    /// 直接子任务
+    pub fn children(&self, id: i32) -> Vec<&TodoListForm> {
+        self.children
+            .get(&id)
+            .map(|c| c.iter().map(|i| &self.todos[*i]).collect())
+            .unwrap_or_default()
+    }
+
+    /// 全部子孙任务（深度优先）
+    pub fn descendants(&self, id: i32) -> Vec<&TodoListForm> {
+        let mut result = Vec::new();
+        let mut visited = HashSet::from([id]);
+        let mut stack: Vec<&TodoListForm> = self.children(id).into_iter().rev().collect();
+        while let Some(todo) = stack.pop() {
+            if !visited.insert(todo.id) {
+                continue;
+            }
+            result.push(todo);
+            stack.extend(self.children(todo.id).into_iter().rev());
+        }
+        result
+    }
+
+    /// 子孙任务完成进度 (已完成, 总数)，已取消的不计入；没有子任务时返回 None
+    pub fn progress(&self, id: i32) -> Option<(usize, usize)> {
+        let counted: Vec<_> = self
+            .descendants(id)
+            .into_iter()
+            .filter(|t| t.status != TodoStatus::Cancelled)
+            .collect();
+        if counted.is_empty() {
+            return None;
+        }
+        let done = counted.iter().filter(|t| t.is_done()).count();
+        Some((done, counted.len()))
+    }
+
+    /// 直接依赖的待办事项
+    pub fn dependencies(&self, id: i32) -> Vec<&TodoListForm> {
+        self.dependencies
+            .get(&id)
+            .map(|deps| deps.iter().filter_map(|d| self.get(*d)).collect())
+            .unwrap_or_default()
+    }
+
+    /// from 是否直接或间接要等 to 完成：沿依赖与子任务两种关系查找
+    fn waits_for(&self, from: i32, to: i32) -> bool {
+        let mut visited = HashSet::new();
+        let mut stack = vec![from];
+        while let Some(id) = stack.pop() {
+            if id == to {
+                return true;
+            }
+            if visited.insert(id) {
+                stack.extend(self.dependencies.get(&id).into_iter().flatten().copied());
+                stack.extend(self.children(id).iter().map(|t| t.id));
+            }
+        }
+        false
+    }
+}
+
+/// 检查能否将 parent_id 设为 id 的父任务（父任务须存在，且不能形成环）
+pub fn check_parent(database: &Database, id: i32, parent_id: i32) -> AnyResult<()> {
+    let tree = TodoTree::load(database)?;
+    let parent = tree
+        .get(parent_id)
+        .ok_or_else(|| anyhow!("未找到ID为 {} 的父任务", parent_id))?;
+    // 设置后父任务要等 id 完成
+    if parent_id == id || tree.waits_for(id, parent_id) {
+        bail!("不能将 '{}' [ID: {}] 设为父任务：会形成循环", parent.title, parent_id);
+    }
+    Ok(())
+}
+
+/// 设置或取消父任务
+pub fn set_parent(database: &Database, id: i32, parent_id: Option<i32>) -> AnyResult<()> {
+    let conn = database.get_connection();
+    let todo = todo_list_dao::get_todo_by_id(conn, id)?
+        .ok_or_else(|| anyhow!("未找到ID为 {} 的待办事项", id))?;
+
+    match parent_id {
+        Some(parent_id) => {
+            check_parent(database, id, parent_id)?;
+            let action = format!("设置父任务 #{}「{}」→ #{}", id, todo.title, parent_id);
+            change_log_serv::record(database, &action, || todo_list_dao::set_parent(conn, id, Some(parent_id)))?;
+            println!("✅ 已将 '{}' 设为 [ID: {}] 的子任务", todo.title, parent_id);
+        }
+        None => {
+            let action = format!("取消父任务 #{}「{}」", id, todo.title);
+            change_log_serv::record(database, &action, || todo_list_dao::set_parent(conn, id, None))?;
+            println!("✅ '{}' 已成为顶层任务", todo.title);
+        }
+    }
+    Ok(())
+}
+
+/// 添加依赖：id 被 depends_on_id 阻塞（不能依赖自身或形成循环依赖）
+pub fn add_dependency(database: &Database, id: i32, depends_on_id: i32) -> AnyResult<()> {
+    let tree = TodoTree::load(database)?;
+    let todo = tree.get(id).ok_or_else(|| anyhow!("未找到ID为 {} 的待办事项", id))?;
+    let blocker = tree
+        .get(depends_on_id)
+        .ok_or_else(|| anyhow!("未找到ID为 {} 的待办事项", depends_on_id))?;
+    if id == depends_on_id || tree.waits_for(depends_on_id, id) {
+        bail!("不能让 '{}' 依赖 '{}'：会形成循环依赖", todo.title, blocker.title);
+    }
+
+    let conn = database.get_connection();
+    let action = format!("添加依赖 #{}「{}」→ #{}「{}」", id, todo.title, depends_on_id, blocker.title);
+    if change_log_serv::record(database, &action, || dependency_dao::add_dependency(conn, id, depends_on_id))? {
+        println!("⛓️  '{}' 现在依赖 '{}' [ID: {}]", todo.title, blocker.title, depends_on_id);
+    } else {
+        println!("💡 依赖关系已存在");
+    }
+    Ok(())
+}
+
+/// 删除依赖
+pub fn remove_dependency(database: &Database, id: i32, depends_on_id: i32) -> AnyResult<()> {
+    let conn = database.get_connection();
+    let action = format!("移除依赖 #{} → #{}", id, depends_on_id);
+    if change_log_serv::record(database, &action, || dependency_dao::remove_dependency(conn, id, depends_on_id))? > 0 {
+        println!("✅ 已移除 [ID: {}] 对 [ID: {}] 的依赖", id, depends_on_id);
+    } else {
+        println!("💡 [ID: {}] 并不依赖 [ID: {}]", id, depends_on_id);
+    }
+    Ok(())
+}
+
+/// 未结束的子孙任务
+pub fn open_descendants(database: &Database, id: i32) -> AnyResult<Vec<TodoListForm>> {
+    let tree = TodoTree::load(database)?;
+    Ok(tree
+        .descendants(id)
+        .into_iter()
+        .filter(|t| t.status.is_open())
+        .cloned()
+        .collect())
+}
+
+/// 未结束的直接依赖
+pub fn open_dependencies(database: &Database, id: i32) -> AnyResult<Vec<TodoListForm>> {
+    let conn = database.get_connection();
+    let mut results = Vec::new();
+    for depends_on_id in dependency_dao::list_dependencies_of(conn, id)? {
+        if let Some(todo) = todo_list_dao::get_todo_by_id(conn, depends_on_id)?
+            && todo.status.is_open()
+        {
+            results.push(todo);
+        }
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 各待办事项的父任务，以及 (待办事项, 依赖项) 列表
+    type Links = (Vec<(i32, Option<i32>)>, Vec<(i32, i32)>);
+
+    /// 创建 1..=count 号待办事项
+    fn database_with(count: i32) -> Database {
+        let database = Database::new(":memory:").unwrap();
+        database.initialize_tables().unwrap();
+        let conn = database.get_connection();
+        for id in 1..=count {
+            conn.execute(
+                "INSERT INTO todo_list (id, title, begin_time) VALUES (?1, ?2, '2030-01-01T09:00:00+00:00')",
+                rusqlite::params![id, format!("任务{}", id)],
+            )
+            .unwrap();
+        }
+        database
+    }
+
+    /// 当前的父任务与依赖关系
+    fn links(database: &Database) -> Links {
+        let conn = database.get_connection();
+        let mut stmt = conn.prepare("SELECT id, parent_id FROM todo_list ORDER BY id").unwrap();
+        let parents = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        (parents, dependency_dao::list_dependencies(conn).unwrap())
+    }
+
+    /// 添加关系应被拒绝，且不写入任何内容
+    fn assert_rejected(database: &Database, link: impl FnOnce(&Database) -> AnyResult<()>) {
+        let before = links(database);
+        let error = link(database).unwrap_err().to_string();
+        assert!(error.contains("循环"), "{error}");
+        assert_eq!(links(database), before);
+    }
+
+    #[test]
+    fn rejects_self_links() {
+        let database = database_with(1);
+        assert_rejected(&database, |db| set_parent(db, 1, Some(1)));
+        assert_rejected(&database, |db| add_dependency(db, 1, 1));
+    }
+
+    #[test]
+    fn rejects_two_cycles() {
+        let database = database_with(2);
+        set_parent(&database, 2, Some(1)).unwrap();
+        assert_rejected(&database, |db| set_parent(db, 1, Some(2)));
+
+        add_dependency(&database, 1, 2).unwrap();
+        assert_rejected(&database, |db| add_dependency(db, 2, 1));
+    }
+
+    #[test]
+    fn rejects_longer_cycles() {
+        let database = database_with(4);
+        // 1 ← 2 ← 3 ← 4 的父子链
+        set_parent(&database, 2, Some(1)).unwrap();
+        set_parent(&database, 3, Some(2)).unwrap();
+        set_parent(&database, 4, Some(3)).unwrap();
+        assert_rejected(&database, |db| set_parent(db, 1, Some(4)));
+        // 移到链中更高的位置不形成环
+        set_parent(&database, 4, Some(1)).unwrap();
+
+        // 1 → 2 → 3 → 4 的依赖链
+        add_dependency(&database, 1, 2).unwrap();
+        add_dependency(&database, 2, 3).unwrap();
+        add_dependency(&database, 3, 4).unwrap();
+        assert_rejected(&database, |db| add_dependency(db, 4, 1));
+        assert_rejected(&database, |db| add_dependency(db, 3, 1));
+        // 同方向的捷径不形成环
+        add_dependency(&database, 1, 4).unwrap();
+    }
+
+    #[test]
+    fn rejects_cycles_through_mixed_links() {
+        let database = database_with(5);
+        // 1 是 2 的父任务，2 是 3 的父任务
+        set_parent(&database, 2, Some(1)).unwrap();
+        set_parent(&database, 3, Some(2)).unwrap();
+        // 子孙不能依赖祖先
+        assert_rejected(&database, |db| add_dependency(db, 2, 1));
+        assert_rejected(&database, |db| add_dependency(db, 3, 1));
+        // 祖先依赖子孙与关系方向一致
+        add_dependency(&database, 1, 3).unwrap();
+
+        // 再加上 3 → 4 → 5 的依赖链，1 经 2、3 间接要等 5 完成
+        add_dependency(&database, 3, 4).unwrap();
+        add_dependency(&database, 4, 5).unwrap();
+        assert_rejected(&database, |db| add_dependency(db, 5, 2));
+        assert_rejected(&database, |db| set_parent(db, 1, Some(5)));
+        assert_rejected(&database, |db| set_parent(db, 2, Some(4)));
+        // 4 依赖 5，不能成为 5 的子任务；反过来可以
+        assert_rejected(&database, |db| set_parent(db, 4, Some(5)));
+        set_parent(&database, 5, Some(4)).unwrap();
+    }
+
+    #[test]
+    fn rejects_missing_todos_without_writing() {
+        let database = database_with(1);
+        let before = links(&database);
+        assert!(set_parent(&database, 1, Some(9)).is_err());
+        assert!(add_dependency(&database, 1, 9).is_err());
+        assert!(add_dependency(&database, 9, 1).is_err());
+        assert_eq!(links(&database), before);
+    }
+}
@@ -1,13 +1,20 @@
-use crate::data::todo_list::TodoListForm;
-use crate::data::reminder::ReminderType;
+use crate::data::local_time;
+use crate::data::todo_list::{TodoListForm, TodoStatus};
+use crate::data::reminder::{Notification, ReminderHistory, ReminderKind, ReminderState};
 use crate::init::database::Database;
 use crate::init::db_json::JsonConfig;
 use crate::init::db_json_content::{ReminderConfig, ReminderRule};
 use crate::dao::todo_list_dao;
 use crate::dao::reminder_dao;
 use crate::service::logger::Logger;
-use chrono::Utc;
+use crate::service::notifier::{self, NotificationAction};
+use crate::service::todo_list_serv;
+use chrono::{DateTime, Duration, Utc};
 use anyhow::Result as AnyResult;
+use std::collections::HashMap;
+
+/// 最近提醒列表的条数上限
+const RECENT_LIMIT: usize = 20;
 
 pub struct ReminderService;
 
@@ -16,7 +23,7 @@ impl ReminderService {
     pub fn check_and_notify(
         db: &Database,
         json_config: &JsonConfig,
-    ) -> AnyResult<Vec<String>> {
+    ) -> AnyResult<Vec<Notification>> {
         let mut notifications = Vec::new();
         
         // 读取配置
@@ -25,39 +32,271 @@ impl ReminderService {
         if !reminder_config.enabled {
             return Ok(notifications);
         }
+
+        // 无效的规则记录到日志后跳过
+        let rules: Vec<&ReminderRule> = reminder_config.rules.iter()
+            .filter(|rule| match rule.validate() {
+                Ok(()) => true,
+                Err(e) => {
+                    Logger::log("WARN", &format!("忽略无效的提醒规则 {}: {}", rule.key(), e));
+                    false
+                }
+            })
+            .collect();
         
-        // 获取所有未完成的待办事项
+        // 获取所有未结束的待办事项（已完成、已取消、已归档的不再提醒）
         let conn = db.get_connection();
-        let todos = todo_list_dao::list_todos(conn)?;
+        let todos = todo_list_dao::list_active_todos(conn)?;
         let uncompleted: Vec<_> = todos.into_iter()
-            .filter(|t| !t.completed)
+            .filter(|t| t.status.is_open())
             .collect();
         
         let now = Utc::now();
         
-        for todo in uncompleted {
+        for todo in &uncompleted {
             // begin_time 总是存在的（非 Option 类型）
             let begin_time = todo.begin_time;
-            // 检查每个提醒规则
-            for rule in &reminder_config.rules {
-                if let Some(notification) = Self::check_rule(
-                    &todo,
-                    begin_time,
+            notifications.extend(Self::check_rules(
+                todo,
+                begin_time,
+                todo.end_time,
+                None,
+                &rules,
+                now,
+                db,
+            )?);
+
+            // 重复待办事项：本次已开始但尚未完成时，下一次重复还未生成，
+            // 按规则推算即将到来的那次重复，同样进行提醒
+            if let Some(recurrence) = &todo.recurrence
+                && begin_time <= now
+                && let Some((next_begin, _)) = recurrence.upcoming(begin_time, todo.occurrence, now)
+            {
+                let next_end = todo.end_time.map(|end| next_begin + (end - begin_time));
+                notifications.extend(Self::check_rules(
+                    todo,
+                    next_begin,
+                    next_end,
+                    Some(next_begin),
+                    &rules,
                     now,
-                    rule,
                     db,
-                )? {
-                    notifications.push(notification.clone());
-                    Logger::log_reminder(&notification);
-                }
+                )?);
             }
         }
+
+        notifications.extend(Self::refire_snoozed(&uncompleted, now, db)?);
         
         Ok(notifications)
     }
+
+    /// 稍后提醒已到期的记录：按原提醒内容再次发送，记录恢复为未处理状态
+    /// 对应的待办事项已结束或已删除时不再发送，直接确认
+    fn refire_snoozed(
+        open_todos: &[TodoListForm],
+        now: DateTime<Utc>,
+        db: &Database,
+    ) -> AnyResult<Vec<Notification>> {
+        let conn = db.get_connection();
+        let todos: HashMap<i32, &TodoListForm> = open_todos.iter().map(|t| (t.id, t)).collect();
+        let mut notifications = Vec::new();
+
+        for reminder in reminder_dao::due_snoozed(conn, now.timestamp())? {
+            let (Some(todo), Some(kind)) = (
+                todos.get(&reminder.todo_id),
+                ReminderKind::from_string(&reminder.reminder_type),
+            ) else {
+                reminder_dao::acknowledge(conn, reminder.id)?;
+                Logger::log("INFO", &format!(
+                    "任务 [ID: {}] 已结束或不存在，取消稍后提醒 [提醒ID: {}]",
+                    reminder.todo_id, reminder.id
+                ));
+                continue;
+            };
+
+            reminder_dao::finish_snooze(conn, reminder.id)?;
+            let message = reminder.message.clone()
+                .unwrap_or_else(|| format!("🔔 任务「{}」(ID:{}) {}", todo.title, todo.id, kind.label()));
+            Logger::log_reminder(&format!("[稍后提醒] {}", message));
+            notifications.push(Notification {
+                history_id: reminder.id,
+                todo_id: todo.id,
+                todo_title: todo.title.clone(),
+                priority: todo.priority,
+                kind,
+                rule_key: reminder.rule_key.clone().unwrap_or_else(|| reminder.reminder_type.clone()),
+                trigger_at: reminder.trigger_time.map_or(now.timestamp(), |t| t.timestamp()),
+                message,
+            });
+        }
+
+        Ok(notifications)
+    }
     
+    /// 下一个尚未到来的提醒触发时间（用于常驻模式决定休眠多久），没有时返回 None
+    ///
+    /// 只计算晚于当前时间的触发时间；已到期的提醒由 check_and_notify 处理
+    pub fn next_due(db: &Database, json_config: &JsonConfig) -> AnyResult<Option<DateTime<Utc>>> {
+        let reminder_config = Self::get_reminder_config(json_config)?;
+        if !reminder_config.enabled {
+            return Ok(None);
+        }
+        let rules: Vec<&ReminderRule> = reminder_config.rules.iter()
+            .filter(|rule| rule.validate().is_ok())
+            .collect();
+
+        let todos = todo_list_dao::list_active_todos(db.get_connection())?;
+        let now = Utc::now();
+        let mut next: Option<DateTime<Utc>> = None;
+        let mut consider = |candidate: Option<DateTime<Utc>>| {
+            if let Some(t) = candidate.filter(|t| *t > now) {
+                next = Some(next.map_or(t, |n| n.min(t)));
+            }
+        };
+
+        // 稍后提醒的时间
+        consider(reminder_dao::next_snoozed(db.get_connection())?
+            .and_then(|t| DateTime::from_timestamp(t, 0)));
+
+        for todo in todos.iter().filter(|t| t.status.is_open()) {
+            let begin_time = todo.begin_time;
+            for rule in &rules {
+                consider(Self::next_trigger(rule, &rules, begin_time, todo.end_time, now));
+            }
+            if let Some(recurrence) = &todo.recurrence {
+                // 本次开始后才会推算下一次重复，因此开始时间本身也是需要醒来的时刻
+                consider(Some(begin_time));
+                if begin_time <= now
+                    && let Some((next_begin, _)) = recurrence.upcoming(begin_time, todo.occurrence, now)
+                {
+                    let next_end = todo.end_time.map(|end| next_begin + (end - begin_time));
+                    for rule in &rules {
+                        consider(Self::next_trigger(rule, &rules, next_begin, next_end, now));
+                    }
+                }
+            }
+        }
+        Ok(next)
+    }
+
+    /// 处理桌面通知上被点击的按钮：完成任务、稍后提醒（推迟 snooze_minutes 分钟）或确认提醒
+    pub fn handle_action(
+        db: &Database,
+        reminder_config: &ReminderConfig,
+        action: &NotificationAction,
+    ) -> AnyResult<()> {
+        let notification = &action.notification;
+        match action.action.as_str() {
+            notifier::ACTION_DONE => {
+                todo_list_serv::set_status(db, notification.todo_id, TodoStatus::Done, false)?;
+                reminder_dao::acknowledge(db.get_connection(), notification.history_id)?;
+                Logger::log("INFO", &format!("通过通知完成任务 [ID: {}]", notification.todo_id));
+            }
+            notifier::ACTION_SNOOZE => {
+                let minutes = reminder_config.snooze_minutes.max(1);
+                Self::snooze_reminder(db, notification.history_id, Duration::minutes(minutes as i64))?;
+            }
+            notifier::ACTION_ACK => {
+                Self::acknowledge_reminder(db, notification.history_id)?;
+            }
+            other => {
+                Logger::log("WARN", &format!("未知的通知操作: {}", other));
+            }
+        }
+        Ok(())
+    }
+
+    /// 按 ID 获取提醒记录
+    pub fn get_reminder(db: &Database, id: i64) -> AnyResult<Option<ReminderHistory>> {
+        Ok(reminder_dao::get_reminder(db.get_connection(), id)?)
+    }
+
+    /// 稍后提醒：duration 之后再次发送同一条提醒，返回再次提醒的时间
+    pub fn snooze_reminder(db: &Database, id: i64, duration: Duration) -> AnyResult<DateTime<Utc>> {
+        if duration <= Duration::zero() {
+            anyhow::bail!("稍后提醒的时长必须大于 0");
+        }
+        let reminder = Self::get_reminder(db, id)?
+            .ok_or_else(|| anyhow::anyhow!("未找到ID为 {} 的提醒记录", id))?;
+
+        let until = Utc::now() + duration;
+        reminder_dao::snooze(db.get_connection(), id, until.timestamp())?;
+        println!("💤 任务 [ID: {}] 的提醒将在 {} 再次发送",
+            reminder.todo_id,
+            local_time::format(&until, "%Y-%m-%d %H:%M")
+        );
+        Logger::log("INFO", &format!(
+            "稍后提醒 [提醒ID: {}, 任务ID: {}]，{} 再次发送",
+            id, reminder.todo_id, until.to_rfc3339()
+        ));
+        Ok(until)
+    }
+
+    /// 确认提醒（"知道了"），同时取消尚未到期的稍后提醒
+    pub fn acknowledge_reminder(db: &Database, id: i64) -> AnyResult<()> {
+        let reminder = Self::get_reminder(db, id)?
+            .ok_or_else(|| anyhow::anyhow!("未找到ID为 {} 的提醒记录", id))?;
+
+        reminder_dao::acknowledge(db.get_connection(), id)?;
+        println!("✅ 已确认任务 [ID: {}] 的提醒", reminder.todo_id);
+        Logger::log("INFO", &format!("确认提醒 [提醒ID: {}, 任务ID: {}]", id, reminder.todo_id));
+        Ok(())
+    }
+
+    /// 确认全部未确认的提醒，返回确认的条数
+    pub fn acknowledge_all(db: &Database) -> AnyResult<usize> {
+        let count = reminder_dao::acknowledge_all(db.get_connection())?;
+        println!("✅ 已确认 {} 条提醒", count);
+        Logger::log("INFO", &format!("确认全部提醒，共 {} 条", count));
+        Ok(count)
+    }
+
+    /// 最近的提醒（默认只含未确认的）
+    pub fn list_recent(db: &Database, include_acknowledged: bool) -> AnyResult<Vec<ReminderHistory>> {
+        Ok(reminder_dao::recent_reminders(db.get_connection(), RECENT_LIMIT, include_acknowledged)?)
+    }
+
+    /// 显示最近的提醒及其处理状态
+    pub fn show_recent(db: &Database, include_acknowledged: bool) -> AnyResult<()> {
+        let reminders = Self::list_recent(db, include_acknowledged)?;
+        if reminders.is_empty() {
+            println!("{}", if include_acknowledged { "📋 暂无提醒" } else { "📋 暂无未确认的提醒" });
+            return Ok(());
+        }
+
+        println!("\n📋 最近的提醒（{} 条）:", reminders.len());
+        println!("{}", "=".repeat(80));
+        for reminder in &reminders {
+            println!("  [ID:{}] 任务ID:{} | {} | {}",
+                reminder.id,
+                reminder.todo_id,
+                Self::state_text(reminder),
+                local_time::format(&reminder.reminder_time, "%Y-%m-%d %H:%M:%S")
+            );
+            if let Some(message) = &reminder.message {
+                println!("      {}", message);
+            }
+        }
+        println!("{}", "=".repeat(80));
+        println!("💡 reminder-snooze <ID> [时长] 稍后提醒，reminder-ack <ID|all> 确认提醒");
+        println!();
+        Ok(())
+    }
+
+    /// 提醒状态的显示文本，稍后提醒时附带再次提醒的时间
+    fn state_text(reminder: &ReminderHistory) -> String {
+        match (reminder.state, &reminder.snoozed_until) {
+            (ReminderState::Snoozed, Some(until)) => format!(
+                "{} → {}",
+                reminder.state.label(),
+                local_time::format(until, "%Y-%m-%d %H:%M")
+            ),
+            _ => reminder.state.label().to_string(),
+        }
+    }
+
     /// 获取提醒配置
-    fn get_reminder_config(json_config: &JsonConfig) -> AnyResult<ReminderConfig> {
+    pub fn get_reminder_config(json_config: &JsonConfig) -> AnyResult<ReminderConfig> {
         // 尝试读取整个配置
         match json_config.get_value("reminder") {
             Ok(value) => {
@@ -70,89 +309,275 @@ impl ReminderService {
             }
         }
     }
-    
-    /// 检查单个提醒规则
-    fn check_rule(
+
+    /// 按全部规则检查一个待办事项（或其某次重复），返回需要发送的提醒
+    /// occurrence_time 不为 None 时表示针对重复待办事项尚未生成的某次重复
+    fn check_rules(
         todo: &TodoListForm,
-        begin_time: chrono::DateTime<chrono::Utc>,
-        now: chrono::DateTime<chrono::Utc>,
-        rule: &ReminderRule,
+        begin_time: DateTime<Utc>,
+        end_time: Option<DateTime<Utc>>,
+        occurrence_time: Option<DateTime<Utc>>,
+        rules: &[&ReminderRule],
+        now: DateTime<Utc>,
         db: &Database,
-    ) -> AnyResult<Option<String>> {
-        let reminder_type = match rule.rule_type.as_str() {
-            "before_start" => {
-                let seconds = rule.seconds_before.unwrap_or(0);
-                if seconds == 86400 {
-                    ReminderType::OneDayBefore
-                } else if seconds == 3600 {
-                    ReminderType::OneHourBefore
-                } else {
-                    return Ok(None);
-                }
+    ) -> AnyResult<Vec<Notification>> {
+        let conn = db.get_connection();
+        let mut notifications = Vec::new();
+
+        for rule in rules {
+            let Some(trigger) = Self::due_trigger(rule, rules, begin_time, end_time, now) else {
+                continue;
+            };
+            let Some(kind) = rule.kind() else {
+                continue;
+            };
+            let key = rule.key();
+            if reminder_dao::has_been_notified(conn, todo.id, &key, trigger.timestamp())? {
+                continue;
             }
-            "overdue" => ReminderType::Overdue,
-            _ => return Ok(None),
+
+            // 生成提醒消息
+            let message = Self::render_message(rule, todo, begin_time, end_time);
+            Logger::log_reminder(&message);
+
+            // 记录提醒历史（保存提醒内容，稍后提醒时沿用）
+            let history_id = reminder_dao::record_notification(
+                conn,
+                todo.id,
+                &rule.rule_type,
+                &key,
+                trigger.timestamp(),
+                occurrence_time.as_ref(),
+                &message,
+            )?;
+            notifications.push(Notification {
+                history_id,
+                todo_id: todo.id,
+                todo_title: todo.title.clone(),
+                priority: todo.priority,
+                kind,
+                rule_key: key,
+                trigger_at: trigger.timestamp(),
+                message,
+            });
+        }
+
+        Ok(notifications)
+    }
+
+    /// 按规则的消息模板生成提醒内容
+    pub fn render_message(
+        rule: &ReminderRule,
+        todo: &TodoListForm,
+        begin_time: DateTime<Utc>,
+        end_time: Option<DateTime<Utc>>,
+    ) -> String {
+        rule.message_template
+            .replace("{title}", &todo.title)
+            .replace("{id}", &todo.id.to_string())
+            .replace("{time}", &local_time::format(&begin_time, "%Y-%m-%d %H:%M"))
+            .replace("{end}", &end_time.map(|t| local_time::format(&t, "%Y-%m-%d %H:%M")).unwrap_or_else(|| "无".to_string()))
+            .replace("{status}", todo.status.label())
+            .replace("{priority}", todo.priority.map(|p| p.label()).unwrap_or("无"))
+    }
+
+    /// 规则的有效期：(开始生效时间, 结束时间)，结束时间为 None 表示一直有效
+    ///
+    /// - before_* 规则从 参照时间 - 提前量 开始生效，到参照时间为止；
+    ///   同一参照下存在提前量更小的规则时，在该规则生效时结束（如 1 小时提醒生效后不再发 1 天提醒）
+    /// - after_* 规则从 参照时间 + 延后量 开始生效，直到待办事项结束
+    pub fn trigger_window(
+        rule: &ReminderRule,
+        rules: &[&ReminderRule],
+        begin_time: DateTime<Utc>,
+        end_time: Option<DateTime<Utc>>,
+    ) -> Option<(DateTime<Utc>, Option<DateTime<Utc>>)> {
+        let kind = rule.kind()?;
+        let anchor = if kind.uses_end_time() { end_time? } else { begin_time };
+        let offset = rule.offset_seconds();
+
+        let window = if kind.is_before() {
+            let next_offset = rules.iter()
+                .filter(|r| r.kind() == Some(kind))
+                .map(|r| r.offset_seconds())
+                .filter(|o| *o < offset)
+                .max()
+                .unwrap_or(0);
+            (anchor - Duration::seconds(offset), Some(anchor - Duration::seconds(next_offset)))
+        } else {
+            (anchor + Duration::seconds(offset), None)
         };
-        
-        // 检查是否需要提醒
-        let should_remind = match &reminder_type {
-            ReminderType::OneDayBefore => {
-                let diff = begin_time.signed_duration_since(now);
-                diff.num_seconds() > 3600 
-                    && diff.num_seconds() <= 86400
-                    && !Self::has_been_notified(db, todo.id, &reminder_type)?
-            }
-            ReminderType::OneHourBefore => {
-                let diff = begin_time.signed_duration_since(now);
-                diff.num_seconds() > 0 
-                    && diff.num_seconds() <= 3600
-                    && !Self::has_been_notified(db, todo.id, &reminder_type)?
-            }
-            ReminderType::Overdue => {
-                begin_time < now 
-                    && !Self::has_been_notified(db, todo.id, &reminder_type)?
+        Some(window)
+    }
+
+    /// 计算规则当前所处的计划触发时间，尚未到触发时间或已过有效期时返回 None
+    ///
+    /// 设置了 repeat_seconds 时，有效期内每隔该时长产生一个新的触发时间
+    fn due_trigger(
+        rule: &ReminderRule,
+        rules: &[&ReminderRule],
+        begin_time: DateTime<Utc>,
+        end_time: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+    ) -> Option<DateTime<Utc>> {
+        let (start, window_end) = Self::trigger_window(rule, rules, begin_time, end_time)?;
+
+        if now < start || window_end.is_some_and(|end| now >= end) {
+            return None;
+        }
+        match rule.repeat_seconds {
+            Some(repeat) if repeat > 0 => {
+                let slots = (now - start).num_seconds() / repeat;
+                Some(start + Duration::seconds(slots * repeat))
             }
-        };
-        
-        if should_remind {
-            // 记录提醒历史
-            Self::record_notification(db, todo.id, &reminder_type)?;
-            
-            // 生成提醒消息
-            let message = rule.message_template
-                .replace("{title}", &todo.title)
-                .replace("{id}", &todo.id.to_string());
-            
-            return Ok(Some(message));
+            _ => Some(start),
         }
-        
-        Ok(None)
     }
     
-    /// 检查是否已经发送过提醒
-    fn has_been_notified(
-        db: &Database,
-        todo_id: i32,
-        reminder_type: &ReminderType,
-    ) -> AnyResult<bool> {
-        let conn = db.get_connection();
-        Ok(reminder_dao::has_been_notified(conn, todo_id, reminder_type)?)
+    /// 规则在当前时间之后的下一个计划触发时间
+    fn next_trigger(
+        rule: &ReminderRule,
+        rules: &[&ReminderRule],
+        begin_time: DateTime<Utc>,
+        end_time: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+    ) -> Option<DateTime<Utc>> {
+        let (start, window_end) = Self::trigger_window(rule, rules, begin_time, end_time)?;
+
+        let next = if now < start {
+            start
+        } else {
+            let repeat = rule.repeat_seconds.filter(|r| *r > 0)?;
+            let slots = (now - start).num_seconds() / repeat;
+            start + Duration::seconds((slots + 1) * repeat)
+        };
+        if window_end.is_some_and(|end| next >= end) {
+            return None;
+        }
+        Some(next)
     }
-    
-    /// 记录提醒历史
-    fn record_notification(
-        db: &Database,
-        todo_id: i32,
-        reminder_type: &ReminderType,
-    ) -> AnyResult<()> {
+
+    /// 获取当前的提醒规则
+    pub fn list_rules(json_config: &JsonConfig) -> AnyResult<Vec<ReminderRule>> {
+        Ok(Self::get_reminder_config(json_config)?.rules)
+    }
+
+    /// 显示提醒规则
+    pub fn show_rules(json_config: &JsonConfig) -> AnyResult<()> {
+        let rules = Self::list_rules(json_config)?;
+        if rules.is_empty() {
+            println!("📋 暂无提醒规则");
+            return Ok(());
+        }
+
+        println!("\n📋 提醒规则 ({} 条):", rules.len());
+        println!("{}", "=".repeat(80));
+        for (i, rule) in rules.iter().enumerate() {
+            println!("  {}. {} [{}]", i + 1, rule.describe(), rule.key());
+            println!("     消息: {}", rule.message_template);
+            if let Err(e) = rule.validate() {
+                println!("     ⚠️  无效规则，检查时将被忽略: {}", e);
+            }
+        }
+        println!("{}", "=".repeat(80));
+        Ok(())
+    }
+
+    /// 添加提醒规则（规则标识不能与已有规则重复）
+    pub fn add_rule(json_config: &JsonConfig, rule: ReminderRule) -> AnyResult<()> {
+        rule.validate().map_err(|e| anyhow::anyhow!("提醒规则无效: {}", e))?;
+        let mut config = Self::get_reminder_config(json_config)?;
+        let key = rule.key();
+        if config.rules.iter().any(|r| r.key() == key) {
+            anyhow::bail!("已存在标识为 '{}' 的提醒规则", key);
+        }
+
+        println!("✅ 已添加提醒规则: {} [{}]", rule.describe(), key);
+        config.rules.push(rule);
+        json_config.set_value("reminder", serde_json::to_value(config)?)?;
+        Ok(())
+    }
+
+    /// 删除提醒规则，selector 为序号（从 1 开始）或规则标识
+    pub fn remove_rule(json_config: &JsonConfig, selector: &str) -> AnyResult<()> {
+        let mut config = Self::get_reminder_config(json_config)?;
+        let selector = selector.trim();
+        let index = match selector.parse::<usize>() {
+            Ok(n) if (1..=config.rules.len()).contains(&n) => Some(n - 1),
+            _ => config.rules.iter().position(|r| r.key() == selector),
+        }
+        .ok_or_else(|| anyhow::anyhow!("未找到提醒规则: {}", selector))?;
+
+        let removed = config.rules.remove(index);
+        json_config.set_value("reminder", serde_json::to_value(config)?)?;
+        println!("✅ 已删除提醒规则: {} [{}]", removed.describe(), removed.key());
+        Ok(())
+    }
+
+    /// 显示通知渠道（未配置 sinks 时显示由 notification_type 生成的渠道）
+    pub fn show_sinks(json_config: &JsonConfig) -> AnyResult<()> {
+        let config = Self::get_reminder_config(json_config)?;
+        let sinks = config.effective_sinks();
+
+        println!("\n📨 通知渠道 ({} 个):", sinks.len());
+        println!("{}", "=".repeat(80));
+        if config.sinks.is_empty() {
+            println!("  （未配置 sinks，按 notification_type = \"{}\" 生成）", config.notification_type);
+        }
+        for (i, sink) in sinks.iter().enumerate() {
+            println!("  {}. {} - {}", i + 1, sink.display_name(), sink.describe());
+            if let Err(e) = sink.validate() {
+                println!("     ⚠️  无效渠道，发送时将被跳过: {}", e);
+            }
+        }
+        println!("{}", "=".repeat(80));
+        Ok(())
+    }
+
+    /// 向指定名称（或全部）通知渠道发送一条测试提醒，忽略过滤条件
+    pub fn test_sinks(json_config: &JsonConfig, name: Option<&str>) -> AnyResult<()> {
+        let config = Self::get_reminder_config(json_config)?;
+        if let Some(name) = name
+            && !config.effective_sinks().iter().any(|s| s.display_name() == name)
+        {
+            anyhow::bail!("未找到通知渠道: {}", name);
+        }
+
+        let sample = Notification {
+            history_id: 0,
+            todo_id: 0,
+            todo_title: "测试提醒".to_string(),
+            priority: None,
+            kind: ReminderKind::BeforeStart,
+            rule_key: "test".to_string(),
+            trigger_at: Utc::now().timestamp(),
+            message: "🔔 这是一条来自 TodoList 的测试提醒".to_string(),
+        };
+        let report = notifier::Notifier::from_config(&config).send_test(name, &sample);
+        for sink in &report.succeeded {
+            println!("✅ {} 发送成功", sink);
+        }
+        for (sink, error) in &report.failed {
+            println!("❌ {} 发送失败: {}", sink, error);
+        }
+        if report.succeeded.is_empty() && report.failed.is_empty() {
+            anyhow::bail!("没有可用的通知渠道（已停用或配置无效）");
+        }
+        if !report.failed.is_empty() {
+            anyhow::bail!("{} 个通知渠道发送失败", report.failed.len());
+        }
+        Ok(())
+    }
+
+    /// 获取提醒历史（最近100条）
+    pub fn list_history(db: &Database) -> AnyResult<Vec<ReminderHistory>> {
         let conn = db.get_connection();
-        Ok(reminder_dao::record_notification(conn, todo_id, reminder_type)?)
+        Ok(reminder_dao::get_all_reminders(conn)?)
     }
     
     /// 显示提醒历史
     pub fn show_history(db: &Database) -> AnyResult<()> {
-        let conn = db.get_connection();
-        let reminders = reminder_dao::get_all_reminders(conn)?;
+        let reminders = Self::list_history(db)?;
         
         if reminders.is_empty() {
             println!("📋 暂无提醒历史");
@@ -163,19 +588,24 @@ impl ReminderService {
         println!("{}", "=".repeat(80));
         
         for reminder in reminders {
-            let type_label = match ReminderType::from_string(&reminder.reminder_type) {
-                Some(ReminderType::OneDayBefore) => "提前1天",
-                Some(ReminderType::OneHourBefore) => "提前1小时",
-                Some(ReminderType::Overdue) => "已逾期",
-                None => "未知类型",
-            };
+            let type_label = ReminderKind::from_string(&reminder.reminder_type)
+                .map(|kind| kind.label())
+                .unwrap_or("未知类型");
             
-            println!("  [ID:{}] 任务ID:{} | {} | {}",
+            println!("  [ID:{}] 任务ID:{} | {} | {} | {} | {}",
                 reminder.id,
                 reminder.todo_id,
                 type_label,
-                reminder.reminder_time.format("%Y-%m-%d %H:%M:%S")
+                reminder.rule_key.as_deref().unwrap_or(&reminder.reminder_type),
+                local_time::format(&reminder.reminder_time, "%Y-%m-%d %H:%M:%S"),
+                Self::state_text(&reminder)
             );
+            if reminder.snooze_count > 0 {
+                println!("      💤 已稍后提醒 {} 次", reminder.snooze_count);
+            }
+            if let Some(occurrence_time) = &reminder.occurrence_time {
+                println!("      🔁 针对 {} 开始的重复", local_time::format(occurrence_time, "%Y-%m-%d %H:%M:%S"));
+            }
         }
         
         println!("{}", "=".repeat(80));
@@ -196,3 +626,55 @@ impl ReminderService {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DurationRound, TimeDelta};
+
+    #[test]
+    fn spawned_occurrence_keeps_reminders_sent_in_advance() {
+        let database = Database::new(":memory:").unwrap();
+        database.initialize_tables().unwrap();
+        let json_path = std::env::temp_dir().join(format!("reminder-serv-test-{}.json", std::process::id()));
+        let json_config = JsonConfig::new(json_path.to_str().unwrap()).unwrap();
+
+        // 每天重复，本次两小时前已开始：默认规则下本次发逾期提醒，明天那次发提前 1 天提醒
+        let begin_time = Utc::now().duration_trunc(TimeDelta::minutes(1)).unwrap() - Duration::hours(2);
+        let todo = TodoListForm {
+            id: 0,
+            title: "站会".to_string(),
+            description: None,
+            status: TodoStatus::Todo,
+            priority: None,
+            tags: Vec::new(),
+            begin_time,
+            end_time: None,
+            key_message1: None,
+            key_message2: None,
+            key_message3: None,
+            recurrence: todo_list_serv::parse_recurrence_input("daily", begin_time).unwrap(),
+            series_id: None,
+            occurrence: 1,
+            parent_id: None,
+            archived_at: None,
+            completed_at: None,
+        };
+        let id = todo_list_dao::insert_todo(database.get_connection(), &todo).unwrap() as i32;
+
+        let first = ReminderService::check_and_notify(&database, &json_config).unwrap();
+        let keys: Vec<(i32, &str)> = first.iter().map(|n| (n.todo_id, n.rule_key.as_str())).collect();
+        assert_eq!(keys, vec![(id, "overdue:0"), (id, "before_start:86400")]);
+
+        // 完成本次后生成明天那次，提前 1 天的提醒已经发过，不再重复
+        todo_list_serv::set_status(&database, id, TodoStatus::Done, false).unwrap();
+        let conn = database.get_connection();
+        let next_id = todo_list_dao::find_occurrence(conn, id, 2).unwrap().unwrap();
+        assert!(ReminderService::check_and_notify(&database, &json_config).unwrap().is_empty());
+
+        // 提醒记录转移到新生成的待办事项上
+        let moved = reminder_dao::get_reminder(conn, first[1].history_id).unwrap().unwrap();
+        assert_eq!((moved.todo_id, moved.occurrence_time), (next_id, None));
+        let _ = std::fs::remove_file(json_path);
+    }
+}
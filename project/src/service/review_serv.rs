@@ -1,43 +1,55 @@
 use anyhow::Result as AnyResult;
 use crate::dao::todo_list_dao;
 use crate::dao::note_dao;
+use crate::dao::tag_dao;
 use crate::init::database::Database;
-use chrono::Utc;
-
-/// 解析优先级字符串为数字（用于排序）
-/// 高优先级返回较小的数字，这样排序时会排在前面
-fn parse_priority(priority: &Option<String>) -> i32 {
-    match priority {
-        None => 999, // 无优先级排在最后
-        Some(p) => {
-            let p_lower = p.to_lowercase();
-            match p_lower.as_str() {
-                "高" | "high" | "1" | "urgent" | "紧急" => 1,
-                "中" | "medium" | "2" | "normal" | "普通" => 2,
-                "低" | "low" | "3" | "minor" | "次要" => 3,
-                _ => {
-                    // 尝试解析为数字
-                    p.parse::<i32>().unwrap_or(999)
-                }
-            }
-        }
+use crate::data::local_time;
+use crate::data::note::NoteForm;
+use crate::data::time_tracking;
+use crate::data::todo_list::{Priority, TodoListForm, TodoStatus};
+use crate::service::time_tracking_serv;
+use chrono::{Duration, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// 整体统计数据（文本与 JSON 输出共用）
+#[derive(Debug, Serialize, Clone)]
+pub struct Statistics {
+    pub total: usize,
+    pub completed: usize,
+    pub pending: usize,
+    pub in_progress: usize,
+    pub blocked: usize,
+    pub cancelled: usize,
+    pub archived: usize,  // 已归档（计入上面的已完成 / 已取消）
+    pub completion_rate: f64,
+    pub total_notes: usize,
+    pub tracked_seconds: i64,  // 全部计时的累计时长
+    pub average_completion_seconds: Option<i64>,  // 已完成待办事项的平均实际用时，没有可用数据时为空
+}
+
+/// 已完成待办事项的实际用时与来源
+///
+/// 有计时的取累计计时，否则取开始到完成的时间；完成时间未知（升级前完成的）时为 None
+pub fn actual_duration(todo: &TodoListForm, tracked: &HashMap<i32, Duration>) -> Option<(Duration, &'static str)> {
+    if let Some(duration) = tracked.get(&todo.id).filter(|d| *d > &Duration::zero()) {
+        return Some((*duration, "计时"));
     }
+    let completed_at = todo.completed_at?;
+    Some(((completed_at - todo.begin_time).max(Duration::zero()), "开始到完成"))
 }
 
-/// 显示整体统计信息
-pub fn show_statistics(database: &Database) -> AnyResult<()> {
+/// 计算整体统计数据
+pub fn collect_statistics(database: &Database) -> AnyResult<Statistics> {
     let conn = database.get_connection();
     let todos = todo_list_dao::list_todos(conn)?;
 
-    if todos.is_empty() {
-        println!("📊 暂无任何待办事项");
-        return Ok(());
-    }
+    let count_status = |status: TodoStatus| todos.iter().filter(|t| t.status == status).count();
 
-    // 统计数据
     let total = todos.len();
-    let completed = todos.iter().filter(|t| t.completed).count();
-    let pending = total - completed;
+    let completed = count_status(TodoStatus::Done);
+    let cancelled = count_status(TodoStatus::Cancelled);
+    let pending = total - completed - cancelled;
     let completion_rate = if total > 0 {
         (completed as f64 / total as f64) * 100.0
     } else {
@@ -51,23 +63,122 @@ pub fn show_statistics(database: &Database) -> AnyResult<()> {
         total_notes += notes.len();
     }
 
+    let tracked = time_tracking_serv::tracked_durations(database)?;
+    let durations: Vec<Duration> = todos
+        .iter()
+        .filter(|t| t.is_done())
+        .filter_map(|t| actual_duration(t, &tracked))
+        .map(|(duration, _)| duration)
+        .collect();
+    let average_completion_seconds = (!durations.is_empty())
+        .then(|| durations.iter().map(|d| d.num_seconds()).sum::<i64>() / durations.len() as i64);
+
+    Ok(Statistics {
+        total,
+        completed,
+        pending,
+        in_progress: count_status(TodoStatus::InProgress),
+        blocked: count_status(TodoStatus::Blocked),
+        cancelled,
+        archived: todos.iter().filter(|t| t.is_archived()).count(),
+        completion_rate,
+        total_notes,
+        tracked_seconds: tracked.values().map(|d| d.num_seconds()).sum(),
+        average_completion_seconds,
+    })
+}
+
+/// 显示整体统计信息
+pub fn show_statistics(database: &Database) -> AnyResult<()> {
+    let stats = collect_statistics(database)?;
+
+    if stats.total == 0 {
+        println!("📊 暂无任何待办事项");
+        return Ok(());
+    }
+
     println!("\n📊 整体统计");
     println!("{:=<80}", "");
-    println!("总待办事项数: {}", total);
-    println!("已完成: {} ({}%)", completed, completion_rate as i32);
-    println!("未完成: {}", pending);
-    println!("笔记总数: {}", total_notes);
+    println!("总待办事项数: {}", stats.total);
+    println!("已完成: {} ({}%)", stats.completed, stats.completion_rate as i32);
+    println!("未完成: {} (进行中 {}，受阻 {})", stats.pending, stats.in_progress, stats.blocked);
+    if stats.cancelled > 0 {
+        println!("已取消: {}", stats.cancelled);
+    }
+    if stats.archived > 0 {
+        println!("已归档: {}", stats.archived);
+    }
+    println!("笔记总数: {}", stats.total_notes);
+    if stats.tracked_seconds > 0 {
+        println!("累计计时: {}", time_tracking::format_duration(Duration::seconds(stats.tracked_seconds)));
+    }
+    if let Some(average) = stats.average_completion_seconds {
+        println!("平均实际用时: {}", time_tracking::format_duration(Duration::seconds(average)));
+    }
     println!("{:=<80}", "");
 
     Ok(())
 }
 
-/// 显示已完成的待办事项
-pub fn show_completed_todos(database: &Database) -> AnyResult<()> {
+/// 标签使用情况
+#[derive(Debug, Serialize, Clone)]
+pub struct TagCount {
+    pub tag: String,
+    pub count: i64,
+}
+
+/// 获取所有标签及其关联的待办数量
+pub fn list_tags(database: &Database) -> AnyResult<Vec<TagCount>> {
+    let conn = database.get_connection();
+    Ok(tag_dao::list_tag_counts(conn)?
+        .into_iter()
+        .map(|(tag, count)| TagCount { tag, count })
+        .collect())
+}
+
+/// 显示标签列表
+pub fn show_tags(database: &Database) -> AnyResult<()> {
+    let tags = list_tags(database)?;
+
+    if tags.is_empty() {
+        println!("🏷️  暂无任何标签");
+        return Ok(());
+    }
+
+    println!("\n🏷️  标签列表 ({} 个):", tags.len());
+    println!("{:=<80}", "");
+    for tag in &tags {
+        println!("  {} ({} 项)", tag.tag, tag.count);
+    }
+    println!("{:=<80}", "");
+
+    Ok(())
+}
+
+/// 获取已完成的待办事项
+pub fn list_completed_todos(database: &Database) -> AnyResult<Vec<TodoListForm>> {
+    let conn = database.get_connection();
+    let todos = todo_list_dao::list_todos(conn)?;
+    Ok(todos.into_iter().filter(|t| t.is_done()).collect())
+}
+
+/// 获取未完成的待办事项（待办 / 进行中 / 受阻），按优先级和开始时间排序
+pub fn list_pending_todos(database: &Database) -> AnyResult<Vec<TodoListForm>> {
     let conn = database.get_connection();
     let todos = todo_list_dao::list_todos(conn)?;
+    let mut pending: Vec<_> = todos.into_iter().filter(|t| t.status.is_open()).collect();
+    pending.sort_by(|a, b| {
+        let rank = |t: &TodoListForm| t.priority.map(|p| p.rank()).unwrap_or(999);
+        rank(a).cmp(&rank(b)).then(a.begin_time.cmp(&b.begin_time))
+    });
+    Ok(pending)
+}
 
-    let completed_todos: Vec<_> = todos.iter().filter(|t| t.completed).collect();
+/// 显示已完成的待办事项
+pub fn show_completed_todos(database: &Database) -> AnyResult<()> {
+    let conn = database.get_connection();
+    let completed_todos = list_completed_todos(database)?;
+    let tracked = time_tracking_serv::tracked_durations(database)?;
 
     if completed_todos.is_empty() {
         println!("✅ 暂无已完成的待办事项");
@@ -78,26 +189,31 @@ pub fn show_completed_todos(database: &Database) -> AnyResult<()> {
     println!("{:=<80}", "");
     
     for (index, todo) in completed_todos.iter().enumerate() {
-        println!("{}. [ID: {}] {}", index + 1, todo.id, todo.title);
+        let archived = if todo.is_archived() { " 📦 已归档" } else { "" };
+        println!("{}. [ID: {}] {}{}", index + 1, todo.id, todo.title, archived);
         if let Some(desc) = &todo.description {
             println!("   描述: {}", desc);
         }
-        println!("   开始时间: {}", todo.begin_time.format("%Y-%m-%d %H:%M:%S"));
+        println!("   开始时间: {}", local_time::format(&todo.begin_time, "%Y-%m-%d %H:%M:%S"));
         if let Some(end_time) = &todo.end_time {
-            println!("   结束时间: {}", end_time.format("%Y-%m-%d %H:%M:%S"));
-            
-            // 计算用时
-            let duration = end_time.signed_duration_since(todo.begin_time);
+            println!("   截止时间: {}", local_time::format(end_time, "%Y-%m-%d %H:%M:%S"));
+        }
+        if let Some(completed_at) = &todo.completed_at {
+            println!("   完成时间: {}", local_time::format(completed_at, "%Y-%m-%d %H:%M:%S"));
+        }
+
+        // 实际用时：有计时的取累计计时，否则取开始到完成的时间
+        if let Some((duration, source)) = actual_duration(todo, &tracked) {
             let days = duration.num_days();
             let hours = duration.num_hours() % 24;
             let minutes = duration.num_minutes() % 60;
-            
+
             if days > 0 {
-                println!("   用时: {} 天 {} 小时 {} 分钟", days, hours, minutes);
+                println!("   用时: {} 天 {} 小时 {} 分钟（{}）", days, hours, minutes, source);
             } else if hours > 0 {
-                println!("   用时: {} 小时 {} 分钟", hours, minutes);
+                println!("   用时: {} 小时 {} 分钟（{}）", hours, minutes, source);
             } else {
-                println!("   用时: {} 分钟", minutes);
+                println!("   用时: {} 分钟（{}）", minutes, source);
             }
         }
         
@@ -116,9 +232,7 @@ pub fn show_completed_todos(database: &Database) -> AnyResult<()> {
 /// 显示未完成的待办事项
 pub fn show_pending_todos(database: &Database) -> AnyResult<()> {
     let conn = database.get_connection();
-    let todos = todo_list_dao::list_todos(conn)?;
-
-    let pending_todos: Vec<_> = todos.iter().filter(|t| !t.completed).collect();
+    let pending_todos = list_pending_todos(database)?;
 
     if pending_todos.is_empty() {
         println!("⬜ 暂无未完成的待办事项");
@@ -130,11 +244,17 @@ pub fn show_pending_todos(database: &Database) -> AnyResult<()> {
     println!("{:=<80}", "");
     
     for (index, todo) in pending_todos.iter().enumerate() {
-        println!("{}. [ID: {}] {}", index + 1, todo.id, todo.title);
+        println!("{}. {} [ID: {}] {} ({})", index + 1, todo.status.icon(), todo.id, todo.title, todo.status.label());
         if let Some(desc) = &todo.description {
             println!("   描述: {}", desc);
         }
-        println!("   开始时间: {}", todo.begin_time.format("%Y-%m-%d %H:%M:%S"));
+        if let Some(priority) = &todo.priority {
+            println!("   优先级: {}", priority.label());
+        }
+        if !todo.tags.is_empty() {
+            println!("   标签: {}", todo.tags.join(", "));
+        }
+        println!("   开始时间: {}", local_time::format(&todo.begin_time, "%Y-%m-%d %H:%M:%S"));
         
         // 计算已经过去的时间
         let elapsed = now.signed_duration_since(todo.begin_time);
@@ -156,7 +276,7 @@ pub fn show_pending_todos(database: &Database) -> AnyResult<()> {
                 let overdue_days = overdue.num_days();
                 println!("   ⚠️  已超期 {} 天", overdue_days);
             } else {
-                println!("   截止时间: {}", end_time.format("%Y-%m-%d %H:%M:%S"));
+                println!("   截止时间: {}", local_time::format(end_time, "%Y-%m-%d %H:%M:%S"));
             }
         }
         
@@ -172,17 +292,14 @@ pub fn show_pending_todos(database: &Database) -> AnyResult<()> {
     Ok(())
 }
 
-/// 显示最近的笔记
-pub fn show_recent_notes(database: &Database, limit: usize) -> AnyResult<()> {
+/// 收集笔记（包含所属待办的 ID 与标题），按待办ID和优先级排序后取前 limit 条
+pub fn collect_recent_notes(
+    database: &Database,
+    limit: usize,
+) -> AnyResult<Vec<(i32, String, NoteForm)>> {
     let conn = database.get_connection();
     let todos = todo_list_dao::list_todos(conn)?;
 
-    if todos.is_empty() {
-        println!("📝 暂无任何笔记");
-        return Ok(());
-    }
-
-    // 收集所有笔记（包含todo_id）
     let mut all_notes = Vec::new();
     for todo in &todos {
         let notes = note_dao::list_notes_by_todo_id(conn, todo.id)?;
@@ -191,11 +308,6 @@ pub fn show_recent_notes(database: &Database, limit: usize) -> AnyResult<()> {
         }
     }
 
-    if all_notes.is_empty() {
-        println!("📝 暂无任何笔记");
-        return Ok(());
-    }
-
     // 首先按待办事项ID排序，其次按优先级排序
     all_notes.sort_by(|a, b| {
         // 首先比较 todo_id
@@ -204,11 +316,21 @@ pub fn show_recent_notes(database: &Database, limit: usize) -> AnyResult<()> {
             return todo_cmp;
         }
         // todo_id 相同时，按优先级排序
-        parse_priority(&a.2.note_priority).cmp(&parse_priority(&b.2.note_priority))
+        Priority::rank_of_text(&a.2.note_priority).cmp(&Priority::rank_of_text(&b.2.note_priority))
     });
 
-    // 取前 limit 个
-    let recent_notes: Vec<_> = all_notes.iter().take(limit).collect();
+    all_notes.truncate(limit);
+    Ok(all_notes)
+}
+
+/// 显示最近的笔记
+pub fn show_recent_notes(database: &Database, limit: usize) -> AnyResult<()> {
+    let recent_notes = collect_recent_notes(database, limit)?;
+
+    if recent_notes.is_empty() {
+        println!("📝 暂无任何笔记");
+        return Ok(());
+    }
 
     println!("\n📝 笔记列表 (最多显示 {} 条，按待办ID和优先级排序):", limit);
     println!("{:=<80}", "");
@@ -217,7 +339,7 @@ pub fn show_recent_notes(database: &Database, limit: usize) -> AnyResult<()> {
         println!("{}. [笔记ID: {}] {}", index + 1, note.id, note.note_title);
         println!("   所属待办: [ID: {}] {}", todo_id, todo_title);
         println!("   内容: {}", note.note_content);
-        println!("   时间: {}", note.note_time.format("%Y-%m-%d %H:%M:%S"));
+        println!("   时间: {}", local_time::format(&note.note_time, "%Y-%m-%d %H:%M:%S"));
         if let Some(ref priority) = note.note_priority {
             println!("   优先级: {}", priority);
         }
@@ -229,4 +351,3 @@ pub fn show_recent_notes(database: &Database, limit: usize) -> AnyResult<()> {
 
     Ok(())
 }
-
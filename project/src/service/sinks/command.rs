@@ -0,0 +1,79 @@
+use anyhow::{Context, Result as AnyResult, bail};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::data::reminder::Notification;
+use crate::init::db_json_content::{ReminderConfig, SinkConfig};
+use crate::service::notifier::{self, NotificationAction, NotificationSink};
+
+/// 对每条提醒执行一次外部命令
+///
+/// 参数中可使用占位符 {id}、{title}、{message}、{kind}、{rule}、{urgency}、{reminder_id}，
+/// 同样的信息也通过环境变量 TODO_ID、TODO_TITLE、REMINDER_MESSAGE、REMINDER_KIND、
+/// REMINDER_RULE、REMINDER_URGENCY、REMINDER_ID 传入；命令以非 0 状态退出或超时视为失败
+pub struct CommandSink {
+    program: String,
+    args: Vec<String>,
+    timeout: Duration,
+}
+
+pub fn create(config: &SinkConfig, _reminder: &ReminderConfig) -> AnyResult<Box<dyn NotificationSink>> {
+    Ok(Box::new(CommandSink {
+        program: config.command.clone().unwrap_or_default().trim().to_string(),
+        args: config.args.clone(),
+        timeout: Duration::from_secs(config.timeout_seconds()),
+    }))
+}
+
+impl CommandSink {
+    fn run(&self, notification: &Notification) -> AnyResult<()> {
+        let fields = [
+            ("{id}", "TODO_ID", notification.todo_id.to_string()),
+            ("{title}", "TODO_TITLE", notification.todo_title.clone()),
+            ("{message}", "REMINDER_MESSAGE", notification.message.clone()),
+            ("{kind}", "REMINDER_KIND", notification.kind.to_string()),
+            ("{rule}", "REMINDER_RULE", notification.rule_key.clone()),
+            ("{urgency}", "REMINDER_URGENCY", notification.urgency().as_str().to_string()),
+            ("{reminder_id}", "REMINDER_ID", notification.history_id.to_string()),
+        ];
+
+        let mut command = Command::new(&self.program);
+        for arg in &self.args {
+            let arg = fields
+                .iter()
+                .fold(arg.clone(), |arg, (placeholder, _, value)| arg.replace(placeholder, value));
+            command.arg(arg);
+        }
+        for (_, env, value) in &fields {
+            command.env(env, value);
+        }
+
+        let mut child = command
+            .stdin(Stdio::null())
+            .spawn()
+            .with_context(|| format!("无法执行命令 {}", self.program))?;
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            if let Some(status) = child.try_wait()? {
+                if !status.success() {
+                    bail!("命令 {} 执行失败（{}）", self.program, status);
+                }
+                return Ok(());
+            }
+            if Instant::now() >= deadline {
+                let _ = child.kill();
+                let _ = child.wait();
+                bail!("命令 {} 超过 {} 秒未结束，已终止", self.program, self.timeout.as_secs());
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+    }
+}
+
+impl NotificationSink for CommandSink {
+    fn send(&mut self, notifications: &[&Notification]) -> AnyResult<Vec<NotificationAction>> {
+        notifier::send_each(notifications, |notification| self.run(notification))?;
+        Ok(Vec::new())
+    }
+}
@@ -0,0 +1,31 @@
+use anyhow::Result as AnyResult;
+
+use crate::data::reminder::Notification;
+use crate::init::db_json_content::{ReminderConfig, SinkConfig};
+use crate::service::notifier::{NotificationAction, NotificationSink};
+
+/// 在终端输出提醒
+pub struct ConsoleSink;
+
+pub fn create(_config: &SinkConfig, _reminder: &ReminderConfig) -> AnyResult<Box<dyn NotificationSink>> {
+    Ok(Box::new(ConsoleSink))
+}
+
+/// 输出一条控制台通知
+pub fn print(message: &str) {
+    println!("\n{}", "=".repeat(60));
+    println!("⏰ 提醒通知");
+    println!("{}", "=".repeat(60));
+    println!("{}", message);
+    println!("{}", "=".repeat(60));
+    println!();
+}
+
+impl NotificationSink for ConsoleSink {
+    fn send(&mut self, notifications: &[&Notification]) -> AnyResult<Vec<NotificationAction>> {
+        for notification in notifications {
+            print(&notification.message);
+        }
+        Ok(Vec::new())
+    }
+}
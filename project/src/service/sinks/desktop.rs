@@ -0,0 +1,103 @@
+//! 系统桌面通知：Windows 托盘弹窗与 Linux freedesktop 通知
+
+use anyhow::Result as AnyResult;
+#[cfg(target_os = "linux")]
+use std::time::Duration;
+
+#[cfg(any(windows, target_os = "linux"))]
+use crate::data::reminder::Notification;
+use crate::init::db_json_content::{ReminderConfig, SinkConfig};
+#[cfg(any(windows, target_os = "linux"))]
+use crate::service::notifier::NotificationAction;
+use crate::service::notifier::NotificationSink;
+
+/// Windows 系统通知
+#[cfg(windows)]
+pub struct WindowsSink;
+
+#[cfg(windows)]
+pub fn create_windows(_config: &SinkConfig, _reminder: &ReminderConfig) -> AnyResult<Box<dyn NotificationSink>> {
+    Ok(Box::new(WindowsSink))
+}
+
+#[cfg(not(windows))]
+pub fn create_windows(_config: &SinkConfig, _reminder: &ReminderConfig) -> AnyResult<Box<dyn NotificationSink>> {
+    anyhow::bail!("Windows 通知仅支持 Windows 系统")
+}
+
+#[cfg(windows)]
+impl NotificationSink for WindowsSink {
+    fn send(&mut self, notifications: &[&Notification]) -> AnyResult<Vec<NotificationAction>> {
+        use winrt_notification::{Toast, Duration as ToastDuration};
+
+        for notification in notifications {
+            Toast::new(Toast::POWERSHELL_APP_ID)
+                .title("TodoList 提醒")
+                .text1(&notification.message)
+                .duration(ToastDuration::Short)
+                .show()?;
+        }
+        Ok(Vec::new())
+    }
+}
+
+/// Linux 桌面通知（freedesktop D-Bus 通知服务）
+///
+/// action_wait 大于 0 且通知服务支持操作按钮时，附带 "完成" / "稍后提醒" / "知道了" 按钮，
+/// 并最多等待 action_wait 让用户点击，返回被点击的操作
+///
+/// 第一次发送时连接会话总线，之后复用同一条连接；发送失败时断开，下次发送时重新连接
+#[cfg(target_os = "linux")]
+pub struct LinuxSink {
+    action_wait: Duration,
+    notifier: Option<crate::service::linux_notifier::LinuxNotifier>,
+}
+
+#[cfg(target_os = "linux")]
+pub fn create_linux(config: &SinkConfig, reminder: &ReminderConfig) -> AnyResult<Box<dyn NotificationSink>> {
+    let seconds = config.action_wait_seconds.unwrap_or(reminder.action_wait_seconds);
+    Ok(Box::new(LinuxSink { action_wait: Duration::from_secs(seconds), notifier: None }))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn create_linux(_config: &SinkConfig, _reminder: &ReminderConfig) -> AnyResult<Box<dyn NotificationSink>> {
+    anyhow::bail!("Linux 桌面通知仅支持 Linux 系统")
+}
+
+#[cfg(target_os = "linux")]
+impl NotificationSink for LinuxSink {
+    fn send(&mut self, notifications: &[&Notification]) -> AnyResult<Vec<NotificationAction>> {
+        use crate::service::linux_notifier::LinuxNotifier;
+
+        // 连接失败按发送失败处理
+        let notifier = match self.notifier.take() {
+            Some(notifier) => notifier,
+            None => LinuxNotifier::connect()?,
+        };
+        let with_actions = !self.action_wait.is_zero() && notifier.supports_actions();
+        let mut sent = Vec::new();
+        for notification in notifications {
+            // 出错时丢弃连接（不放回 self.notifier）
+            let id = notifier.send(notification, with_actions)?;
+            sent.push((id, *notification));
+        }
+        let invoked = if with_actions {
+            let ids: Vec<u32> = sent.iter().map(|(id, _)| *id).collect();
+            notifier.wait_for_actions(&ids, self.action_wait)
+        } else {
+            Vec::new()
+        };
+        self.notifier = Some(notifier);
+        Ok(invoked
+            .into_iter()
+            .filter_map(|invoked| {
+                sent.iter()
+                    .find(|(id, _)| *id == invoked.notification_id)
+                    .map(|(_, notification)| NotificationAction {
+                        notification: (*notification).clone(),
+                        action: invoked.action,
+                    })
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,68 @@
+use anyhow::{Context, Result as AnyResult};
+use lettre::message::{Mailbox, header::ContentType};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Message, SmtpTransport, Transport};
+use std::time::Duration;
+
+use crate::data::reminder::Notification;
+use crate::init::db_json_content::{ReminderConfig, SinkConfig};
+use crate::service::notifier::{NotificationAction, NotificationSink};
+
+/// 通过 SMTP 发送邮件，一次检查中的提醒合并为一封
+///
+/// 不使用 TLS，适合本机或内网的邮件中继（postfix、msmtpd 等），缺省端口 25
+pub struct EmailSink {
+    from: Mailbox,
+    to: Vec<Mailbox>,
+    transport: SmtpTransport,
+}
+
+pub fn create(config: &SinkConfig, _reminder: &ReminderConfig) -> AnyResult<Box<dyn NotificationSink>> {
+    let from_text = config.from.as_deref().unwrap_or_default();
+    let from = from_text
+        .parse::<Mailbox>()
+        .with_context(|| format!("无效的发件人地址 {}", from_text))?;
+    let to = config
+        .to
+        .iter()
+        .map(|address| {
+            address
+                .parse::<Mailbox>()
+                .with_context(|| format!("无效的收件人地址 {}", address))
+        })
+        .collect::<AnyResult<Vec<_>>>()?;
+
+    let host = config.smtp_host.as_deref().unwrap_or_default().trim();
+    let mut builder = SmtpTransport::builder_dangerous(host)
+        .port(config.smtp_port.unwrap_or(25))
+        .timeout(Some(Duration::from_secs(config.timeout_seconds())));
+    if let (Some(username), Some(password)) = (&config.username, &config.password) {
+        builder = builder.credentials(Credentials::new(username.clone(), password.clone()));
+    }
+    Ok(Box::new(EmailSink { from, to, transport: builder.build() }))
+}
+
+impl NotificationSink for EmailSink {
+    fn send(&mut self, notifications: &[&Notification]) -> AnyResult<Vec<NotificationAction>> {
+        let subject = match notifications {
+            [single] => format!("TodoList 提醒 · {}", single.todo_title),
+            _ => format!("TodoList 提醒（{} 条）", notifications.len()),
+        };
+        let body = notifications
+            .iter()
+            .map(|n| format!("• {}", n.message))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let mut builder = Message::builder().from(self.from.clone()).subject(subject);
+        for to in &self.to {
+            builder = builder.to(to.clone());
+        }
+        let email = builder
+            .header(ContentType::TEXT_PLAIN)
+            .body(body)
+            .context("生成邮件失败")?;
+        self.transport.send(&email).context("SMTP 发送失败")?;
+        Ok(Vec::new())
+    }
+}
@@ -0,0 +1,57 @@
+use anyhow::{Context, Result as AnyResult};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+use crate::data::local_time;
+use crate::data::reminder::Notification;
+use crate::init::db_json_content::{DEFAULT_SINK_LOG, ReminderConfig, SinkConfig};
+use crate::service::notifier::{NotificationAction, NotificationSink};
+use crate::service::sinks::webhook;
+
+/// 将提醒追加写入日志文件，格式为文本行或 JSON Lines
+pub struct LogFileSink {
+    path: PathBuf,
+    json: bool,
+}
+
+pub fn create(config: &SinkConfig, _reminder: &ReminderConfig) -> AnyResult<Box<dyn NotificationSink>> {
+    let path = config.path.as_deref().filter(|p| !p.trim().is_empty()).unwrap_or(DEFAULT_SINK_LOG);
+    Ok(Box::new(LogFileSink {
+        path: PathBuf::from(path),
+        json: config.format.as_deref() == Some("json"),
+    }))
+}
+
+impl NotificationSink for LogFileSink {
+    fn send(&mut self, notifications: &[&Notification]) -> AnyResult<Vec<NotificationAction>> {
+        if let Some(dir) = self.path.parent().filter(|d| !d.as_os_str().is_empty() && !d.exists()) {
+            fs::create_dir_all(dir).with_context(|| format!("无法创建目录 {}", dir.display()))?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("无法打开日志文件 {}", self.path.display()))?;
+
+        let mut content = String::new();
+        for notification in notifications {
+            if self.json {
+                content.push_str(&webhook::payload(notification).to_string());
+            } else {
+                content.push_str(&format!(
+                    "[{}] [{}] [{}] [ID:{}] {}",
+                    local_time::now().format("%Y-%m-%d %H:%M:%S"),
+                    notification.kind,
+                    notification.rule_key,
+                    notification.todo_id,
+                    notification.message
+                ));
+            }
+            content.push('\n');
+        }
+        file.write_all(content.as_bytes())
+            .with_context(|| format!("写入日志文件 {} 失败", self.path.display()))?;
+        Ok(Vec::new())
+    }
+}
@@ -0,0 +1,8 @@
+// 通知渠道的实现，由 notifier::SinkRegistry 按类型名创建
+
+pub mod console;
+pub mod desktop;
+pub mod log_file;
+pub mod webhook;
+pub mod email;
+pub mod command;
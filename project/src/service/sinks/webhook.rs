@@ -0,0 +1,62 @@
+use anyhow::{Context, Result as AnyResult};
+use chrono::DateTime;
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::data::local_time;
+use crate::data::reminder::Notification;
+use crate::init::db_json_content::{ReminderConfig, SinkConfig};
+use crate::service::notifier::{self, NotificationAction, NotificationSink};
+
+/// 以 JSON POST 到指定 URL，每条提醒一个请求
+pub struct WebhookSink {
+    url: String,
+    headers: BTreeMap<String, String>,
+    agent: ureq::Agent,
+}
+
+pub fn create(config: &SinkConfig, _reminder: &ReminderConfig) -> AnyResult<Box<dyn NotificationSink>> {
+    let agent = ureq::Agent::config_builder()
+        .timeout_global(Some(Duration::from_secs(config.timeout_seconds())))
+        .build()
+        .into();
+    Ok(Box::new(WebhookSink {
+        url: config.url.clone().unwrap_or_default().trim().to_string(),
+        headers: config.headers.clone(),
+        agent,
+    }))
+}
+
+/// 提醒的 JSON 表示；`text` 字段可直接被 Slack / Mattermost 等的 incoming webhook 显示
+pub fn payload(notification: &Notification) -> Value {
+    let trigger_at = DateTime::from_timestamp(notification.trigger_at, 0)
+        .map(|t| local_time::to_local(&t).to_rfc3339());
+    json!({
+        "text": notification.message,
+        "reminder_id": notification.history_id,
+        "todo_id": notification.todo_id,
+        "title": notification.todo_title,
+        "priority": notification.priority.map(|p| p.as_str()),
+        "kind": notification.kind.to_string(),
+        "rule": notification.rule_key,
+        "urgency": notification.urgency().as_str(),
+        "trigger_at": trigger_at,
+    })
+}
+
+impl NotificationSink for WebhookSink {
+    fn send(&mut self, notifications: &[&Notification]) -> AnyResult<Vec<NotificationAction>> {
+        notifier::send_each(notifications, |notification| {
+            let mut request = self.agent.post(&self.url).header("Content-Type", "application/json");
+            for (name, value) in &self.headers {
+                request = request.header(name, value);
+            }
+            request
+                .send(payload(notification).to_string())
+                .with_context(|| format!("请求 {} 失败", self.url))?;
+            Ok(())
+        })?;
+        Ok(Vec::new())
+    }
+}
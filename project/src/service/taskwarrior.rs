@@ -0,0 +1,299 @@
+//! Taskwarrior 导入
+//!
+//! 读取 `task export` 输出的 JSON 数组：
+//!
+//! - description → 标题，status → 状态（pending / waiting 为待办，有 start 时为进行中，
+//!   completed 为已完成，deleted 为已取消），end → 完成时间，priority H / M / L → 高 / 中 / 低
+//! - project 与 tags → 标签，due → 截止时间，scheduled（其次 wait、entry）→ 开始时间
+//! - annotations → 笔记，depends → 任务依赖
+//! - 重复任务的模板（status 为 recurring）不导入，由它生成的各次任务作为普通待办导入
+//!
+//! 其余字段（如 until、recur 与自定义属性）无法对应，导入报告中列出字段名与涉及的任务数。
+
+use anyhow::{Context, Result as AnyResult, anyhow};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::data::note::NoteForm;
+use crate::data::todo_list::{Priority, TodoListForm, TodoStatus};
+use crate::data::transfer::{Dependency, ExportDocument};
+
+/// 已经使用或不需要导入的字段（计算值、内部标识、重复任务与模板的关联等）
+const HANDLED_FIELDS: [&str; 20] = [
+    "id", "uuid", "description", "status", "priority", "project", "tags", "due", "scheduled", "wait",
+    "entry", "start", "annotations", "depends", "urgency", "modified", "end", "mask", "imask", "parent",
+];
+
+/// 是否为 Taskwarrior 的导出内容（JSON 数组）
+pub fn looks_like_export(content: &str) -> bool {
+    content.trim_start().starts_with('[')
+}
+
+/// 解析 `task export` 的输出
+pub fn parse(content: &str) -> AnyResult<ExportDocument> {
+    let tasks: Vec<Map<String, Value>> =
+        serde_json::from_str(content).context("不是有效的 Taskwarrior 导出文件（应为 task export 输出的 JSON 数组）")?;
+    let mut document = ExportDocument::new(None);
+    let mut unmapped: BTreeMap<String, usize> = BTreeMap::new();
+
+    // 待办的临时 ID 使用 Taskwarrior 的任务编号，已完成 / 已删除的任务编号为 0，依次排在后面
+    let max_id = tasks.iter().filter_map(|t| t.get("id").and_then(Value::as_i64)).max().unwrap_or(0);
+    let mut next_id = max_id as i32;
+    let mut uuids: HashMap<String, i32> = HashMap::new();
+    let mut ids = Vec::with_capacity(tasks.len());
+    for task in &tasks {
+        let id = match task.get("id").and_then(Value::as_i64).filter(|id| *id > 0) {
+            Some(id) => id as i32,
+            None => {
+                next_id += 1;
+                next_id
+            }
+        };
+        if let Some(uuid) = task.get("uuid").and_then(Value::as_str) {
+            uuids.insert(uuid.to_string(), id);
+        }
+        ids.push(id);
+    }
+
+    let mut templates = 0;
+    let mut note_id = 0;
+    for (task, id) in tasks.iter().zip(ids) {
+        let status = str_field(task, "status").unwrap_or("pending");
+        if status == "recurring" {
+            templates += 1;
+            continue;
+        }
+        for key in task.keys().filter(|k| !HANDLED_FIELDS.contains(&k.as_str())) {
+            *unmapped.entry(key.clone()).or_default() += 1;
+        }
+        let todo = match task_todo(task, id, status) {
+            Ok(todo) => todo,
+            Err(e) => {
+                document.read_warnings.push(format!("任务 #{}: {}，已忽略", id, e));
+                continue;
+            }
+        };
+
+        for annotation in task.get("annotations").and_then(Value::as_array).into_iter().flatten() {
+            let Some(text) = annotation.get("description").and_then(Value::as_str) else {
+                continue;
+            };
+            note_id += 1;
+            document.notes.push(NoteForm {
+                id: note_id,
+                todo_id: id,
+                note_title: "Taskwarrior 注释".to_string(),
+                note_content: text.to_string(),
+                note_time: annotation.get("entry").and_then(Value::as_str).and_then(parse_time).unwrap_or_else(Utc::now),
+                noter: None,
+                note_type: None,
+                note_status: None,
+                note_tag: None,
+                note_priority: None,
+            });
+        }
+
+        // 旧版本为逗号分隔的字符串，新版本为数组
+        let depends: Vec<&str> = match task.get("depends") {
+            Some(Value::String(text)) => text.split(',').map(str::trim).filter(|u| !u.is_empty()).collect(),
+            Some(Value::Array(items)) => items.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        for uuid in depends {
+            match uuids.get(uuid) {
+                Some(&depends_on_id) => document.dependencies.push(Dependency { todo_id: id, depends_on_id }),
+                None => document.read_warnings.push(format!("任务 #{} 依赖的 {} 不在文件中，已忽略", id, uuid)),
+            }
+        }
+        document.todos.push(todo);
+    }
+
+    if templates > 0 {
+        document.read_warnings.push(format!(
+            "跳过 {} 个重复任务模板（status: recurring），它生成的各次任务作为普通待办导入",
+            templates
+        ));
+    }
+    if !unmapped.is_empty() {
+        let fields: Vec<String> = unmapped.iter().map(|(key, count)| format!("{}（{} 个任务）", key, count)).collect();
+        document.read_warnings.push(format!("以下字段无法对应，未导入: {}", fields.join("、")));
+    }
+    Ok(document)
+}
+
+fn task_todo(task: &Map<String, Value>, id: i32, status: &str) -> AnyResult<TodoListForm> {
+    let title = str_field(task, "description")
+        .map(str::trim)
+        .filter(|d| !d.is_empty())
+        .ok_or_else(|| anyhow!("缺少 description"))?;
+    let time_field = |key: &str| -> AnyResult<Option<DateTime<Utc>>> {
+        str_field(task, key)
+            .map(|value| parse_time(value).ok_or_else(|| anyhow!("{} 不是有效的时间: {}", key, value)))
+            .transpose()
+    };
+
+    let status = match status {
+        "completed" => TodoStatus::Done,
+        "deleted" => TodoStatus::Cancelled,
+        _ if task.contains_key("start") => TodoStatus::InProgress,
+        _ => TodoStatus::Todo,
+    };
+    let mut tags: Vec<String> = Vec::new();
+    if let Some(project) = str_field(task, "project") {
+        tags.push(project.to_string());
+    }
+    for tag in task.get("tags").and_then(Value::as_array).into_iter().flatten().filter_map(Value::as_str) {
+        if !tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            tags.push(tag.to_string());
+        }
+    }
+    let begin_time = match time_field("scheduled")? {
+        Some(time) => time,
+        None => time_field("wait")?.or(time_field("entry")?).unwrap_or_else(Utc::now),
+    };
+
+    Ok(TodoListForm {
+        id,
+        title: title.to_string(),
+        description: None,
+        status,
+        priority: match str_field(task, "priority") {
+            Some("H") => Some(Priority::High),
+            Some("M") => Some(Priority::Medium),
+            Some("L") => Some(Priority::Low),
+            _ => None,
+        },
+        tags,
+        begin_time,
+        end_time: time_field("due")?,
+        key_message1: None,
+        key_message2: None,
+        key_message3: None,
+        recurrence: None,
+        series_id: None,
+        occurrence: 1,
+        parent_id: None,
+        archived_at: None,
+        completed_at: if status == TodoStatus::Done { time_field("end")? } else { None },
+    })
+}
+
+fn str_field<'a>(task: &'a Map<String, Value>, key: &str) -> Option<&'a str> {
+    task.get(key).and_then(Value::as_str)
+}
+
+/// Taskwarrior 的时间格式：20261018T120000Z
+fn parse_time(value: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%SZ")
+        .map(|dt| dt.and_utc())
+        .ok()
+        .or_else(|| DateTime::parse_from_rfc3339(value).ok().map(|dt| dt.with_timezone(&Utc)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn utc(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+    }
+
+    fn find<'a>(document: &'a ExportDocument, title: &str) -> &'a TodoListForm {
+        document.todos.iter().find(|t| t.title == title).unwrap()
+    }
+
+    const EXPORT: &str = r#"[
+        {"id": 1, "uuid": "u-1", "description": "写周报", "status": "pending", "priority": "H",
+         "project": "work", "tags": ["weekly", "Work"], "entry": "20300101T080000Z",
+         "scheduled": "20300103T090000Z", "due": "20300105T170000Z", "urgency": 9.1,
+         "annotations": [{"entry": "20300102T100000Z", "description": "参考上周的格式"}]},
+        {"id": 2, "uuid": "u-2", "description": "整理数据", "status": "waiting", "priority": "L",
+         "entry": "20300101T080000Z", "wait": "20300104T000000Z", "depends": "u-1,u-3"},
+        {"id": 3, "uuid": "u-3", "description": "正在做", "status": "pending", "priority": "M",
+         "entry": "20300101T080000Z", "start": "20300101T090000Z", "depends": ["u-1"]},
+        {"id": 0, "uuid": "u-4", "description": "已完成", "status": "completed",
+         "entry": "20300101T080000Z", "end": "20300102T120000Z", "recur": "weekly"},
+        {"id": 0, "uuid": "u-5", "description": "已删除", "status": "deleted",
+         "entry": "20300101T080000Z", "end": "20300102T120000Z"},
+        {"id": 0, "uuid": "u-6", "description": "模板", "status": "recurring", "recur": "weekly",
+         "entry": "20300101T080000Z"}
+    ]"#;
+
+    #[test]
+    fn maps_status_and_priority() {
+        let document = parse(EXPORT).unwrap();
+        assert_eq!(document.todos.len(), 5);
+
+        let report = find(&document, "写周报");
+        assert_eq!(report.id, 1);
+        assert_eq!(report.status, TodoStatus::Todo);
+        assert_eq!(report.priority, Some(Priority::High));
+        assert_eq!(find(&document, "整理数据").status, TodoStatus::Todo);
+        assert_eq!(find(&document, "整理数据").priority, Some(Priority::Low));
+        // 有 start 的未完成任务为进行中
+        assert_eq!(find(&document, "正在做").status, TodoStatus::InProgress);
+        assert_eq!(find(&document, "正在做").priority, Some(Priority::Medium));
+
+        // 已完成 / 已删除的任务编号为 0，临时 ID 排在最大编号之后
+        let done = find(&document, "已完成");
+        assert_eq!(done.id, 4);
+        assert_eq!(done.status, TodoStatus::Done);
+        assert_eq!(done.priority, None);
+        assert_eq!(done.completed_at, Some(utc(2030, 1, 2, 12, 0)));
+        let deleted = find(&document, "已删除");
+        assert_eq!(deleted.id, 5);
+        assert_eq!(deleted.status, TodoStatus::Cancelled);
+        assert_eq!(deleted.completed_at, None);
+    }
+
+    #[test]
+    fn maps_times_tags_and_annotations() {
+        let document = parse(EXPORT).unwrap();
+        let report = find(&document, "写周报");
+        // project 与 tags 合并为标签，不区分大小写去重
+        assert_eq!(report.tags, vec!["work", "weekly"]);
+        // scheduled 优先于 wait 与 entry
+        assert_eq!(report.begin_time, utc(2030, 1, 3, 9, 0));
+        assert_eq!(report.end_time, Some(utc(2030, 1, 5, 17, 0)));
+        assert_eq!(find(&document, "整理数据").begin_time, utc(2030, 1, 4, 0, 0));
+        assert_eq!(find(&document, "正在做").begin_time, utc(2030, 1, 1, 8, 0));
+
+        assert_eq!(document.notes.len(), 1);
+        let note = &document.notes[0];
+        assert_eq!(note.todo_id, report.id);
+        assert_eq!(note.note_content, "参考上周的格式");
+        assert_eq!(note.note_time, utc(2030, 1, 2, 10, 0));
+    }
+
+    #[test]
+    fn maps_dependencies_and_reports_the_rest() {
+        let document = parse(EXPORT).unwrap();
+        // 字符串与数组两种 depends 写法
+        let pairs: Vec<(i32, i32)> = document.dependencies.iter().map(|d| (d.todo_id, d.depends_on_id)).collect();
+        assert_eq!(pairs, vec![(2, 1), (2, 3), (3, 1)]);
+
+        let warnings = document.read_warnings.join("\n");
+        assert!(warnings.contains("跳过 1 个重复任务模板"), "{warnings}");
+        assert!(warnings.contains("recur（1 个任务）"), "{warnings}");
+        assert!(!warnings.contains("urgency"), "{warnings}");
+    }
+
+    #[test]
+    fn rejects_invalid_tasks() {
+        assert!(parse("not json").is_err());
+        assert!(parse(r#"{"description": "不是数组"}"#).is_err());
+
+        let document = parse(
+            r#"[{"id": 1, "description": "  ", "status": "pending"},
+                {"id": 2, "description": "坏时间", "due": "tomorrow"},
+                {"id": 3, "description": "好的", "depends": ["missing"]}]"#,
+        )
+        .unwrap();
+        assert_eq!(document.todos.len(), 1);
+        assert_eq!(document.todos[0].title, "好的");
+        assert_eq!(document.dependencies.len(), 0);
+        assert_eq!(document.read_warnings.len(), 3, "{:?}", document.read_warnings);
+    }
+}
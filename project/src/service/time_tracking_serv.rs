@@ -0,0 +1,247 @@
+//! 计时
+//!
+//! `track start <ID>` 开始对待办事项计时，`track stop` 停止，`track status` 查看：
+//! - 同一时间只有一个正在进行的计时，开始新的计时时自动停止上一个
+//! - 对待办状态的待办事项开始计时时改为进行中；待办事项完成或取消时停止它的计时
+//! - 查看模式的报表中，有计时的待办事项以累计计时作为实际用时
+//!
+//! 开始与停止都记入修改历史，可以撤销。
+
+use anyhow::{Result as AnyResult, bail};
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::Connection;
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::dao::{time_tracking_dao, todo_list_dao};
+use crate::data::local_time;
+use crate::data::time_tracking::{self, CompletionEvent, TimeSession};
+use crate::data::todo_list::{TodoListForm, TodoStatus};
+use crate::init::database::Database;
+use crate::service::change_log_serv;
+use crate::service::output::{self, OutputFormat};
+
+/// 开始计时的结果
+pub struct Started {
+    pub todo: TodoListForm,
+    /// 被自动停止的上一个计时及其待办事项
+    pub stopped: Option<(TimeSession, TodoListForm)>,
+}
+
+/// 开始对待办事项计时；不存在时返回 None
+pub fn start(database: &Database, todo_id: i32) -> AnyResult<Option<Started>> {
+    let conn = database.get_connection();
+    let Some(todo) = todo_list_dao::get_todo_by_id(conn, todo_id)? else {
+        return Ok(None);
+    };
+    if !todo.status.is_open() {
+        bail!("待办事项 #{}「{}」{}，不能开始计时", todo.id, todo.title, todo.status.label());
+    }
+    let running = time_tracking_dao::running_session(conn)?;
+    if running.as_ref().is_some_and(|s| s.todo_id == todo_id) {
+        bail!("待办事项 #{}「{}」正在计时", todo.id, todo.title);
+    }
+
+    let action = format!("开始计时 #{}「{}」", todo.id, todo.title);
+    change_log_serv::record(database, &action, || {
+        let now = Utc::now();
+        let stopped = match running {
+            Some(session) => {
+                time_tracking_dao::stop_session(conn, session.id, &now)?;
+                let previous = todo_list_dao::get_todo_by_id(conn, session.todo_id)?;
+                previous.map(|t| (TimeSession { stopped_at: Some(now), ..session }, t))
+            }
+            None => None,
+        };
+        time_tracking_dao::start_session(conn, todo.id, &now)?;
+        if todo.status == TodoStatus::Todo {
+            todo_list_dao::set_status(conn, todo.id, TodoStatus::InProgress)?;
+        }
+        Ok(Some(Started { todo: todo.clone(), stopped }))
+    })
+}
+
+/// 停止正在进行的计时，返回停止的计时及其待办事项；没有正在进行的计时时返回 None
+pub fn stop(database: &Database) -> AnyResult<Option<(TimeSession, TodoListForm)>> {
+    let conn = database.get_connection();
+    let Some(session) = time_tracking_dao::running_session(conn)? else {
+        return Ok(None);
+    };
+    let Some(todo) = todo_list_dao::get_todo_by_id(conn, session.todo_id)? else {
+        return Ok(None);
+    };
+
+    let action = format!("停止计时 #{}「{}」", todo.id, todo.title);
+    change_log_serv::record(database, &action, || {
+        let now = Utc::now();
+        time_tracking_dao::stop_session(conn, session.id, &now)?;
+        Ok(Some((TimeSession { stopped_at: Some(now), ..session }, todo)))
+    })
+}
+
+/// 停止某个待办事项正在进行的计时（待办事项完成或取消时调用，在调用方的操作中执行），返回计时长度
+pub fn stop_for_todo(conn: &Connection, todo_id: i32) -> AnyResult<Option<Duration>> {
+    match time_tracking_dao::running_session(conn)? {
+        Some(session) if session.todo_id == todo_id => {
+            let now = Utc::now();
+            time_tracking_dao::stop_session(conn, session.id, &now)?;
+            Ok(Some(session.duration(now)))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// 每个待办事项的累计计时（正在进行的计时算到现在）
+pub fn tracked_durations(database: &Database) -> AnyResult<HashMap<i32, Duration>> {
+    let now = Utc::now();
+    let mut totals: HashMap<i32, Duration> = HashMap::new();
+    for session in time_tracking_dao::list_sessions(database.get_connection())? {
+        *totals.entry(session.todo_id).or_insert_with(Duration::zero) += session.duration(now);
+    }
+    Ok(totals)
+}
+
+/// 正在进行的计时
+#[derive(Debug, Serialize, Clone)]
+pub struct RunningTrack {
+    pub todo_id: i32,
+    pub title: String,
+    pub started_at: DateTime<Utc>,
+    pub elapsed_seconds: i64,
+}
+
+/// 某个待办事项的计时情况
+#[derive(Debug, Serialize, Clone)]
+pub struct TodoTrack {
+    pub todo_id: i32,
+    pub title: String,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub tracked_seconds: i64,
+    pub sessions: Vec<TimeSession>,
+    pub events: Vec<CompletionEvent>,
+}
+
+/// 计时状态（文本与 JSON 输出共用）
+#[derive(Debug, Serialize, Clone)]
+pub struct TrackStatus {
+    pub running: Option<RunningTrack>,
+    /// 指定了待办事项时为它的计时情况
+    pub todo: Option<TodoTrack>,
+}
+
+/// 收集计时状态；指定的待办事项不存在时返回 None
+pub fn collect_status(database: &Database, todo_id: Option<i32>) -> AnyResult<Option<TrackStatus>> {
+    let conn = database.get_connection();
+    let now = Utc::now();
+
+    let mut running = None;
+    if let Some(session) = time_tracking_dao::running_session(conn)?
+        && let Some(todo) = todo_list_dao::get_todo_by_id(conn, session.todo_id)?
+    {
+        running = Some(RunningTrack {
+            todo_id: todo.id,
+            title: todo.title,
+            started_at: session.started_at,
+            elapsed_seconds: session.duration(now).num_seconds(),
+        });
+    }
+
+    let todo = match todo_id {
+        Some(id) => {
+            let Some(todo) = todo_list_dao::get_todo_by_id(conn, id)? else {
+                return Ok(None);
+            };
+            let sessions = time_tracking_dao::list_sessions_by_todo(conn, id)?;
+            Some(TodoTrack {
+                todo_id: todo.id,
+                title: todo.title,
+                completed_at: todo.completed_at,
+                tracked_seconds: sessions.iter().map(|s| s.duration(now).num_seconds()).sum(),
+                sessions,
+                events: time_tracking_dao::list_completion_events(conn, id)?,
+            })
+        }
+        None => None,
+    };
+    Ok(Some(TrackStatus { running, todo }))
+}
+
+/// 输出计时状态；指定的待办事项不存在时返回 false
+pub fn print_status(database: &Database, todo_id: Option<i32>, format: OutputFormat) -> AnyResult<bool> {
+    let Some(status) = collect_status(database, todo_id)? else {
+        return Ok(false);
+    };
+    if format != OutputFormat::Text {
+        output::print_records(&[status], format)?;
+        return Ok(true);
+    }
+
+    match &status.running {
+        Some(running) => println!(
+            "⏱️  正在计时: [ID: {}] {}（开始于 {}，已计时 {}）",
+            running.todo_id,
+            running.title,
+            local_time::format(&running.started_at, "%Y-%m-%d %H:%M"),
+            time_tracking::format_duration(Duration::seconds(running.elapsed_seconds))
+        ),
+        None => println!("⏱️  当前没有正在进行的计时"),
+    }
+
+    if let Some(track) = &status.todo {
+        println!("\n⏱️  [ID: {}] {}", track.todo_id, track.title);
+        println!("{}", "=".repeat(80));
+        println!("累计计时: {}", time_tracking::format_duration(Duration::seconds(track.tracked_seconds)));
+        if let Some(completed_at) = &track.completed_at {
+            println!("完成时间: {}", local_time::format(completed_at, "%Y-%m-%d %H:%M:%S"));
+        }
+        if !track.sessions.is_empty() {
+            println!("计时记录:");
+            for session in &track.sessions {
+                let stopped = match &session.stopped_at {
+                    Some(stopped_at) => local_time::format(stopped_at, "%Y-%m-%d %H:%M"),
+                    None => "进行中".to_string(),
+                };
+                println!(
+                    "  {} ~ {}  ({})",
+                    local_time::format(&session.started_at, "%Y-%m-%d %H:%M"),
+                    stopped,
+                    time_tracking::format_duration(session.duration(Utc::now()))
+                );
+            }
+        }
+        if !track.events.is_empty() {
+            println!("完成记录:");
+            for event in &track.events {
+                println!("  {}  {}", local_time::format(&event.created_at, "%Y-%m-%d %H:%M:%S"), event.event.label());
+            }
+        }
+        println!("{}", "=".repeat(80));
+    }
+    Ok(true)
+}
+
+/// 打印开始计时的结果
+pub fn print_started(started: &Started) {
+    if let Some((session, todo)) = &started.stopped {
+        println!(
+            "⏹️  已停止 [ID: {}] {} 的计时（{}）",
+            todo.id,
+            todo.title,
+            time_tracking::format_duration(session.duration(Utc::now()))
+        );
+    }
+    println!("⏱️  开始计时: [ID: {}] {}", started.todo.id, started.todo.title);
+    if started.todo.status == TodoStatus::Todo {
+        println!("   状态已改为{}", TodoStatus::InProgress.label());
+    }
+}
+
+/// 打印停止计时的结果
+pub fn print_stopped(session: &TimeSession, todo: &TodoListForm) {
+    println!(
+        "⏹️  已停止 [ID: {}] {} 的计时，本次 {}",
+        todo.id,
+        todo.title,
+        time_tracking::format_duration(session.duration(Utc::now()))
+    );
+}
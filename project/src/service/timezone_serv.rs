@@ -0,0 +1,55 @@
+use crate::data::local_time;
+use crate::init::db_json::JsonConfig;
+use anyhow::{Result as AnyResult, anyhow};
+use serde_json::Value;
+use std::io::{self, Write};
+
+/// 显示当前时区
+pub fn show_timezone(json_config: &JsonConfig) -> AnyResult<()> {
+    let configured = json_config
+        .get_value("timezone")
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string));
+
+    match configured {
+        Some(name) => println!("🌍 当前时区: {}（配置）", name),
+        None => println!("🌍 当前时区: {}（系统时区）", local_time::zone().name()),
+    }
+    println!("🕐 本地时间: {}", local_time::now().format("%Y-%m-%d %H:%M:%S %:z"));
+    Ok(())
+}
+
+/// 设置时区并保存到配置，value 为 IANA 时区名称，`system` 表示跟随系统时区
+pub fn set_timezone(json_config: &JsonConfig, value: &str) -> AnyResult<()> {
+    let value = value.trim();
+    if value.eq_ignore_ascii_case("system") || value == "系统" {
+        json_config.set_value("timezone", Value::Null)?;
+        let zone = local_time::system_zone();
+        local_time::set_zone(zone);
+        println!("✅ 已改为使用系统时区（{}）", zone.name());
+        return Ok(());
+    }
+
+    let zone = local_time::parse_zone(value)
+        .ok_or_else(|| anyhow!("无法识别的时区 '{}'，请使用 IANA 名称，如 Asia/Shanghai、Europe/London", value))?;
+    json_config.set_value("timezone", Value::String(zone.name().to_string()))?;
+    local_time::set_zone(zone);
+    println!("✅ 时区已设置为 {}", zone.name());
+    Ok(())
+}
+
+/// 交互式修改时区
+pub fn change_timezone(json_config: &JsonConfig) -> AnyResult<()> {
+    show_timezone(json_config)?;
+    print!("请输入新的时区（IANA 名称，如 Asia/Shanghai；输入 system 跟随系统，直接回车取消）: ");
+    io::stdout().flush()?;
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    let input = input.trim();
+    if input.is_empty() {
+        println!("❌ 已取消修改时区");
+        return Ok(());
+    }
+    set_timezone(json_config, input)
+}
@@ -1,34 +1,89 @@
 use anyhow::Result as AnyResult;
 use crate::dao::todo_list_dao;
 use crate::dao::note_dao;
+use crate::dao::tag_dao;
+use crate::dao::dependency_dao;
+use crate::dao::reminder_dao;
 use crate::init::database::Database;
-use crate::data::todo_list::TodoListForm;
-use chrono::{Utc, NaiveDateTime, TimeZone, DateTime};
+use crate::data::todo_list::{Priority, TodoListForm, TodoStatus};
+use crate::data::local_time;
+use crate::data::natural_time;
+use crate::data::note::NoteForm;
+use crate::data::recurrence::RecurrenceRule;
+use crate::data::time_tracking;
+use crate::service::change_log_serv;
+use crate::service::relation_serv::{self, TodoTree};
+use crate::service::time_tracking_serv;
+use chrono::{Utc, NaiveDateTime, DateTime};
+use rusqlite::Connection;
+use serde::Serialize;
 use std::io::{self, Write};
 
-/// 解析优先级字符串为数字（用于排序）
-/// 高优先级返回较小的数字，这样排序时会排在前面
-fn parse_priority(priority: &Option<String>) -> i32 {
-    match priority {
-        None => 999, // 无优先级排在最后
-        Some(p) => {
-            let p_lower = p.to_lowercase();
-            match p_lower.as_str() {
-                "高" | "high" | "1" | "urgent" | "紧急" => 1,
-                "中" | "medium" | "2" | "normal" | "普通" => 2,
-                "低" | "low" | "3" | "minor" | "次要" => 3,
-                _ => {
-                    // 尝试解析为数字
-                    p.parse::<i32>().unwrap_or(999)
-                }
-            }
+/// 待办事项及其笔记（用于 JSON 输出）
+#[derive(Debug, Serialize, Clone)]
+pub struct TodoWithNotes {
+    #[serde(flatten)]
+    pub todo: TodoListForm,
+    pub depends_on: Vec<i32>,  // 依赖的待办事项 ID
+    pub notes: Vec<NoteForm>,
+}
+
+/// 解析标签输入（逗号分隔，支持中文逗号），去除空白、`#` 前缀与重复项
+pub fn parse_tags(input: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for raw in input.split([',', '，']) {
+        let tag = raw.trim().trim_start_matches('#').trim();
+        if tag.is_empty() || tag.contains(tag_dao::TAG_SEPARATOR) {
+            continue;
+        }
+        if !tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            tags.push(tag.to_string());
         }
     }
+    tags
+}
+
+/// 解析优先级输入，空输入表示无优先级
+pub fn parse_priority_input(input: &str) -> Result<Option<Priority>, String> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Ok(None);
+    }
+    Priority::from_string(input)
+        .map(Some)
+        .ok_or_else(|| format!("无法识别的优先级: {}（可选 高/中/低 或 high/medium/low）", input))
+}
+
+/// 解析重复规则输入，空输入表示不重复；每月重复默认固定在开始日期当天
+pub fn parse_recurrence_input(input: &str, begin_time: DateTime<Utc>) -> Result<Option<RecurrenceRule>, String> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Ok(None);
+    }
+    RecurrenceRule::parse(input).map(|rule| Some(rule.anchored(begin_time)))
 }
 
-// 解析时间字符串，支持多种格式
-// 支持：YYYY-MM-DD HH:MM:SS, YYYY-MM-DD HH:MM, YYYY-MM-DD HH, YYYY-MM-DD
-fn parse_datetime(time_str: &str) -> Result<DateTime<Utc>, String> {
+/// 打印重复规则（如果存在），indent 为额外的缩进
+fn print_recurrence(todo: &TodoListForm, indent: &str) {
+    if let Some(rule) = &todo.recurrence {
+        println!("{}   🔁 重复: {}（第 {} 次）", indent, rule.describe(), todo.occurrence);
+    }
+}
+
+/// 打印优先级与标签（如果存在），indent 为额外的缩进
+fn print_priority_and_tags(todo: &TodoListForm, indent: &str) {
+    if let Some(priority) = &todo.priority {
+        println!("{}   优先级: {}", indent, priority.label());
+    }
+    if !todo.tags.is_empty() {
+        println!("{}   标签: {}", indent, todo.tags.join(", "));
+    }
+}
+
+// 解析时间字符串，支持多种格式，输入按本地时区理解
+// 支持：YYYY-MM-DD HH:MM:SS, YYYY-MM-DD HH:MM, YYYY-MM-DD HH, YYYY-MM-DD，带时区偏移的 RFC 3339，
+// 以及 "tomorrow 9am"、"+3d"、"明天下午3点"、"下周一" 等自然语言写法（见 natural_time）
+pub fn parse_datetime(time_str: &str) -> Result<DateTime<Utc>, String> {
     // 尝试多种格式
     let formats = vec![
         ("%Y-%m-%d %H:%M:%S", ""),           // 完整格式
@@ -40,97 +95,189 @@ fn parse_datetime(time_str: &str) -> Result<DateTime<Utc>, String> {
     for (_format, suffix) in formats {
         let full_time_str = format!("{}{}", time_str, suffix);
         if let Ok(naive_dt) = NaiveDateTime::parse_from_str(&full_time_str, "%Y-%m-%d %H:%M:%S") {
-            return Ok(Utc.from_utc_datetime(&naive_dt));
+            return local_time::from_local(&naive_dt).ok_or_else(|| "时间超出范围".to_string());
         }
     }
 
+    if let Ok(dt) = DateTime::parse_from_rfc3339(time_str.trim()) {
+        return Ok(dt.with_timezone(&Utc));
+    }
+
+    if let Some(naive_dt) = natural_time::parse(time_str, local_time::now().naive_local()) {
+        return local_time::from_local(&naive_dt).ok_or_else(|| "时间超出范围".to_string());
+    }
+
     Err("时间格式错误".to_string())
 }
 
-// 输出所有的事项
-pub fn show_all_todos(database: &Database) -> AnyResult<()> {
+/// 时间格式错误时的提示
+pub fn print_time_format_help() {
+    println!("❌ 时间格式错误");
+    println!("   支持格式：");
+    println!("   - 2025-01-01 10:30:00 (完整，也可省略秒、分或时刻)");
+    println!("   - 明天下午3点 / 下周一 10:00 / 周五 / 月底 / 3天后 / 半小时后");
+    println!("   - tomorrow 9am / next friday / +3d / in 2 hours / end of month");
+}
+
+/// 获取所有待办事项及其笔记（按 ID 升序，笔记按优先级排序），include_archived 为 false 时不包括已归档的
+pub fn list_todos_with_notes(database: &Database, include_archived: bool) -> AnyResult<Vec<TodoWithNotes>> {
     let conn = database.get_connection();
-    let todos = todo_list_dao::list_todos(conn)?;
+    let todos = if include_archived {
+        todo_list_dao::list_todos(conn)?
+    } else {
+        todo_list_dao::list_active_todos(conn)?
+    };
+
+    let mut results = Vec::with_capacity(todos.len());
+    for todo in todos.into_iter().rev() {
+        let mut notes = note_dao::list_notes_by_todo_id(conn, todo.id)?;
+        notes.sort_by(|a, b| {
+            Priority::rank_of_text(&a.note_priority).cmp(&Priority::rank_of_text(&b.note_priority))
+        });
+        let depends_on = dependency_dao::list_dependencies_of(conn, todo.id)?;
+        results.push(TodoWithNotes { todo, depends_on, notes });
+    }
+    Ok(results)
+}
+
+// 输出所有的事项（子任务缩进显示在父任务下方），include_archived 为 false 时不显示已归档的
+pub fn show_all_todos(database: &Database, include_archived: bool) -> AnyResult<()> {
+    let tree = TodoTree::load(database)?;
+    let roots = if include_archived { tree.roots() } else { tree.active_roots() };
 
     // 打印所有任务到命令行（控制台）
     // 检查 todos 是否为空，并根据结果输出相应的信息
-    if todos.is_empty() {
+    if roots.is_empty() {
         println!("暂无代办事项");
     } else {
         println!("📋 所有待办事项:");
         println!("{:=<80}", "");
-        for (index, todo) in todos.iter().rev().enumerate() {
-            let status = if todo.completed { "✅" } else { "⬜" };
-            let status_text = if todo.completed { "已完成" } else { "未完成" };
-            println!("{}. {} [ID: {}] {} ({})", index + 1, status, todo.id, todo.title, status_text);
-            if let Some(desc) = &todo.description {
-                println!("   描述: {}", desc);
-            }
-            println!("   开始时间: {}", todo.begin_time.format("%Y-%m-%d %H:%M:%S"));
-            if let Some(end_time) = &todo.end_time {
-                println!("   结束时间: {}", end_time.format("%Y-%m-%d %H:%M:%S"));
-            }
-            // 显示关键信息（如果存在）
-            if let Some(key1) = &todo.key_message1 {
-                println!("   关键信息1: {}", key1);
-            }
-            if let Some(key2) = &todo.key_message2 {
-                println!("   关键信息2: {}", key2);
+        for (index, todo) in roots.into_iter().enumerate() {
+            print_todo_tree(database, &tree, todo, &(index + 1).to_string(), 0, include_archived)?;
+            println!("{:=<80}", "");
+        }
+    }
+    let archived = tree.archived_count();
+    if !include_archived && archived > 0 {
+        println!("📦 另有 {} 个已归档的待办事项，使用 'archive' 命令查看", archived);
+    }
+
+    Ok(())
+}
+
+// 打印一个待办事项及其全部子任务，label 为层级编号（如 1.2）
+fn print_todo_tree(
+    database: &Database,
+    tree: &TodoTree,
+    todo: &TodoListForm,
+    label: &str,
+    depth: usize,
+    include_archived: bool,
+) -> AnyResult<()> {
+    let conn = database.get_connection();
+    let indent = "    ".repeat(depth);
+    let branch = if depth > 0 { "└─ " } else { "" };
+
+    let archived = if todo.is_archived() { " · 已归档" } else { "" };
+    println!("{}{}{}. {} [ID: {}] {} ({}{})", indent, branch, label, todo.status.icon(), todo.id, todo.title, todo.status.label(), archived);
+    let indent = format!("{}{}", indent, if depth > 0 { "   " } else { "" });
+    if let Some(desc) = &todo.description {
+        println!("{}   描述: {}", indent, desc);
+    }
+    print_priority_and_tags(todo, &indent);
+    print_recurrence(todo, &indent);
+    println!("{}   开始时间: {}", indent, local_time::format(&todo.begin_time, "%Y-%m-%d %H:%M:%S"));
+    if let Some(end_time) = &todo.end_time {
+        println!("{}   结束时间: {}", indent, local_time::format(end_time, "%Y-%m-%d %H:%M:%S"));
+    }
+    // 显示关键信息（如果存在）
+    if let Some(key1) = &todo.key_message1 {
+        println!("{}   关键信息1: {}", indent, key1);
+    }
+    if let Some(key2) = &todo.key_message2 {
+        println!("{}   关键信息2: {}", indent, key2);
+    }
+    if let Some(key3) = &todo.key_message3 {
+        println!("{}   关键信息3: {}", indent, key3);
+    }
+
+    // 显示依赖与子任务进度
+    let dependencies = tree.dependencies(todo.id);
+    if !dependencies.is_empty() {
+        let list: Vec<String> = dependencies
+            .iter()
+            .map(|d| format!("{} [ID: {}] {}", d.status.icon(), d.id, d.title))
+            .collect();
+        let waiting = todo.status.is_open() && dependencies.iter().any(|d| d.status.is_open());
+        println!("{}   ⛓️  依赖: {}{}", indent, list.join(", "), if waiting { "（等待中）" } else { "" });
+    }
+    if let Some((done, total)) = tree.progress(todo.id) {
+        println!("{}   📊 子任务进度: {}/{} ({}%)", indent, done, total, done * 100 / total);
+    }
+
+    // 显示该 todo 的所有笔记
+    let mut notes = note_dao::list_notes_by_todo_id(conn, todo.id)?;
+    if !notes.is_empty() {
+        // 按优先级排序
+        notes.sort_by(|a, b| {
+            Priority::rank_of_text(&a.note_priority).cmp(&Priority::rank_of_text(&b.note_priority))
+        });
+        
+        println!("\n{}   📝 笔记 ({} 条):", indent, notes.len());
+        for (note_idx, note) in notes.iter().enumerate() {
+            println!("{}      {}. [笔记ID: {}] {}", indent, note_idx + 1, note.id, note.note_title);
+            println!("{}         内容: {}", indent, note.note_content);
+            println!("{}         时间: {}", indent, local_time::format(&note.note_time, "%Y-%m-%d %H:%M:%S"));
+            if let Some(ref priority) = note.note_priority {
+                println!("{}         优先级: {}", indent, priority);
             }
-            if let Some(key3) = &todo.key_message3 {
-                println!("   关键信息3: {}", key3);
+            if let Some(ref tag) = note.note_tag {
+                println!("{}         标签: {}", indent, tag);
             }
-            
-            // 显示该 todo 的所有笔记
-            let mut notes = note_dao::list_notes_by_todo_id(conn, todo.id)?;
-            if !notes.is_empty() {
-                // 按优先级排序
-                notes.sort_by(|a, b| {
-                    parse_priority(&a.note_priority).cmp(&parse_priority(&b.note_priority))
-                });
-                
-                println!("\n   📝 笔记 ({} 条):", notes.len());
-                for (note_idx, note) in notes.iter().enumerate() {
-                    println!("      {}. [笔记ID: {}] {}", note_idx + 1, note.id, note.note_title);
-                    println!("         内容: {}", note.note_content);
-                    println!("         时间: {}", note.note_time.format("%Y-%m-%d %H:%M:%S"));
-                    if let Some(ref priority) = note.note_priority {
-                        println!("         优先级: {}", priority);
-                    }
-                    if let Some(ref tag) = note.note_tag {
-                        println!("         标签: {}", tag);
-                    }
-                    if note_idx < notes.len() - 1 {
-                        println!("         {}", "·".repeat(40));
-                    }
-                }
+            if note_idx < notes.len() - 1 {
+                println!("{}         {}", indent, "·".repeat(40));
             }
-            
-            println!("{:=<80}", "");
         }
     }
 
+    let children = tree.children(todo.id).into_iter().filter(|c| include_archived || !c.is_archived());
+    for (index, child) in children.enumerate() {
+        print_todo_tree(database, tree, child, &format!("{}.{}", label, index + 1), depth + 1, include_archived)?;
+    }
     Ok(())
 }
 
 pub fn delete_todo(database: &Database, id: i32) -> AnyResult<()> {
     use std::io::{self, Write};
-    println!("🔴 即将删除ID为 {} 的待办事项，确定删除吗？(y/N)", id);
+    println!("🔴 即将把ID为 {} 的待办事项移到回收站，确定删除吗？(y/N)", id);
     print!("请输入 y 确认，其他任意键取消: ");
     io::stdout().flush()?;
     let mut confirm = String::new();
     io::stdin().read_line(&mut confirm)?;
     let confirm = confirm.trim().to_lowercase();
     if confirm == "y" {
-        let conn = database.get_connection();
-        todo_list_dao::delete_todo(conn, id)?;
-        println!("✅ 删除成功");
+        delete_todo_direct(database, id)?;
     } else {
         println!("❎ 取消删除");
     }
     Ok(())
 }
 
+/// 删除待办事项：移到回收站，笔记、标签、依赖与提醒历史保留（不再确认，供命令行模式使用）
+pub fn delete_todo_direct(database: &Database, id: i32) -> AnyResult<()> {
+    let conn = database.get_connection();
+    let action = match todo_list_dao::get_todo_by_id(conn, id)? {
+        Some(todo) => format!("删除待办事项 #{}「{}」", id, todo.title),
+        None => format!("删除待办事项 #{}", id),
+    };
+    if change_log_serv::record(database, &action, || todo_list_dao::trash_todo(conn, id))? {
+        println!("🗑️  已移到回收站，可使用 'trash' 命令恢复");
+    } else {
+        println!("❌ 未找到ID为 {} 的待办事项", id);
+    }
+    Ok(())
+}
+
 pub fn update_todo(database: &Database, form: &TodoListForm) -> AnyResult<()> {
     use std::io::{self, Write};
     println!("📝 即将更新ID为 {} 的待办事项，确定更新吗？(y/N)", form.id);
@@ -140,22 +287,55 @@ pub fn update_todo(database: &Database, form: &TodoListForm) -> AnyResult<()> {
     io::stdin().read_line(&mut confirm)?;
     let confirm = confirm.trim().to_lowercase();
     if confirm == "y" {
-        let conn = database.get_connection();
-        todo_list_dao::update_todo(conn, form)?;
-        println!("✅ 更新成功");
+        update_todo_direct(database, form, false)?;
     } else {
         println!("❎ 取消更新");
     }
     Ok(())
 }
 
+/// 检查截止时间不早于开始时间（新建与更新共用）
+fn check_time_range(form: &TodoListForm) -> AnyResult<()> {
+    if let Some(end_time) = form.end_time
+        && end_time < form.begin_time
+    {
+        anyhow::bail!(
+            "结束时间 {} 不能早于开始时间 {}",
+            local_time::format(&end_time, "%Y-%m-%d %H:%M:%S"),
+            local_time::format(&form.begin_time, "%Y-%m-%d %H:%M:%S")
+        );
+    }
+    Ok(())
+}
+
+/// 更新待办事项（不再确认，供命令行模式使用）
+/// 状态有变化时按 set_status 处理（子任务检查、停止计时、生成下一次重复），cascade 含义同 toggle_completed
+pub fn update_todo_direct(database: &Database, form: &TodoListForm, cascade: bool) -> AnyResult<()> {
+    check_time_range(form)?;
+    let conn = database.get_connection();
+    let todo = todo_list_dao::get_todo_by_id(conn, form.id)?
+        .ok_or_else(|| anyhow::anyhow!("未找到ID为 {} 的待办事项", form.id))?;
+    let action = format!("更新待办事项 #{}「{}」", form.id, form.title);
+    change_log_serv::record(database, &action, || {
+        // 先以原状态保存其他字段，再单独切换状态
+        todo_list_dao::update_todo(conn, &TodoListForm { status: todo.status, ..form.clone() })?;
+        if form.status != todo.status {
+            let updated = TodoListForm { status: todo.status, ..form.clone() };
+            apply_status(database, &updated, form.status, cascade)?;
+        }
+        Ok(())
+    })?;
+    println!("✅ 更新成功");
+    Ok(())
+}
+
 /// 显示已完成的待办事项
 #[allow(dead_code)]
 pub fn show_completed_todos(database: &Database) -> AnyResult<()> {
     let conn = database.get_connection();
     let todos = todo_list_dao::list_todos(conn)?;
 
-    let completed_todos: Vec<_> = todos.iter().filter(|t| t.completed).collect();
+    let completed_todos: Vec<_> = todos.iter().filter(|t| t.is_done()).collect();
 
     if completed_todos.is_empty() {
         println!("✅ 暂无已完成的待办事项");
@@ -170,9 +350,9 @@ pub fn show_completed_todos(database: &Database) -> AnyResult<()> {
         if let Some(desc) = &todo.description {
             println!("   描述: {}", desc);
         }
-        println!("   开始时间: {}", todo.begin_time.format("%Y-%m-%d %H:%M:%S"));
+        println!("   开始时间: {}", local_time::format(&todo.begin_time, "%Y-%m-%d %H:%M:%S"));
         if let Some(end_time) = &todo.end_time {
-            println!("   结束时间: {}", end_time.format("%Y-%m-%d %H:%M:%S"));
+            println!("   结束时间: {}", local_time::format(end_time, "%Y-%m-%d %H:%M:%S"));
         }
         
         // 显示笔记数量
@@ -193,7 +373,7 @@ pub fn show_pending_todos(database: &Database) -> AnyResult<()> {
     let conn = database.get_connection();
     let todos = todo_list_dao::list_todos(conn)?;
 
-    let pending_todos: Vec<_> = todos.iter().filter(|t| !t.completed).collect();
+    let pending_todos: Vec<_> = todos.iter().filter(|t| t.status.is_open()).collect();
 
     if pending_todos.is_empty() {
         println!("⬜ 暂无未完成的待办事项");
@@ -209,7 +389,7 @@ pub fn show_pending_todos(database: &Database) -> AnyResult<()> {
         if let Some(desc) = &todo.description {
             println!("   描述: {}", desc);
         }
-        println!("   开始时间: {}", todo.begin_time.format("%Y-%m-%d %H:%M:%S"));
+        println!("   开始时间: {}", local_time::format(&todo.begin_time, "%Y-%m-%d %H:%M:%S"));
         
         // 计算已经过去的时间
         let elapsed = now.signed_duration_since(todo.begin_time);
@@ -233,7 +413,7 @@ pub fn show_pending_todos(database: &Database) -> AnyResult<()> {
                 let overdue_days = overdue.num_days();
                 println!("   ⚠️  已超期 {} 天", overdue_days);
             } else {
-                println!("   截止时间: {}", end_time.format("%Y-%m-%d %H:%M:%S"));
+                println!("   截止时间: {}", local_time::format(end_time, "%Y-%m-%d %H:%M:%S"));
             }
         }
         
@@ -250,23 +430,164 @@ pub fn show_pending_todos(database: &Database) -> AnyResult<()> {
 }
 
 /// 切换待办事项的完成状态
-pub fn toggle_completed(database: &Database, id: i32) -> AnyResult<()> {
+/// cascade 为 true 时，标记完成会一并完成所有未结束的子任务
+pub fn toggle_completed(database: &Database, id: i32, cascade: bool) -> AnyResult<()> {
     let conn = database.get_connection();
     
     // 先检查待办事项是否存在
     let todo = todo_list_dao::get_todo_by_id(conn, id)?
         .ok_or_else(|| anyhow::anyhow!("未找到ID为 {} 的待办事项", id))?;
-    
-    todo_list_dao::toggle_completed(conn, id)?;
-    
-    if todo.completed {
-        println!("⬜ 已将待办事项 '{}' 标记为未完成", todo.title);
+    let action = if todo.is_done() {
+        format!("标记未完成 #{}「{}」", id, todo.title)
     } else {
-        println!("✅ 已将待办事项 '{}' 标记为完成", todo.title);
+        format!("标记完成 #{}「{}」", id, todo.title)
+    };
+
+    change_log_serv::record(database, &action, || {
+        if !todo.is_done() {
+            close_children_before_done(database, &todo, cascade)?;
+        }
+
+        todo_list_dao::toggle_completed(conn, id)?;
+
+        if todo.is_done() {
+            println!("⬜ 已将待办事项 '{}' 标记为未完成", todo.title);
+        } else {
+            println!("✅ 已将待办事项 '{}' 标记为完成", todo.title);
+            stop_tracking(conn, id)?;
+            spawn_next_occurrence(database, &todo)?;
+        }
+        Ok(())
+    })
+}
+
+/// 设置待办事项状态（cascade 含义同 toggle_completed）
+pub fn set_status(database: &Database, id: i32, status: TodoStatus, cascade: bool) -> AnyResult<()> {
+    let conn = database.get_connection();
+
+    let todo = todo_list_dao::get_todo_by_id(conn, id)?
+        .ok_or_else(|| anyhow::anyhow!("未找到ID为 {} 的待办事项", id))?;
+    let action = format!("修改状态 #{}「{}」: {} → {}", id, todo.title, todo.status.label(), status.label());
+
+    change_log_serv::record(database, &action, || apply_status(database, &todo, status, cascade))
+}
+
+/// 切换状态并处理随之发生的变化（在调用方的操作中执行）
+fn apply_status(database: &Database, todo: &TodoListForm, status: TodoStatus, cascade: bool) -> AnyResult<()> {
+    let conn = database.get_connection();
+    if status == TodoStatus::Done && !todo.is_done() {
+        close_children_before_done(database, todo, cascade)?;
+    }
+
+    todo_list_dao::set_status(conn, todo.id, status)?;
+    println!(
+        "{} 已将待办事项 '{}' 的状态由 {} 改为 {}",
+        status.icon(),
+        todo.title,
+        todo.status.label(),
+        status.label()
+    );
+    if !status.is_open() {
+        stop_tracking(conn, todo.id)?;
+    }
+    if status == TodoStatus::Done && !todo.is_done() {
+        spawn_next_occurrence(database, todo)?;
+    }
+    Ok(())
+}
+
+/// 待办事项结束时停止它正在进行的计时
+fn stop_tracking(conn: &Connection, id: i32) -> AnyResult<()> {
+    if let Some(duration) = time_tracking_serv::stop_for_todo(conn, id)? {
+        println!("   ⏹️  已停止计时，本次 {}", time_tracking::format_duration(duration));
+    }
+    Ok(())
+}
+
+/// 标记完成前检查子任务与依赖
+/// 存在未结束的子任务时：cascade 为 true 则一并完成，否则拒绝；依赖未完成只给出提示
+fn close_children_before_done(database: &Database, todo: &TodoListForm, cascade: bool) -> AnyResult<()> {
+    let open_children = relation_serv::open_descendants(database, todo.id)?;
+    if !open_children.is_empty() {
+        if !cascade {
+            anyhow::bail!(
+                "待办事项 '{}' 还有 {} 个未完成的子任务，请先完成子任务或选择级联完成",
+                todo.title,
+                open_children.len()
+            );
+        }
+        let conn = database.get_connection();
+        for child in &open_children {
+            todo_list_dao::set_status(conn, child.id, TodoStatus::Done)?;
+            println!("   ✅ 子任务 [ID: {}] '{}' 已一并完成", child.id, child.title);
+            stop_tracking(conn, child.id)?;
+            spawn_next_occurrence(database, child)?;
+        }
+    }
+
+    for blocker in relation_serv::open_dependencies(database, todo.id)? {
+        println!("⚠️  注意: 依赖的任务 [ID: {}] '{}' 尚未完成", blocker.id, blocker.title);
     }
-    
     Ok(())
 }
+
+/// 交互模式下，完成带有未结束子任务的待办事项前询问是否级联完成
+pub fn ask_cascade(database: &Database, id: i32) -> AnyResult<bool> {
+    let open_children = relation_serv::open_descendants(database, id)?;
+    if open_children.is_empty() {
+        return Ok(false);
+    }
+
+    println!("⚠️  该待办事项还有 {} 个未完成的子任务:", open_children.len());
+    for child in &open_children {
+        println!("   {} [ID: {}] {}", child.status.icon(), child.id, child.title);
+    }
+    print!("是否同时完成这些子任务？(y/N): ");
+    io::stdout().flush()?;
+    let mut confirm = String::new();
+    io::stdin().read_line(&mut confirm)?;
+    Ok(confirm.trim().eq_ignore_ascii_case("y"))
+}
+
+/// 重复待办事项完成后生成下一次重复（已生成过、达到次数或超过截止日期时跳过）
+/// 下一次沿用标题、描述、优先级、标签与关键信息，截止时间随开始时间平移，笔记不复制
+fn spawn_next_occurrence(database: &Database, todo: &TodoListForm) -> AnyResult<Option<i64>> {
+    let Some(rule) = &todo.recurrence else {
+        return Ok(None);
+    };
+    let conn = database.get_connection();
+    let series_id = todo.series_id.unwrap_or(todo.id);
+    let next_occurrence = todo.occurrence + 1;
+
+    if let Some(existing) = todo_list_dao::find_occurrence(conn, series_id, next_occurrence)? {
+        println!("🔁 下一次重复已存在 [ID: {}]", existing);
+        return Ok(None);
+    }
+    let Some(next_begin) = rule.next_occurrence(todo.begin_time, todo.occurrence) else {
+        println!("🏁 重复已结束（{}）", rule.describe());
+        return Ok(None);
+    };
+
+    let next = TodoListForm {
+        id: 0,
+        status: TodoStatus::Todo,
+        begin_time: next_begin,
+        end_time: todo.end_time.map(|end| next_begin + (end - todo.begin_time)),
+        series_id: Some(series_id),
+        occurrence: next_occurrence,
+        archived_at: None,
+        completed_at: None,
+        ..todo.clone()
+    };
+    let next_id = todo_list_dao::insert_todo(conn, &next)?;
+    reminder_dao::move_to_occurrence(conn, todo.id, next_id, &next_begin)?;
+    println!(
+        "🔁 已生成下一次重复 [ID: {}]，开始时间: {}",
+        next_id,
+        local_time::format(&next_begin, "%Y-%m-%d %H:%M:%S")
+    );
+    Ok(Some(next_id))
+}
 // 创建新的待办事项（交互式输入）
 pub fn create_new_todo(database: &Database) -> AnyResult<()> {
     println!("📝 创建新的待办事项");
@@ -302,9 +623,28 @@ pub fn create_new_todo(database: &Database) -> AnyResult<()> {
         }
     };
 
+    // 读取优先级（可选）
+    let priority = loop {
+        print!("优先级 [可选，高/中/低]: ");
+        io::stdout().flush()?;
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+        match parse_priority_input(&input) {
+            Ok(p) => break p,
+            Err(msg) => println!("❌ {}", msg),
+        }
+    };
+
+    // 读取标签（可选）
+    print!("标签 [可选，多个标签用逗号分隔]: ");
+    io::stdout().flush()?;
+    let mut tags_input = String::new();
+    io::stdin().read_line(&mut tags_input)?;
+    let tags = parse_tags(&tags_input);
+
     // 读取开始时间（必填）
     let begin_time = loop {
-        print!("开始时间 [必填，格式：YYYY-MM-DD [HH[:MM[:SS]]]]: ");
+        print!("开始时间 [必填，如 2025-01-01 10:30、明天下午3点、tomorrow 9am]: ");
         io::stdout().flush()?;
         
         let mut input = String::new();
@@ -319,40 +659,53 @@ pub fn create_new_todo(database: &Database) -> AnyResult<()> {
         // 尝试解析时间
         match parse_datetime(time_str) {
             Ok(dt) => break dt,
-            Err(_) => {
-                println!("❌ 时间格式错误");
-                println!("   支持格式：");
-                println!("   - 2025-01-01 10:30:00 (完整)");
-                println!("   - 2025-01-01 10:30    (秒默认为00)");
-                println!("   - 2025-01-01 10       (分秒默认为00:00)");
-                println!("   - 2025-01-01          (时分秒默认为00:00:00)");
-                continue;
-            }
+            Err(_) => print_time_format_help(),
         }
     };
 
     // 读取结束时间（可选）
-    print!("结束时间 [可选，格式：YYYY-MM-DD [HH[:MM[:SS]]]]: ");
-    io::stdout().flush()?;
-    let mut end_time_input = String::new();
-    io::stdin().read_line(&mut end_time_input)?;
     let end_time = loop {
-        let time_str = end_time_input.trim();
+        print!("结束时间 [可选，如 2025-01-01 18:00、+3d、月底、end of week]: ");
+        io::stdout().flush()?;
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+        let time_str = input.trim();
         if time_str.is_empty() {
-            break None
-        } else {
-            match parse_datetime(time_str) {
-                Ok(dt) => break Some(dt),
-                Err(_) => {
-                    println!("❌ 时间格式错误");
-                    println!("   支持格式：");
-                    println!("   - 2025-01-01 10:30:00 (完整)");
-                    println!("   - 2025-01-01 10:30    (秒默认为00)");
-                    println!("   - 2025-01-01 10       (分秒默认为00:00)");
-                    println!("   - 2025-01-01          (时分秒默认为00:00:00)");
-                    continue;
-                }
-            }
+            break None;
+        }
+        match parse_datetime(time_str) {
+            Ok(dt) if dt < begin_time => println!("❌ 结束时间不能早于开始时间"),
+            Ok(dt) => break Some(dt),
+            Err(_) => print_time_format_help(),
+        }
+    };
+
+    // 读取父任务（可选）
+    let parent_id = loop {
+        print!("父任务ID [可选，作为子任务时填写]: ");
+        io::stdout().flush()?;
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+        let input = input.trim();
+        if input.is_empty() {
+            break None;
+        }
+        match input.parse::<i32>() {
+            Ok(id) if todo_list_dao::get_todo_by_id(database.get_connection(), id)?.is_some() => break Some(id),
+            Ok(id) => println!("❌ 未找到ID为 {} 的待办事项", id),
+            Err(_) => println!("❌ 请输入数字ID"),
+        }
+    };
+
+    // 读取重复规则（可选）
+    let recurrence = loop {
+        print!("重复 [可选，如 daily / weekdays / weekly;BYDAY=MO,WE / monthly;BYMONTHDAY=15 / daily;INTERVAL=3;COUNT=10]: ");
+        io::stdout().flush()?;
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+        match parse_recurrence_input(&input, begin_time) {
+            Ok(rule) => break rule,
+            Err(msg) => println!("❌ {}", msg),
         }
     };
 
@@ -374,12 +727,8 @@ pub fn create_new_todo(database: &Database) -> AnyResult<()> {
         io::stdout().flush()?;
         let mut key2_input = String::new();
         io::stdin().read_line(&mut key2_input)?;
-        let key2 = {
-            let k2 = key2_input.trim();
-            if k2.is_empty() { None } else { Some(k2.to_string()) }
-        };
-
-        key2
+        let k2 = key2_input.trim();
+        if k2.is_empty() { None } else { Some(k2.to_string()) }
     };
 
     // 如果关键信息1为空，则跳过后续关键信息的输入
@@ -391,12 +740,8 @@ pub fn create_new_todo(database: &Database) -> AnyResult<()> {
         io::stdout().flush()?;
         let mut key3_input = String::new();
         io::stdin().read_line(&mut key3_input)?;
-        let key3 = {
-            let k3 = key3_input.trim();
-            if k3.is_empty() { None } else { Some(k3.to_string()) }
-        };
-
-        key3
+        let k3 = key3_input.trim();
+        if k3.is_empty() { None } else { Some(k3.to_string()) }
     };
 
     // 创建 TodoListForm
@@ -404,23 +749,43 @@ pub fn create_new_todo(database: &Database) -> AnyResult<()> {
         id: 0, // ID 由数据库自动生成，这里的值会被忽略
         title,
         description,
-        completed: false, // 新创建的待办事项默认未完成
+        status: TodoStatus::Todo, // 新创建的待办事项默认为待办
+        priority,
+        tags,
         begin_time,
         end_time,
         key_message1,
         key_message2,
         key_message3,
+        recurrence,
+        series_id: None,
+        occurrence: 1,
+        parent_id,
+        archived_at: None,
+        completed_at: None,
     };
 
-    // 插入数据库
+    add_todo(database, &new_todo)?;
+    Ok(())
+}
+
+/// 插入新的待办事项并打印创建结果，返回新记录的 ID
+pub fn add_todo(database: &Database, new_todo: &TodoListForm) -> AnyResult<i64> {
+    check_time_range(new_todo)?;
     let conn = database.get_connection();
-    let todo_id = todo_list_dao::insert_todo(conn, &new_todo)?;
+    let action = format!("创建待办事项「{}」", new_todo.title);
+    let todo_id = change_log_serv::record(database, &action, || todo_list_dao::insert_todo(conn, new_todo))?;
 
     println!("\n✅ 待办事项创建成功！ID: {}", todo_id);
     println!("   标题: {}", new_todo.title);
     if let Some(desc) = &new_todo.description {
         println!("   描述: {}", desc);
     }
+    print_priority_and_tags(new_todo, "");
+    print_recurrence(new_todo, "");
+    if let Some(parent_id) = new_todo.parent_id {
+        println!("   父任务ID: {}", parent_id);
+    }
     // 显示关键信息（如果存在）
     if let Some(key1) = &new_todo.key_message1 {
         println!("   关键信息1: {}", key1);
@@ -431,5 +796,51 @@ pub fn create_new_todo(database: &Database) -> AnyResult<()> {
     if let Some(key3) = &new_todo.key_message3 {
         println!("   关键信息3: {}", key3);
     }
-    Ok(())
-}
\ No newline at end of file
+    Ok(todo_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    fn todo(begin_time: DateTime<Utc>, end_time: Option<DateTime<Utc>>) -> TodoListForm {
+        TodoListForm {
+            id: 0,
+            title: "写周报".to_string(),
+            description: None,
+            status: TodoStatus::Todo,
+            priority: None,
+            tags: Vec::new(),
+            begin_time,
+            end_time,
+            key_message1: None,
+            key_message2: None,
+            key_message3: None,
+            recurrence: None,
+            series_id: None,
+            occurrence: 1,
+            parent_id: None,
+            archived_at: None,
+            completed_at: None,
+        }
+    }
+
+    #[test]
+    fn end_time_must_not_precede_begin_time() {
+        let database = Database::new(":memory:").unwrap();
+        database.initialize_tables().unwrap();
+        let conn = database.get_connection();
+        let begin = Utc.with_ymd_and_hms(2030, 1, 2, 9, 0, 0).unwrap();
+
+        assert!(add_todo(&database, &todo(begin, Some(begin - Duration::hours(1)))).is_err());
+        assert!(todo_list_dao::list_active_todos(conn).unwrap().is_empty());
+
+        // 截止时间可以等于开始时间
+        let id = add_todo(&database, &todo(begin, Some(begin))).unwrap() as i32;
+        let stored = todo_list_dao::get_todo_by_id(conn, id).unwrap().unwrap();
+        let moved = TodoListForm { begin_time: begin + Duration::days(1), ..stored.clone() };
+        assert!(update_todo_direct(&database, &moved, false).is_err());
+        assert_eq!(todo_list_dao::get_todo_by_id(conn, id).unwrap().unwrap().begin_time, begin);
+    }
+}
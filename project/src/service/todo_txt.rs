@@ -0,0 +1,462 @@
+//! todo.txt 导入导出
+//!
+//! 每行一个待办事项：`x 完成日期 创建日期 (A) 标题 +项目 @情境 key:value`
+//!
+//! - `x` 表示已完成，其后的完成日期对应完成时间；未完成时 `(A)` / `(B)` / `(C)` 对应高 / 中 / 低优先级（D-Z 视为低），已完成时写作 `pri:A`
+//! - `+项目` 导入为同名标签，`@情境` 导入为带 `@` 的标签；导出时带 `@` 的标签写为情境，其余写为项目
+//! - `t:` / `due:` 为开始 / 截止日期，时刻不是 00:00 / 23:59 时另写 `at:HH:MM` / `dueat:HH:MM`；
+//!   没有 `t:` 时以创建日期作为开始日期
+//! - `rec:` 为重复规则（`1d`、`2w`、`1m`、`1b` 工作日），无法用 `rec:` 表示的写为 `rrule:`（RRULE 文本）
+//! - `id:` / `p:` 为任务标识与父任务（与 topydo 相同），`status:` 记录进行中 / 受阻 / 已取消
+//!
+//! 描述、关键信息、笔记与依赖没有对应的写法，导出时省略。
+
+use anyhow::Result as AnyResult;
+use chrono::{DateTime, Datelike, NaiveDate, NaiveTime, Timelike, Utc, Weekday};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::data::local_time;
+use crate::data::recurrence::{Frequency, RecurrenceRule};
+use crate::data::todo_list::{Priority, TodoListForm, TodoStatus};
+use crate::data::transfer::ExportDocument;
+use crate::service::todo_list_serv;
+
+/// 只有日期的截止时间按当天 23:59 处理
+fn end_of_day() -> NaiveTime {
+    NaiveTime::from_hms_opt(23, 59, 0).unwrap_or_default()
+}
+
+/// 生成 todo.txt 内容
+pub fn render(document: &ExportDocument) -> String {
+    let mut text = String::new();
+    for todo in &document.todos {
+        text.push_str(&render_line(todo));
+        text.push('\n');
+    }
+    text
+}
+
+fn render_line(todo: &TodoListForm) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    let priority = todo.priority.map(|p| match p {
+        Priority::High => "A",
+        Priority::Medium => "B",
+        Priority::Low => "C",
+    });
+    if todo.status.is_open() {
+        if let Some(priority) = priority {
+            parts.push(format!("({})", priority));
+        }
+    } else {
+        parts.push("x".to_string());
+        if let Some(completed_at) = todo.completed_at.filter(|_| todo.is_done()) {
+            parts.push(local_time::to_local(&completed_at).format("%Y-%m-%d").to_string());
+        }
+    }
+    parts.push(todo.title.split_whitespace().collect::<Vec<_>>().join(" "));
+
+    for tag in &todo.tags {
+        let tag = tag.split_whitespace().collect::<Vec<_>>().join("_");
+        if tag.starts_with('@') {
+            parts.push(tag);
+        } else {
+            parts.push(format!("+{}", tag));
+        }
+    }
+    if !todo.status.is_open()
+        && let Some(priority) = priority
+    {
+        parts.push(format!("pri:{}", priority));
+    }
+
+    let begin = local_time::to_local(&todo.begin_time);
+    parts.push(format!("t:{}", begin.format("%Y-%m-%d")));
+    if begin.time() != NaiveTime::MIN {
+        parts.push(format!("at:{}", begin.format("%H:%M")));
+    }
+    if let Some(end_time) = &todo.end_time {
+        let end = local_time::to_local(end_time);
+        parts.push(format!("due:{}", end.format("%Y-%m-%d")));
+        if end.time().with_second(0) != Some(end_of_day()) {
+            parts.push(format!("dueat:{}", end.format("%H:%M")));
+        }
+    }
+    if matches!(todo.status, TodoStatus::InProgress | TodoStatus::Blocked | TodoStatus::Cancelled) {
+        parts.push(format!("status:{}", todo.status.as_str()));
+    }
+    if todo.status.is_open()
+        && let Some(rule) = &todo.recurrence
+    {
+        match rec_value(rule, &todo.begin_time) {
+            Some(value) => parts.push(format!("rec:{}", value)),
+            None => parts.push(format!("rrule:{}", rule)),
+        }
+    }
+    parts.push(format!("id:{}", todo.id));
+    if let Some(parent_id) = todo.parent_id {
+        parts.push(format!("p:{}", parent_id));
+    }
+    parts.join(" ")
+}
+
+/// 能用 `rec:` 表示的重复规则
+fn rec_value(rule: &RecurrenceRule, begin: &DateTime<Utc>) -> Option<String> {
+    if rule.count.is_some() || rule.until.is_some() {
+        return None;
+    }
+    let workdays = [Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri];
+    match rule.freq {
+        Frequency::Daily => Some(format!("{}d", rule.interval)),
+        Frequency::Weekly if rule.by_weekday.is_empty() => Some(format!("{}w", rule.interval)),
+        Frequency::Weekly if rule.interval == 1 && rule.by_weekday == workdays => Some("1b".to_string()),
+        Frequency::Monthly if rule.by_month_day.is_none_or(|day| day == local_time::to_local(begin).day()) => {
+            Some(format!("{}m", rule.interval))
+        }
+        _ => None,
+    }
+}
+
+/// `rec:` 转换为重复规则文本
+fn parse_rec(value: &str) -> Result<String, String> {
+    let value = value.trim_start_matches('+');
+    let split = value.find(|c: char| !c.is_ascii_digit()).unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let interval: u32 = if number.is_empty() { 1 } else { number.parse().map_err(|_| format!("无效的间隔: {}", value))? };
+    match unit.to_lowercase().as_str() {
+        "d" => Ok(format!("FREQ=DAILY;INTERVAL={}", interval)),
+        "w" => Ok(format!("FREQ=WEEKLY;INTERVAL={}", interval)),
+        "m" => Ok(format!("FREQ=MONTHLY;INTERVAL={}", interval)),
+        "b" if interval == 1 => Ok("weekdays".to_string()),
+        _ => Err(format!("不支持的重复间隔 rec:{}", value)),
+    }
+}
+
+/// 解析 todo.txt 内容
+pub fn parse(content: &str) -> AnyResult<ExportDocument> {
+    let mut document = ExportDocument::new(None);
+    let mut unmapped: BTreeMap<String, usize> = BTreeMap::new();
+    let mut ids: HashMap<String, i32> = HashMap::new();
+    let mut parents: Vec<(i32, usize, String)> = Vec::new();
+
+    for (index, raw) in content.lines().enumerate() {
+        let line = index + 1;
+        if raw.trim().is_empty() {
+            continue;
+        }
+        let Some(parsed) = parse_line(raw, line, &mut document.read_warnings, &mut unmapped) else {
+            continue;
+        };
+        if let Some(key) = parsed.key {
+            ids.insert(key, parsed.todo.id);
+        }
+        if let Some(parent) = parsed.parent {
+            parents.push((parsed.todo.id, line, parent));
+        }
+        document.todos.push(parsed.todo);
+    }
+
+    for (todo_id, line, parent) in parents {
+        match ids.get(&parent) {
+            Some(&parent_id) => {
+                if let Some(todo) = document.todos.iter_mut().find(|t| t.id == todo_id) {
+                    todo.parent_id = Some(parent_id);
+                }
+            }
+            None => document.read_warnings.push(format!("第 {} 行的父任务 p:{} 不在文件中，已忽略", line, parent)),
+        }
+    }
+    if !unmapped.is_empty() {
+        let keys: Vec<String> = unmapped.iter().map(|(key, count)| format!("{}:（{} 行）", key, count)).collect();
+        document.read_warnings.push(format!("以下标记无法对应，已保留在标题中: {}", keys.join("、")));
+    }
+    Ok(document)
+}
+
+struct ParsedLine {
+    todo: TodoListForm,
+    /// `id:` 的值
+    key: Option<String>,
+    /// `p:` 的值
+    parent: Option<String>,
+}
+
+fn parse_line(
+    raw: &str,
+    line: usize,
+    warnings: &mut Vec<String>,
+    unmapped: &mut BTreeMap<String, usize>,
+) -> Option<ParsedLine> {
+    let mut tokens = raw.split_whitespace().peekable();
+    let parse_date = |token: &str| NaiveDate::parse_from_str(token, "%Y-%m-%d").ok();
+
+    let done = tokens.next_if_eq(&"x").is_some();
+    let mut priority = None;
+    let mut completed = None;
+    if done {
+        // 完成日期
+        completed = tokens.next_if(|t| parse_date(t).is_some()).and_then(parse_date);
+    } else if let Some(token) = tokens.next_if(|t| t.len() == 3 && t.starts_with('(') && t.ends_with(')')) {
+        priority = letter_priority(&token[1..2]);
+    }
+    let created = tokens.next_if(|t| parse_date(t).is_some()).and_then(parse_date);
+
+    let mut words: Vec<&str> = Vec::new();
+    let mut tags: Vec<String> = Vec::new();
+    let mut fields: HashMap<&str, &str> = HashMap::new();
+    for token in tokens {
+        if (token.starts_with('+') || token.starts_with('@')) && token.len() > 1 {
+            let tag = token.trim_start_matches('+').to_string();
+            if !tags.iter().any(|t| t.eq_ignore_ascii_case(&tag)) {
+                tags.push(tag);
+            }
+            continue;
+        }
+        if let Some((key, value)) = token.split_once(':')
+            && !key.is_empty()
+            && !value.is_empty()
+            && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+            && !value.starts_with("//")
+        {
+            match key {
+                "t" | "at" | "due" | "dueat" | "pri" | "status" | "rec" | "rrule" | "id" | "p" => {
+                    fields.insert(key, value);
+                    continue;
+                }
+                _ => *unmapped.entry(key.to_string()).or_default() += 1,
+            }
+        }
+        words.push(token);
+    }
+
+    let title = words.join(" ");
+    if title.is_empty() {
+        warnings.push(format!("第 {} 行没有标题，已忽略", line));
+        return None;
+    }
+    let mut date_field = |key: &str| {
+        let value = fields.get(key)?;
+        let date = parse_date(value);
+        if date.is_none() {
+            warnings.push(format!("第 {} 行的 {}:{} 不是有效的日期（YYYY-MM-DD），已忽略", line, key, value));
+        }
+        date
+    };
+    let threshold = date_field("t");
+    let due = date_field("due");
+    let mut time_field = |key: &str| {
+        let value = fields.get(key)?;
+        let time = NaiveTime::parse_from_str(value, "%H:%M").ok();
+        if time.is_none() {
+            warnings.push(format!("第 {} 行的 {}:{} 不是有效的时刻（HH:MM），已忽略", line, key, value));
+        }
+        time
+    };
+    let begin_at = time_field("at").unwrap_or(NaiveTime::MIN);
+    let due_at = time_field("dueat").unwrap_or_else(end_of_day);
+
+    // 开始日期：t: → 创建日期 → 截止日期 → 今天
+    let begin_date = threshold
+        .or(created)
+        .or(due)
+        .unwrap_or_else(|| local_time::now().date_naive());
+    let begin_time = local_time::from_local(&begin_date.and_time(begin_at))?;
+    let end_time = due.and_then(|date| local_time::from_local(&date.and_time(due_at)));
+
+    let mut status = if done { TodoStatus::Done } else { TodoStatus::Todo };
+    if let Some(value) = fields.get("status") {
+        match TodoStatus::from_string(value) {
+            Some(parsed) => status = parsed,
+            None => warnings.push(format!("第 {} 行的 status:{} 无法识别，已忽略", line, value)),
+        }
+    }
+    if let Some(value) = fields.get("pri") {
+        priority = letter_priority(value);
+    }
+
+    let recurrence_text = match (fields.get("rrule"), fields.get("rec")) {
+        (Some(rrule), _) => Some(Ok(rrule.to_string())),
+        (None, Some(rec)) => Some(parse_rec(rec)),
+        _ => None,
+    };
+    let recurrence = match recurrence_text {
+        Some(Ok(text)) => match todo_list_serv::parse_recurrence_input(&text, begin_time) {
+            Ok(rule) => rule,
+            Err(e) => {
+                warnings.push(format!("第 {} 行的重复规则无效（{}），按单次导入", line, e));
+                None
+            }
+        },
+        Some(Err(e)) => {
+            warnings.push(format!("第 {} 行: {}，按单次导入", line, e));
+            None
+        }
+        None => None,
+    };
+
+    Some(ParsedLine {
+        todo: TodoListForm {
+            // 临时 ID 使用负的行号，导入报告中显示为行号
+            id: -(line as i32),
+            title,
+            description: None,
+            status,
+            priority,
+            tags,
+            begin_time,
+            end_time,
+            key_message1: None,
+            key_message2: None,
+            key_message3: None,
+            recurrence,
+            series_id: None,
+            occurrence: 1,
+            parent_id: None,
+            archived_at: None,
+            completed_at: completed
+                .filter(|_| status == TodoStatus::Done)
+                .and_then(|date| local_time::from_local(&date.and_time(NaiveTime::MIN))),
+        },
+        key: fields.get("id").map(|v| v.to_string()),
+        parent: fields.get("p").map(|v| v.to_string()),
+    })
+}
+
+/// 优先级字母：A 高、B 中、C-Z 低
+fn letter_priority(letter: &str) -> Option<Priority> {
+    match letter.to_ascii_uppercase().as_str() {
+        "A" => Some(Priority::High),
+        "B" => Some(Priority::Medium),
+        l if l.len() == 1 && l.chars().all(|c| c.is_ascii_uppercase()) => Some(Priority::Low),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    /// 本地日期时间对应的 UTC 时刻
+    fn local(date: NaiveDate, h: u32, min: u32) -> DateTime<Utc> {
+        local_time::from_local(&date.and_hms_opt(h, min, 0).unwrap()).unwrap()
+    }
+
+    fn find<'a>(document: &'a ExportDocument, title: &str) -> &'a TodoListForm {
+        document.todos.iter().find(|t| t.title == title).unwrap()
+    }
+
+    #[test]
+    fn priorities() {
+        let document = parse("(A) 高\n(B) 中\n(C) 低\n(F) 更低\n没有优先级\n(AB) 不是优先级\n").unwrap();
+        assert_eq!(find(&document, "高").priority, Some(Priority::High));
+        assert_eq!(find(&document, "中").priority, Some(Priority::Medium));
+        assert_eq!(find(&document, "低").priority, Some(Priority::Low));
+        assert_eq!(find(&document, "更低").priority, Some(Priority::Low));
+        assert_eq!(find(&document, "没有优先级").priority, None);
+        assert_eq!(find(&document, "(AB) 不是优先级").priority, None);
+    }
+
+    #[test]
+    fn projects_and_contexts() {
+        let document = parse("写周报 +work @office +Work @office 周五前\n").unwrap();
+        let todo = &document.todos[0];
+        // 标签从标题中去掉，重复的不区分大小写只保留一个
+        assert_eq!(todo.title, "写周报 周五前");
+        assert_eq!(todo.tags, vec!["work", "@office"]);
+
+        // 导出时带 @ 的标签写为情境，其余写为项目
+        let line = render(&document);
+        assert!(line.starts_with("写周报 周五前 +work @office "), "{line}");
+    }
+
+    #[test]
+    fn dates_and_times() {
+        let document = parse(
+            "2030-01-01 有创建日期\n\
+             2030-01-01 开始与截止 t:2030-01-03 at:09:30 due:2030-01-10\n\
+             有截止时刻 due:2030-01-10 dueat:18:00\n\
+             错误日期 due:2030-13-01 at:25:00\n",
+        )
+        .unwrap();
+        let created = find(&document, "有创建日期");
+        assert_eq!(created.begin_time, local(date(2030, 1, 1), 0, 0));
+        assert_eq!(created.end_time, None);
+
+        let both = find(&document, "开始与截止");
+        assert_eq!(both.begin_time, local(date(2030, 1, 3), 9, 30));
+        // 只有日期的截止时间为当天 23:59
+        assert_eq!(both.end_time, Some(local(date(2030, 1, 10), 23, 59)));
+
+        // 没有开始日期时以截止日期作为开始日期
+        let due_at = find(&document, "有截止时刻");
+        assert_eq!(due_at.begin_time, local(date(2030, 1, 10), 0, 0));
+        assert_eq!(due_at.end_time, Some(local(date(2030, 1, 10), 18, 0)));
+
+        let invalid = find(&document, "错误日期");
+        assert_eq!(invalid.end_time, None);
+        assert_eq!(document.read_warnings.len(), 2, "{:?}", document.read_warnings);
+
+        // 导出后再导入，时间保持不变
+        let again = parse(&render(&document)).unwrap();
+        for todo in &document.todos {
+            let other = find(&again, &todo.title);
+            assert_eq!(other.begin_time, todo.begin_time);
+            assert_eq!(other.end_time, todo.end_time);
+        }
+    }
+
+    #[test]
+    fn completed_lines() {
+        let document = parse(
+            "x 2030-01-06 2030-01-01 已完成 +work pri:B\n\
+             x 没有日期\n\
+             x 2030-01-06 已取消 status:cancelled\n\
+             X 大写的不是完成标记\n",
+        )
+        .unwrap();
+        let done = find(&document, "已完成");
+        assert_eq!(done.status, TodoStatus::Done);
+        assert_eq!(done.priority, Some(Priority::Medium));
+        assert_eq!(done.tags, vec!["work"]);
+        // 第一个日期为完成日期，第二个为创建日期
+        assert_eq!(done.begin_time, local(date(2030, 1, 1), 0, 0));
+        assert_eq!(done.completed_at, Some(local(date(2030, 1, 6), 0, 0)));
+
+        assert_eq!(find(&document, "没有日期").status, TodoStatus::Done);
+        let cancelled = find(&document, "已取消");
+        assert_eq!(cancelled.status, TodoStatus::Cancelled);
+        assert_eq!(cancelled.completed_at, None);
+        assert_eq!(find(&document, "X 大写的不是完成标记").status, TodoStatus::Todo);
+
+        // 已完成的优先级写作 pri:，完成日期紧跟在 x 之后
+        let line = render_line(done);
+        assert!(line.starts_with("x 2030-01-06 已完成 +work pri:B t:2030-01-01"), "{line}");
+        let again = parse(&render(&document)).unwrap();
+        let done = find(&again, "已完成");
+        assert_eq!(done.priority, Some(Priority::Medium));
+        assert_eq!(done.completed_at, Some(local(date(2030, 1, 6), 0, 0)));
+        assert_eq!(find(&again, "已取消").status, TodoStatus::Cancelled);
+    }
+
+    #[test]
+    fn recurrence_and_parents() {
+        let document = parse(
+            "日报 t:2030-01-01 rec:1d id:daily\n\
+             工作日 t:2030-01-01 rec:1b p:daily\n\
+             孤儿 p:missing\n\
+             坏规则 rec:3y\n",
+        )
+        .unwrap();
+        let daily = find(&document, "日报");
+        assert_eq!(daily.recurrence.as_ref().map(|r| r.freq), Some(Frequency::Daily));
+        let workdays = find(&document, "工作日");
+        assert_eq!(workdays.parent_id, Some(daily.id));
+        assert_eq!(workdays.recurrence.as_ref().map(|r| r.by_weekday.len()), Some(5));
+        assert_eq!(find(&document, "孤儿").parent_id, None);
+        assert_eq!(find(&document, "坏规则").recurrence, None);
+        assert_eq!(document.read_warnings.len(), 2, "{:?}", document.read_warnings);
+    }
+}
@@ -0,0 +1,622 @@
+//! 导入与导出
+//!
+//! - JSON：完整导出待办事项、依赖、笔记与提醒历史，结构见 `data::transfer`
+//! - CSV：单张表（待办事项 / 笔记 / 提醒历史），列与 `--format csv` 的列表输出一致
+//! - Markdown：待办清单（`- [ ] 标题`），子任务缩进，附带笔记，仅导出
+//! - iCalendar：待办事项导出为 VTODO，可导入 VTODO 与 VEVENT，见 `service::ical`
+//! - todo.txt：待办事项导出与导入，见 `service::todo_txt`
+//! - Taskwarrior：导入 `task export` 的输出，见 `service::taskwarrior`
+//!
+//! 导入时所有记录都重新分配 ID，并据此改写父任务、重复系列、依赖和笔记、提醒历史的引用。
+//! 整个导入在一个事务中完成；试运行（dry run）执行同样的步骤后回滚，因此报告与实际导入完全一致。
+
+use anyhow::{Context, Result as AnyResult, anyhow, bail};
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+use crate::dao::{dependency_dao, note_dao, reminder_dao, todo_list_dao};
+use crate::data::local_time;
+use crate::data::note::NoteForm;
+use crate::data::reminder::ReminderHistory;
+use crate::data::todo_list::{TodoListForm, TodoStatus};
+use crate::data::transfer::{Dependency, ExportDocument, TransferFormat, TransferTable};
+use crate::init::database::Database;
+use crate::init::db_json_content::ReminderConfig;
+use crate::service::{change_log_serv, ical, taskwarrior, todo_txt};
+use crate::service::logger::Logger;
+use crate::service::output::{self, OutputFormat};
+use crate::service::todo_list_serv;
+
+/// 读取数据库中的全部数据（回收站中的待办事项与笔记不导出）
+pub fn export_document(db: &Database) -> AnyResult<ExportDocument> {
+    let conn = db.get_connection();
+    let mut document = ExportDocument::new(Some(local_time::zone().name().to_string()));
+
+    let mut todos = todo_list_dao::list_todos(conn)?;
+    todos.sort_by_key(|t| t.id);
+    let exported: HashSet<i32> = todos.iter().map(|t| t.id).collect();
+    document.todos = todos;
+    document.dependencies = dependency_dao::list_dependencies(conn)?
+        .into_iter()
+        .filter(|(todo_id, depends_on_id)| exported.contains(todo_id) && exported.contains(depends_on_id))
+        .map(|(todo_id, depends_on_id)| Dependency { todo_id, depends_on_id })
+        .collect();
+    document.notes = note_dao::list_notes(conn)?
+        .into_iter()
+        .filter(|n| exported.contains(&n.todo_id))
+        .collect();
+    document.reminder_history = reminder_dao::list_all_history(conn)?
+        .into_iter()
+        .filter(|r| exported.contains(&r.todo_id))
+        .collect();
+    Ok(document)
+}
+
+/// 按格式写出导出数据；CSV 只写出 table 指定的表，iCalendar 按 reminder 中的规则生成 VALARM
+pub fn write_export(
+    document: &ExportDocument,
+    format: TransferFormat,
+    table: TransferTable,
+    reminder: &ReminderConfig,
+    out: &mut dyn Write,
+) -> AnyResult<()> {
+    match format {
+        TransferFormat::Json => {
+            serde_json::to_writer_pretty(&mut *out, document)?;
+            writeln!(out)?;
+        }
+        TransferFormat::Csv => match table {
+            TransferTable::Todos => output::write_records(&mut *out, &document.todos, OutputFormat::Csv)?,
+            TransferTable::Notes => output::write_records(&mut *out, &document.notes, OutputFormat::Csv)?,
+            TransferTable::Reminders => {
+                output::write_records(&mut *out, &document.reminder_history, OutputFormat::Csv)?
+            }
+        },
+        TransferFormat::Markdown => out.write_all(render_markdown(document).as_bytes())?,
+        TransferFormat::Ics => out.write_all(ical::render_calendar(document, reminder).as_bytes())?,
+        TransferFormat::TodoTxt => out.write_all(todo_txt::render(document).as_bytes())?,
+        TransferFormat::Taskwarrior => bail!("Taskwarrior 格式仅支持导入"),
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// 导出到文件并打印摘要
+pub fn export_to_file(
+    db: &Database,
+    format: TransferFormat,
+    table: TransferTable,
+    reminder: &ReminderConfig,
+    path: &Path,
+) -> AnyResult<()> {
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty() && !d.exists()) {
+        fs::create_dir_all(dir).with_context(|| format!("无法创建目录 {}", dir.display()))?;
+    }
+    if !format.can_export() {
+        bail!("{} 格式仅支持导入", format.extension());
+    }
+    let document = export_document(db)?;
+    let mut file = fs::File::create(path).with_context(|| format!("无法创建文件 {}", path.display()))?;
+    write_export(&document, format, table, reminder, &mut file)?;
+
+    let summary = match format {
+        TransferFormat::Csv => {
+            let count = match table {
+                TransferTable::Todos => document.todos.len(),
+                TransferTable::Notes => document.notes.len(),
+                TransferTable::Reminders => document.reminder_history.len(),
+            };
+            format!("{} 条{}", count, table.label())
+        }
+        TransferFormat::Ics | TransferFormat::TodoTxt => format!("{} 条待办事项", document.todos.len()),
+        _ => format!(
+            "{} 条待办事项、{} 条笔记、{} 条提醒历史",
+            document.todos.len(),
+            document.notes.len(),
+            document.reminder_history.len()
+        ),
+    };
+    println!("✅ 已导出 {} 到 {}", summary, path.display());
+    Logger::log("INFO", &format!("导出 {} 到 {}", summary, path.display()));
+    Ok(())
+}
+
+/// 渲染 Markdown 待办清单：已完成 / 已取消的勾选，子任务缩进在父任务下
+fn render_markdown(document: &ExportDocument) -> String {
+    let mut text = String::new();
+    let _ = writeln!(text, "# TodoList 待办清单");
+    let _ = writeln!(text);
+    let _ = writeln!(
+        text,
+        "> 导出时间：{}（{}），共 {} 项",
+        local_time::format(&document.exported_at, "%Y-%m-%d %H:%M"),
+        local_time::zone().name(),
+        document.todos.len()
+    );
+    let _ = writeln!(text);
+
+    let ids: HashSet<i32> = document.todos.iter().map(|t| t.id).collect();
+    let mut children: HashMap<Option<i32>, Vec<&TodoListForm>> = HashMap::new();
+    for todo in &document.todos {
+        // 父任务不在导出范围内时按顶层任务显示
+        let parent = todo.parent_id.filter(|p| ids.contains(p));
+        children.entry(parent).or_default().push(todo);
+    }
+    let mut notes: HashMap<i32, Vec<&NoteForm>> = HashMap::new();
+    for note in &document.notes {
+        notes.entry(note.todo_id).or_default().push(note);
+    }
+
+    let mut stack: Vec<(&TodoListForm, usize)> = children
+        .get(&None)
+        .map(|roots| roots.iter().rev().map(|t| (*t, 0)).collect())
+        .unwrap_or_default();
+    while let Some((todo, depth)) = stack.pop() {
+        let indent = "  ".repeat(depth);
+        let _ = writeln!(text, "{}{}", indent, markdown_item(todo));
+        if let Some(description) = todo.description.as_deref().filter(|d| !d.trim().is_empty()) {
+            let _ = writeln!(text, "{}  > {}", indent, single_line(description));
+        }
+        for note in notes.get(&todo.id).into_iter().flatten() {
+            let _ = writeln!(
+                text,
+                "{}  - 📝 **{}**：{}",
+                indent,
+                single_line(&note.note_title),
+                single_line(&note.note_content)
+            );
+        }
+        if let Some(subtasks) = children.get(&Some(todo.id)) {
+            stack.extend(subtasks.iter().rev().map(|t| (*t, depth + 1)));
+        }
+    }
+    text
+}
+
+/// 单个待办事项的清单行
+fn markdown_item(todo: &TodoListForm) -> String {
+    let checked = if todo.status.is_open() { " " } else { "x" };
+    let title = if todo.status == TodoStatus::Cancelled {
+        format!("~~{}~~", single_line(&todo.title))
+    } else {
+        single_line(&todo.title)
+    };
+
+    let mut details = vec![format!("开始 {}", local_time::format(&todo.begin_time, "%Y-%m-%d %H:%M"))];
+    if let Some(end_time) = &todo.end_time {
+        details.push(format!("截止 {}", local_time::format(end_time, "%Y-%m-%d %H:%M")));
+    }
+    if matches!(todo.status, TodoStatus::InProgress | TodoStatus::Blocked) {
+        details.push(todo.status.label().to_string());
+    }
+    if let Some(priority) = todo.priority {
+        details.push(format!("优先级 {}", priority.label()));
+    }
+    if let Some(recurrence) = &todo.recurrence {
+        details.push(format!("🔁 {}", recurrence.describe()));
+    }
+    let tags: String = todo.tags.iter().map(|t| format!(" #{}", t)).collect();
+    format!("- [{}] {} (ID:{}) · {}{}", checked, title, todo.id, details.join(" · "), tags)
+}
+
+fn single_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// 遇到重复数据（冲突）时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// 跳过重复的记录，引用改为指向已有记录
+    Skip,
+    /// 仍然作为新记录导入
+    Duplicate,
+}
+
+impl ConflictPolicy {
+    pub fn from_string(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "skip" => Some(ConflictPolicy::Skip),
+            "duplicate" | "keep" => Some(ConflictPolicy::Duplicate),
+            _ => None,
+        }
+    }
+}
+
+/// 单张表的导入结果
+#[derive(Debug, Default)]
+pub struct TableReport {
+    pub created: usize,
+    pub skipped: usize,
+    pub invalid: usize,
+}
+
+/// 导入结果
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub todos: TableReport,
+    pub dependencies: TableReport,
+    pub notes: TableReport,
+    pub reminders: TableReport,
+    /// 待办 ID 对照：(文件中的 ID, 数据库中的 ID, 是否新建)，未新建的指向已有的重复记录
+    pub id_map: Vec<(i32, i32, bool)>,
+    /// 与已有数据重复的记录
+    pub conflicts: Vec<String>,
+    /// 无效记录、找不到的引用等
+    pub warnings: Vec<String>,
+}
+
+/// 读取导入文件；CSV 按 table 解析为对应的表，其余表为空
+pub fn read_import_file(path: &Path, format: TransferFormat, table: TransferTable) -> AnyResult<ExportDocument> {
+    let content = fs::read_to_string(path).with_context(|| format!("无法读取文件 {}", path.display()))?;
+    match format {
+        // task export 的输出同样以 .json 结尾，按内容区分
+        TransferFormat::Json if taskwarrior::looks_like_export(&content) => taskwarrior::parse(&content),
+        TransferFormat::Json => {
+            let document: ExportDocument =
+                serde_json::from_str(&content).with_context(|| format!("{} 不是有效的导出文件", path.display()))?;
+            document.validate().map_err(|e| anyhow!(e))?;
+            Ok(document)
+        }
+        TransferFormat::Csv => read_csv(&content, table),
+        TransferFormat::Ics => ical::parse_calendar(&content),
+        TransferFormat::TodoTxt => todo_txt::parse(&content),
+        TransferFormat::Taskwarrior => taskwarrior::parse(&content),
+        TransferFormat::Markdown => bail!("Markdown 仅支持导出"),
+    }
+}
+
+/// 解析 CSV：列名与导出的 CSV 一致，缺少的可选列视为空
+fn read_csv(content: &str, table: TransferTable) -> AnyResult<ExportDocument> {
+    let mut reader = csv::Reader::from_reader(content.as_bytes());
+    let headers = reader.headers()?.clone();
+    let mut document = ExportDocument::new(None);
+
+    for (index, record) in reader.records().enumerate() {
+        let record = record?;
+        let row = CsvRow { headers: &headers, record: &record };
+        // 表头为第 1 行
+        let line = index + 2;
+        match table {
+            TransferTable::Todos => document.todos.push(row.todo(line)?),
+            TransferTable::Notes => document.notes.push(row.note(line)?),
+            TransferTable::Reminders => bail!("提醒历史只能通过 JSON 导入"),
+        }
+    }
+    Ok(document)
+}
+
+struct CsvRow<'a> {
+    headers: &'a csv::StringRecord,
+    record: &'a csv::StringRecord,
+}
+
+impl CsvRow<'_> {
+    /// 列的值，不存在或为空时返回 None
+    fn get(&self, column: &str) -> Option<&str> {
+        let index = self.headers.iter().position(|h| h.trim() == column)?;
+        self.record.get(index).map(str::trim).filter(|v| !v.is_empty())
+    }
+
+    fn text(&self, column: &str) -> Option<String> {
+        self.get(column).map(str::to_string)
+    }
+
+    fn required(&self, column: &str, line: usize) -> AnyResult<&str> {
+        self.get(column).ok_or_else(|| anyhow!("第 {} 行缺少 {}", line, column))
+    }
+
+    fn id(&self, column: &str, line: usize) -> AnyResult<Option<i32>> {
+        self.get(column)
+            .map(|v| v.parse::<i32>().map_err(|_| anyhow!("第 {} 行的 {} 不是有效的 ID: {}", line, column, v)))
+            .transpose()
+    }
+
+    fn time(&self, column: &str, line: usize) -> AnyResult<Option<DateTime<Utc>>> {
+        self.get(column)
+            .map(|v| todo_list_serv::parse_datetime(v).map_err(|e| anyhow!("第 {} 行的 {} 无效（{}）: {}", line, column, e, v)))
+            .transpose()
+    }
+
+    fn todo(&self, line: usize) -> AnyResult<TodoListForm> {
+        let begin_time = self.time("begin_time", line)?
+            .ok_or_else(|| anyhow!("第 {} 行缺少 begin_time", line))?;
+        let status = match self.get("status") {
+            Some(value) => TodoStatus::from_string(value).ok_or_else(|| anyhow!("第 {} 行的状态无效: {}", line, value))?,
+            None => TodoStatus::Todo,
+        };
+        let priority = todo_list_serv::parse_priority_input(self.get("priority").unwrap_or(""))
+            .map_err(|e| anyhow!("第 {} 行: {}", line, e))?;
+        let recurrence = todo_list_serv::parse_recurrence_input(self.get("recurrence").unwrap_or(""), begin_time)
+            .map_err(|e| anyhow!("第 {} 行的重复规则无效: {}", line, e))?;
+        let occurrence = match self.get("occurrence") {
+            Some(value) => value.parse().map_err(|_| anyhow!("第 {} 行的 occurrence 无效: {}", line, value))?,
+            None => 1,
+        };
+
+        Ok(TodoListForm {
+            // 没有 ID 列时按行号生成，仅用于导入时的引用
+            id: self.id("id", line)?.unwrap_or(-(line as i32)),
+            title: self.required("title", line)?.to_string(),
+            description: self.text("description"),
+            status,
+            priority,
+            tags: self.get("tags")
+                .map(|v| v.split([';', ',']).map(str::trim).filter(|t| !t.is_empty()).map(str::to_string).collect())
+                .unwrap_or_default(),
+            begin_time,
+            end_time: self.time("end_time", line)?,
+            key_message1: self.text("key_message1"),
+            key_message2: self.text("key_message2"),
+            key_message3: self.text("key_message3"),
+            recurrence,
+            series_id: self.id("series_id", line)?,
+            occurrence,
+            parent_id: self.id("parent_id", line)?,
+            archived_at: self.time("archived_at", line)?,
+            completed_at: self.time("completed_at", line)?,
+        })
+    }
+
+    fn note(&self, line: usize) -> AnyResult<NoteForm> {
+        Ok(NoteForm {
+            id: self.id("id", line)?.unwrap_or(-(line as i32)),
+            todo_id: self.id("todo_id", line)?.ok_or_else(|| anyhow!("第 {} 行缺少 todo_id", line))?,
+            note_title: self.required("note_title", line)?.to_string(),
+            note_content: self.text("note_content").unwrap_or_default(),
+            note_time: self.time("note_time", line)?.unwrap_or_else(Utc::now),
+            noter: self.text("noter"),
+            note_type: self.text("note_type"),
+            note_status: self.text("note_status"),
+            note_tag: self.text("note_tag"),
+            note_priority: self.text("note_priority"),
+        })
+    }
+}
+
+/// 导入数据，dry_run 为 true 时只生成报告不写入
+///
+/// 笔记、提醒历史与依赖引用的待办 ID 先在文件中的待办里查找；
+/// 文件中没有任何待办事项时（如单独导入笔记 CSV），视为数据库中已有待办的 ID
+pub fn import_document(
+    db: &Database,
+    document: &ExportDocument,
+    policy: ConflictPolicy,
+    dry_run: bool,
+) -> AnyResult<ImportReport> {
+    let conn = db.get_connection();
+    let tx = conn.unchecked_transaction()?;
+    let change_set = change_log_serv::begin(&tx, "导入数据")?;
+    let mut report = ImportReport { dry_run, warnings: document.read_warnings.clone(), ..Default::default() };
+
+    // 待办事项：按原 ID 顺序新建，父任务与重复系列在全部新建后再改写
+    let existing: HashMap<(String, DateTime<Utc>), i32> = todo_list_dao::list_todos(&tx)?
+        .into_iter()
+        .map(|t| ((t.title, t.begin_time), t.id))
+        .collect();
+    let existing_ids: HashSet<i32> = existing.values().copied().collect();
+    let mut todos: Vec<&TodoListForm> = document.todos.iter().collect();
+    // 没有 ID 的 CSV 行与 iCalendar 组件以负的行号表示，排在后面并保持文件中的顺序
+    todos.sort_by_key(|t| (t.id < 0, t.id.abs()));
+
+    let mut id_map: HashMap<i32, i32> = HashMap::new();
+    let mut created: Vec<(&TodoListForm, i32)> = Vec::new();
+    for todo in todos {
+        if id_map.contains_key(&todo.id) {
+            report.todos.invalid += 1;
+            report.warnings.push(format!("待办 {} 的 ID 在文件中重复出现，已忽略", ref_label(todo.id)));
+            continue;
+        }
+        if todo.title.trim().is_empty() {
+            report.todos.invalid += 1;
+            report.warnings.push(format!("待办 {} 缺少标题，已忽略", ref_label(todo.id)));
+            continue;
+        }
+        if let Some(&existing_id) = existing.get(&(todo.title.clone(), todo.begin_time)) {
+            match policy {
+                ConflictPolicy::Skip => {
+                    report.conflicts.push(format!(
+                        "待办 {}「{}」与已有的 #{} 标题和开始时间相同，已跳过",
+                        ref_label(todo.id), todo.title, existing_id
+                    ));
+                    report.todos.skipped += 1;
+                    id_map.insert(todo.id, existing_id);
+                    report.id_map.push((todo.id, existing_id, false));
+                    continue;
+                }
+                ConflictPolicy::Duplicate => report.conflicts.push(format!(
+                    "待办 {}「{}」与已有的 #{} 标题和开始时间相同，仍作为新记录导入",
+                    ref_label(todo.id), todo.title, existing_id
+                )),
+            }
+        }
+
+        let mut form = todo.clone();
+        form.parent_id = None;
+        form.series_id = None;
+        let new_id = todo_list_dao::insert_todo(&tx, &form)? as i32;
+        id_map.insert(todo.id, new_id);
+        report.id_map.push((todo.id, new_id, true));
+        created.push((todo, new_id));
+        report.todos.created += 1;
+    }
+
+    let resolve = |id: i32| -> Option<i32> {
+        match id_map.get(&id) {
+            Some(&mapped) => Some(mapped),
+            None if document.todos.is_empty() && existing_ids.contains(&id) => Some(id),
+            None => None,
+        }
+    };
+
+    for (todo, new_id) in &created {
+        if let Some(parent_id) = todo.parent_id {
+            match resolve(parent_id) {
+                Some(parent) => {
+                    todo_list_dao::set_parent(&tx, *new_id, Some(parent))?;
+                }
+                None => report.warnings.push(format!(
+                    "待办 {} 的父任务 #{} 不在导入文件中，已作为顶层任务导入",
+                    ref_label(todo.id), parent_id
+                )),
+            }
+        }
+        if let Some(series_id) = todo.series_id {
+            let series = id_map.get(&series_id).copied().unwrap_or(*new_id);
+            todo_list_dao::set_series_id(&tx, *new_id, Some(series))?;
+        }
+    }
+
+    // 依赖关系
+    for dependency in &document.dependencies {
+        match (resolve(dependency.todo_id), resolve(dependency.depends_on_id)) {
+            (Some(todo_id), Some(depends_on_id)) if todo_id != depends_on_id => {
+                if dependency_dao::add_dependency(&tx, todo_id, depends_on_id)? {
+                    report.dependencies.created += 1;
+                } else {
+                    report.dependencies.skipped += 1;
+                }
+            }
+            _ => {
+                report.dependencies.invalid += 1;
+                report.warnings.push(format!(
+                    "依赖 #{} → #{} 引用的待办不存在，已忽略",
+                    dependency.todo_id, dependency.depends_on_id
+                ));
+            }
+        }
+    }
+
+    // 笔记：同一待办下标题与时间都相同视为重复
+    let mut existing_notes: HashSet<(i32, String, DateTime<Utc>)> = note_dao::list_notes(&tx)?
+        .into_iter()
+        .map(|n| (n.todo_id, n.note_title, n.note_time))
+        .collect();
+    for note in &document.notes {
+        let Some(todo_id) = resolve(note.todo_id) else {
+            report.notes.invalid += 1;
+            report.warnings.push(format!(
+                "笔记 {}「{}」所属的待办 #{} 不存在，已忽略",
+                ref_label(note.id), note.note_title, note.todo_id
+            ));
+            continue;
+        };
+        let key = (todo_id, note.note_title.clone(), note.note_time);
+        if existing_notes.contains(&key) {
+            report.conflicts.push(format!("笔记 {}「{}」在待办 #{} 下已存在", ref_label(note.id), note.note_title, todo_id));
+            if policy == ConflictPolicy::Skip {
+                report.notes.skipped += 1;
+                continue;
+            }
+        }
+        let mut form = note.clone();
+        form.todo_id = todo_id;
+        note_dao::insert_note(&tx, &form)?;
+        existing_notes.insert(key);
+        report.notes.created += 1;
+    }
+
+    // 提醒历史：同一待办、同一规则、同一计划触发时间视为重复
+    for history in &document.reminder_history {
+        let Some(todo_id) = resolve(history.todo_id) else {
+            report.reminders.invalid += 1;
+            report.warnings.push(format!(
+                "提醒历史 #{} 所属的待办 #{} 不存在，已忽略",
+                history.id, history.todo_id
+            ));
+            continue;
+        };
+        if let (Some(rule_key), Some(trigger_time)) = (&history.rule_key, &history.trigger_time)
+            && reminder_dao::has_been_notified(&tx, todo_id, rule_key, trigger_time.timestamp())?
+        {
+            report.conflicts.push(format!("提醒历史 #{}（{}）在待办 #{} 下已存在", history.id, rule_key, todo_id));
+            if policy == ConflictPolicy::Skip {
+                report.reminders.skipped += 1;
+                continue;
+            }
+        }
+        let record = ReminderHistory { todo_id, ..history.clone() };
+        reminder_dao::insert_history(&tx, &record)?;
+        report.reminders.created += 1;
+    }
+
+    let summary = format!(
+        "导入 {} 条待办事项、{} 条笔记、{} 条提醒历史、{} 条依赖",
+        report.todos.created, report.notes.created, report.reminders.created, report.dependencies.created
+    );
+    change_log_serv::end(&tx, change_set, &summary)?;
+    if dry_run {
+        tx.rollback()?;
+    } else {
+        tx.commit()?;
+        Logger::log("INFO", &summary);
+    }
+    Ok(report)
+}
+
+/// 记录在文件中的标识：CSV 中没有 ID 列时、以及 iCalendar 中的组件以行号表示
+fn ref_label(id: i32) -> String {
+    if id < 0 {
+        format!("(第 {} 行)", -id)
+    } else {
+        format!("#{}", id)
+    }
+}
+
+/// 打印导入报告
+pub fn print_report(report: &ImportReport) {
+    println!();
+    if report.dry_run {
+        println!("🔍 试运行（未写入数据库）:");
+    } else {
+        println!("📥 导入完成:");
+    }
+    println!("{}", "=".repeat(60));
+    let tables = [
+        ("待办事项", &report.todos),
+        ("任务依赖", &report.dependencies),
+        ("笔记", &report.notes),
+        ("提醒历史", &report.reminders),
+    ];
+    for (label, table) in tables {
+        if table.created + table.skipped + table.invalid == 0 {
+            continue;
+        }
+        println!(
+            "  {}: {} {} 条，跳过重复 {} 条，无效 {} 条",
+            label,
+            if report.dry_run { "将新建" } else { "新建" },
+            table.created,
+            table.skipped,
+            table.invalid
+        );
+    }
+
+    if !report.id_map.is_empty() {
+        println!();
+        println!("🔢 待办 ID 对照（文件 → 数据库）:");
+        for (old_id, new_id, created) in &report.id_map {
+            let note = if *created { "新建" } else { "已有" };
+            println!("  {} → #{} ({})", ref_label(*old_id), new_id, note);
+        }
+    }
+    if !report.conflicts.is_empty() {
+        println!();
+        println!("⚠️  冲突 ({} 条):", report.conflicts.len());
+        for conflict in &report.conflicts {
+            println!("  • {}", conflict);
+        }
+    }
+    if !report.warnings.is_empty() {
+        println!();
+        println!("⚠️  警告 ({} 条):", report.warnings.len());
+        for warning in &report.warnings {
+            println!("  • {}", warning);
+        }
+    }
+    println!("{}", "=".repeat(60));
+}
@@ -0,0 +1,166 @@
+//! 回收站
+//!
+//! 删除待办事项或笔记时只移入回收站（记录 deleted_at），不再出现在列表、查询、提醒与导出中：
+//! - 待办事项的笔记、标签、依赖与提醒历史原样保留，恢复后一并回到原处
+//! - 子任务不随父任务移入回收站，父任务在回收站期间显示为顶层任务，恢复后重新挂回父任务下
+//! - 移入回收站超过 retention_days 天的项目，在常驻模式、定时任务与交互模式启动时自动彻底删除
+//!
+//! 移入、恢复与彻底删除都记入修改历史，可以撤销。
+
+use anyhow::{Result as AnyResult, anyhow, bail};
+use chrono::{DateTime, Duration, Utc};
+use std::cmp::Reverse;
+
+use crate::dao::{note_dao, todo_list_dao};
+use crate::data::local_time;
+use crate::data::trash::{TrashItem, TrashKind};
+use crate::init::database::Database;
+use crate::init::db_json::JsonConfig;
+use crate::init::db_json_content::TrashConfig;
+use crate::service::change_log_serv;
+use crate::service::output::{self, OutputFormat};
+
+/// 读取回收站配置，没有配置时使用默认值
+pub fn get_trash_config(json_config: &JsonConfig) -> AnyResult<TrashConfig> {
+    let config: TrashConfig = match json_config.get_value("trash") {
+        Ok(value) => serde_json::from_value(value)?,
+        Err(_) => TrashConfig::default(),
+    };
+    config.validate().map_err(|e| anyhow!(e))?;
+    Ok(config)
+}
+
+/// 自动彻底删除的时间，未开启自动清理或超出可表示的时间范围时为 None（不会过期）
+fn purge_time(config: &TrashConfig, deleted_at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    if config.retention_days == 0 {
+        return None;
+    }
+    deleted_at.checked_add_signed(Duration::days(config.retention_days as i64))
+}
+
+/// 回收站中的全部项目（最近删除的在前）
+pub fn list_trash(database: &Database, config: &TrashConfig) -> AnyResult<Vec<TrashItem>> {
+    let conn = database.get_connection();
+    let mut items = todo_list_dao::list_trashed(conn)?;
+    items.extend(note_dao::list_trashed(conn)?);
+    items.sort_by_key(|item| Reverse(item.deleted_at));
+    for item in &mut items {
+        item.purge_at = purge_time(config, item.deleted_at);
+    }
+    Ok(items)
+}
+
+/// 查找回收站中的项目
+fn find(database: &Database, kind: TrashKind, id: i32) -> AnyResult<Option<TrashItem>> {
+    let conn = database.get_connection();
+    let items = match kind {
+        TrashKind::Todo => todo_list_dao::list_trashed(conn)?,
+        TrashKind::Note => note_dao::list_trashed(conn)?,
+    };
+    Ok(items.into_iter().find(|item| item.id == id))
+}
+
+/// 从回收站恢复，返回恢复的项目；不在回收站中时返回 None
+///
+/// 笔记所属的待办事项也在回收站中时需要先恢复待办事项
+pub fn restore(database: &Database, kind: TrashKind, id: i32) -> AnyResult<Option<TrashItem>> {
+    let Some(item) = find(database, kind, id)? else {
+        return Ok(None);
+    };
+    let conn = database.get_connection();
+    let action = format!("从回收站恢复{} #{}「{}」", kind.label(), id, item.title);
+    match kind {
+        TrashKind::Todo => {
+            change_log_serv::record(database, &action, || todo_list_dao::restore_todo(conn, id))?;
+        }
+        TrashKind::Note => {
+            if todo_list_dao::get_todo_by_id(conn, item.todo_id)?.is_none() {
+                bail!("笔记 #{} 所属的待办事项 #{} 也在回收站中，请先恢复该待办事项", id, item.todo_id);
+            }
+            change_log_serv::record(database, &action, || note_dao::restore_note(conn, id))?;
+        }
+    }
+    Ok(Some(item))
+}
+
+/// 彻底删除回收站中的一项，返回删除的项目；不在回收站中时返回 None
+pub fn purge(database: &Database, kind: TrashKind, id: i32) -> AnyResult<Option<TrashItem>> {
+    let Some(item) = find(database, kind, id)? else {
+        return Ok(None);
+    };
+    let action = format!("彻底删除{} #{}「{}」", kind.label(), id, item.title);
+    purge_items(database, std::slice::from_ref(&item), &action)?;
+    Ok(Some(item))
+}
+
+/// 彻底删除回收站中过期的项目（all 为 true 时清空回收站），返回删除的项目；dry_run 时只返回将要删除的项目
+pub fn purge_expired(database: &Database, config: &TrashConfig, all: bool, dry_run: bool) -> AnyResult<Vec<TrashItem>> {
+    let now = Utc::now();
+    let items: Vec<TrashItem> = list_trash(database, config)?
+        .into_iter()
+        .filter(|item| all || item.purge_at.is_some_and(|at| at <= now))
+        .collect();
+    if !dry_run && !items.is_empty() {
+        let action = if all {
+            format!("清空回收站（{} 项）", items.len())
+        } else {
+            format!("清理回收站中超过 {} 天的 {} 项", config.retention_days, items.len())
+        };
+        purge_items(database, &items, &action)?;
+    }
+    Ok(items)
+}
+
+/// 彻底删除：待办事项的笔记与提醒历史随之删除，子任务提升为顶层任务
+fn purge_items(database: &Database, items: &[TrashItem], action: &str) -> AnyResult<()> {
+    let conn = database.get_connection();
+    change_log_serv::record(database, action, || {
+        for item in items {
+            match item.kind {
+                TrashKind::Todo => todo_list_dao::delete_todo(conn, item.id)?,
+                TrashKind::Note => note_dao::delete_note(conn, item.id)?,
+            };
+        }
+        Ok(())
+    })
+}
+
+/// 自动清理过期的项目，返回彻底删除的数量
+///
+/// 常驻模式、定时任务模式与交互模式启动时调用
+pub fn run_scheduled(database: &Database, json_config: &JsonConfig) -> AnyResult<usize> {
+    let config = get_trash_config(json_config)?;
+    if config.retention_days == 0 {
+        return Ok(0);
+    }
+    Ok(purge_expired(database, &config, false, false)?.len())
+}
+
+/// 输出回收站内容
+pub fn print_trash(database: &Database, config: &TrashConfig, format: OutputFormat) -> AnyResult<()> {
+    let items = list_trash(database, config)?;
+    if format != OutputFormat::Text {
+        return output::print_records(&items, format);
+    }
+
+    if items.is_empty() {
+        println!("🗑️  回收站是空的");
+        return Ok(());
+    }
+    println!("\n🗑️  回收站 ({} 项):", items.len());
+    println!("{}", "=".repeat(80));
+    for item in &items {
+        let owner = match item.kind {
+            TrashKind::Todo => String::new(),
+            TrashKind::Note => format!("（待办事项 #{}）", item.todo_id),
+        };
+        println!("  [{} #{}] {}{}", item.kind.label(), item.id, item.title, owner);
+        let purge = match item.purge_at {
+            Some(at) => format!("，{} 自动彻底删除", local_time::format(&at, "%Y-%m-%d")),
+            None => String::new(),
+        };
+        println!("      删除于 {}{}", local_time::format(&item.deleted_at, "%Y-%m-%d %H:%M:%S"), purge);
+    }
+    println!("{}", "=".repeat(80));
+    Ok(())
+}